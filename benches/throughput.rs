@@ -0,0 +1,217 @@
+//! Benchmarks for event throughput and modification latency.
+//!
+//! These drive a real window, so every modification ends up calling into the
+//! shell and the benchmarks only run on Windows. To know when the event loop
+//! has processed everything sent so far we schedule and cancel a shutdown,
+//! since the resulting events are only emitted after all previously queued
+//! inputs have been handled.
+//!
+//! Only the public API is measured. The `modify_menu_item` benchmarks measure
+//! how modifications scale with the size of a popup menu, not how quickly menu
+//! clicks are dispatched.
+//!
+//! # Not covered
+//!
+//! The following are left for separate changes, since they require the shell
+//! to be called through a layer which can be replaced:
+//! * Running on a fake backend, so that the benchmarks can run in CI on other
+//!   platforms than Windows. Until then they fail to link elsewhere.
+//! * Recording the timestamps of calls into the shell, to measure the latency
+//!   from `send()` until the shell has been called instead of until the event
+//!   loop has caught up.
+//! * Dispatching menu clicks through the menu manager for menus with 10, 100
+//!   and 1000 items. Clicks can't be synthesized without access to the
+//!   internal menu handles.
+//! * Allocation counts per event.
+//! * Sharing metric definitions with production code, since the crate doesn't
+//!   have any metrics hooks yet.
+
+use std::time::{Duration, Instant};
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use tokio::runtime::Runtime;
+use winctx::{AreaId, CreateWindow, Event, EventLoop, ItemId, Sender};
+
+const ICON: &[u8] = include_bytes!("../examples/tokio.ico");
+
+/// Batch sizes used when sending modifications.
+const BATCHES: [u64; 3] = [1, 10, 100];
+
+/// Sizes of the popup menus being modified, used to see how modifications
+/// scale with the number of menu items.
+const MENU_SIZES: [usize; 3] = [10, 100, 1000];
+
+struct Fixture {
+    sender: Sender,
+    event_loop: EventLoop,
+    area_id: AreaId,
+    last_item: Option<ItemId>,
+}
+
+impl Fixture {
+    /// Build a window with a single area and a popup menu with the given
+    /// number of items.
+    async fn new(class_name: &str, menu_items: usize) -> Fixture {
+        let mut window = CreateWindow::new(class_name);
+        let icon = window.icons().insert_buffer(ICON, 22, 22);
+
+        let area = window.new_area().icon(icon).tooltip("Benchmark");
+        let area_id = area.id();
+        let mut last_item = None;
+
+        if menu_items > 0 {
+            let menu = area.popup_menu();
+
+            for n in 0..menu_items {
+                last_item = Some(menu.push_entry(format!("Item #{n}")).id());
+            }
+        }
+
+        let (sender, event_loop) = window.build().await.expect("building window");
+
+        Fixture {
+            sender,
+            event_loop,
+            area_id,
+            last_item,
+        }
+    }
+
+    /// Wait until the event loop has processed all inputs sent so far.
+    async fn sync(&mut self) {
+        self.sender
+            .shutdown_after(Duration::from_secs(3600))
+            .expect("sending input");
+        self.wait(|e| matches!(e, Event::ShutdownScheduled { .. }))
+            .await;
+        self.sender
+            .cancel_scheduled_shutdown()
+            .expect("sending input");
+        self.wait(|e| matches!(e, Event::ShutdownCancelled {}))
+            .await;
+    }
+
+    async fn wait(&mut self, mut f: impl FnMut(&Event) -> bool) {
+        loop {
+            let event = self.event_loop.tick().await.expect("ticking event loop");
+
+            if let Event::Error { error, .. } = &event {
+                panic!("error during benchmark: {error}");
+            }
+
+            if f(&event) {
+                break;
+            }
+        }
+    }
+
+    async fn shutdown(mut self) {
+        self.sender.shutdown().expect("sending input");
+        self.wait(|e| matches!(e, Event::Shutdown { .. })).await;
+    }
+}
+
+fn modify_area(c: &mut Criterion) {
+    let runtime = Runtime::new().expect("building runtime");
+    let mut fixture = runtime.block_on(Fixture::new("se.tedro.winctx.Bench.ModifyArea", 0));
+
+    let mut group = c.benchmark_group("modify_area");
+
+    // The cost of queueing a modification, excluding its processing.
+    group.throughput(Throughput::Elements(1));
+    group.bench_function("send", |b| {
+        b.iter_custom(|iters| {
+            runtime.block_on(async {
+                let start = Instant::now();
+
+                for n in 0..iters {
+                    fixture
+                        .sender
+                        .modify_area(fixture.area_id)
+                        .tooltip(n)
+                        .send()
+                        .expect("sending input");
+                }
+
+                let elapsed = start.elapsed();
+                fixture.sync().await;
+                elapsed
+            })
+        });
+    });
+
+    // The time from sending a batch of modifications until all of them have
+    // been submitted to the shell.
+    for batch in BATCHES {
+        group.throughput(Throughput::Elements(batch));
+        group.bench_with_input(
+            BenchmarkId::new("round_trip", batch),
+            &batch,
+            |b, &batch| {
+                b.iter_custom(|iters| {
+                    runtime.block_on(async {
+                        let start = Instant::now();
+
+                        for _ in 0..iters {
+                            for n in 0..batch {
+                                fixture
+                                    .sender
+                                    .modify_area(fixture.area_id)
+                                    .tooltip(n)
+                                    .send()
+                                    .expect("sending input");
+                            }
+
+                            fixture.sync().await;
+                        }
+
+                        start.elapsed()
+                    })
+                });
+            },
+        );
+    }
+
+    group.finish();
+    runtime.block_on(fixture.shutdown());
+}
+
+fn modify_menu_item(c: &mut Criterion) {
+    let runtime = Runtime::new().expect("building runtime");
+    let mut group = c.benchmark_group("modify_menu_item");
+    group.throughput(Throughput::Elements(1));
+
+    for size in MENU_SIZES {
+        let class_name = format!("se.tedro.winctx.Bench.ModifyMenuItem{size}");
+        let mut fixture = runtime.block_on(Fixture::new(&class_name, size));
+        let item_id = fixture.last_item.expect("menu has items");
+
+        group.bench_with_input(BenchmarkId::new("round_trip", size), &size, |b, _| {
+            b.iter_custom(|iters| {
+                runtime.block_on(async {
+                    let start = Instant::now();
+
+                    for n in 0..iters {
+                        fixture
+                            .sender
+                            .modify_menu_item(item_id)
+                            .checked(n % 2 == 0)
+                            .send()
+                            .expect("sending input");
+
+                        fixture.sync().await;
+                    }
+
+                    start.elapsed()
+                })
+            });
+        });
+
+        runtime.block_on(fixture.shutdown());
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, modify_area, modify_menu_item);
+criterion_main!(benches);
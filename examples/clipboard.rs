@@ -24,7 +24,7 @@ async fn main() -> Result<()> {
     loop {
         let event = tokio::select! {
             _ = ctrl_c.as_mut(), if !shutdown => {
-                sender.shutdown();
+                sender.shutdown()?;
                 shutdown = true;
                 continue;
             }
@@ -34,7 +34,7 @@ async fn main() -> Result<()> {
         };
 
         match event {
-            Event::Clipboard { event } => match event {
+            Event::Clipboard { event, .. } => match event {
                 ClipboardEvent::BitMap(bitmap) => {
                     let decoder = image::codecs::bmp::BmpDecoder::new_without_file_header(
                         Cursor::new(&bitmap[..]),
@@ -43,9 +43,27 @@ async fn main() -> Result<()> {
                     image.save("clipboard.png")?;
                     println!("Saved clipboard image to clipboard.png");
                 }
+                ClipboardEvent::Image {
+                    width,
+                    height,
+                    rgba,
+                } => {
+                    let Some(image) = image::RgbaImage::from_raw(width, height, rgba) else {
+                        println!("Bad clipboard image");
+                        continue;
+                    };
+
+                    image.save("clipboard.png")?;
+                    println!("Saved {width}x{height} clipboard image to clipboard.png");
+                }
                 ClipboardEvent::Text(text) => {
                     println!("Clipboard text: {text:?}");
                 }
+                ClipboardEvent::Files(files) => {
+                    for file in files {
+                        println!("Clipboard file: {}", file.display());
+                    }
+                }
                 _ => {}
             },
             Event::Shutdown { .. } => {
@@ -32,7 +32,7 @@ async fn main() -> Result<()> {
     loop {
         let event = tokio::select! {
             _ = ctrl_c.as_mut(), if !shutdown => {
-                sender.shutdown();
+                sender.shutdown()?;
                 shutdown = true;
                 continue;
             }
@@ -51,7 +51,7 @@ async fn main() -> winctx::Result<()> {
     loop {
         let event = tokio::select! {
             _ = ctrl_c.as_mut(), if !shutdown => {
-                sender.shutdown();
+                sender.shutdown()?;
                 shutdown = true;
                 continue;
             }
@@ -76,28 +76,34 @@ async fn main() -> winctx::Result<()> {
                             .large_icon()
                             .stock_icon(StockIcon::AUDIOFILES)
                             .icon_link_overlay()
-                            .send();
+                            .send()?;
                     }
                     winctx::item_id!(0, 2) => {
-                        sender.notification(area_id).message("First").send();
-                        sender.notification(area_id).message("Second").send();
+                        sender.notification(area_id).message("First").send()?;
+                        sender.notification(area_id).message("Second").send()?;
                     }
                     winctx::item_id!(0, 3) => {
                         if has_tooltip {
-                            sender.modify_area(area_id).tooltip("").send();
+                            sender.modify_area(area_id).tooltip("").send()?;
                         } else {
                             sender
                                 .modify_area(area_id)
                                 .tooltip("This is a tooltip!")
-                                .send();
+                                .send()?;
                         }
 
                         has_tooltip = !has_tooltip;
-                        sender.modify_menu_item(item_id).checked(has_tooltip).send();
+                        sender
+                            .modify_menu_item(item_id)
+                            .checked(has_tooltip)
+                            .send()?;
                     }
                     winctx::item_id!(0, 4) => {
                         is_checked = !is_checked;
-                        sender.modify_menu_item(item_id).checked(is_checked).send();
+                        sender
+                            .modify_menu_item(item_id)
+                            .checked(is_checked)
+                            .send()?;
                     }
                     winctx::item_id!(0, 5) => {
                         is_highlighted = !is_highlighted;
@@ -105,7 +111,7 @@ async fn main() -> winctx::Result<()> {
                             .modify_menu_item(item_id)
                             .checked(is_highlighted)
                             .highlight(is_highlighted)
-                            .send();
+                            .send()?;
                     }
                     _ => {
                         println!("Unhandled: {item_id:?}");
@@ -113,7 +119,7 @@ async fn main() -> winctx::Result<()> {
                 }
 
                 if item_id == quit {
-                    sender.shutdown();
+                    sender.shutdown()?;
                 }
             }
             Event::NotificationClicked { area_id, id, .. } => {
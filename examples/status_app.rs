@@ -0,0 +1,429 @@
+//! An end-to-end reference application which exercises the runtime mutation
+//! APIs of winctx.
+//!
+//! Run with `--headless-check` to perform every operation once and exit. The
+//! process exits with a non-zero status if any error is reported.
+
+use std::env;
+use std::pin::pin;
+use std::process::ExitCode;
+use std::time::{Duration, Instant};
+
+use anyhow::{bail, Result};
+use tokio::signal::ctrl_c;
+use tokio::time;
+use winctx::event::ClipboardEvent;
+use winctx::{AreaId, Badge, CreateWindow, Event, IconId, ItemId, Sender};
+
+const ICON: &[u8] = include_bytes!("tokio.ico");
+
+/// How long the headless check is allowed to run.
+const HEADLESS_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// The position at which recent files are inserted into the popup menu, which
+/// is right after the title and its separator.
+const RECENT_POSITION: u32 = 2;
+
+#[tokio::main]
+async fn main() -> Result<ExitCode> {
+    let headless = env::args().skip(1).any(|a| a == "--headless-check");
+
+    let mut window = CreateWindow::new("se.tedro.StatusApp")
+        .window_name("Status Application")
+        .app_version(env!("CARGO_PKG_VERSION"))
+        .clipboard_events(true);
+
+    let light = window.icons().insert_buffer(ICON, 22, 22);
+    let dark = window.icons().insert_buffer(ICON, 16, 16);
+
+    let area = window.new_area().icon(light).tooltip("Status Application");
+    let area_id = area.id();
+
+    let menu = area.popup_menu();
+    let title = menu.push_entry("Status Application").id();
+    menu.push_separator();
+
+    let transfers = menu.group("transfers");
+    let upload = menu.push_entry("Upload").group(transfers).id();
+    let download = menu.push_entry("Download").group(transfers).id();
+    let fail = menu
+        .push_entry("Simulate failed transfer")
+        .group(transfers)
+        .id();
+    let toggle_transfers = menu.push_entry("Transfers enabled").checked(true).id();
+    menu.push_separator();
+
+    let toggle_account = menu.push_entry("Show account area").id();
+    let add_recent = menu.push_entry("Add recent file").id();
+    let clear_recent = menu.push_entry("Clear recent files").id();
+    menu.push_separator();
+
+    let notify = menu.push_entry("Send notification").id();
+    let mark_read = menu.push_entry("Mark all as read").id();
+    let schedule = menu.push_entry("Shut down in 30 seconds").id();
+    let cancel = menu.push_entry("Cancel scheduled shutdown").id();
+    menu.push_separator();
+    let quit = menu.push_entry("Quit").id();
+    menu.set_default(title);
+
+    let (sender, mut event_loop) = window.build().await?;
+
+    let mut app = App {
+        sender,
+        area_id,
+        transfers_enabled: true,
+        dark_icon: false,
+        recent: Vec::new(),
+        account: None,
+        unread: 0,
+        shutdown_at: None,
+        errors: 0,
+    };
+
+    if headless {
+        app.sender
+            .modify_area(area_id)
+            .tooltip("Headless check")
+            .send()?;
+        app.sender.modify_area(area_id).icon(dark).send()?;
+        app.sender.modify_menu_item(title).highlight(true).send()?;
+        app.sender.modify_group(transfers).enabled(false).send()?;
+        app.sender.modify_group(transfers).enabled(true).send()?;
+
+        let recent = app
+            .sender
+            .insert_menu_item(area_id, RECENT_POSITION, "recent.txt")
+            .send()?;
+        let separator = app
+            .sender
+            .insert_menu_separator(area_id, RECENT_POSITION + 1)
+            .send()?;
+        app.sender.modify_menu_item(recent).checked(true).send()?;
+        app.sender.remove_menu_item(separator)?;
+        app.sender.remove_menu_item(recent)?;
+
+        let (account, _) = app.new_account_area(dark)?;
+        app.sender
+            .modify_area(account)
+            .tooltip("Headless account")
+            .send()?;
+        app.sender.remove_area(account)?;
+
+        for badge in [
+            Some(Badge::Count(3)),
+            Some(Badge::Count(120)),
+            Some(Badge::Dot),
+        ] {
+            app.sender.modify_area(area_id).badge(badge).send()?;
+        }
+
+        app.sender.modify_area(area_id).badge(None).send()?;
+        app.sender
+            .notification(area_id)
+            .info()
+            .message("Headless check")
+            .send()?;
+        app.sender
+            .notification(area_id)
+            .warning()
+            .message("Headless warning")
+            .send()?;
+        app.sender
+            .notification(area_id)
+            .error()
+            .message("Headless error")
+            .send()?;
+        app.sender.shutdown_after(Duration::from_secs(60))?;
+        app.sender.cancel_scheduled_shutdown()?;
+        app.sender.shutdown_after(Duration::from_millis(500))?;
+    }
+
+    let mut ctrl_c = pin!(ctrl_c());
+    let mut deadline = pin!(time::sleep(HEADLESS_TIMEOUT));
+    let mut icon_timer = time::interval(Duration::from_secs(5));
+    let mut countdown = time::interval(Duration::from_secs(1));
+    let mut shutdown = false;
+
+    loop {
+        let event = tokio::select! {
+            _ = ctrl_c.as_mut(), if !shutdown => {
+                app.sender.shutdown()?;
+                shutdown = true;
+                continue;
+            }
+            _ = deadline.as_mut(), if headless => {
+                bail!("Headless check timed out");
+            }
+            _ = icon_timer.tick(), if !headless => {
+                app.dark_icon = !app.dark_icon;
+                let icon = if app.dark_icon { dark } else { light };
+                app.sender.modify_area(area_id).icon(icon).send()?;
+                continue;
+            }
+            _ = countdown.tick(), if app.shutdown_at.is_some() => {
+                app.update_tooltip()?;
+                continue;
+            }
+            event = event_loop.tick() => {
+                event?
+            }
+        };
+
+        match event {
+            Event::MenuItemClicked { item_id, .. } => {
+                if item_id == upload || item_id == download {
+                    app.sender
+                        .notification(area_id)
+                        .info()
+                        .message(format!("Started transfer: {item_id:?}"))
+                        .send()?;
+                } else if item_id == fail {
+                    app.sender
+                        .notification(area_id)
+                        .error()
+                        .title("Transfer failed")
+                        .message("The connection was reset")
+                        .send()?;
+                } else if item_id == toggle_transfers {
+                    app.transfers_enabled = !app.transfers_enabled;
+
+                    app.sender
+                        .modify_group(transfers)
+                        .enabled(app.transfers_enabled)
+                        .send()?;
+
+                    app.sender
+                        .modify_menu_item(toggle_transfers)
+                        .checked(app.transfers_enabled)
+                        .send()?;
+                } else if item_id == toggle_account {
+                    let show = match app.account.take() {
+                        Some((account, _)) => {
+                            app.sender.remove_area(account)?;
+                            false
+                        }
+                        None => {
+                            app.account = Some(app.new_account_area(dark)?);
+                            true
+                        }
+                    };
+
+                    app.sender
+                        .modify_menu_item(toggle_account)
+                        .checked(show)
+                        .send()?;
+                } else if app.account.is_some_and(|(_, sign_out)| sign_out == item_id) {
+                    if let Some((account, _)) = app.account.take() {
+                        app.sender.remove_area(account)?;
+                    }
+
+                    app.sender
+                        .modify_menu_item(toggle_account)
+                        .checked(false)
+                        .send()?;
+                } else if item_id == add_recent {
+                    let text = format!("recent-{}.txt", app.recent.len() + 1);
+
+                    let item = app
+                        .sender
+                        .insert_menu_item(area_id, RECENT_POSITION, text)
+                        .send()?;
+
+                    app.recent.push(item);
+                } else if item_id == clear_recent {
+                    for item in app.recent.drain(..) {
+                        app.sender.remove_menu_item(item)?;
+                    }
+                } else if app.recent.contains(&item_id) {
+                    println!("Recent file clicked: {item_id:?}");
+                } else if item_id == notify {
+                    app.sender
+                        .notification(area_id)
+                        .title("Status Application")
+                        .message("This is a notification")
+                        .send()?;
+
+                    app.unread += 1;
+                    app.update_badge()?;
+                } else if item_id == mark_read {
+                    app.unread = 0;
+                    app.update_badge()?;
+                } else if item_id == schedule {
+                    app.sender.shutdown_after(Duration::from_secs(30))?;
+
+                    app.sender
+                        .notification(area_id)
+                        .warning()
+                        .message("Shutting down in 30 seconds")
+                        .send()?;
+                } else if item_id == cancel {
+                    app.sender.cancel_scheduled_shutdown()?;
+                } else if item_id == quit {
+                    app.sender.shutdown()?;
+                }
+            }
+            Event::MenuOpened { area_id } => {
+                println!("Menu opened: {area_id:?}");
+            }
+            Event::MenuClosed { area_id } => {
+                println!("Menu closed: {area_id:?}");
+            }
+            Event::IconClicked { area_id, event } => {
+                println!("Icon clicked: {area_id:?}: {event:?}");
+            }
+            Event::IconDoubleClicked { area_id, event } => {
+                println!("Icon double clicked: {area_id:?}: {event:?}");
+            }
+            Event::IconHovered { area_id, entered } => {
+                println!("Icon hovered: {area_id:?}: entered: {entered}");
+            }
+            Event::NotificationClicked { area_id, id, .. } => {
+                println!("Notification clicked: {area_id:?}: {id:?}");
+            }
+            Event::NotificationDismissed { area_id, id } => {
+                println!("Notification dismissed: {area_id:?}: {id:?}");
+            }
+            Event::ShutdownScheduled { at } => {
+                app.shutdown_at = Some(at);
+                app.update_tooltip()?;
+            }
+            Event::ShutdownCancelled {} => {
+                app.shutdown_at = None;
+                app.update_tooltip()?;
+            }
+            Event::Clipboard { event, .. } => match event {
+                ClipboardEvent::Image { width, height, .. } => {
+                    println!("Clipboard image: {width}x{height}");
+                }
+                ClipboardEvent::BitMap(bitmap) => {
+                    println!("Clipboard bitmap: {} bytes", bitmap.len());
+                }
+                ClipboardEvent::Text(text) => {
+                    println!("Clipboard text: {text:?}");
+                }
+                ClipboardEvent::Files(paths) => {
+                    println!("Clipboard files: {paths:?}");
+                }
+                event => {
+                    println!("Unhandled clipboard event: {event:?}");
+                }
+            },
+            Event::CopyData { ty, data, .. } => {
+                println!("Data of type {ty} copied to process: {data:?}");
+            }
+            Event::CopyDataProgress {
+                ty,
+                received,
+                total,
+            } => {
+                println!("Data of type {ty} being copied: {received}/{total} bytes");
+            }
+            #[cfg(feature = "serde")]
+            Event::Message { tag, .. } => {
+                println!("Message received: {tag}");
+            }
+            Event::Arguments { args } => {
+                println!("Arguments forwarded: {args:?}");
+            }
+            Event::AppMessage { id, wparam, lparam } => {
+                println!("App message {id}: {wparam}, {lparam}");
+            }
+            Event::EndSession { reason, critical } => {
+                println!("Session ending: {reason:?} (critical: {critical})");
+            }
+            Event::EndSessionCancelled {} => {
+                println!("Session ending cancelled");
+            }
+            Event::Timer { id } => {
+                println!("Timer elapsed: {id}");
+            }
+            Event::WindowClosed {} => {
+                println!("Window closed");
+            }
+            Event::FilesDropped { paths, position } => {
+                println!("Files dropped at {position:?}: {paths:?}");
+            }
+            Event::Idle { duration } => {
+                println!("User idle for {duration:?}");
+            }
+            Event::Active {} => {
+                println!("User active");
+            }
+            Event::WindowHidden {} => {
+                println!("Window hidden");
+            }
+            Event::Resized { width, height } => {
+                println!("Window resized: {width}x{height}");
+            }
+            Event::Error { error, .. } => {
+                println!("Error: {error}");
+                app.errors += 1;
+            }
+            Event::Shutdown { reason } => {
+                println!("Window shut down: {reason:?}");
+                break;
+            }
+            event => {
+                println!("Unhandled event: {event:?}");
+            }
+        }
+    }
+
+    if app.errors > 0 {
+        println!("{} error(s) reported", app.errors);
+        return Ok(ExitCode::FAILURE);
+    }
+
+    Ok(ExitCode::SUCCESS)
+}
+
+struct App {
+    sender: Sender,
+    area_id: AreaId,
+    transfers_enabled: bool,
+    dark_icon: bool,
+    recent: Vec<ItemId>,
+    /// The account area along with its sign out menu item, if it's shown.
+    account: Option<(AreaId, ItemId)>,
+    /// The number of notifications which haven't been read, shown as a badge.
+    unread: u32,
+    shutdown_at: Option<Instant>,
+    errors: usize,
+}
+
+impl App {
+    /// Add a second notification area, returning it along with its sign out
+    /// menu item.
+    fn new_account_area(&self, icon: IconId) -> Result<(AreaId, ItemId)> {
+        let mut area = self.sender.new_area();
+        area.icon(icon).tooltip("john@example.com");
+        let sign_out = area.popup_menu().push_entry("Sign out").id();
+        let area_id = area.send()?;
+        Ok((area_id, sign_out))
+    }
+
+    /// Update the badge to reflect the number of unread notifications.
+    fn update_badge(&self) -> Result<()> {
+        let badge = (self.unread > 0).then_some(Badge::Count(self.unread));
+        self.sender.modify_area(self.area_id).badge(badge).send()?;
+        Ok(())
+    }
+
+    /// Update the tooltip to reflect any scheduled shutdown.
+    fn update_tooltip(&self) -> Result<()> {
+        let tooltip = match self.shutdown_at {
+            Some(at) => {
+                let remaining = at.saturating_duration_since(Instant::now());
+                format!("Shutting down in {} seconds", remaining.as_secs())
+            }
+            None => String::from("Status Application"),
+        };
+
+        self.sender
+            .modify_area(self.area_id)
+            .tooltip(tooltip)
+            .send()?;
+
+        Ok(())
+    }
+}
@@ -2,7 +2,7 @@
 
 use std::fmt;
 
-use crate::{AreaId, IconId, ModifyArea, PopupMenu};
+use crate::{AreaId, IconId, ItemId, MenuAction, ModifyArea, PopupMenu};
 
 /// A notification area.
 ///
@@ -12,10 +12,13 @@ use crate::{AreaId, IconId, ModifyArea, PopupMenu};
 ///
 /// Note that if an area doesn't have the icon, it will still be added to the
 /// notification tray but with an empty space.
+#[derive(Debug)]
 pub struct Area {
     pub(super) id: AreaId,
     pub(super) popup_menu: Option<PopupMenu>,
     pub(super) initial: ModifyArea,
+    pub(super) take_foreground: bool,
+    pub(super) double_click_default: bool,
 }
 
 impl Area {
@@ -30,6 +33,8 @@ impl Area {
             id: area_id,
             popup_menu: None,
             initial: ModifyArea::default(),
+            take_foreground: true,
+            double_click_default: false,
         }
     }
 
@@ -38,6 +43,22 @@ impl Area {
         self.id
     }
 
+    /// The number of menu item identifiers allocated by the popup menu of the
+    /// area.
+    pub(crate) fn menu_item_ids(&self) -> u32 {
+        self.popup_menu
+            .as_ref()
+            .map_or(0, |popup_menu| popup_menu.next_id)
+    }
+
+    /// Actions associated with the menu items of the popup menu of the area.
+    pub(crate) fn menu_actions(&self) -> impl Iterator<Item = (ItemId, MenuAction)> + '_ {
+        self.popup_menu
+            .iter()
+            .flat_map(|popup_menu| &popup_menu.menu)
+            .filter_map(|item| Some((item.item_id, item.action.clone()?)))
+    }
+
     /// Set the icon of the notification area.
     #[inline]
     pub fn icon(&mut self, icon: IconId) -> &mut Self {
@@ -55,6 +76,83 @@ impl Area {
         self
     }
 
+    /// Set whether the standard tooltip is shown when hovering the icon.
+    ///
+    /// By default this is `true`. Disabling it allows applications to render
+    /// their own flyout in response to [`Event::IconHovered`] instead. The
+    /// tooltip text is still used by accessibility tools such as screen
+    /// readers.
+    ///
+    /// [`Event::IconHovered`]: crate::Event::IconHovered
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use winctx::CreateWindow;
+    ///
+    /// let mut window = CreateWindow::new("se.tedro.Example");
+    /// window.new_area().tooltip("Example Application").show_tooltip(false);
+    /// ```
+    #[inline]
+    pub fn show_tooltip(&mut self, show_tooltip: bool) -> &mut Self {
+        self.initial.show_tooltip(show_tooltip);
+        self
+    }
+
+    /// Set whether the window should be brought to the foreground when the
+    /// popup menu of this area is opened.
+    ///
+    /// By default this is `true`, which is what allows the popup menu to be
+    /// dismissed by clicking outside of it. Disabling this leaves focus with
+    /// whatever application currently has it, at the cost of the menu only
+    /// being dismissed by pressing escape or selecting an item.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use winctx::CreateWindow;
+    ///
+    /// let mut window = CreateWindow::new("se.tedro.Example");;
+    /// let area = window.new_area().take_foreground(false);
+    /// area.popup_menu().push_entry("Example Application");
+    /// ```
+    #[inline]
+    pub fn take_foreground(&mut self, take_foreground: bool) -> &mut Self {
+        self.take_foreground = take_foreground;
+        self
+    }
+
+    /// Set whether double clicking the icon of this area should click the
+    /// default item of its popup menu.
+    ///
+    /// This matches how most applications in the notification tray behave.
+    /// When enabled, a double click emits [`Event::MenuItemClicked`] for the
+    /// item set through [`PopupMenu::set_default`] in addition to
+    /// [`Event::IconDoubleClicked`]. Nothing is clicked if the popup menu
+    /// doesn't have a default item, or if it's disabled.
+    ///
+    /// [`Event::MenuItemClicked`]: crate::Event::MenuItemClicked
+    /// [`Event::IconDoubleClicked`]: crate::Event::IconDoubleClicked
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use winctx::CreateWindow;
+    ///
+    /// let mut window = CreateWindow::new("se.tedro.Example");
+    /// let area = window.new_area().double_click_default(true);
+    ///
+    /// let menu = area.popup_menu();
+    /// let open = menu.push_entry("Open").id();
+    /// menu.push_entry("Quit");
+    /// menu.set_default(open);
+    /// ```
+    #[inline]
+    pub fn double_click_default(&mut self, double_click_default: bool) -> &mut Self {
+        self.double_click_default = double_click_default;
+        self
+    }
+
     /// Set that a popup menu should be used and return a handle to populate it.
     #[inline]
     pub fn popup_menu(&mut self) -> &mut PopupMenu {
@@ -65,3 +163,33 @@ impl Area {
         self.popup_menu.as_mut().unwrap()
     }
 }
+
+/// The bounding rectangle of a notification area icon in screen coordinates.
+///
+/// This is returned by [`Sender::area_rect`].
+///
+/// [`Sender::area_rect`]: crate::Sender::area_rect
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Rect {
+    /// The x coordinate of the left edge.
+    pub left: i32,
+    /// The y coordinate of the top edge.
+    pub top: i32,
+    /// The x coordinate of the right edge.
+    pub right: i32,
+    /// The y coordinate of the bottom edge.
+    pub bottom: i32,
+}
+
+impl Rect {
+    /// The width of the rectangle.
+    pub fn width(&self) -> i32 {
+        self.right - self.left
+    }
+
+    /// The height of the rectangle.
+    pub fn height(&self) -> i32 {
+        self.bottom - self.top
+    }
+}
@@ -0,0 +1,36 @@
+use windows_sys::Win32::UI::WindowsAndMessaging as winuser;
+
+/// How the window requests the attention of the user, as used with
+/// [`Sender::request_attention`].
+///
+/// [`Sender::request_attention`]: crate::Sender::request_attention
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Attention {
+    /// Flash the window and its taskbar button once.
+    Once,
+    /// Flash the window and its taskbar button until the window is brought to
+    /// the foreground.
+    UntilForeground,
+    /// Stop flashing the window.
+    Stop,
+}
+
+impl Attention {
+    /// Get the raw flash flags of the request.
+    pub(crate) fn flags(self) -> winuser::FLASHWINFO_FLAGS {
+        match self {
+            Attention::Once => winuser::FLASHW_ALL,
+            Attention::UntilForeground => winuser::FLASHW_ALL | winuser::FLASHW_TIMERNOFG,
+            Attention::Stop => winuser::FLASHW_STOP,
+        }
+    }
+
+    /// Get the number of times the window is flashed.
+    pub(crate) fn count(self) -> u32 {
+        match self {
+            Attention::Once => 1,
+            _ => 0,
+        }
+    }
+}
@@ -0,0 +1,635 @@
+use std::env::current_exe;
+use std::ffi::{OsStr, OsString};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crate::convert::{decode_escaped_os_str, encode_escaped_os_str};
+use crate::error::ErrorRepr::*;
+use crate::error::{Error, ErrorRepr};
+use crate::registry::{OpenRegistryKey, RegistryKey, RegistryValue};
+use crate::Result;
+
+use super::shortcut;
+use super::task_scheduler::{self, TaskScheduler};
+
+/// The registry key holding programs which are started at logon.
+const RUN_KEY: &str = "Software\\Microsoft\\Windows\\CurrentVersion\\Run";
+
+/// The security identifier of the built-in Users group, which is the
+/// principal of tasks started for every user.
+const USERS_GROUP_SID: &str = "S-1-5-32-545";
+
+/// The registry key in which Windows records whether programs started from
+/// the `Run` key have been disabled, such as through the Task Manager.
+const APPROVED_RUN_KEY: &str =
+    "Software\\Microsoft\\Windows\\CurrentVersion\\Explorer\\StartupApproved\\Run";
+
+/// The registry key in which Windows records whether shortcuts in the Startup
+/// folder have been disabled.
+const APPROVED_STARTUP_FOLDER_KEY: &str =
+    "Software\\Microsoft\\Windows\\CurrentVersion\\Explorer\\StartupApproved\\StartupFolder";
+
+/// Whether a program is installed to start automatically, as returned by
+/// [`AutoStart::status`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AutoStartStatus {
+    /// The program is installed and will be started.
+    Installed,
+    /// The program is installed, but has been disabled by the user such as
+    /// through the Task Manager.
+    Disabled,
+    /// The program is not installed.
+    NotInstalled,
+}
+
+/// The command line which is registered to start automatically, as returned
+/// by [`AutoStart::current_entry`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct AutoStartEntry {
+    /// The executable which is started.
+    pub executable: PathBuf,
+    /// The arguments passed to the executable.
+    pub arguments: Vec<OsString>,
+}
+
+/// The mechanism used to start the program.
+enum Backend {
+    /// An entry in the `Run` key of the registry.
+    Run,
+    /// A logon task registered with the Task Scheduler.
+    TaskScheduler,
+    /// A shortcut in the Startup folder of the start menu.
+    StartupFolder,
+}
+
+/// Helper to register and qeury for a binary to autostart.
+#[non_exhaustive]
+pub struct AutoStart {
+    name: Box<OsStr>,
+    executable: Box<Path>,
+    arguments: Vec<OsString>,
+    backend: Backend,
+    highest_privileges: bool,
+    all_users: bool,
+    delay: Option<Duration>,
+}
+
+impl AutoStart {
+    /// Helper to make the current executable automatically start.
+    pub fn current_exe<N>(name: N) -> Result<Self>
+    where
+        N: AsRef<OsStr>,
+    {
+        let executable = current_exe().map_err(CurrentExecutable)?;
+        Ok(Self::new(name, executable))
+    }
+
+    /// Construct a new auto start helper.
+    ///
+    /// The name should be something suitable for a registry key, like
+    /// `OxidizeBot`. Note that in the registry it is case-insensitive.
+    #[inline]
+    pub fn new<N, E>(name: N, executable: E) -> Self
+    where
+        N: AsRef<OsStr>,
+        E: AsRef<Path>,
+    {
+        Self {
+            name: name.as_ref().into(),
+            executable: executable.as_ref().into(),
+            arguments: Vec::new(),
+            backend: Backend::Run,
+            highest_privileges: false,
+            all_users: false,
+            delay: None,
+        }
+    }
+
+    /// Append arguments to the executable when autostarting.
+    pub fn arguments<A>(&mut self, arguments: A)
+    where
+        A: IntoIterator,
+        A::Item: AsRef<OsStr>,
+    {
+        self.arguments = arguments
+            .into_iter()
+            .map(|a| a.as_ref().to_os_string())
+            .collect();
+    }
+
+    /// Register the program as a logon task with the Task Scheduler instead
+    /// of through the `Run` key of the registry.
+    ///
+    /// This allows for options which the registry can't express, such as
+    /// [`AutoStart::highest_privileges`].
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use winctx::AutoStart;
+    ///
+    /// let mut autostart = AutoStart::current_exe("se.tedro.Example")?;
+    /// autostart.task_scheduler();
+    /// autostart.highest_privileges(true);
+    /// autostart.install()?;
+    /// # Ok::<_, winctx::Error>(())
+    /// ```
+    pub fn task_scheduler(&mut self) {
+        self.backend = Backend::TaskScheduler;
+    }
+
+    /// Register the program as a shortcut in the Startup folder of the start
+    /// menu instead of through the `Run` key of the registry.
+    ///
+    /// Shortcuts are visible to users who audit which programs are started
+    /// from Explorer.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use winctx::AutoStart;
+    ///
+    /// let mut autostart = AutoStart::current_exe("se.tedro.Example")?;
+    /// autostart.startup_folder();
+    /// autostart.install()?;
+    /// # Ok::<_, winctx::Error>(())
+    /// ```
+    pub fn startup_folder(&mut self) {
+        self.backend = Backend::StartupFolder;
+    }
+
+    /// Start the program for every user who logs on, rather than only for the
+    /// current user.
+    ///
+    /// The program is registered under the `Run` key of the
+    /// `HKEY_LOCAL_MACHINE` registry, in the Startup folder shared by all
+    /// users, or with the Task Scheduler as a task which is triggered when
+    /// any user logs on. Installing or uninstalling
+    /// it requires the current process to be elevated, and if it isn't an
+    /// error is returned which indicates that elevation is required.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use winctx::AutoStart;
+    ///
+    /// let mut autostart = AutoStart::current_exe("se.tedro.Example")?;
+    /// autostart.all_users(true);
+    /// autostart.install()?;
+    /// # Ok::<_, winctx::Error>(())
+    /// ```
+    pub fn all_users(&mut self, all_users: bool) {
+        self.all_users = all_users;
+    }
+
+    /// Run the program with the highest privileges available to the user.
+    ///
+    /// This only has an effect when the program is registered with the Task
+    /// Scheduler through [`AutoStart::task_scheduler`], and installing such a
+    /// task requires the current process to be elevated.
+    pub fn highest_privileges(&mut self, highest_privileges: bool) {
+        self.highest_privileges = highest_privileges;
+    }
+
+    /// Delay starting the program by the given duration after the user logs
+    /// on.
+    ///
+    /// This spreads out the work done at logon when many programs are started
+    /// automatically. It only has an effect when the program is registered
+    /// with the Task Scheduler through [`AutoStart::task_scheduler`], and the
+    /// delay is rounded down to whole seconds.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use std::time::Duration;
+    ///
+    /// use winctx::AutoStart;
+    ///
+    /// let mut autostart = AutoStart::current_exe("se.tedro.Example")?;
+    /// autostart.task_scheduler();
+    /// autostart.delay(Duration::from_secs(30));
+    /// autostart.install()?;
+    /// # Ok::<_, winctx::Error>(())
+    /// ```
+    pub fn delay(&mut self, delay: Duration) {
+        self.delay = Some(delay);
+    }
+}
+
+impl AutoStart {
+    /// Entry for automatic startup.
+    fn registry_entry(&self) -> Result<String> {
+        let mut entry = String::new();
+
+        encode_escaped_os_str(&mut entry, self.executable.as_os_str())
+            .map_err(BadAutoStartExecutable)?;
+
+        for argument in &self.arguments {
+            entry.push(' ');
+            encode_escaped_os_str(&mut entry, argument).map_err(BadAutoStartArgument)?;
+        }
+
+        Ok(entry)
+    }
+
+    /// The escaped arguments to pass to the executable.
+    fn arguments_line(&self) -> Result<String> {
+        let mut arguments = String::new();
+
+        for (n, argument) in self.arguments.iter().enumerate() {
+            if n > 0 {
+                arguments.push(' ');
+            }
+
+            encode_escaped_os_str(&mut arguments, argument).map_err(BadAutoStartArgument)?;
+        }
+
+        Ok(arguments)
+    }
+
+    /// The path to the shortcut in the Startup folder.
+    fn shortcut_path(&self) -> Result<PathBuf> {
+        let folder = shortcut::startup_folder(self.all_users).map_err(StartupShortcut)?;
+        Ok(folder.join(shortcut::file_name(&self.name)))
+    }
+
+    /// The XML definition of the logon task.
+    fn task_xml(&self) -> Result<String> {
+        let mut command = String::new();
+
+        encode_escaped_os_str(&mut command, self.executable.as_os_str())
+            .map_err(BadAutoStartExecutable)?;
+
+        let arguments = self.arguments_line()?;
+
+        // NB: Without a user the task runs for every user who logs on, so
+        // failing to determine the current user is an error.
+        let user_id = if self.all_users {
+            String::new()
+        } else {
+            let user = task_scheduler::current_user().map_err(TaskScheduler)?;
+            format!("<UserId>{}</UserId>", escape_xml(&user))
+        };
+
+        let delay = match self.delay {
+            Some(delay) if delay.as_secs() > 0 => {
+                format!("<Delay>PT{}S</Delay>", delay.as_secs())
+            }
+            _ => String::new(),
+        };
+
+        let run_level = if self.highest_privileges {
+            "HighestAvailable"
+        } else {
+            "LeastPrivilege"
+        };
+
+        // NB: A task started for every user has to run as whichever user
+        // logged on, rather than as the user who installed it.
+        let principal = if self.all_users {
+            format!("<GroupId>{USERS_GROUP_SID}</GroupId>")
+        } else {
+            String::from("<LogonType>InteractiveToken</LogonType>")
+        };
+
+        Ok(format!(
+            r#"<?xml version="1.0" encoding="UTF-16"?>
+<Task version="1.2" xmlns="http://schemas.microsoft.com/windows/2004/02/mit/task">
+  <Triggers>
+    <LogonTrigger>
+      <Enabled>true</Enabled>
+      {user_id}
+      {delay}
+    </LogonTrigger>
+  </Triggers>
+  <Principals>
+    <Principal id="Author">
+      {principal}
+      <RunLevel>{run_level}</RunLevel>
+    </Principal>
+  </Principals>
+  <Settings>
+    <DisallowStartIfOnBatteries>false</DisallowStartIfOnBatteries>
+    <StopIfGoingOnBatteries>false</StopIfGoingOnBatteries>
+    <ExecutionTimeLimit>PT0S</ExecutionTimeLimit>
+    <MultipleInstancesPolicy>IgnoreNew</MultipleInstancesPolicy>
+  </Settings>
+  <Actions Context="Author">
+    <Exec>
+      <Command>{command}</Command>
+      <Arguments>{arguments}</Arguments>
+    </Exec>
+  </Actions>
+</Task>"#,
+            command = escape_xml(&command),
+            arguments = escape_xml(&arguments),
+        ))
+    }
+
+    /// Open the `Run` key which the program is registered in.
+    fn run_key(&self, set_value: bool) -> Result<RegistryKey> {
+        let open = if self.all_users {
+            OpenRegistryKey::local_machine()
+        } else {
+            OpenRegistryKey::current_user()
+        };
+
+        let open = if set_value { open.set_value() } else { open };
+
+        open.open(RUN_KEY)
+            .map_err(|e| self.elevation_error(e, OpenRegistryKey))
+    }
+
+    /// Convert an error, indicating that elevation is required if access to
+    /// something shared by all users was denied.
+    fn elevation_error(&self, e: io::Error, kind: fn(io::Error) -> ErrorRepr) -> Error {
+        if self.all_users && e.kind() == io::ErrorKind::PermissionDenied {
+            return Error::new(ElevationRequired(e));
+        }
+
+        Error::new(kind(e))
+    }
+
+    /// The name of the logon task.
+    fn task_name(&self) -> String {
+        self.name.to_string_lossy().into_owned()
+    }
+
+    /// If the program is installed to run at startup.
+    pub fn is_installed(&self) -> Result<bool> {
+        if let Backend::TaskScheduler = self.backend {
+            let scheduler = TaskScheduler::connect().map_err(TaskScheduler)?;
+            let enabled = scheduler
+                .enabled(&self.task_name())
+                .map_err(TaskScheduler)?;
+            return Ok(enabled.is_some());
+        }
+
+        if let Backend::StartupFolder = self.backend {
+            let (executable, arguments) = match shortcut::read(&self.shortcut_path()?) {
+                Ok(shortcut) => shortcut,
+                Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(false),
+                Err(e) => return Err(Error::new(StartupShortcut(e))),
+            };
+
+            return Ok(
+                *executable == *self.executable && arguments == self.arguments_line()?.as_str()
+            );
+        }
+
+        let key = self.run_key(false)?;
+
+        let path = match key.get_string(&self.name) {
+            Ok(path) => path,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(false),
+            Err(e) => return Err(Error::new(GetRegistryValue(e))),
+        };
+
+        Ok(self.registry_entry()?.as_str() == path)
+    }
+
+    /// Get the command line which is currently registered to start
+    /// automatically under the name of this helper, or `None` if nothing is
+    /// registered.
+    ///
+    /// This can be used to show what is registered, or to detect that the
+    /// registered executable no longer matches the current one such as after
+    /// it has been moved.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use winctx::AutoStart;
+    ///
+    /// let autostart = AutoStart::current_exe("se.tedro.Example")?;
+    ///
+    /// if let Some(entry) = autostart.current_entry()? {
+    ///     if entry.executable != std::env::current_exe()? {
+    ///         println!("Registered executable has moved: {}", entry.executable.display());
+    ///     }
+    /// }
+    /// # Ok::<_, Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn current_entry(&self) -> Result<Option<AutoStartEntry>> {
+        let command_line = match self.backend {
+            Backend::TaskScheduler => {
+                let scheduler = TaskScheduler::connect().map_err(TaskScheduler)?;
+
+                let Some(xml) = scheduler.xml(&self.task_name()).map_err(TaskScheduler)? else {
+                    return Ok(None);
+                };
+
+                let mut command_line = unescape_xml(xml_element(&xml, "Command"));
+                command_line.push(' ');
+                command_line.push_str(&unescape_xml(xml_element(&xml, "Arguments")));
+                OsString::from(command_line)
+            }
+            Backend::StartupFolder => {
+                let (executable, arguments) = match shortcut::read(&self.shortcut_path()?) {
+                    Ok(shortcut) => shortcut,
+                    Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+                    Err(e) => return Err(Error::new(StartupShortcut(e))),
+                };
+
+                return Ok(Some(AutoStartEntry {
+                    executable,
+                    arguments: decode_escaped_os_str(&arguments),
+                }));
+            }
+            Backend::Run => {
+                let key = self.run_key(false)?;
+
+                match key.get_string(&self.name) {
+                    Ok(command_line) => command_line,
+                    Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+                    Err(e) => return Err(Error::new(GetRegistryValue(e))),
+                }
+            }
+        };
+
+        let mut parts = decode_escaped_os_str(&command_line).into_iter();
+
+        let Some(executable) = parts.next() else {
+            return Ok(None);
+        };
+
+        Ok(Some(AutoStartEntry {
+            executable: PathBuf::from(executable),
+            arguments: parts.collect(),
+        }))
+    }
+
+    /// Get whether the program is installed to run at startup, taking into
+    /// account whether the user has disabled it.
+    ///
+    /// Unlike [`AutoStart::is_installed`], this reports
+    /// [`AutoStartStatus::Disabled`] if Windows won't start the program even
+    /// though it is installed.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use winctx::{AutoStart, AutoStartStatus};
+    ///
+    /// let autostart = AutoStart::current_exe("se.tedro.Example")?;
+    ///
+    /// if autostart.status()? == AutoStartStatus::Disabled {
+    ///     println!("Startup has been disabled through the Task Manager");
+    /// }
+    /// # Ok::<_, winctx::Error>(())
+    /// ```
+    pub fn status(&self) -> Result<AutoStartStatus> {
+        if let Backend::TaskScheduler = self.backend {
+            let scheduler = TaskScheduler::connect().map_err(TaskScheduler)?;
+
+            let status = match scheduler
+                .enabled(&self.task_name())
+                .map_err(TaskScheduler)?
+            {
+                Some(true) => AutoStartStatus::Installed,
+                Some(false) => AutoStartStatus::Disabled,
+                None => AutoStartStatus::NotInstalled,
+            };
+
+            return Ok(status);
+        }
+
+        if !self.is_installed()? {
+            return Ok(AutoStartStatus::NotInstalled);
+        }
+
+        let (key, name) = match self.backend {
+            Backend::StartupFolder => {
+                (APPROVED_STARTUP_FOLDER_KEY, shortcut::file_name(&self.name))
+            }
+            _ => (APPROVED_RUN_KEY, self.name.to_os_string()),
+        };
+
+        if self.is_disabled(key, &name)? {
+            return Ok(AutoStartStatus::Disabled);
+        }
+
+        Ok(AutoStartStatus::Installed)
+    }
+
+    /// Test if the given startup item has been marked as disabled.
+    fn is_disabled(&self, key: &str, name: &OsStr) -> Result<bool> {
+        let open = if self.all_users {
+            OpenRegistryKey::local_machine()
+        } else {
+            OpenRegistryKey::current_user()
+        };
+
+        let key = match open.open(key) {
+            Ok(key) => key,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(false),
+            Err(e) => return Err(Error::new(OpenRegistryKey(e))),
+        };
+
+        let value = match key.get_value(name) {
+            Ok(value) => value,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(false),
+            Err(e) => return Err(Error::new(GetRegistryValue(e))),
+        };
+
+        // NB: The first byte of the value is even if the item is enabled, and
+        // odd if it has been disabled.
+        let RegistryValue::Binary(data) = value else {
+            return Ok(false);
+        };
+
+        Ok(data.first().is_some_and(|b| b % 2 == 1))
+    }
+
+    /// Install the current executable to be automatically started.
+    pub fn install(&self) -> Result<()> {
+        if let Backend::TaskScheduler = self.backend {
+            let scheduler = TaskScheduler::connect().map_err(TaskScheduler)?;
+            scheduler
+                .register(&self.task_name(), &self.task_xml()?, self.all_users)
+                .map_err(|e| self.elevation_error(e, TaskScheduler))?;
+            return Ok(());
+        }
+
+        if let Backend::StartupFolder = self.backend {
+            let arguments = self.arguments_line()?;
+            shortcut::create(&self.shortcut_path()?, &self.executable, &arguments)
+                .map_err(|e| self.elevation_error(e, StartupShortcut))?;
+            return Ok(());
+        }
+
+        let key = self.run_key(true)?;
+        key.set(&self.name, self.registry_entry()?)
+            .map_err(SetRegistryKey)?;
+        Ok(())
+    }
+
+    /// Remove the program from automatic startup.
+    pub fn uninstall(&self) -> Result<()> {
+        if let Backend::TaskScheduler = self.backend {
+            let scheduler = TaskScheduler::connect().map_err(TaskScheduler)?;
+            scheduler
+                .delete(&self.task_name())
+                .map_err(|e| self.elevation_error(e, TaskScheduler))?;
+            return Ok(());
+        }
+
+        if let Backend::StartupFolder = self.backend {
+            fs::remove_file(self.shortcut_path()?)
+                .map_err(|e| self.elevation_error(e, StartupShortcut))?;
+            return Ok(());
+        }
+
+        let key = self.run_key(true)?;
+        key.delete(&self.name).map_err(DeleteRegistryKey)?;
+        Ok(())
+    }
+}
+
+/// Get the text of the first element with the given name in an XML document,
+/// or an empty string if there is no such element.
+fn xml_element<'a>(xml: &'a str, name: &str) -> &'a str {
+    let open = format!("<{name}>");
+    let close = format!("</{name}>");
+
+    let Some(start) = xml.find(&open).map(|n| n + open.len()) else {
+        return "";
+    };
+
+    match xml[start..].find(&close) {
+        Some(end) => &xml[start..start + end],
+        None => "",
+    }
+}
+
+/// Unescape XML text, the inverse of [`escape_xml`].
+fn unescape_xml(string: &str) -> String {
+    string
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+/// Escape a string for use in XML text.
+fn escape_xml(string: &str) -> String {
+    let mut out = String::with_capacity(string.len());
+
+    for c in string.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&apos;"),
+            c => out.push(c),
+        }
+    }
+
+    out
+}
@@ -0,0 +1,6 @@
+pub use self::autostart::{AutoStart, AutoStartEntry, AutoStartStatus};
+mod autostart;
+
+mod shortcut;
+
+mod task_scheduler;
@@ -0,0 +1,162 @@
+//! Shortcuts in the Startup folder of the start menu.
+
+use std::ffi::{c_void, OsStr, OsString};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::ptr;
+
+use windows_sys::core::{GUID, HRESULT, PCWSTR, PWSTR};
+use windows_sys::Win32::Foundation::{BOOL, TRUE};
+use windows_sys::Win32::System::Com::{CoTaskMemFree, STGM_READ};
+use windows_sys::Win32::UI::Shell::{
+    FOLDERID_CommonStartup, FOLDERID_Startup, SHGetKnownFolderPath, KF_FLAG_DEFAULT, SLGP_RAWPATH,
+};
+
+use crate::com::{check, Com, IUnknownVtbl};
+use crate::convert::{FromWide, ToWide};
+
+const CLSID_SHELL_LINK: GUID = GUID::from_u128(0x00021401_0000_0000_c000_000000000046);
+const IID_ISHELL_LINK_W: GUID = GUID::from_u128(0x000214f9_0000_0000_c000_000000000046);
+const IID_IPERSIST_FILE: GUID = GUID::from_u128(0x0000010b_0000_0000_c000_000000000046);
+
+/// The maximum length of the paths and arguments read from a shortcut.
+const MAX_LEN: usize = 32768;
+
+#[repr(C)]
+struct IShellLinkWVtbl {
+    base: IUnknownVtbl,
+    get_path: unsafe extern "system" fn(*mut c_void, PWSTR, i32, *mut c_void, u32) -> HRESULT,
+    get_id_list: usize,
+    set_id_list: usize,
+    get_description: usize,
+    set_description: usize,
+    get_working_directory: usize,
+    set_working_directory: unsafe extern "system" fn(*mut c_void, PCWSTR) -> HRESULT,
+    get_arguments: unsafe extern "system" fn(*mut c_void, PWSTR, i32) -> HRESULT,
+    set_arguments: unsafe extern "system" fn(*mut c_void, PCWSTR) -> HRESULT,
+    get_hotkey: usize,
+    set_hotkey: usize,
+    get_show_cmd: usize,
+    set_show_cmd: usize,
+    get_icon_location: usize,
+    set_icon_location: usize,
+    set_relative_path: usize,
+    resolve: usize,
+    set_path: unsafe extern "system" fn(*mut c_void, PCWSTR) -> HRESULT,
+}
+
+#[repr(C)]
+struct IPersistFileVtbl {
+    base: IUnknownVtbl,
+    get_class_id: usize,
+    is_dirty: usize,
+    load: unsafe extern "system" fn(*mut c_void, PCWSTR, u32) -> HRESULT,
+    save: unsafe extern "system" fn(*mut c_void, PCWSTR, BOOL) -> HRESULT,
+}
+
+/// Get the Startup folder of the current user, or of all users.
+pub(super) fn startup_folder(all_users: bool) -> io::Result<PathBuf> {
+    let id = if all_users {
+        &FOLDERID_CommonStartup
+    } else {
+        &FOLDERID_Startup
+    };
+
+    unsafe {
+        let mut path = ptr::null_mut();
+        let result = check(SHGetKnownFolderPath(
+            id,
+            KF_FLAG_DEFAULT as u32,
+            0,
+            &mut path,
+        ));
+
+        // NB: The path has to be freed even if the call fails.
+        let folder = result.map(|()| PathBuf::from(OsString::from_wide(wide(path))));
+        CoTaskMemFree(path.cast());
+        folder
+    }
+}
+
+/// Create or replace a shortcut which starts the given executable.
+pub(super) fn create(path: &Path, executable: &Path, arguments: &str) -> io::Result<()> {
+    let com = Com::new()?;
+
+    unsafe {
+        let link = com.create::<IShellLinkWVtbl>(&CLSID_SHELL_LINK, &IID_ISHELL_LINK_W)?;
+
+        let executable_wide = executable.to_wide_null();
+        check((link.set_path)(link.as_raw(), executable_wide.as_ptr()))?;
+
+        let arguments = arguments.to_wide_null();
+        check((link.set_arguments)(link.as_raw(), arguments.as_ptr()))?;
+
+        if let Some(parent) = executable.parent() {
+            let parent = parent.to_wide_null();
+            check((link.set_working_directory)(link.as_raw(), parent.as_ptr()))?;
+        }
+
+        let file = link.cast::<IPersistFileVtbl>(&IID_IPERSIST_FILE)?;
+        let path = path.to_wide_null();
+        check((file.save)(file.as_raw(), path.as_ptr(), TRUE))?;
+    }
+
+    Ok(())
+}
+
+/// Read the executable and arguments which the given shortcut starts.
+pub(super) fn read(path: &Path) -> io::Result<(PathBuf, OsString)> {
+    let com = Com::new()?;
+
+    unsafe {
+        let link = com.create::<IShellLinkWVtbl>(&CLSID_SHELL_LINK, &IID_ISHELL_LINK_W)?;
+
+        let file = link.cast::<IPersistFileVtbl>(&IID_IPERSIST_FILE)?;
+        let path = path.to_wide_null();
+        check((file.load)(file.as_raw(), path.as_ptr(), STGM_READ))?;
+
+        let mut buf = vec![0u16; MAX_LEN];
+
+        check((link.get_path)(
+            link.as_raw(),
+            buf.as_mut_ptr(),
+            buf.len() as i32,
+            ptr::null_mut(),
+            SLGP_RAWPATH as u32,
+        ))?;
+
+        let executable = PathBuf::from(OsString::from_wide(until_null(&buf)));
+
+        check((link.get_arguments)(
+            link.as_raw(),
+            buf.as_mut_ptr(),
+            buf.len() as i32,
+        ))?;
+
+        let arguments = OsString::from_wide(until_null(&buf));
+        Ok((executable, arguments))
+    }
+}
+
+/// Get the name of the shortcut file for the given name.
+pub(super) fn file_name(name: &OsStr) -> OsString {
+    let mut file_name = name.to_os_string();
+    file_name.push(".lnk");
+    file_name
+}
+
+fn until_null(buf: &[u16]) -> &[u16] {
+    let len = buf.iter().position(|&c| c == 0).unwrap_or(buf.len());
+    &buf[..len]
+}
+
+/// Get a null-terminated wide string as a slice.
+unsafe fn wide<'a>(ptr: *const u16) -> &'a [u16] {
+    let mut len = 0;
+
+    while *ptr.add(len) != 0 {
+        len += 1;
+    }
+
+    std::slice::from_raw_parts(ptr, len)
+}
@@ -0,0 +1,248 @@
+//! Registration of logon tasks through the Task Scheduler.
+
+use std::ffi::c_void;
+use std::io;
+use std::mem::zeroed;
+use std::ptr;
+
+use windows_sys::core::{BSTR, GUID, HRESULT};
+use windows_sys::Win32::Foundation::VARIANT_BOOL;
+use windows_sys::Win32::Security::Authentication::Identity::{GetUserNameExW, NameSamCompatible};
+use windows_sys::Win32::System::Variant::VARIANT;
+
+use crate::com::{check, Bstr, Com, ComPtr, IDispatchVtbl};
+
+const CLSID_TASK_SCHEDULER: GUID = GUID::from_u128(0x0f87369f_a4e5_4cfc_bd3e_73e6154572dd);
+const IID_ITASK_SERVICE: GUID = GUID::from_u128(0x2faba4c7_4da9_4013_9697_20cc3fd40f85);
+
+/// Create the task or update it if it already exists.
+const TASK_CREATE_OR_UPDATE: i32 = 6;
+/// Run the task as the registering user, only while they're logged on.
+const TASK_LOGON_INTERACTIVE_TOKEN: i32 = 3;
+/// Run the task as whichever member of the group of the principal is logged
+/// on.
+const TASK_LOGON_GROUP: i32 = 4;
+
+#[repr(C)]
+struct ITaskServiceVtbl {
+    base: IDispatchVtbl,
+    get_folder: unsafe extern "system" fn(*mut c_void, BSTR, *mut *mut c_void) -> HRESULT,
+    get_running_tasks: usize,
+    new_task: usize,
+    connect: unsafe extern "system" fn(*mut c_void, VARIANT, VARIANT, VARIANT, VARIANT) -> HRESULT,
+}
+
+#[repr(C)]
+struct ITaskFolderVtbl {
+    base: IDispatchVtbl,
+    get_name: usize,
+    get_path: usize,
+    get_folder: usize,
+    get_folders: usize,
+    create_folder: usize,
+    delete_folder: usize,
+    get_task: unsafe extern "system" fn(*mut c_void, BSTR, *mut *mut c_void) -> HRESULT,
+    get_tasks: usize,
+    delete_task: unsafe extern "system" fn(*mut c_void, BSTR, i32) -> HRESULT,
+    #[allow(clippy::type_complexity)]
+    register_task: unsafe extern "system" fn(
+        *mut c_void,
+        BSTR,
+        BSTR,
+        i32,
+        VARIANT,
+        VARIANT,
+        i32,
+        VARIANT,
+        *mut *mut c_void,
+    ) -> HRESULT,
+}
+
+#[repr(C)]
+struct IRegisteredTaskVtbl {
+    base: IDispatchVtbl,
+    get_name: usize,
+    get_path: usize,
+    get_state: usize,
+    get_enabled: unsafe extern "system" fn(*mut c_void, *mut VARIANT_BOOL) -> HRESULT,
+    put_enabled: usize,
+    run: usize,
+    run_ex: usize,
+    get_instances: usize,
+    get_last_run_time: usize,
+    get_last_task_result: usize,
+    get_number_of_missed_runs: usize,
+    get_next_run_time: usize,
+    get_definition: usize,
+    get_xml: unsafe extern "system" fn(*mut c_void, *mut BSTR) -> HRESULT,
+}
+
+/// A connection to the root folder of the Task Scheduler.
+pub(super) struct TaskScheduler {
+    folder: ComPtr<ITaskFolderVtbl>,
+    // NB: Must be dropped last.
+    _com: Com,
+}
+
+impl TaskScheduler {
+    /// Connect to the Task Scheduler on the local machine.
+    pub(super) fn connect() -> io::Result<Self> {
+        let com = Com::new()?;
+
+        unsafe {
+            let service =
+                com.create::<ITaskServiceVtbl>(&CLSID_TASK_SCHEDULER, &IID_ITASK_SERVICE)?;
+
+            check((service.connect)(
+                service.as_raw(),
+                empty(),
+                empty(),
+                empty(),
+                empty(),
+            ))?;
+
+            let root = Bstr::new("\\")?;
+            let mut folder = ptr::null_mut();
+            check((service.get_folder)(
+                service.as_raw(),
+                root.as_raw(),
+                &mut folder,
+            ))?;
+
+            Ok(Self {
+                folder: ComPtr::from_raw(folder),
+                _com: com,
+            })
+        }
+    }
+
+    /// Register a task from its XML definition, replacing any existing task
+    /// with the same name.
+    ///
+    /// If `group` is set, the principal of the task is a group rather than
+    /// the registering user.
+    pub(super) fn register(&self, name: &str, xml: &str, group: bool) -> io::Result<()> {
+        let name = Bstr::new(name)?;
+        let xml = Bstr::new(xml)?;
+
+        let logon_type = if group {
+            TASK_LOGON_GROUP
+        } else {
+            TASK_LOGON_INTERACTIVE_TOKEN
+        };
+
+        unsafe {
+            let mut task = ptr::null_mut();
+
+            check((self.folder.register_task)(
+                self.folder.as_raw(),
+                name.as_raw(),
+                xml.as_raw(),
+                TASK_CREATE_OR_UPDATE,
+                empty(),
+                empty(),
+                logon_type,
+                empty(),
+                &mut task,
+            ))?;
+
+            drop(ComPtr::<IRegisteredTaskVtbl>::from_raw(task));
+        }
+
+        Ok(())
+    }
+
+    /// Test if the task with the given name is enabled, returning `None` if
+    /// it doesn't exist.
+    pub(super) fn enabled(&self, name: &str) -> io::Result<Option<bool>> {
+        let Some(task) = self.task(name)? else {
+            return Ok(None);
+        };
+
+        unsafe {
+            let mut enabled = 0;
+            check((task.get_enabled)(task.as_raw(), &mut enabled))?;
+            Ok(Some(enabled != 0))
+        }
+    }
+
+    /// Get the XML definition of the task with the given name, returning
+    /// `None` if it doesn't exist.
+    pub(super) fn xml(&self, name: &str) -> io::Result<Option<String>> {
+        let Some(task) = self.task(name)? else {
+            return Ok(None);
+        };
+
+        let xml = unsafe {
+            let mut xml = ptr::null();
+            check((task.get_xml)(task.as_raw(), &mut xml))?;
+            Bstr::from_raw(xml)
+        };
+
+        Ok(Some(xml.to_os_string().to_string_lossy().into_owned()))
+    }
+
+    /// Get the registered task with the given name.
+    fn task(&self, name: &str) -> io::Result<Option<ComPtr<IRegisteredTaskVtbl>>> {
+        let name = Bstr::new(name)?;
+
+        unsafe {
+            let mut task = ptr::null_mut();
+
+            let result = check((self.folder.get_task)(
+                self.folder.as_raw(),
+                name.as_raw(),
+                &mut task,
+            ));
+
+            match result {
+                Ok(()) => Ok(Some(ComPtr::from_raw(task))),
+                Err(error) if error.kind() == io::ErrorKind::NotFound => Ok(None),
+                Err(error) => Err(error),
+            }
+        }
+    }
+
+    /// Delete the task with the given name.
+    pub(super) fn delete(&self, name: &str) -> io::Result<()> {
+        let name = Bstr::new(name)?;
+
+        unsafe {
+            check((self.folder.delete_task)(
+                self.folder.as_raw(),
+                name.as_raw(),
+                0,
+            ))
+        }
+    }
+}
+
+/// Get the name of the current user in the `DOMAIN\user` form, which is how
+/// the users of tasks are identified.
+pub(super) fn current_user() -> io::Result<String> {
+    unsafe {
+        // NB: The first call fails and reports the length of the name,
+        // including the terminating null.
+        let mut len = 0;
+        GetUserNameExW(NameSamCompatible, ptr::null_mut(), &mut len);
+
+        if len == 0 {
+            return Err(io::Error::last_os_error());
+        }
+
+        let mut name = vec![0u16; len as usize];
+
+        if GetUserNameExW(NameSamCompatible, name.as_mut_ptr(), &mut len) == 0 {
+            return Err(io::Error::last_os_error());
+        }
+
+        name.truncate(len as usize);
+        Ok(String::from_utf16_lossy(&name))
+    }
+}
+
+/// Construct an empty variant, which is used for optional arguments.
+fn empty() -> VARIANT {
+    // SAFETY: A zeroed variant is VT_EMPTY.
+    unsafe { zeroed() }
+}
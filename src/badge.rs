@@ -0,0 +1,29 @@
+/// A badge drawn on top of an icon, such as to indicate a number of unread
+/// items.
+///
+/// Badges are set on the icon of a notification area using
+/// [`ModifyAreaBuilder::badge`], or on the taskbar button of the window using
+/// [`Sender::set_taskbar_badge`].
+///
+/// [`ModifyAreaBuilder::badge`]: crate::sender::ModifyAreaBuilder::badge
+/// [`Sender::set_taskbar_badge`]: crate::Sender::set_taskbar_badge
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub enum Badge {
+    /// A dot without any text.
+    Dot,
+    /// A count, where counts above 99 are shown as `99+`.
+    Count(u32),
+}
+
+impl Badge {
+    /// Get the text shown in the badge, if any.
+    pub(crate) fn text(self) -> Option<String> {
+        match self {
+            Badge::Dot => None,
+            Badge::Count(count) if count > 99 => Some(String::from("99+")),
+            Badge::Count(count) => Some(count.to_string()),
+        }
+    }
+}
@@ -3,7 +3,7 @@ use std::fmt;
 use windows_sys::Win32::System::Ole as ole;
 
 /// A clipboard format.
-#[derive(Clone, Copy, PartialEq, Eq)]
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
 #[repr(transparent)]
 pub struct ClipboardFormat(u16);
 
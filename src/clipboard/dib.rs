@@ -0,0 +1,382 @@
+//! Decoding of device-independent bitmaps as found on the clipboard.
+
+use windows_sys::Win32::Graphics::Gdi::{BI_BITFIELDS, BI_RGB};
+
+/// The size of a `BITMAPINFOHEADER`.
+const INFO_HEADER_SIZE: usize = 40;
+/// Header size from which the red, green, and blue masks are part of the header.
+const V2_HEADER_SIZE: usize = 52;
+/// Header size from which the alpha mask is part of the header.
+const V3_HEADER_SIZE: usize = 56;
+
+/// A decoded bitmap.
+pub(crate) struct Image {
+    pub(crate) width: u32,
+    pub(crate) height: u32,
+    pub(crate) rgba: Vec<u8>,
+}
+
+/// Decode a `CF_DIB` or `CF_DIBV5` buffer into top-down RGBA pixels.
+///
+/// Supports bit depths of 16, 24 and 32 using either `BI_RGB` or
+/// `BI_BITFIELDS` compression. Returns `None` if the bitmap is malformed or
+/// uses an unsupported format.
+pub(crate) fn decode(data: &[u8]) -> Option<Image> {
+    let header_size = read_u32(data, 0)? as usize;
+
+    if header_size < INFO_HEADER_SIZE {
+        return None;
+    }
+
+    let width = read_i32(data, 4)?;
+    let height = read_i32(data, 8)?;
+    let bit_count = read_u16(data, 14)?;
+    let compression = read_u32(data, 16)?;
+    let colors_used = read_u32(data, 32)? as usize;
+
+    if width <= 0 || height == 0 {
+        return None;
+    }
+
+    let top_down = height < 0;
+    let width = width as u32;
+    let height = height.unsigned_abs();
+
+    let mut offset = header_size;
+
+    let masks = match (compression, bit_count) {
+        (BI_RGB, 16) => Masks::new(0x7c00, 0x03e0, 0x001f, 0),
+        (BI_RGB, 24) => Masks::new(0xff0000, 0xff00, 0xff, 0),
+        (BI_RGB, 32) => Masks::new(0xff0000, 0xff00, 0xff, 0),
+        (BI_BITFIELDS, 16 | 32) => {
+            if header_size >= V2_HEADER_SIZE {
+                let alpha = if header_size >= V3_HEADER_SIZE {
+                    read_u32(data, 52)?
+                } else {
+                    0
+                };
+
+                Masks::new(
+                    read_u32(data, 40)?,
+                    read_u32(data, 44)?,
+                    read_u32(data, 48)?,
+                    alpha,
+                )
+            } else {
+                let masks = Masks::new(
+                    read_u32(data, offset)?,
+                    read_u32(data, offset + 4)?,
+                    read_u32(data, offset + 8)?,
+                    0,
+                );
+
+                offset += 12;
+                masks
+            }
+        }
+        _ => return None,
+    };
+
+    // Skip over any optional color table.
+    offset = offset.checked_add(colors_used.checked_mul(4)?)?;
+
+    let bytes_per_pixel = usize::from(bit_count / 8);
+    let stride = (width as usize)
+        .checked_mul(usize::from(bit_count))?
+        .checked_add(31)?
+        / 32
+        * 4;
+
+    let pixels = data.get(offset..)?;
+
+    if pixels.len() < stride.checked_mul(height as usize)? {
+        return None;
+    }
+
+    let mut rgba = Vec::with_capacity(
+        (width as usize)
+            .checked_mul(height as usize)?
+            .checked_mul(4)?,
+    );
+    let mut any_alpha = false;
+
+    for y in 0..height as usize {
+        let row = if top_down { y } else { height as usize - 1 - y };
+
+        let row = &pixels[row * stride..][..width as usize * bytes_per_pixel];
+
+        for pixel in row.chunks_exact(bytes_per_pixel) {
+            let value = match *pixel {
+                [a, b] => u32::from(u16::from_le_bytes([a, b])),
+                [a, b, c] => u32::from_le_bytes([a, b, c, 0]),
+                [a, b, c, d] => u32::from_le_bytes([a, b, c, d]),
+                _ => return None,
+            };
+
+            let alpha = masks.alpha.extract(value).unwrap_or(u8::MAX);
+            any_alpha |= alpha != 0;
+
+            rgba.extend_from_slice(&[
+                masks.red.extract(value).unwrap_or(0),
+                masks.green.extract(value).unwrap_or(0),
+                masks.blue.extract(value).unwrap_or(0),
+                alpha,
+            ]);
+        }
+    }
+
+    // Some applications specify an alpha mask without populating it, in which
+    // case the image is treated as opaque.
+    if !any_alpha {
+        for pixel in rgba.chunks_exact_mut(4) {
+            pixel[3] = u8::MAX;
+        }
+    }
+
+    Some(Image {
+        width,
+        height,
+        rgba,
+    })
+}
+
+struct Masks {
+    red: Mask,
+    green: Mask,
+    blue: Mask,
+    alpha: Mask,
+}
+
+impl Masks {
+    fn new(red: u32, green: u32, blue: u32, alpha: u32) -> Self {
+        Self {
+            red: Mask(red),
+            green: Mask(green),
+            blue: Mask(blue),
+            alpha: Mask(alpha),
+        }
+    }
+}
+
+struct Mask(u32);
+
+impl Mask {
+    /// Extract the masked component from the given value, scaled to 8 bits.
+    fn extract(&self, value: u32) -> Option<u8> {
+        if self.0 == 0 {
+            return None;
+        }
+
+        let bits = self.0.count_ones();
+        let value = (value & self.0) >> self.0.trailing_zeros();
+
+        if bits >= 8 {
+            return Some((value >> (bits - 8)) as u8);
+        }
+
+        let max = (1u32 << bits) - 1;
+        Some(((value * 255 + max / 2) / max) as u8)
+    }
+}
+
+fn read_u16(data: &[u8], at: usize) -> Option<u16> {
+    Some(u16::from_le_bytes(data.get(at..at + 2)?.try_into().ok()?))
+}
+
+fn read_u32(data: &[u8], at: usize) -> Option<u32> {
+    Some(u32::from_le_bytes(data.get(at..at + 4)?.try_into().ok()?))
+}
+
+fn read_i32(data: &[u8], at: usize) -> Option<i32> {
+    Some(i32::from_le_bytes(data.get(at..at + 4)?.try_into().ok()?))
+}
+
+#[cfg(test)]
+mod tests {
+    use windows_sys::Win32::Graphics::Gdi::{BI_BITFIELDS, BI_RGB};
+
+    use super::{decode, Image, INFO_HEADER_SIZE};
+
+    /// Construct a bitmap header of the given size.
+    fn header(size: usize, width: i32, height: i32, bit_count: u16, compression: u32) -> Vec<u8> {
+        let mut data = vec![0; size];
+        data[0..4].copy_from_slice(&(size as u32).to_le_bytes());
+        data[4..8].copy_from_slice(&width.to_le_bytes());
+        data[8..12].copy_from_slice(&height.to_le_bytes());
+        data[12..14].copy_from_slice(&1u16.to_le_bytes());
+        data[14..16].copy_from_slice(&bit_count.to_le_bytes());
+        data[16..20].copy_from_slice(&compression.to_le_bytes());
+        data
+    }
+
+    fn masks(data: &mut Vec<u8>, masks: &[u32]) {
+        for mask in masks {
+            data.extend_from_slice(&mask.to_le_bytes());
+        }
+    }
+
+    fn pixels(image: &Image) -> Vec<[u8; 4]> {
+        image
+            .rgba
+            .chunks_exact(4)
+            .map(|p| [p[0], p[1], p[2], p[3]])
+            .collect()
+    }
+
+    const RED: [u8; 4] = [255, 0, 0, 255];
+    const GREEN: [u8; 4] = [0, 255, 0, 255];
+    const BLUE: [u8; 4] = [0, 0, 255, 255];
+    const WHITE: [u8; 4] = [255, 255, 255, 255];
+
+    #[test]
+    fn rgb24_bottom_up() {
+        let mut data = header(INFO_HEADER_SIZE, 2, 2, 24, BI_RGB);
+        // Rows are padded to four bytes and stored bottom row first.
+        data.extend_from_slice(&[0, 0, 255, 0, 255, 0, 0, 0]);
+        data.extend_from_slice(&[255, 0, 0, 255, 255, 255, 0, 0]);
+
+        let image = decode(&data).unwrap();
+        assert_eq!((image.width, image.height), (2, 2));
+        assert_eq!(pixels(&image), [BLUE, WHITE, RED, GREEN]);
+    }
+
+    #[test]
+    fn rgb24_top_down() {
+        let mut data = header(INFO_HEADER_SIZE, 2, -2, 24, BI_RGB);
+        data.extend_from_slice(&[0, 0, 255, 0, 255, 0, 0, 0]);
+        data.extend_from_slice(&[255, 0, 0, 255, 255, 255, 0, 0]);
+
+        let image = decode(&data).unwrap();
+        assert_eq!((image.width, image.height), (2, 2));
+        assert_eq!(pixels(&image), [RED, GREEN, BLUE, WHITE]);
+    }
+
+    #[test]
+    fn rgb32() {
+        let mut data = header(INFO_HEADER_SIZE, 2, 1, 32, BI_RGB);
+        // The fourth byte is unused without an alpha mask.
+        data.extend_from_slice(&[0, 0, 255, 0x12, 255, 0, 0, 0x34]);
+
+        let image = decode(&data).unwrap();
+        assert_eq!(pixels(&image), [RED, BLUE]);
+    }
+
+    #[test]
+    fn rgb16() {
+        let mut data = header(INFO_HEADER_SIZE, 3, 1, 16, BI_RGB);
+        // 5-5-5 pixels, padded to eight bytes.
+        data.extend_from_slice(&0x7c00u16.to_le_bytes());
+        data.extend_from_slice(&0x03e0u16.to_le_bytes());
+        data.extend_from_slice(&0x4210u16.to_le_bytes());
+        data.extend_from_slice(&[0, 0]);
+
+        let image = decode(&data).unwrap();
+        assert_eq!(pixels(&image), [RED, GREEN, [132, 132, 132, 255]]);
+    }
+
+    #[test]
+    fn bitfields16() {
+        let mut data = header(INFO_HEADER_SIZE, 2, 1, 16, BI_BITFIELDS);
+        // 5-6-5 masks follow the header.
+        masks(&mut data, &[0xf800, 0x07e0, 0x001f]);
+        data.extend_from_slice(&0x07e0u16.to_le_bytes());
+        data.extend_from_slice(&0x001fu16.to_le_bytes());
+
+        let image = decode(&data).unwrap();
+        assert_eq!(pixels(&image), [GREEN, BLUE]);
+    }
+
+    #[test]
+    fn bitfields32_with_alpha() {
+        // A version 5 header includes the masks.
+        let mut data = header(124, 2, 1, 32, BI_BITFIELDS);
+        data[40..56].copy_from_slice(&[
+            0, 0, 0xff, 0, // red
+            0, 0xff, 0, 0, // green
+            0xff, 0, 0, 0, // blue
+            0, 0, 0, 0xff, // alpha
+        ]);
+        data.extend_from_slice(&[0, 0, 255, 0x80, 255, 0, 0, 0]);
+
+        let image = decode(&data).unwrap();
+        assert_eq!(pixels(&image), [[255, 0, 0, 0x80], [0, 0, 255, 0]]);
+    }
+
+    #[test]
+    fn unpopulated_alpha_is_opaque() {
+        let mut data = header(124, 1, 1, 32, BI_BITFIELDS);
+        data[40..56].copy_from_slice(&[
+            0, 0, 0xff, 0, // red
+            0, 0xff, 0, 0, // green
+            0xff, 0, 0, 0, // blue
+            0, 0, 0, 0xff, // alpha
+        ]);
+        data.extend_from_slice(&[0, 255, 0, 0]);
+
+        let image = decode(&data).unwrap();
+        assert_eq!(pixels(&image), [GREEN]);
+    }
+
+    #[test]
+    fn color_table() {
+        let mut data = header(INFO_HEADER_SIZE, 1, 1, 32, BI_RGB);
+        data[32..36].copy_from_slice(&2u32.to_le_bytes());
+        data.extend_from_slice(&[0xaa; 8]);
+        data.extend_from_slice(&[255, 0, 0, 0]);
+
+        let image = decode(&data).unwrap();
+        assert_eq!(pixels(&image), [BLUE]);
+    }
+
+    #[test]
+    fn invalid() {
+        fn rgb24(width: i32, height: i32) -> Vec<u8> {
+            let mut data = header(INFO_HEADER_SIZE, width, height, 24, BI_RGB);
+            data.extend_from_slice(&[0; 4]);
+            data
+        }
+
+        assert!(decode(&[]).is_none());
+        assert!(decode(&rgb24(1, 1)[..20]).is_none());
+        assert!(decode(&rgb24(1, 1)[..INFO_HEADER_SIZE + 2]).is_none());
+        assert!(decode(&rgb24(0, 1)).is_none());
+        assert!(decode(&rgb24(-1, 1)).is_none());
+        assert!(decode(&rgb24(1, 0)).is_none());
+        assert!(decode(&rgb24(2, 1)).is_none());
+        assert!(decode(&rgb24(1, 2)).is_none());
+        assert!(decode(&rgb24(i32::MAX, i32::MIN)).is_none());
+
+        // Header sizes which are too small or larger than the buffer.
+        let mut data = rgb24(1, 1);
+        data[0..4].copy_from_slice(&12u32.to_le_bytes());
+        assert!(decode(&data).is_none());
+        data[0..4].copy_from_slice(&1024u32.to_le_bytes());
+        assert!(decode(&data).is_none());
+        data[0..4].copy_from_slice(&u32::MAX.to_le_bytes());
+        assert!(decode(&data).is_none());
+
+        // Color tables which don't fit.
+        let mut data = rgb24(1, 1);
+        data[32..36].copy_from_slice(&1u32.to_le_bytes());
+        assert!(decode(&data).is_none());
+        data[32..36].copy_from_slice(&u32::MAX.to_le_bytes());
+        assert!(decode(&data).is_none());
+
+        // Unsupported bit counts and compression.
+        let mut data = header(INFO_HEADER_SIZE, 1, 1, 8, BI_RGB);
+        data.extend_from_slice(&[0; 4]);
+        assert!(decode(&data).is_none());
+        let mut data = header(INFO_HEADER_SIZE, 1, 1, 24, BI_BITFIELDS);
+        masks(&mut data, &[0xff0000, 0xff00, 0xff]);
+        data.extend_from_slice(&[0; 4]);
+        assert!(decode(&data).is_none());
+        let mut data = header(INFO_HEADER_SIZE, 1, 1, 32, 1);
+        data.extend_from_slice(&[0; 4]);
+        assert!(decode(&data).is_none());
+
+        // Bitfields which are missing their masks.
+        let mut data = header(INFO_HEADER_SIZE, 1, 1, 32, BI_BITFIELDS);
+        masks(&mut data, &[0xff0000, 0xff00]);
+        assert!(decode(&data).is_none());
+    }
+}
@@ -1,17 +1,141 @@
-pub(super) use self::clipboard_format::ClipboardFormat;
+//! Types and functions for accessing the system clipboard.
+
+pub use self::clipboard_format::ClipboardFormat;
 mod clipboard_format;
 
-use std::ffi::c_void;
+pub(crate) mod dib;
+
+use std::ffi::{c_void, OsString};
 use std::io;
 use std::marker::PhantomData;
 use std::ops::Range;
+use std::path::PathBuf;
+use std::ptr;
 use std::slice;
 
 use windows_sys::Win32::Foundation::INVALID_HANDLE_VALUE;
 use windows_sys::Win32::Foundation::{FALSE, HANDLE, HWND};
-use windows_sys::Win32::System::DataExchange::GetUpdatedClipboardFormats;
+use windows_sys::Win32::Globalization::{
+    GetLocaleInfoW, MultiByteToWideChar, CP_ACP, LOCALE_IDEFAULTANSICODEPAGE, LOCALE_RETURN_NUMBER,
+};
 use windows_sys::Win32::System::DataExchange::{CloseClipboard, GetClipboardData, OpenClipboard};
+use windows_sys::Win32::System::DataExchange::{
+    EnumClipboardFormats, GetClipboardFormatNameW, GetClipboardSequenceNumber,
+    GetUpdatedClipboardFormats,
+};
 use windows_sys::Win32::System::Memory::{GlobalLock, GlobalSize, GlobalUnlock};
+use windows_sys::Win32::UI::Shell::DragQueryFileW;
+
+use crate::convert::FromWide;
+
+/// Lock the data of the given format on the clipboard and pass it to the
+/// provided closure, without copying it.
+///
+/// This can be used to fetch data on demand, such as after receiving
+/// [`ClipboardEvent::TooLarge`] or [`ClipboardEvent::Changed`]. The data is
+/// only locked for the duration of the closure, so large payloads can be
+/// processed incrementally without first copying them into memory.
+///
+/// This fails if the data is not available in the given format or if the
+/// clipboard is currently in use.
+///
+/// [`ClipboardEvent::TooLarge`]: crate::event::ClipboardEvent::TooLarge
+/// [`ClipboardEvent::Changed`]: crate::event::ClipboardEvent::Changed
+///
+/// # Examples
+///
+/// ```no_run
+/// use winctx::clipboard;
+/// use winctx::ClipboardFormat;
+///
+/// let len = clipboard::with_data(ClipboardFormat::DIBV5, |data| data.len())?;
+/// println!("Bitmap is {len} bytes");
+/// # Ok::<_, std::io::Error>(())
+/// ```
+pub fn with_data<F, O>(format: ClipboardFormat, f: F) -> io::Result<O>
+where
+    F: FnOnce(&[u8]) -> O,
+{
+    // SAFETY: Opening the clipboard without a window handle associates it
+    // with the current task, and fails if someone else has it open.
+    let clipboard = unsafe { Clipboard::new(0)? };
+    let data = clipboard.data(format)?;
+    let lock = data.lock()?;
+    Ok(f(lock.as_slice()))
+}
+
+/// Get the current sequence number of the clipboard, which changes every time
+/// its contents change.
+///
+/// This can be compared against the sequence number in
+/// [`Event::Clipboard`].
+///
+/// [`Event::Clipboard`]: crate::Event::Clipboard
+pub fn sequence_number() -> u32 {
+    Clipboard::sequence_number()
+}
+
+/// Enumerate all formats which are currently available on the clipboard, in
+/// the order that they were placed on it.
+///
+/// Each format is accompanied by its name if it's a registered format.
+/// Standard formats such as [`ClipboardFormat::UNICODETEXT`] have no name, but
+/// can be identified through their [`Debug`] implementation.
+///
+/// # Examples
+///
+/// ```no_run
+/// use winctx::clipboard;
+///
+/// for (format, name) in clipboard::formats()? {
+///     match name {
+///         Some(name) => println!("{format:?}: {name}"),
+///         None => println!("{format:?}"),
+///     }
+/// }
+/// # Ok::<_, std::io::Error>(())
+/// ```
+pub fn formats() -> io::Result<Vec<(ClipboardFormat, Option<String>)>> {
+    // SAFETY: Opening the clipboard without a window handle associates it
+    // with the current task, and fails if someone else has it open.
+    let _clipboard = unsafe { Clipboard::new(0)? };
+
+    let mut formats = Vec::new();
+    let mut format = 0;
+
+    loop {
+        format = unsafe { EnumClipboardFormats(format) };
+
+        if format == 0 {
+            let error = io::Error::last_os_error();
+
+            // NB: The end of the formats is indicated by a successful error
+            // code.
+            if error.raw_os_error() == Some(0) {
+                break;
+            }
+
+            return Err(error);
+        }
+
+        formats.push((ClipboardFormat::new(format as u16), format_name(format)));
+    }
+
+    Ok(formats)
+}
+
+/// Get the name of a registered clipboard format.
+fn format_name(format: u32) -> Option<String> {
+    let mut name = [0u16; 256];
+
+    let len = unsafe { GetClipboardFormatNameW(format, name.as_mut_ptr(), name.len() as i32) };
+
+    if len <= 0 {
+        return None;
+    }
+
+    Some(String::from_utf16_lossy(&name[..len as usize]))
+}
 
 /// An open clipboard handle.
 pub(crate) struct Clipboard;
@@ -35,7 +159,7 @@ impl Clipboard {
         unsafe {
             let mut formats = [0u32; N];
             let mut actual = 0;
-            GetUpdatedClipboardFormats(formats.as_mut_ptr(), 16, &mut actual);
+            GetUpdatedClipboardFormats(formats.as_mut_ptr(), N as u32, &mut actual);
 
             UpdatedFormats {
                 formats,
@@ -44,6 +168,53 @@ impl Clipboard {
         }
     }
 
+    /// Get the sequence number of the clipboard, which changes every time its
+    /// contents change.
+    pub(super) fn sequence_number() -> u32 {
+        unsafe { GetClipboardSequenceNumber() }
+    }
+
+    /// Get the ANSI code page of text on the clipboard.
+    ///
+    /// This is determined by the locale stored in `CF_LOCALE`, and falls back
+    /// to the default ANSI code page of the system.
+    pub(super) fn text_code_page(&self) -> u32 {
+        let Ok(data) = self.data(ClipboardFormat::LOCALE) else {
+            return CP_ACP;
+        };
+
+        let Ok(lock) = data.lock() else {
+            return CP_ACP;
+        };
+
+        let Some(locale) = lock.as_slice().get(..4) else {
+            return CP_ACP;
+        };
+
+        let Ok(locale) = <[u8; 4]>::try_from(locale) else {
+            return CP_ACP;
+        };
+
+        let mut code_page = 0u32;
+
+        // SAFETY: With LOCALE_RETURN_NUMBER the number is written into the
+        // buffer, which has space for two wide characters.
+        let len = unsafe {
+            GetLocaleInfoW(
+                u32::from_ne_bytes(locale),
+                LOCALE_IDEFAULTANSICODEPAGE | LOCALE_RETURN_NUMBER,
+                (&mut code_page as *mut u32).cast(),
+                2,
+            )
+        };
+
+        if len == 0 {
+            return CP_ACP;
+        }
+
+        code_page
+    }
+
     /// Acquire data with the specified format.
     pub(crate) fn data(&self, format: ClipboardFormat) -> io::Result<Data<'_>> {
         // SAFETY: This is safe as long as construction is correct.
@@ -70,6 +241,41 @@ impl Drop for Clipboard {
     }
 }
 
+/// Decode text encoded in the given ANSI code page.
+pub(super) fn decode_ansi(data: &[u8], code_page: u32) -> Option<String> {
+    if data.is_empty() {
+        return Some(String::new());
+    }
+
+    let len = i32::try_from(data.len()).ok()?;
+
+    // SAFETY: The input buffer is valid, and the output buffer is sized
+    // according to what the first call tells us.
+    unsafe {
+        let wide_len = MultiByteToWideChar(code_page, 0, data.as_ptr(), len, ptr::null_mut(), 0);
+
+        if wide_len <= 0 {
+            return None;
+        }
+
+        let mut wide = vec![0u16; wide_len as usize];
+        let wide_len = MultiByteToWideChar(
+            code_page,
+            0,
+            data.as_ptr(),
+            len,
+            wide.as_mut_ptr(),
+            wide_len,
+        );
+
+        if wide_len <= 0 {
+            return None;
+        }
+
+        String::from_utf16(&wide[..wide_len as usize]).ok()
+    }
+}
+
 /// A clipboard data handle.
 pub(super) struct Data<'a> {
     handle: HANDLE,
@@ -93,6 +299,33 @@ impl Data<'_> {
             })
         }
     }
+
+    /// Query the list of files stored in data of the `CF_HDROP` format.
+    pub(super) fn files(&self) -> Vec<PathBuf> {
+        // SAFETY: Construction of Clipboard ensures that this is used
+        // correctly, and the caller ensures that the format is correct.
+        unsafe {
+            let count = DragQueryFileW(self.handle, u32::MAX, ptr::null_mut(), 0);
+
+            let mut files = Vec::with_capacity(count as usize);
+            let mut buf = Vec::new();
+
+            for index in 0..count {
+                let len = DragQueryFileW(self.handle, index, ptr::null_mut(), 0) as usize;
+
+                if len == 0 {
+                    continue;
+                }
+
+                // NB: The returned length does not include the null terminator.
+                buf.resize(len + 1, 0);
+                let len = DragQueryFileW(self.handle, index, buf.as_mut_ptr(), buf.len() as u32);
+                files.push(PathBuf::from(OsString::from_wide(&buf[..len as usize])));
+            }
+
+            files
+        }
+    }
 }
 
 pub(super) struct Lock<'a> {
@@ -0,0 +1,122 @@
+use std::time::Duration;
+
+/// Options for monitoring the clipboard, used with
+/// [`CreateWindow::clipboard_events_with`].
+///
+/// Updates to the clipboard are debounced, and reading the clipboard is
+/// retried for a limited number of times since it might still be in use by the
+/// application which updated it. These can be tuned to poll the clipboard more
+/// aggressively or to be more relaxed about it.
+///
+/// [`CreateWindow::clipboard_events_with`]: crate::CreateWindow::clipboard_events_with
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+///
+/// use winctx::{ClipboardOptions, CreateWindow};
+///
+/// let options = ClipboardOptions::new()
+///     .debounce(Duration::from_millis(100))
+///     .retry_interval(Duration::from_millis(50))
+///     .retry_attempts(20);
+///
+/// let mut builder = CreateWindow::new("se.tedro.Example")
+///     .clipboard_events_with(options);
+/// ```
+#[derive(Debug, Clone)]
+pub struct ClipboardOptions {
+    debounce: Duration,
+    retry_interval: Duration,
+    retry_attempts: usize,
+    max_size: Option<usize>,
+}
+
+impl ClipboardOptions {
+    /// Construct the default clipboard options.
+    ///
+    /// This debounces updates for 25 milliseconds, and retries reading the
+    /// clipboard up to 10 times every 25 milliseconds.
+    pub fn new() -> Self {
+        Self {
+            debounce: Duration::from_millis(25),
+            retry_interval: Duration::from_millis(25),
+            retry_attempts: 10,
+            max_size: None,
+        }
+    }
+
+    /// Set how long to wait after the clipboard has been updated before
+    /// reading it.
+    ///
+    /// Updates which happen in the meantime are coalesced into one.
+    pub fn debounce(self, debounce: Duration) -> Self {
+        Self { debounce, ..self }
+    }
+
+    /// Set how long to wait before retrying to read the clipboard if it could
+    /// not be read.
+    pub fn retry_interval(self, retry_interval: Duration) -> Self {
+        Self {
+            retry_interval,
+            ..self
+        }
+    }
+
+    /// Set the number of times to retry reading the clipboard before giving
+    /// up and reporting an error.
+    pub fn retry_attempts(self, retry_attempts: usize) -> Self {
+        Self {
+            retry_attempts,
+            ..self
+        }
+    }
+
+    /// Set the maximum size in bytes of clipboard data which is copied into
+    /// clipboard events.
+    ///
+    /// Data which is larger than this is reported through
+    /// [`ClipboardEvent::TooLarge`] instead, after which it can be fetched on
+    /// demand using [`clipboard::with_data`]. By default there is no limit.
+    ///
+    /// [`ClipboardEvent::TooLarge`]: crate::event::ClipboardEvent::TooLarge
+    /// [`clipboard::with_data`]: crate::clipboard::with_data
+    pub fn max_size(self, max_size: usize) -> Self {
+        Self {
+            max_size: Some(max_size),
+            ..self
+        }
+    }
+
+    /// The debounce interval in milliseconds.
+    pub(crate) fn debounce_millis(&self) -> u32 {
+        millis(self.debounce)
+    }
+
+    /// The retry interval in milliseconds.
+    pub(crate) fn retry_millis(&self) -> u32 {
+        millis(self.retry_interval)
+    }
+
+    /// The maximum number of retry attempts.
+    pub(crate) fn max_attempts(&self) -> usize {
+        self.retry_attempts
+    }
+
+    /// Test if data of the given size exceeds the maximum size.
+    pub(crate) fn is_too_large(&self, size: usize) -> bool {
+        self.max_size.is_some_and(|max| size > max)
+    }
+}
+
+impl Default for ClipboardOptions {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn millis(duration: Duration) -> u32 {
+    u32::try_from(duration.as_millis()).unwrap_or(u32::MAX)
+}
@@ -0,0 +1,223 @@
+//! Minimal helpers for calling COM interfaces which are not provided by
+//! windows-sys.
+
+use std::ffi::{c_void, OsStr, OsString};
+use std::io;
+use std::marker::PhantomData;
+use std::ops::Deref;
+use std::ptr;
+
+use windows_sys::core::{BSTR, GUID, HRESULT};
+use windows_sys::Win32::Foundation::{
+    SysAllocString, SysFreeString, SysStringLen, RPC_E_CHANGED_MODE,
+};
+use windows_sys::Win32::System::Com::{
+    CoCreateInstance, CoInitializeEx, CoUninitialize, CLSCTX_INPROC_SERVER,
+    COINIT_APARTMENTTHREADED,
+};
+
+use crate::convert::{FromWide, ToWide};
+
+/// The virtual table which every COM interface starts with.
+#[repr(C)]
+pub(crate) struct IUnknownVtbl {
+    pub(crate) query_interface:
+        unsafe extern "system" fn(*mut c_void, *const GUID, *mut *mut c_void) -> HRESULT,
+    pub(crate) add_ref: unsafe extern "system" fn(*mut c_void) -> u32,
+    pub(crate) release: unsafe extern "system" fn(*mut c_void) -> u32,
+}
+
+/// The virtual table of `IDispatch`, which automation interfaces start with.
+///
+/// The methods of `IDispatch` itself are never called, so they're left
+/// untyped.
+#[repr(C)]
+pub(crate) struct IDispatchVtbl {
+    pub(crate) base: IUnknownVtbl,
+    pub(crate) dispatch: [usize; 4],
+}
+
+/// Convert a `HRESULT` into an I/O result.
+pub(crate) fn check(hr: HRESULT) -> io::Result<()> {
+    if hr < 0 {
+        // NB: Results which wrap a Win32 error are unwrapped, so that they map
+        // onto the correct error kind.
+        if (hr as u32) & 0xffff_0000 == 0x8007_0000 {
+            return Err(io::Error::from_raw_os_error(hr & 0xffff));
+        }
+
+        return Err(io::Error::from_raw_os_error(hr));
+    }
+
+    Ok(())
+}
+
+/// Guard which keeps COM initialized on the current thread.
+pub(crate) struct Com {
+    uninitialize: bool,
+}
+
+impl Com {
+    /// Initialize COM on the current thread.
+    ///
+    /// If the thread has already been initialized with a different
+    /// concurrency model the existing initialization is used.
+    pub(crate) fn new() -> io::Result<Self> {
+        let hr = unsafe { CoInitializeEx(ptr::null(), COINIT_APARTMENTTHREADED as u32) };
+
+        if hr == RPC_E_CHANGED_MODE {
+            return Ok(Self {
+                uninitialize: false,
+            });
+        }
+
+        check(hr)?;
+        Ok(Self { uninitialize: true })
+    }
+
+    /// Create an instance of the given class.
+    ///
+    /// # Safety
+    ///
+    /// The caller must ensure that `V` is the virtual table of the interface
+    /// identified by `iid`.
+    pub(crate) unsafe fn create<V>(&self, clsid: &GUID, iid: &GUID) -> io::Result<ComPtr<V>> {
+        let mut ptr = ptr::null_mut();
+        check(CoCreateInstance(
+            clsid,
+            ptr::null_mut(),
+            CLSCTX_INPROC_SERVER,
+            iid,
+            &mut ptr,
+        ))?;
+        Ok(ComPtr::from_raw(ptr))
+    }
+}
+
+impl Drop for Com {
+    fn drop(&mut self) {
+        if self.uninitialize {
+            unsafe {
+                CoUninitialize();
+            }
+        }
+    }
+}
+
+/// An owned reference to a COM interface with the virtual table `V`.
+pub(crate) struct ComPtr<V> {
+    ptr: *mut c_void,
+    _marker: PhantomData<V>,
+}
+
+impl<V> ComPtr<V> {
+    /// Take ownership of a raw interface pointer.
+    ///
+    /// # Safety
+    ///
+    /// The pointer must be a non-null pointer to an interface whose virtual
+    /// table is `V`, and the reference it holds is transferred to the
+    /// returned value.
+    pub(crate) unsafe fn from_raw(ptr: *mut c_void) -> Self {
+        Self {
+            ptr,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Get the raw interface pointer, which is passed as the first argument
+    /// to methods.
+    pub(crate) fn as_raw(&self) -> *mut c_void {
+        self.ptr
+    }
+
+    /// Query for another interface of the same object.
+    ///
+    /// # Safety
+    ///
+    /// The caller must ensure that `U` is the virtual table of the interface
+    /// identified by `iid`.
+    pub(crate) unsafe fn cast<U>(&self, iid: &GUID) -> io::Result<ComPtr<U>> {
+        let mut ptr = ptr::null_mut();
+        check((self.unknown().query_interface)(self.ptr, iid, &mut ptr))?;
+        Ok(ComPtr::from_raw(ptr))
+    }
+
+    fn unknown(&self) -> &IUnknownVtbl {
+        // SAFETY: Every virtual table starts with the methods of IUnknown.
+        unsafe { &**self.ptr.cast::<*const IUnknownVtbl>() }
+    }
+}
+
+impl<V> Deref for ComPtr<V> {
+    type Target = V;
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        // SAFETY: The type of the virtual table is guaranteed by the
+        // constructor.
+        unsafe { &**self.ptr.cast::<*const V>() }
+    }
+}
+
+impl<V> Drop for ComPtr<V> {
+    fn drop(&mut self) {
+        unsafe {
+            (self.unknown().release)(self.ptr);
+        }
+    }
+}
+
+/// An owned `BSTR`.
+pub(crate) struct Bstr(BSTR);
+
+impl Bstr {
+    /// Allocate a new string.
+    pub(crate) fn new<S>(string: S) -> io::Result<Self>
+    where
+        S: AsRef<OsStr>,
+    {
+        let string = string.to_wide_null();
+        let bstr = unsafe { SysAllocString(string.as_ptr()) };
+
+        if bstr.is_null() {
+            return Err(io::ErrorKind::OutOfMemory.into());
+        }
+
+        Ok(Self(bstr))
+    }
+
+    /// Take ownership of a string returned from a method.
+    ///
+    /// # Safety
+    ///
+    /// The string must be null or have been allocated with `SysAllocString`.
+    pub(crate) unsafe fn from_raw(bstr: BSTR) -> Self {
+        Self(bstr)
+    }
+
+    /// Get the raw string.
+    pub(crate) fn as_raw(&self) -> BSTR {
+        self.0
+    }
+
+    /// Convert the string into an owned string.
+    pub(crate) fn to_os_string(&self) -> OsString {
+        if self.0.is_null() {
+            return OsString::new();
+        }
+
+        unsafe {
+            let len = SysStringLen(self.0) as usize;
+            OsString::from_wide(std::slice::from_raw_parts(self.0, len))
+        }
+    }
+}
+
+impl Drop for Bstr {
+    fn drop(&mut self) {
+        unsafe {
+            SysFreeString(self.0);
+        }
+    }
+}
@@ -80,3 +80,36 @@ pub(super) fn encode_escaped_os_str(
 
     Ok(())
 }
+
+/// Split a command line produced by [`encode_escaped_os_str`] back into its
+/// parts.
+pub(super) fn decode_escaped_os_str(input: &OsStr) -> Vec<OsString> {
+    let mut parts = Vec::new();
+    let mut part = Vec::new();
+    let mut quoted = false;
+
+    for c in input.encode_wide() {
+        match c {
+            // '"'
+            0x00000022 => {
+                quoted = !quoted;
+            }
+            // ' '
+            0x00000020 if !quoted => {
+                if !part.is_empty() {
+                    parts.push(OsStringExt::from_wide(&part));
+                    part.clear();
+                }
+            }
+            c => {
+                part.push(c);
+            }
+        }
+    }
+
+    if !part.is_empty() {
+        parts.push(OsStringExt::from_wide(&part));
+    }
+
+    parts
+}
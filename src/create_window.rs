@@ -1,16 +1,26 @@
+use std::collections::HashMap;
 use std::ffi::OsStr;
 use std::ffi::OsString;
+use std::io;
+use std::time::Duration;
 
 use tokio::sync::mpsc;
+use windows_sys::Win32::UI::Shell::SetCurrentProcessExplicitAppUserModelID;
 
 use crate::area::Area;
-use crate::error::ErrorKind::*;
-use crate::error::{SetupIconsError, SetupMenuError};
+use crate::com::check;
+use crate::convert::ToWide;
+use crate::description::{AreaDescription, IconDescription, WindowDescription};
+use crate::error::ErrorRepr::*;
+use crate::error::{SetupIconsError, SetupMenuError, ValidateError};
 use crate::icons::Icons;
 use crate::menu_item::{MenuItem, MenuItemKind};
+use crate::window::{RawMessage, Window};
 use crate::window_loop::PopupMenuHandle;
-use crate::window_loop::{AreaHandle, IconHandle, WindowLoop};
-use crate::{AreaId, EventLoop, Result, Sender};
+use crate::window_loop::{AreaHandle, ClipboardSettings, IconHandle, VisibleSettings, WindowLoop};
+use crate::{
+    AreaId, ClipboardOptions, Event, EventLoop, IconId, MessageThread, ModifyArea, Result, Sender,
+};
 
 /// Construct a window.
 ///
@@ -50,9 +60,20 @@ use crate::{AreaId, EventLoop, Result, Sender};
 pub struct CreateWindow {
     class_name: OsString,
     window_name: Option<OsString>,
+    app_version: Option<String>,
     areas: Vec<Area>,
     clipboard_events: bool,
+    clipboard_raw_bitmaps: bool,
+    clipboard_metadata_only: bool,
+    clipboard_options: ClipboardOptions,
+    rtl: bool,
     icons: Icons,
+    messages: Vec<u32>,
+    message_filter: Option<fn(&RawMessage) -> Option<Event>>,
+    app_user_model_id: Option<OsString>,
+    visible: Option<(u32, u32)>,
+    hide_to_tray: bool,
+    idle_threshold: Option<Duration>,
 }
 
 impl CreateWindow {
@@ -73,9 +94,20 @@ impl CreateWindow {
         Self {
             class_name: class_name.as_ref().to_owned(),
             window_name: None,
+            app_version: None,
             areas: Vec::new(),
             clipboard_events: false,
+            clipboard_raw_bitmaps: false,
+            clipboard_metadata_only: false,
+            clipboard_options: ClipboardOptions::new(),
+            rtl: false,
             icons: Icons::default(),
+            messages: Vec::new(),
+            message_filter: None,
+            app_user_model_id: None,
+            visible: None,
+            hide_to_tray: false,
+            idle_threshold: None,
         }
     }
 
@@ -96,6 +128,105 @@ impl CreateWindow {
         }
     }
 
+    /// Monitor the system clipboard for changes using the specified options.
+    ///
+    /// This is the same as [`CreateWindow::clipboard_events`] with `true`,
+    /// except that the debouncing and retrying of clipboard reads can be
+    /// tuned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    ///
+    /// use winctx::{ClipboardOptions, CreateWindow};
+    ///
+    /// let options = ClipboardOptions::new().debounce(Duration::from_millis(100));
+    ///
+    /// let mut builder = CreateWindow::new("se.tedro.Example")
+    ///     .clipboard_events_with(options);
+    /// ```
+    pub fn clipboard_events_with(self, clipboard_options: ClipboardOptions) -> Self {
+        Self {
+            clipboard_events: true,
+            clipboard_options,
+            ..self
+        }
+    }
+
+    /// Indicates whether copied bitmaps should be delivered as raw
+    /// device-independent bitmaps through [`ClipboardEvent::BitMap`].
+    ///
+    /// By default bitmaps are decoded and delivered as
+    /// [`ClipboardEvent::Image`].
+    ///
+    /// [`ClipboardEvent::BitMap`]: crate::event::ClipboardEvent::BitMap
+    /// [`ClipboardEvent::Image`]: crate::event::ClipboardEvent::Image
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use winctx::CreateWindow;
+    ///
+    /// let mut builder = CreateWindow::new("se.tedro.Example")
+    ///     .clipboard_events(true)
+    ///     .clipboard_raw_bitmaps(true);
+    /// ```
+    pub fn clipboard_raw_bitmaps(self, clipboard_raw_bitmaps: bool) -> Self {
+        Self {
+            clipboard_raw_bitmaps,
+            ..self
+        }
+    }
+
+    /// Indicates whether clipboard events should only report which formats are
+    /// available through [`ClipboardEvent::Changed`], without copying any data
+    /// off the clipboard.
+    ///
+    /// This is useful for applications such as clipboard managers which want
+    /// to decide for themselves whether the data is worth fetching.
+    ///
+    /// [`ClipboardEvent::Changed`]: crate::event::ClipboardEvent::Changed
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use winctx::CreateWindow;
+    ///
+    /// let mut builder = CreateWindow::new("se.tedro.Example")
+    ///     .clipboard_events(true)
+    ///     .clipboard_metadata_only(true);
+    /// ```
+    pub fn clipboard_metadata_only(self, clipboard_metadata_only: bool) -> Self {
+        Self {
+            clipboard_metadata_only,
+            ..self
+        }
+    }
+
+    /// Indicates whether the window should use a right-to-left layout.
+    ///
+    /// This mirrors the layout of the window and its popup menus, so that
+    /// check marks and icons are placed to the right of the text of menu items
+    /// and text is read from right to left. This should be enabled for
+    /// applications which are localized into right-to-left languages such as
+    /// Arabic or Hebrew.
+    ///
+    /// Note that the layout of tooltips and notifications is determined by the
+    /// shell.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use winctx::CreateWindow;
+    ///
+    /// let mut builder = CreateWindow::new("se.tedro.Example")
+    ///     .rtl(true);
+    /// ```
+    pub fn rtl(self, rtl: bool) -> Self {
+        Self { rtl, ..self }
+    }
+
     /// Modify the window name for use in the application.
     ///
     /// # Examples
@@ -116,6 +247,138 @@ impl CreateWindow {
         }
     }
 
+    /// Set the version of the application.
+    ///
+    /// This is reported to other processes which query the window using
+    /// [`Window::query_info`], allowing companion tooling to verify that it's
+    /// compatible with the running application.
+    ///
+    /// [`Window::query_info`]: crate::window::Window::query_info
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use winctx::CreateWindow;
+    ///
+    /// let mut builder = CreateWindow::new("se.tedro.Example")
+    ///     .app_version(env!("CARGO_PKG_VERSION"));
+    /// ```
+    pub fn app_version<V>(self, app_version: V) -> Self
+    where
+        V: AsRef<str>,
+    {
+        Self {
+            app_version: Some(app_version.as_ref().to_owned()),
+            ..self
+        }
+    }
+
+    /// Create a visible and resizable top-level window with the given width
+    /// and height, instead of a hidden one.
+    ///
+    /// The window is shown once it's been built, and can be hidden and shown
+    /// again using [`Sender::hide`] and [`Sender::show`]. Pressing its close
+    /// button emits [`Event::WindowClosed`] without closing the window, and
+    /// resizing it emits [`Event::Resized`]. Files which are dragged onto it
+    /// are reported through [`Event::FilesDropped`]. The window name is used
+    /// as its title.
+    ///
+    /// This is intended for small auxiliary windows, such as settings, which
+    /// are drawn by handling raw messages through [`message_filter`].
+    ///
+    /// [`message_filter`]: Self::message_filter
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use winctx::CreateWindow;
+    ///
+    /// let mut builder = CreateWindow::new("se.tedro.Example")
+    ///     .window_name("Example Settings")
+    ///     .visible(400, 300);
+    /// ```
+    pub fn visible(self, width: u32, height: u32) -> Self {
+        Self {
+            visible: Some((width, height)),
+            ..self
+        }
+    }
+
+    /// Hide the window created using [`visible`] when it's minimized or
+    /// closed, leaving only its notification areas.
+    ///
+    /// When this is set [`Event::WindowHidden`] is emitted instead of
+    /// [`Event::WindowClosed`], and the window can be shown again using
+    /// [`Sender::show`].
+    ///
+    /// [`visible`]: Self::visible
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use winctx::CreateWindow;
+    ///
+    /// let mut builder = CreateWindow::new("se.tedro.Example")
+    ///     .visible(400, 300)
+    ///     .hide_to_tray(true);
+    /// ```
+    pub fn hide_to_tray(self, hide_to_tray: bool) -> Self {
+        Self {
+            hide_to_tray,
+            ..self
+        }
+    }
+
+    /// Monitor whether the user is idle, emitting [`Event::Idle`] once no
+    /// input has been received for the given threshold and [`Event::Active`]
+    /// once input is received again.
+    ///
+    /// Input is polled about once a second, so thresholds shorter than that
+    /// are imprecise.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    ///
+    /// use winctx::CreateWindow;
+    ///
+    /// let mut builder = CreateWindow::new("se.tedro.Example")
+    ///     .idle_threshold(Duration::from_secs(5 * 60));
+    /// ```
+    pub fn idle_threshold(self, threshold: Duration) -> Self {
+        Self {
+            idle_threshold: Some(threshold),
+            ..self
+        }
+    }
+
+    /// Set the application user model id of the process, such as
+    /// `"Company.App"`.
+    ///
+    /// This is set when the window is built, and is used by the system to
+    /// attribute notifications to the application and to group its windows
+    /// in the taskbar. Since it applies to the whole process it should be set
+    /// before any other windows are shown.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use winctx::CreateWindow;
+    ///
+    /// let mut builder = CreateWindow::new("se.tedro.Example")
+    ///     .app_user_model_id("Tedro.Example");
+    /// ```
+    pub fn app_user_model_id<I>(self, app_user_model_id: I) -> Self
+    where
+        I: AsRef<OsStr>,
+    {
+        Self {
+            app_user_model_id: Some(app_user_model_id.as_ref().to_owned()),
+            ..self
+        }
+    }
+
     /// Push a notification area onto the window and return its id.
     ///
     /// # Examples
@@ -163,57 +426,284 @@ impl CreateWindow {
         &mut self.icons
     }
 
+    /// Register a window message with the given name which the window should
+    /// receive, returning its identifier.
+    ///
+    /// Any instance of the message posted to the window, such as through
+    /// [`Window::post_message`], is delivered as an [`Event::AppMessage`].
+    ///
+    /// [`Window::post_message`]: crate::Window::post_message
+    /// [`Event::AppMessage`]: crate::Event::AppMessage
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use winctx::CreateWindow;
+    ///
+    /// let mut window = CreateWindow::new("se.tedro.Example");
+    /// let ping = window.register_message("se.tedro.Example.Ping")?;
+    /// # Ok::<_, winctx::Error>(())
+    /// ```
+    pub fn register_message<N>(&mut self, name: N) -> Result<u32>
+    where
+        N: AsRef<OsStr>,
+    {
+        let id = crate::window::register_message(name).map_err(RegisterMessage)?;
+
+        if !self.messages.contains(&id) {
+            self.messages.push(id);
+        }
+
+        Ok(id)
+    }
+
+    /// Install a filter which is called with window messages that winctx
+    /// doesn't handle itself, before they are passed on to the default window
+    /// procedure.
+    ///
+    /// If the filter returns an event, the message is considered handled and
+    /// the event is emitted from the event loop. The filter is called on the
+    /// window thread, so it should return quickly.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use winctx::window::RawMessage;
+    /// use winctx::{CreateWindow, Event};
+    ///
+    /// const WM_POWERBROADCAST: u32 = 0x0218;
+    /// const PBT_APMRESUMEAUTOMATIC: usize = 0x0012;
+    /// const RESUMED: u32 = 0x8000;
+    ///
+    /// fn filter(msg: &RawMessage) -> Option<Event> {
+    ///     match (msg.message, msg.wparam) {
+    ///         (WM_POWERBROADCAST, PBT_APMRESUMEAUTOMATIC) => Some(Event::AppMessage {
+    ///             id: RESUMED,
+    ///             wparam: 0,
+    ///             lparam: 0,
+    ///         }),
+    ///         _ => None,
+    ///     }
+    /// }
+    ///
+    /// let window = CreateWindow::new("se.tedro.Example").message_filter(filter);
+    /// ```
+    pub fn message_filter(self, filter: fn(&RawMessage) -> Option<Event>) -> Self {
+        Self {
+            message_filter: Some(filter),
+            ..self
+        }
+    }
+
+    /// Describe the configuration of the window.
+    ///
+    /// If `redact` is set, text which might be sensitive such as tooltips and
+    /// the text of menu items is replaced.
+    ///
+    /// To include modifications made after the window has been built, use
+    /// [`Sender::describe`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use winctx::CreateWindow;
+    ///
+    /// # macro_rules! include_bytes { ($path:literal) => { &[1, 2, 3, 4] } }
+    /// const ICON: &[u8] = include_bytes!("tokio.ico");
+    ///
+    /// let mut window = CreateWindow::new("se.tedro.Example")
+    ///     .window_name("Example Application")
+    ///     .clipboard_events(true);
+    ///
+    /// let icon = window.icons().insert_buffer(ICON, 22, 22);
+    /// let area = window.new_area().icon(icon).tooltip("Example Application");
+    ///
+    /// let menu = area.popup_menu();
+    /// let transfers = menu.group("transfers");
+    /// let first = menu.push_entry("Upload").group(transfers).checked(true).id();
+    /// menu.push_separator();
+    /// menu.set_default(first);
+    ///
+    /// let description = window.describe(false);
+    /// assert_eq!(description.class_name, "se.tedro.Example");
+    /// assert_eq!(description.window_name.as_deref(), Some("Example Application"));
+    /// assert!(description.clipboard_events);
+    /// assert_eq!(description.icons.len(), 1);
+    /// assert_eq!(description.icons[0].width, 22);
+    /// assert_eq!(description.icons[0].len, 4);
+    ///
+    /// let area = &description.areas[0];
+    /// assert_eq!(area.icon, Some(0));
+    /// assert_eq!(area.tooltip.as_deref(), Some("Example Application"));
+    ///
+    /// let items = &area.popup_menu.as_ref().unwrap().items;
+    /// assert_eq!(items.len(), 2);
+    /// assert_eq!(items[0].text.as_deref(), Some("Upload"));
+    /// assert!(items[0].checked && items[0].default && items[0].enabled);
+    /// assert_eq!(items[0].groups, ["transfers"]);
+    /// assert_eq!(items[1].text, None);
+    ///
+    /// let redacted = window.describe(true);
+    /// assert_eq!(redacted.areas[0].tooltip.as_deref(), Some("<redacted>"));
+    /// ```
+    pub fn describe(&self, redact: bool) -> WindowDescription {
+        let icons = self
+            .icons
+            .icons
+            .iter()
+            .map(|icon| IconDescription::new(icon.as_bytes(), icon.width(), icon.height()))
+            .collect();
+
+        let areas = self.areas.iter().map(AreaDescription::new).collect();
+
+        let mut description = WindowDescription {
+            class_name: self.class_name.to_string_lossy().into_owned(),
+            window_name: self
+                .window_name
+                .as_ref()
+                .map(|n| n.to_string_lossy().into_owned()),
+            app_version: self.app_version.clone(),
+            clipboard_events: self.clipboard_events,
+            clipboard_raw_bitmaps: self.clipboard_raw_bitmaps,
+            clipboard_metadata_only: self.clipboard_metadata_only,
+            rtl: self.rtl,
+            icons,
+            areas,
+        };
+
+        if redact {
+            description.redact();
+        }
+
+        description
+    }
+
     /// Construct a new event loop and system integration.
+    ///
+    /// Building fails if an area or menu item references an icon which
+    /// wasn't added to [`CreateWindow::icons`], or if a popup menu refers to
+    /// menu items or groups of another area.
+    ///
+    /// If the taskbar isn't ready to accept notification areas, such as when
+    /// starting at logon, adding them is retried for a while before building
+    /// fails.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use winctx::CreateWindow;
+    /// use winctx::icons::Icons;
+    ///
+    /// # macro_rules! include_bytes { ($path:literal) => { &[] } }
+    /// const ICON: &[u8] = include_bytes!("tokio.ico");
+    ///
+    /// # async fn test() -> winctx::Result<()> {
+    /// let mut other = Icons::new();
+    /// let icon = other.insert_buffer(ICON, 22, 22);
+    ///
+    /// let mut window = CreateWindow::new("se.tedro.Example");
+    /// window.new_area().icon(icon);
+    ///
+    /// assert!(window.build().await.is_err());
+    /// # Ok(()) }
+    /// ```
     pub async fn build(self) -> Result<(Sender, EventLoop)> {
+        let thread = MessageThread::new()?;
+        self.build_on(&thread).await
+    }
+
+    /// Construct the window on the given [`MessageThread`], which can be
+    /// shared with other windows.
+    ///
+    /// Windows built on the same thread are independent, but share the
+    /// thread running their message loops instead of each having their own.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use winctx::{CreateWindow, MessageThread};
+    ///
+    /// # async fn test() -> winctx::Result<()> {
+    /// let thread = MessageThread::new()?;
+    ///
+    /// let (first, first_loop) = CreateWindow::new("se.tedro.First").build_on(&thread).await?;
+    /// let (second, second_loop) = CreateWindow::new("se.tedro.Second").build_on(&thread).await?;
+    /// # Ok(()) }
+    /// ```
+    pub async fn build_on(self, thread: &MessageThread) -> Result<(Sender, EventLoop)> {
+        if let Some(app_user_model_id) = &self.app_user_model_id {
+            set_app_user_model_id(app_user_model_id).map_err(AppUserModelId)?;
+        }
+
         let (events_tx, events_rx) = mpsc::unbounded_channel();
+        let description = self.describe(false);
 
         let icons = self.setup_icons(&self.icons).map_err(SetupIcons)?;
+        let areas = self.areas.len() as u32;
         let mut menus = Vec::with_capacity(self.areas.len());
         let mut initial = Vec::new();
+        let mut menu_items = 0;
+        let mut actions = HashMap::new();
 
-        for (id, m) in self.areas.into_iter().enumerate() {
-            let area_id = AreaId::new(id as u32);
+        for area in self.areas {
+            menu_items = menu_items.max(area.menu_item_ids());
+            actions.extend(area.menu_actions());
+            let (handle, modify) = build_area(area, &icons, self.icons.id, self.rtl)?;
+            initial.push((handle.area_id, modify));
+            menus.push(handle);
+        }
 
-            let popup_menu = if let Some(popup_menu) = m.popup_menu {
-                let mut menu =
-                    PopupMenuHandle::new(popup_menu.open_menu).map_err(BuildPopupMenu)?;
-                build_menu(&mut menu, popup_menu.menu, popup_menu.default).map_err(SetupMenu)?;
-                Some(menu)
-            } else {
-                None
-            };
+        let clipboard = self.clipboard_events.then_some(ClipboardSettings {
+            raw_bitmaps: self.clipboard_raw_bitmaps,
+            metadata_only: self.clipboard_metadata_only,
+            options: self.clipboard_options,
+        });
 
-            initial.push((area_id, m.initial));
-            menus.push(AreaHandle::new(area_id, popup_menu));
-        }
+        let visible = self.visible.map(|(width, height)| VisibleSettings {
+            width,
+            height,
+            hide_to_tray: self.hide_to_tray,
+        });
 
         let mut window = WindowLoop::new(
             &self.class_name,
             self.window_name.as_deref(),
-            self.clipboard_events,
+            clipboard,
+            self.rtl,
+            visible,
+            self.idle_threshold,
+            self.app_version.as_deref(),
             menus,
+            self.messages,
+            self.message_filter,
+            thread,
         )
         .await
         .map_err(WindowSetup)?;
 
-        for menu in &window.areas {
-            window
-                .window
-                .add_notification(menu.area_id)
-                .map_err(AddNotification)?;
-        }
+        let mut area_state = HashMap::with_capacity(initial.len());
 
         for (area_id, modify) in initial {
-            let icon = modify.icon.and_then(|icon| icons.get(icon.as_usize()));
-
             window
-                .window
-                .modify_notification(area_id, icon, modify.tooltip.as_deref())
-                .map_err(ModifyNotification)?;
+                .add_notification_with_retry(area_id)
+                .await
+                .map_err(AddNotification)?;
+
+            add_area(&mut window, area_id, &modify, &icons)?;
+            area_state.insert(area_id, modify);
         }
 
-        let event_loop = EventLoop::new(events_rx, window, icons);
-        let system = Sender::new(events_tx);
+        let handle = Window::new(window.window.hwnd());
+        let event_loop = EventLoop::new(
+            events_rx,
+            window,
+            icons,
+            self.icons.id,
+            description,
+            actions,
+            area_state,
+        );
+        let system = Sender::new(events_tx, menu_items, areas, handle);
         Ok((system, event_loop))
     }
 
@@ -231,28 +721,145 @@ impl CreateWindow {
     }
 }
 
+/// Set the application user model id of the current process.
+fn set_app_user_model_id(app_user_model_id: &OsStr) -> io::Result<()> {
+    let app_user_model_id = app_user_model_id.to_wide_null();
+    check(unsafe { SetCurrentProcessExplicitAppUserModelID(app_user_model_id.as_ptr()) })
+}
+
+/// Build the handle for an area, returning it along with the modifications
+/// which need to be applied once the notification area has been added.
+///
+/// If `rtl` is set, the popup menu is laid out from right to left.
+pub(crate) fn build_area(
+    area: Area,
+    icons: &[IconHandle],
+    icons_id: u32,
+    rtl: bool,
+) -> Result<(AreaHandle, ModifyArea)> {
+    validate_area(&area, icons_id).map_err(Validate)?;
+
+    let popup_menu = if let Some(popup_menu) = area.popup_menu {
+        let track_flags = popup_menu.track_flags();
+        let mut menu =
+            PopupMenuHandle::new(popup_menu.open_menu, track_flags, popup_menu.dark_mode, rtl)
+                .map_err(BuildPopupMenu)?;
+        let default = popup_menu.default.map(|item_id| item_id.id());
+        build_menu(&mut menu, popup_menu.menu, default, icons).map_err(SetupMenu)?;
+        Some(menu)
+    } else {
+        None
+    };
+
+    let handle = AreaHandle::new(
+        area.id,
+        popup_menu,
+        area.take_foreground,
+        area.double_click_default,
+    );
+    Ok((handle, area.initial))
+}
+
+/// Add the notification area for an area, apply its initial modifications and
+/// register the global hotkeys of its popup menu.
+pub(crate) fn add_area(
+    window: &mut WindowLoop,
+    area_id: AreaId,
+    modify: &ModifyArea,
+    icons: &[IconHandle],
+) -> Result<()> {
+    window
+        .window
+        .add_notification(area_id)
+        .map_err(AddNotification)?;
+
+    window
+        .window
+        .modify_notification(area_id, modify, icons)
+        .map_err(ModifyNotification)?;
+
+    let hotkeys = window
+        .area(area_id)
+        .and_then(|area| area.popup_menu.as_ref())
+        .into_iter()
+        .flat_map(PopupMenuHandle::hotkeys);
+
+    for (item_id, hotkey) in hotkeys {
+        window
+            .window
+            .add_hotkey(area_id, item_id, hotkey)
+            .map_err(RegisterHotkey)?;
+    }
+
+    Ok(())
+}
+
+/// Validate that the icons and menu items referenced by an area belong to it.
+fn validate_area(area: &Area, icons_id: u32) -> Result<(), ValidateError> {
+    if let Some(Some(icon)) = area.initial.icon {
+        validate_icon(icon, icons_id)?;
+    }
+
+    let Some(popup_menu) = &area.popup_menu else {
+        return Ok(());
+    };
+
+    for item in &popup_menu.menu {
+        validate_menu_item(item, icons_id)?;
+    }
+
+    if let Some(default) = popup_menu.default {
+        if !popup_menu.menu.iter().any(|item| item.item_id == default) {
+            return Err(ValidateError::UnknownDefault(area.id, default));
+        }
+    }
+
+    Ok(())
+}
+
+/// Validate that the icons and groups referenced by a menu item belong to the
+/// window and popup menu it's added to.
+pub(crate) fn validate_menu_item(item: &MenuItem, icons_id: u32) -> Result<(), ValidateError> {
+    for icon in [item.icon, item.checked_icon, item.unchecked_icon]
+        .into_iter()
+        .flatten()
+    {
+        validate_icon(icon, icons_id)?;
+    }
+
+    for &group in &item.groups {
+        if group.area_id() != item.item_id.area_id() {
+            return Err(ValidateError::ForeignGroup(item.item_id, group));
+        }
+    }
+
+    Ok(())
+}
+
+pub(crate) fn validate_icon(icon: IconId, icons_id: u32) -> Result<(), ValidateError> {
+    if !icon.belongs_to(icons_id) {
+        return Err(ValidateError::UnknownIcon(icon));
+    }
+
+    Ok(())
+}
+
 fn build_menu(
     menu: &mut PopupMenuHandle,
     menu_items: Vec<MenuItem>,
     default: Option<u32>,
+    icons: &[IconHandle],
 ) -> Result<(), SetupMenuError> {
     for (index, item) in menu_items.into_iter().enumerate() {
         debug_assert!(u32::try_from(index).is_ok());
-        let menu_item_id = index as u32;
-
-        match item.kind {
-            MenuItemKind::Separator => {
-                let default = default == Some(menu_item_id);
-
-                menu.add_menu_separator(menu_item_id, default, &item.initial)
-                    .map_err(|e| SetupMenuError::AddMenuSeparator(index, e))?;
-            }
-            MenuItemKind::String { text } => {
-                let default = default == Some(menu_item_id);
+        let menu_item_id = item.item_id.id();
+        let default = default == Some(menu_item_id);
 
-                menu.add_menu_entry(menu_item_id, text.as_str(), default, &item.initial)
-                    .map_err(|e| SetupMenuError::AddMenuEntry(index, e))?;
-            }
+        if let Err(e) = menu.insert_item(index as u32, &item, default, icons) {
+            return Err(match item.kind {
+                MenuItemKind::Separator => SetupMenuError::AddMenuSeparator(index, e),
+                MenuItemKind::String { .. } => SetupMenuError::AddMenuEntry(index, e),
+            });
         }
     }
 
@@ -0,0 +1,556 @@
+//! Types describing the configuration of a window.
+//!
+//! A description is produced by [`CreateWindow::describe`] before a window is
+//! built, or by [`Sender::describe`] to include modifications which have been
+//! made at runtime. With the `serde` feature enabled they can be serialized,
+//! which is useful for attaching the exact configuration of a window to a bug
+//! report.
+//!
+//! [`CreateWindow::describe`]: crate::CreateWindow::describe
+//! [`Sender::describe`]: crate::Sender::describe
+
+use crate::area::Area;
+use crate::menu_item::{MenuItem, MenuItemKind};
+use crate::{Badge, ModifyArea, ModifyMenuItem};
+
+/// Text used in place of redacted strings.
+const REDACTED: &str = "<redacted>";
+
+/// A description of a window.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub struct WindowDescription {
+    /// The class name of the window.
+    pub class_name: String,
+    /// The name of the window.
+    pub window_name: Option<String>,
+    /// The version of the application.
+    pub app_version: Option<String>,
+    /// Whether clipboard events are enabled.
+    pub clipboard_events: bool,
+    /// Whether clipboard bitmaps are delivered without being decoded.
+    pub clipboard_raw_bitmaps: bool,
+    /// Whether clipboard events only report the available formats.
+    pub clipboard_metadata_only: bool,
+    /// Whether the window uses a right-to-left layout.
+    pub rtl: bool,
+    /// Icons registered with the window.
+    pub icons: Vec<IconDescription>,
+    /// Notification areas of the window.
+    pub areas: Vec<AreaDescription>,
+}
+
+impl WindowDescription {
+    /// Replace any text which might be sensitive, such as tooltips and the
+    /// text of menu items.
+    pub(crate) fn redact(&mut self) {
+        for area in &mut self.areas {
+            if let Some(tooltip) = &mut area.tooltip {
+                *tooltip = REDACTED.to_owned();
+            }
+
+            if let Some(popup_menu) = &mut area.popup_menu {
+                for item in &mut popup_menu.items {
+                    if let Some(text) = &mut item.text {
+                        *text = REDACTED.to_owned();
+                    }
+                }
+            }
+        }
+    }
+
+    fn area(&mut self, area_id: u32) -> Option<&mut AreaDescription> {
+        self.areas.iter_mut().find(|area| area.id == area_id)
+    }
+
+    /// Add an area.
+    pub(crate) fn add_area(&mut self, area: AreaDescription) {
+        self.areas.push(area);
+    }
+
+    /// Remove an area.
+    pub(crate) fn remove_area(&mut self, area_id: u32) {
+        self.areas.retain(|area| area.id != area_id);
+    }
+
+    /// Apply an area modification.
+    pub(crate) fn modify_area(&mut self, area_id: u32, modify: &ModifyArea) {
+        let Some(area) = self.area(area_id) else {
+            return;
+        };
+
+        if let Some(icon) = modify.icon {
+            area.icon = icon.map(|icon| icon.as_usize());
+        }
+
+        if let Some(tooltip) = &modify.tooltip {
+            area.tooltip = tooltip.as_deref().map(str::to_owned);
+        }
+
+        if let Some(hidden) = modify.hidden {
+            area.hidden = hidden;
+        }
+
+        if let Some(show_tooltip) = modify.show_tooltip {
+            area.show_tooltip = show_tooltip;
+        }
+
+        if let Some(badge) = modify.badge {
+            area.badge = badge;
+        }
+    }
+
+    /// Get the current text of a menu item.
+    pub(crate) fn menu_item_text(&self, area_id: u32, item_id: u32) -> Option<&str> {
+        let area = self.areas.iter().find(|area| area.id == area_id)?;
+        let menu = area.popup_menu.as_ref()?;
+        let item = menu.items.iter().find(|item| item.id == item_id)?;
+        item.text.as_deref()
+    }
+
+    fn popup_menu(&mut self, area_id: u32) -> Option<&mut PopupMenuDescription> {
+        self.area(area_id).and_then(|area| area.popup_menu.as_mut())
+    }
+
+    /// Insert a menu item at the given position.
+    pub(crate) fn insert_menu_item(&mut self, position: u32, item: &MenuItem) {
+        let Some(menu) = self.popup_menu(item.item_id.area_id) else {
+            return;
+        };
+
+        let position = (position as usize).min(menu.items.len());
+        let item = MenuItemDescription::new(item, false, &menu.groups);
+        menu.items.insert(position, item);
+    }
+
+    /// Remove a menu item.
+    pub(crate) fn remove_menu_item(&mut self, area_id: u32, item_id: u32) {
+        if let Some(menu) = self.popup_menu(area_id) {
+            menu.items.retain(|item| item.id != item_id);
+        }
+    }
+
+    /// Apply a menu item modification.
+    pub(crate) fn modify_menu_item(&mut self, area_id: u32, item_id: u32, modify: &ModifyMenuItem) {
+        let Some(item) = self
+            .popup_menu(area_id)
+            .and_then(|menu| menu.items.iter_mut().find(|item| item.id == item_id))
+        else {
+            return;
+        };
+
+        if let Some(checked) = modify.checked {
+            item.checked = checked;
+        }
+
+        if let Some(highlight) = modify.highlight {
+            item.highlight = highlight;
+        }
+
+        if let Some(enabled) = modify.enabled {
+            item.enabled = enabled;
+        }
+    }
+}
+
+/// A description of an icon.
+///
+/// This doesn't include the icon data, only enough information to tell icons
+/// apart.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub struct IconDescription {
+    /// The width of the icon.
+    pub width: u32,
+    /// The height of the icon.
+    pub height: u32,
+    /// The length of the icon buffer in bytes.
+    pub len: usize,
+    /// A 64-bit FNV-1a hash of the icon buffer.
+    pub hash: u64,
+}
+
+impl IconDescription {
+    pub(crate) fn new(buffer: &[u8], width: u32, height: u32) -> Self {
+        Self {
+            width,
+            height,
+            len: buffer.len(),
+            hash: fnv1a(buffer),
+        }
+    }
+}
+
+/// A description of a notification area.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub struct AreaDescription {
+    /// The identifier of the area.
+    pub id: u32,
+    /// The index of the icon used by the area in [`WindowDescription::icons`].
+    pub icon: Option<usize>,
+    /// The tooltip of the area.
+    pub tooltip: Option<String>,
+    /// Whether the standard tooltip is shown when hovering the icon.
+    pub show_tooltip: bool,
+    /// Whether the window is brought to the foreground when the popup menu is
+    /// opened.
+    pub take_foreground: bool,
+    /// Whether double clicking the icon clicks the default menu item.
+    pub double_click_default: bool,
+    /// Whether the area is hidden from the notification tray.
+    pub hidden: bool,
+    /// The badge drawn on the icon of the area.
+    pub badge: Option<Badge>,
+    /// The popup menu of the area.
+    pub popup_menu: Option<PopupMenuDescription>,
+}
+
+impl AreaDescription {
+    pub(crate) fn new(area: &Area) -> Self {
+        let popup_menu = area.popup_menu.as_ref().map(|popup_menu| {
+            let groups = popup_menu.groups.iter().map(|g| g.to_string()).collect();
+
+            let mut description = PopupMenuDescription {
+                items: Vec::with_capacity(popup_menu.menu.len()),
+                groups,
+            };
+
+            for item in &popup_menu.menu {
+                let default = popup_menu.default == Some(item.item_id);
+                let item = MenuItemDescription::new(item, default, &description.groups);
+                description.items.push(item);
+            }
+
+            description
+        });
+
+        Self {
+            id: area.id.id(),
+            icon: area.initial.icon.flatten().map(|icon| icon.as_usize()),
+            tooltip: area
+                .initial
+                .tooltip
+                .as_ref()
+                .and_then(Option::as_deref)
+                .map(str::to_owned),
+            show_tooltip: area.initial.show_tooltip.unwrap_or(true),
+            take_foreground: area.take_foreground,
+            double_click_default: area.double_click_default,
+            hidden: area.initial.hidden.unwrap_or_default(),
+            badge: area.initial.badge.flatten(),
+            popup_menu,
+        }
+    }
+}
+
+/// A description of a popup menu.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub struct PopupMenuDescription {
+    /// Items in the popup menu.
+    pub items: Vec<MenuItemDescription>,
+    /// The names of groups in the popup menu.
+    pub groups: Vec<String>,
+}
+
+/// A description of a menu item.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub struct MenuItemDescription {
+    /// The identifier of the menu item within its popup menu.
+    pub id: u32,
+    /// The text of the menu item, or `None` if it's a separator.
+    pub text: Option<String>,
+    /// Whether the menu item is checked.
+    pub checked: bool,
+    /// Whether the menu item is highlighted.
+    pub highlight: bool,
+    /// Whether the menu item is enabled.
+    pub enabled: bool,
+    /// Whether the menu item is the default item.
+    pub default: bool,
+    /// The names of groups the menu item is a member of.
+    pub groups: Vec<String>,
+    /// The index of the icon used by the menu item in
+    /// [`WindowDescription::icons`].
+    pub icon: Option<usize>,
+    /// The index of the icon used in place of the check mark when the menu
+    /// item is checked.
+    pub checked_icon: Option<usize>,
+    /// The index of the icon used in place of the check mark when the menu
+    /// item isn't checked.
+    pub unchecked_icon: Option<usize>,
+    /// The shortcut hint displayed next to the text of the menu item.
+    pub shortcut: Option<String>,
+    /// Whether the shortcut is registered as a global hotkey.
+    pub global_hotkey: bool,
+}
+
+impl MenuItemDescription {
+    pub(crate) fn new(item: &MenuItem, default: bool, groups: &[String]) -> Self {
+        let text = match &item.kind {
+            MenuItemKind::Separator => None,
+            MenuItemKind::String { text } => Some(text.clone()),
+        };
+
+        let groups = item
+            .groups
+            .iter()
+            .filter(|g| g.area_id() == item.item_id.area_id())
+            .filter_map(|g| groups.get(g.id() as usize))
+            .cloned()
+            .collect();
+
+        Self {
+            id: item.item_id.id(),
+            text,
+            checked: item.initial.checked.unwrap_or_default(),
+            highlight: item.initial.highlight.unwrap_or_default(),
+            enabled: item.initial.enabled.unwrap_or(true),
+            default,
+            groups,
+            icon: item.icon.map(|icon| icon.as_usize()),
+            checked_icon: item.checked_icon.map(|icon| icon.as_usize()),
+            unchecked_icon: item.unchecked_icon.map(|icon| icon.as_usize()),
+            shortcut: item.shortcut.clone(),
+            global_hotkey: item.global_hotkey,
+        }
+    }
+}
+
+fn fnv1a(bytes: &[u8]) -> u64 {
+    let mut hash = 0xcbf29ce484222325u64;
+
+    for &b in bytes {
+        hash ^= u64::from(b);
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+
+    hash
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::menu_item::{MenuItem, MenuItemKind};
+    use crate::{Badge, CreateWindow, ItemId, ModifyArea};
+
+    use super::{
+        AreaDescription, IconDescription, MenuItemDescription, PopupMenuDescription,
+        WindowDescription,
+    };
+
+    fn window() -> CreateWindow {
+        let mut window = CreateWindow::new("se.tedro.Example")
+            .window_name("Example Application")
+            .clipboard_events(true);
+
+        let icon = window.icons().insert_buffer([1, 2, 3, 4], 22, 22);
+        let area = window.new_area().icon(icon).tooltip("Example Application");
+
+        let menu = area.popup_menu();
+        let transfers = menu.group("transfers");
+        let first = menu
+            .push_entry("Upload")
+            .group(transfers)
+            .checked(true)
+            .id();
+        menu.push_separator();
+        menu.push_entry("Quit").shortcut("Ctrl+Q").enabled(false);
+        menu.set_default(first);
+        window
+    }
+
+    fn item(id: u32, text: Option<&str>) -> MenuItemDescription {
+        MenuItemDescription {
+            id,
+            text: text.map(str::to_owned),
+            checked: false,
+            highlight: false,
+            enabled: true,
+            default: false,
+            groups: Vec::new(),
+            icon: None,
+            checked_icon: None,
+            unchecked_icon: None,
+            shortcut: None,
+            global_hotkey: false,
+        }
+    }
+
+    fn texts(description: &WindowDescription) -> Vec<Option<&str>> {
+        let menu = description.areas[0].popup_menu.as_ref().unwrap();
+        menu.items.iter().map(|item| item.text.as_deref()).collect()
+    }
+
+    #[test]
+    fn snapshot() {
+        let expected = WindowDescription {
+            class_name: String::from("se.tedro.Example"),
+            window_name: Some(String::from("Example Application")),
+            app_version: None,
+            clipboard_events: true,
+            clipboard_raw_bitmaps: false,
+            clipboard_metadata_only: false,
+            rtl: false,
+            icons: vec![IconDescription {
+                width: 22,
+                height: 22,
+                len: 4,
+                hash: 0xbe7a5e775165785d,
+            }],
+            areas: vec![AreaDescription {
+                id: 0,
+                icon: Some(0),
+                tooltip: Some(String::from("Example Application")),
+                show_tooltip: true,
+                take_foreground: true,
+                double_click_default: false,
+                hidden: false,
+                badge: None,
+                popup_menu: Some(PopupMenuDescription {
+                    items: vec![
+                        MenuItemDescription {
+                            checked: true,
+                            default: true,
+                            groups: vec![String::from("transfers")],
+                            ..item(0, Some("Upload"))
+                        },
+                        item(1, None),
+                        MenuItemDescription {
+                            enabled: false,
+                            shortcut: Some(String::from("Ctrl+Q")),
+                            ..item(2, Some("Quit"))
+                        },
+                    ],
+                    groups: vec![String::from("transfers")],
+                }),
+            }],
+        };
+
+        assert_eq!(window().describe(false), expected);
+    }
+
+    #[test]
+    fn redact() {
+        let mut description = window().describe(false);
+        description.redact();
+
+        let area = &description.areas[0];
+        assert_eq!(area.tooltip.as_deref(), Some("<redacted>"));
+        assert_eq!(
+            texts(&description),
+            [Some("<redacted>"), None, Some("<redacted>")]
+        );
+
+        // Only text is redacted, the rest of the structure is preserved.
+        let mut expected = window().describe(false);
+        expected.areas[0].tooltip = Some(String::from("<redacted>"));
+
+        for item in &mut expected.areas[0].popup_menu.as_mut().unwrap().items {
+            if item.text.is_some() {
+                item.text = Some(String::from("<redacted>"));
+            }
+        }
+
+        assert_eq!(description, expected);
+        assert_eq!(window().describe(true), expected);
+    }
+
+    #[test]
+    fn modify_area() {
+        let mut description = window().describe(false);
+
+        let mut modify = ModifyArea::default();
+        modify.clear_icon();
+        modify.tooltip("Syncing");
+        modify.hidden(true);
+        modify.show_tooltip(false);
+        modify.badge(Some(Badge::Count(3)));
+        description.modify_area(0, &modify);
+
+        let area = &description.areas[0];
+        assert_eq!(area.icon, None);
+        assert_eq!(area.tooltip.as_deref(), Some("Syncing"));
+        assert!(area.hidden);
+        assert!(!area.show_tooltip);
+        assert_eq!(area.badge, Some(Badge::Count(3)));
+
+        // Fields which are not part of the modification are left alone.
+        let mut modify = ModifyArea::default();
+        modify.badge(None);
+        description.modify_area(0, &modify);
+
+        let area = &description.areas[0];
+        assert_eq!(area.tooltip.as_deref(), Some("Syncing"));
+        assert_eq!(area.badge, None);
+
+        // Modifying an unknown area does nothing.
+        let before = description.clone();
+        description.modify_area(1, &modify);
+        assert_eq!(description, before);
+    }
+
+    #[test]
+    fn insert_menu_item() {
+        let mut description = window().describe(false);
+
+        let text = String::from("Download");
+        let new = MenuItem::new(ItemId::new(0, 3), MenuItemKind::String { text });
+        description.insert_menu_item(1, &new);
+
+        let separator = MenuItem::new(ItemId::new(0, 4), MenuItemKind::Separator);
+        description.insert_menu_item(100, &separator);
+
+        assert_eq!(
+            texts(&description),
+            [Some("Upload"), Some("Download"), None, Some("Quit"), None]
+        );
+
+        let items = &description.areas[0].popup_menu.as_ref().unwrap().items;
+        assert_eq!(items[1], item(3, Some("Download")));
+        assert_eq!(items[4], item(4, None));
+
+        // Inserting into an unknown area does nothing.
+        let before = description.clone();
+        let foreign = MenuItem::new(ItemId::new(1, 0), MenuItemKind::Separator);
+        description.insert_menu_item(0, &foreign);
+        assert_eq!(description, before);
+    }
+
+    #[test]
+    fn remove_menu_item() {
+        let mut description = window().describe(false);
+
+        description.remove_menu_item(0, 1);
+        assert_eq!(texts(&description), [Some("Upload"), Some("Quit")]);
+
+        description.remove_menu_item(0, 0);
+        assert_eq!(texts(&description), [Some("Quit")]);
+
+        // Removing unknown items does nothing.
+        let before = description.clone();
+        description.remove_menu_item(0, 42);
+        description.remove_menu_item(1, 2);
+        assert_eq!(description, before);
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn serde_round_trip() {
+        let mut description = window().describe(false);
+
+        let mut modify = ModifyArea::default();
+        modify.badge(Some(Badge::Dot));
+        description.modify_area(0, &modify);
+
+        let json = serde_json::to_string(&description).unwrap();
+        let decoded = serde_json::from_str::<WindowDescription>(&json).unwrap();
+        assert_eq!(decoded, description);
+    }
+}
@@ -2,25 +2,156 @@ use std::char::DecodeUtf16Error;
 use std::fmt;
 use std::io;
 
+use crate::{AreaId, GroupId, IconId, ItemId};
+
 /// The error raised by this library.
 #[derive(Debug)]
 pub struct Error {
-    kind: ErrorKind,
+    kind: ErrorRepr,
 }
 
 impl Error {
     /// Construct a new error.
     pub(super) fn new<K>(kind: K) -> Self
     where
-        ErrorKind: From<K>,
+        ErrorRepr: From<K>,
     {
         Self { kind: kind.into() }
     }
+
+    /// Get the kind of the error.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use winctx::{CreateWindow, ErrorKind};
+    ///
+    /// # async fn test() -> winctx::Result<()> {
+    /// let (sender, _) = CreateWindow::new("se.tedro.Example").build().await?;
+    ///
+    /// if let Err(error) = sender.shutdown() {
+    ///     assert_eq!(error.kind(), ErrorKind::WindowClosed);
+    /// }
+    /// # Ok(()) }
+    /// ```
+    pub fn kind(&self) -> ErrorKind {
+        match &self.kind {
+            ErrorRepr::WindowSetup(..)
+            | ErrorRepr::StartMessageThread(..)
+            | ErrorRepr::SetupIcons(..)
+            | ErrorRepr::SetupMenu(..)
+            | ErrorRepr::BuildPopupMenu(..)
+            | ErrorRepr::RegisterMessage(..)
+            | ErrorRepr::AppUserModelId(..) => ErrorKind::WindowSetup,
+            ErrorRepr::Validate(..) => ErrorKind::InvalidConfiguration,
+            ErrorRepr::ThreadError(..)
+            | ErrorRepr::PostMessageDestroy
+            | ErrorRepr::PostMessage(..) => ErrorKind::WindowThread,
+            ErrorRepr::WindowClosed => ErrorKind::WindowClosed,
+            ErrorRepr::ClipboardPoll(..) => ErrorKind::Clipboard,
+            ErrorRepr::OpenRegistryKey(..)
+            | ErrorRepr::DeleteRegistryKey(..)
+            | ErrorRepr::GetRegistryValue(..)
+            | ErrorRepr::SetRegistryKey(..) => ErrorKind::Registry,
+            ErrorRepr::CurrentExecutable(..)
+            | ErrorRepr::BadAutoStartExecutable(..)
+            | ErrorRepr::BadAutoStartArgument(..)
+            | ErrorRepr::TaskScheduler(..)
+            | ErrorRepr::StartupShortcut(..) => ErrorKind::AutoStart,
+            ErrorRepr::ElevationRequired(..) => ErrorKind::ElevationRequired,
+            ErrorRepr::AddNotification(..)
+            | ErrorRepr::ModifyNotification(..)
+            | ErrorRepr::AreaRect(..)
+            | ErrorRepr::AddArea(..)
+            | ErrorRepr::RemoveArea(..) => ErrorKind::NotificationArea,
+            ErrorRepr::ModifyMenuItem(..)
+            | ErrorRepr::InsertMenuItem(..)
+            | ErrorRepr::RemoveMenuItem(..)
+            | ErrorRepr::OpenMenu(..)
+            | ErrorRepr::RegisterHotkey(..) => ErrorKind::Menu,
+            ErrorRepr::SendNotification(..)
+            | ErrorRepr::MissingNotification
+            | ErrorRepr::DismissNotification(..)
+            | ErrorRepr::ScheduleNotification(..)
+            | ErrorRepr::PersistNotificationUnsupported
+            | ErrorRepr::RemoveFromCenterUnsupported => ErrorKind::Notification,
+            ErrorRepr::ScheduleShutdown(..)
+            | ErrorRepr::CancelScheduledShutdown(..)
+            | ErrorRepr::BlockShutdown(..) => ErrorKind::Shutdown,
+            ErrorRepr::CreateMutex(..) => ErrorKind::Mutex,
+            ErrorRepr::CopyData(..) | ErrorRepr::ForwardArguments(..) => ErrorKind::CopyData,
+            ErrorRepr::SetTimer(..) => ErrorKind::Timer,
+            ErrorRepr::TaskbarProgress(..) | ErrorRepr::TaskbarBadge(..) => ErrorKind::Taskbar,
+        }
+    }
+
+    /// Get the raw operating system error code which caused the error, if
+    /// any.
+    ///
+    /// This corresponds to [`io::Error::raw_os_error`] of the underlying
+    /// error, such as `ERROR_ACCESS_DENIED` if the clipboard couldn't be
+    /// opened because another process is using it.
+    pub fn os_error(&self) -> Option<i32> {
+        let mut source = std::error::Error::source(self);
+
+        while let Some(error) = source {
+            if let Some(error) = error.downcast_ref::<io::Error>() {
+                return error.raw_os_error();
+            }
+
+            source = error.source();
+        }
+
+        None
+    }
+}
+
+/// The kind of an [`Error`], as returned by [`Error::kind`].
+///
+/// This can be used to decide how to handle an error, such as retrying an
+/// operation which failed because the clipboard was in use.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ErrorKind {
+    /// Setting up the window failed.
+    WindowSetup,
+    /// The window configuration references icons, menu items or groups which
+    /// don't belong to it.
+    InvalidConfiguration,
+    /// Communicating with the thread running the window failed.
+    WindowThread,
+    /// The window has been closed.
+    WindowClosed,
+    /// Accessing the clipboard failed, for example because it's in use by
+    /// another process.
+    Clipboard,
+    /// Accessing the registry failed.
+    Registry,
+    /// Setting up or inspecting automatic start failed.
+    AutoStart,
+    /// Administrator privileges are required for the operation.
+    ElevationRequired,
+    /// Adding, modifying or removing a notification area failed.
+    NotificationArea,
+    /// Modifying or opening a popup menu failed.
+    Menu,
+    /// Sending or dismissing a notification failed.
+    Notification,
+    /// Scheduling or blocking shutdown failed.
+    Shutdown,
+    /// Constructing a named mutex failed.
+    Mutex,
+    /// Copying data to another window failed.
+    CopyData,
+    /// Setting or cancelling a timer failed.
+    Timer,
+    /// Updating the taskbar button of the window failed.
+    Taskbar,
 }
 
-impl From<ErrorKind> for Error {
+impl From<ErrorRepr> for Error {
     #[inline]
-    fn from(kind: ErrorKind) -> Self {
+    fn from(kind: ErrorRepr) -> Self {
         Self { kind }
     }
 }
@@ -28,27 +159,69 @@ impl From<ErrorKind> for Error {
 impl fmt::Display for Error {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match &self.kind {
-            ErrorKind::WindowSetup(..) => write!(f, "Failed to set up window"),
-            ErrorKind::ThreadError(..) => write!(f, "Error in window thread"),
-            ErrorKind::ClipboardPoll(..) => write!(f, "Failed to poll clipboard"),
-            ErrorKind::DeleteRegistryKey(..) => write!(f, "Failed to delete registry key"),
-            ErrorKind::GetRegistryValue(..) => write!(f, "Failed to get registry value"),
-            ErrorKind::SetRegistryKey(..) => write!(f, "Failed to set registry key"),
-            ErrorKind::CurrentExecutable(..) => write!(f, "Could not get current executable"),
-            ErrorKind::BuildPopupMenu(..) => write!(f, "Failed to build popup menu"),
-            ErrorKind::SetupIcons(..) => write!(f, "Failed to setup icons"),
-            ErrorKind::SetupMenu(..) => write!(f, "Failed to setup menu"),
-            ErrorKind::ModifyMenuItem(..) => write!(f, "Failed to modify menu item"),
-            ErrorKind::AddNotification(..) => write!(f, "Failed to add notification area"),
-            ErrorKind::ModifyNotification(..) => write!(f, "Failed to modify notification area"),
-            ErrorKind::SendNotification(..) => write!(f, "Failed to send notification"),
-            ErrorKind::CreateMutex(..) => write!(f, "Failed to construct mutex"),
-            ErrorKind::OpenRegistryKey(..) => write!(f, "Failed to open registry key"),
-            ErrorKind::MissingNotification => write!(f, "Missing notification state"),
-            ErrorKind::BadAutoStartExecutable(..) => write!(f, "Bad autostart executable"),
-            ErrorKind::BadAutoStartArgument(..) => write!(f, "Bad autostart argument"),
-            ErrorKind::WindowClosed => write!(f, "Window has been closed"),
-            ErrorKind::PostMessageDestroy => write!(f, "Failed to post destroy window message"),
+            ErrorRepr::WindowSetup(..) => write!(f, "Failed to set up window"),
+            ErrorRepr::ThreadError(..) => write!(f, "Error in window thread"),
+            ErrorRepr::ClipboardPoll(..) => write!(f, "Failed to poll clipboard"),
+            ErrorRepr::DeleteRegistryKey(..) => write!(f, "Failed to delete registry key"),
+            ErrorRepr::GetRegistryValue(..) => write!(f, "Failed to get registry value"),
+            ErrorRepr::SetRegistryKey(..) => write!(f, "Failed to set registry key"),
+            ErrorRepr::CurrentExecutable(..) => write!(f, "Could not get current executable"),
+            ErrorRepr::BuildPopupMenu(..) => write!(f, "Failed to build popup menu"),
+            ErrorRepr::SetupIcons(..) => write!(f, "Failed to setup icons"),
+            ErrorRepr::SetupMenu(..) => write!(f, "Failed to setup menu"),
+            ErrorRepr::Validate(..) => write!(f, "Invalid window configuration"),
+            ErrorRepr::ModifyMenuItem(..) => write!(f, "Failed to modify menu item"),
+            ErrorRepr::InsertMenuItem(..) => write!(f, "Failed to insert menu item"),
+            ErrorRepr::RemoveMenuItem(..) => write!(f, "Failed to remove menu item"),
+            ErrorRepr::AddNotification(..) => write!(f, "Failed to add notification area"),
+            ErrorRepr::ModifyNotification(..) => write!(f, "Failed to modify notification area"),
+            ErrorRepr::SendNotification(..) => write!(f, "Failed to send notification"),
+            ErrorRepr::CreateMutex(..) => write!(f, "Failed to construct mutex"),
+            ErrorRepr::OpenRegistryKey(..) => write!(f, "Failed to open registry key"),
+            ErrorRepr::MissingNotification => write!(f, "Missing notification state"),
+            ErrorRepr::BadAutoStartExecutable(..) => write!(f, "Bad autostart executable"),
+            ErrorRepr::BadAutoStartArgument(..) => write!(f, "Bad autostart argument"),
+            ErrorRepr::WindowClosed => write!(f, "Window has been closed"),
+            ErrorRepr::PostMessageDestroy => write!(f, "Failed to post destroy window message"),
+            ErrorRepr::ScheduleShutdown(..) => write!(f, "Failed to schedule shutdown"),
+            ErrorRepr::AreaRect(..) => write!(f, "Failed to get notification area rectangle"),
+            ErrorRepr::AddArea(..) => write!(f, "Failed to add notification area"),
+            ErrorRepr::RemoveArea(..) => write!(f, "Failed to remove notification area"),
+            ErrorRepr::OpenMenu(..) => write!(f, "Failed to open popup menu"),
+            ErrorRepr::RegisterHotkey(..) => write!(f, "Failed to register global hotkey"),
+            ErrorRepr::DismissNotification(..) => write!(f, "Failed to dismiss notification"),
+            ErrorRepr::ScheduleNotification(..) => write!(f, "Failed to schedule notification"),
+            ErrorRepr::CopyData(..) => write!(f, "Failed to copy data to window"),
+            ErrorRepr::ForwardArguments(..) => {
+                write!(f, "Failed to forward arguments to running instance")
+            }
+            ErrorRepr::RegisterMessage(..) => write!(f, "Failed to register window message"),
+            ErrorRepr::TaskScheduler(..) => write!(f, "Failed to access the task scheduler"),
+            ErrorRepr::StartupShortcut(..) => write!(f, "Failed to access startup shortcut"),
+            ErrorRepr::BlockShutdown(..) => write!(f, "Failed to block shutdown"),
+            ErrorRepr::SetTimer(..) => write!(f, "Failed to set timer"),
+            ErrorRepr::PostMessage(..) => write!(f, "Failed to post message to window"),
+            ErrorRepr::StartMessageThread(..) => write!(f, "Failed to start message thread"),
+            ErrorRepr::TaskbarProgress(..) => write!(f, "Failed to set taskbar progress"),
+            ErrorRepr::TaskbarBadge(..) => write!(f, "Failed to set taskbar badge"),
+            ErrorRepr::AppUserModelId(..) => {
+                write!(f, "Failed to set application user model id")
+            }
+            ErrorRepr::ElevationRequired(..) => write!(
+                f,
+                "Administrator privileges are required to start the program for all users"
+            ),
+            ErrorRepr::PersistNotificationUnsupported => write!(
+                f,
+                "Persisting notifications is not supported by balloon notifications"
+            ),
+            ErrorRepr::RemoveFromCenterUnsupported => write!(
+                f,
+                "Removing notifications from the notification center is not supported by balloon notifications"
+            ),
+            ErrorRepr::CancelScheduledShutdown(..) => {
+                write!(f, "Failed to cancel scheduled shutdown")
+            }
         }
     }
 }
@@ -56,24 +229,49 @@ impl fmt::Display for Error {
 impl std::error::Error for Error {
     fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
         match &self.kind {
-            ErrorKind::WindowSetup(error) => Some(error),
-            ErrorKind::ThreadError(error) => Some(error),
-            ErrorKind::ClipboardPoll(error) => Some(error),
-            ErrorKind::DeleteRegistryKey(error) => Some(error),
-            ErrorKind::GetRegistryValue(error) => Some(error),
-            ErrorKind::SetRegistryKey(error) => Some(error),
-            ErrorKind::CurrentExecutable(error) => Some(error),
-            ErrorKind::BuildPopupMenu(error) => Some(error),
-            ErrorKind::SetupIcons(error) => Some(error),
-            ErrorKind::SetupMenu(error) => Some(error),
-            ErrorKind::ModifyMenuItem(error) => Some(error),
-            ErrorKind::AddNotification(error) => Some(error),
-            ErrorKind::ModifyNotification(error) => Some(error),
-            ErrorKind::SendNotification(error) => Some(error),
-            ErrorKind::CreateMutex(error) => Some(error),
-            ErrorKind::OpenRegistryKey(error) => Some(error),
-            ErrorKind::BadAutoStartExecutable(error) => Some(error),
-            ErrorKind::BadAutoStartArgument(error) => Some(error),
+            ErrorRepr::WindowSetup(error) => Some(error),
+            ErrorRepr::ThreadError(error) => Some(error),
+            ErrorRepr::ClipboardPoll(error) => Some(error),
+            ErrorRepr::DeleteRegistryKey(error) => Some(error),
+            ErrorRepr::GetRegistryValue(error) => Some(error),
+            ErrorRepr::SetRegistryKey(error) => Some(error),
+            ErrorRepr::CurrentExecutable(error) => Some(error),
+            ErrorRepr::BuildPopupMenu(error) => Some(error),
+            ErrorRepr::SetupIcons(error) => Some(error),
+            ErrorRepr::SetupMenu(error) => Some(error),
+            ErrorRepr::Validate(error) => Some(error),
+            ErrorRepr::ModifyMenuItem(error) => Some(error),
+            ErrorRepr::InsertMenuItem(error) => Some(error),
+            ErrorRepr::RemoveMenuItem(error) => Some(error),
+            ErrorRepr::AddNotification(error) => Some(error),
+            ErrorRepr::ModifyNotification(error) => Some(error),
+            ErrorRepr::SendNotification(error) => Some(error),
+            ErrorRepr::CreateMutex(error) => Some(error),
+            ErrorRepr::OpenRegistryKey(error) => Some(error),
+            ErrorRepr::BadAutoStartExecutable(error) => Some(error),
+            ErrorRepr::BadAutoStartArgument(error) => Some(error),
+            ErrorRepr::ScheduleShutdown(error) => Some(error),
+            ErrorRepr::CancelScheduledShutdown(error) => Some(error),
+            ErrorRepr::AreaRect(error) => Some(error),
+            ErrorRepr::AddArea(error) => Some(error),
+            ErrorRepr::RemoveArea(error) => Some(error),
+            ErrorRepr::OpenMenu(error) => Some(error),
+            ErrorRepr::RegisterHotkey(error) => Some(error),
+            ErrorRepr::DismissNotification(error) => Some(error),
+            ErrorRepr::ScheduleNotification(error) => Some(error),
+            ErrorRepr::CopyData(error) => Some(error),
+            ErrorRepr::ForwardArguments(error) => Some(error),
+            ErrorRepr::RegisterMessage(error) => Some(error),
+            ErrorRepr::TaskScheduler(error) => Some(error),
+            ErrorRepr::ElevationRequired(error) => Some(error),
+            ErrorRepr::StartupShortcut(error) => Some(error),
+            ErrorRepr::BlockShutdown(error) => Some(error),
+            ErrorRepr::SetTimer(error) => Some(error),
+            ErrorRepr::PostMessage(error) => Some(error),
+            ErrorRepr::StartMessageThread(error) => Some(error),
+            ErrorRepr::TaskbarProgress(error) => Some(error),
+            ErrorRepr::AppUserModelId(error) => Some(error),
+            ErrorRepr::TaskbarBadge(error) => Some(error),
             _ => None,
         }
     }
@@ -127,7 +325,7 @@ impl std::error::Error for WindowError {
 }
 
 #[derive(Debug)]
-pub(super) enum ErrorKind {
+pub(super) enum ErrorRepr {
     WindowSetup(WindowError),
     ThreadError(WindowError),
     ClipboardPoll(WindowError),
@@ -138,7 +336,10 @@ pub(super) enum ErrorKind {
     BuildPopupMenu(io::Error),
     SetupIcons(SetupIconsError),
     SetupMenu(SetupMenuError),
+    Validate(ValidateError),
     ModifyMenuItem(io::Error),
+    InsertMenuItem(io::Error),
+    RemoveMenuItem(io::Error),
     AddNotification(io::Error),
     ModifyNotification(io::Error),
     SendNotification(io::Error),
@@ -149,6 +350,30 @@ pub(super) enum ErrorKind {
     BadAutoStartArgument(DecodeUtf16Error),
     WindowClosed,
     PostMessageDestroy,
+    ScheduleShutdown(io::Error),
+    CancelScheduledShutdown(io::Error),
+    PersistNotificationUnsupported,
+    RemoveFromCenterUnsupported,
+    AreaRect(io::Error),
+    AddArea(io::Error),
+    RemoveArea(io::Error),
+    OpenMenu(io::Error),
+    RegisterHotkey(io::Error),
+    DismissNotification(io::Error),
+    ScheduleNotification(io::Error),
+    CopyData(io::Error),
+    ForwardArguments(io::Error),
+    RegisterMessage(io::Error),
+    TaskScheduler(io::Error),
+    ElevationRequired(io::Error),
+    StartupShortcut(io::Error),
+    BlockShutdown(io::Error),
+    SetTimer(io::Error),
+    PostMessage(io::Error),
+    StartMessageThread(io::Error),
+    TaskbarProgress(io::Error),
+    AppUserModelId(io::Error),
+    TaskbarBadge(io::Error),
 }
 
 #[derive(Debug)]
@@ -199,3 +424,42 @@ impl std::error::Error for SetupMenuError {
         }
     }
 }
+
+#[derive(Debug)]
+pub(super) enum ValidateError {
+    UnknownIcon(IconId),
+    UnknownDefault(AreaId, ItemId),
+    ForeignGroup(ItemId, GroupId),
+    UnknownArea(AreaId),
+    UnknownMenuItem(ItemId),
+}
+
+impl fmt::Display for ValidateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnknownIcon(icon) => {
+                write!(f, "Icon {icon:?} is not from the icons of the window")
+            }
+            Self::UnknownDefault(area_id, item_id) => {
+                write!(
+                    f,
+                    "Default item {item_id:?} is not in the popup menu of {area_id:?}"
+                )
+            }
+            Self::ForeignGroup(item_id, group_id) => {
+                write!(
+                    f,
+                    "Menu item {item_id:?} can't be added to {group_id:?} of another area"
+                )
+            }
+            Self::UnknownArea(area_id) => {
+                write!(f, "Area {area_id:?} doesn't exist")
+            }
+            Self::UnknownMenuItem(item_id) => {
+                write!(f, "Menu item {item_id:?} doesn't exist")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ValidateError {}
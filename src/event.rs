@@ -1,6 +1,11 @@
 //! Types related to events produced by this library.
 
-use crate::{AreaId, Error, ItemId, NotificationId};
+use std::ffi::OsString;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+use crate::window::{Peer, Reply};
+use crate::{AreaId, ClipboardFormat, Error, ItemId, MenuAction, NotificationId};
 
 /// A mouse button.
 #[derive(Debug, Clone, Copy)]
@@ -11,6 +16,8 @@ pub enum MouseButton {
     Left = 0x1,
     /// Right mouse button.
     Right = 0x2,
+    /// Middle mouse button.
+    Middle = 0x4,
 }
 
 /// A collection of mouse buttons.
@@ -54,12 +61,60 @@ impl MouseButtons {
     }
 }
 
+/// A modifier key.
+#[derive(Debug, Clone, Copy)]
+#[non_exhaustive]
+#[repr(u32)]
+pub enum Modifier {
+    /// The shift key.
+    Shift = 0x1,
+    /// The control key.
+    Control = 0x2,
+    /// The alt key.
+    Alt = 0x4,
+}
+
+/// A collection of modifier keys.
+#[derive(Debug)]
+pub struct Modifiers(u32);
+
+impl Modifiers {
+    /// Create a new collection of modifier keys.
+    pub(super) fn from_iter<I>(iter: I) -> Self
+    where
+        I: IntoIterator<Item = Modifier>,
+    {
+        let mut modifiers = 0;
+
+        for modifier in iter {
+            modifiers |= modifier as u32;
+        }
+
+        Self(modifiers)
+    }
+
+    /// Test if the given modifier key is held down.
+    pub fn test(&self, modifier: Modifier) -> bool {
+        self.0 & modifier as u32 != 0
+    }
+
+    /// Test if no modifier keys are held down.
+    pub fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+}
+
 /// An event generated by a mouse click.
 #[derive(Debug)]
 #[non_exhaustive]
 pub struct MouseEvent {
     /// Mouse button responsible for the event.
     pub buttons: MouseButtons,
+    /// The position of the cursor in screen coordinates when the event was
+    /// generated.
+    pub position: (i32, i32),
+    /// Modifier keys which were held down when the event was generated.
+    pub modifiers: Modifiers,
 }
 
 /// A clipbaord event.
@@ -67,9 +122,86 @@ pub struct MouseEvent {
 #[non_exhaustive]
 pub enum ClipboardEvent {
     /// A bitmap has been copied.
+    ///
+    /// This contains the raw device-independent bitmap, and is only emitted
+    /// if [`CreateWindow::clipboard_raw_bitmaps`] is enabled or if the bitmap
+    /// could not be decoded into an [`ClipboardEvent::Image`].
+    ///
+    /// [`CreateWindow::clipboard_raw_bitmaps`]: crate::CreateWindow::clipboard_raw_bitmaps
     BitMap(Vec<u8>),
+    /// A bitmap has been copied and decoded.
+    Image {
+        /// The width of the image in pixels.
+        width: u32,
+        /// The height of the image in pixels.
+        height: u32,
+        /// The pixels of the image, with four bytes per pixel in RGBA order
+        /// and rows laid out top to bottom.
+        rgba: Vec<u8>,
+    },
     /// A string has been copied.
     Text(String),
+    /// A list of files has been copied, such as when copying files in
+    /// Explorer.
+    Files(Vec<PathBuf>),
+    /// Data has been copied which is larger than the maximum size configured
+    /// with [`ClipboardOptions::max_size`].
+    ///
+    /// The data can be fetched on demand using [`clipboard::with_data`].
+    ///
+    /// [`ClipboardOptions::max_size`]: crate::ClipboardOptions::max_size
+    /// [`clipboard::with_data`]: crate::clipboard::with_data
+    TooLarge {
+        /// The format of the data.
+        format: ClipboardFormat,
+        /// The size of the data in bytes.
+        size: usize,
+    },
+    /// The contents of the clipboard have changed.
+    ///
+    /// This is emitted instead of any other clipboard event if
+    /// [`CreateWindow::clipboard_metadata_only`] is enabled.
+    ///
+    /// [`CreateWindow::clipboard_metadata_only`]: crate::CreateWindow::clipboard_metadata_only
+    Changed {
+        /// The formats which are available on the clipboard.
+        formats: Vec<ClipboardFormat>,
+        /// The sequence number of the clipboard.
+        sequence: u32,
+    },
+}
+
+/// The reason the window was shut down, as reported by [`Event::Shutdown`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ShutdownReason {
+    /// Shutdown was requested using [`Sender::shutdown`] or
+    /// [`Sender::shutdown_after`].
+    ///
+    /// [`Sender::shutdown`]: crate::Sender::shutdown
+    /// [`Sender::shutdown_after`]: crate::Sender::shutdown_after
+    Requested,
+    /// The window was closed or destroyed from outside of winctx, such as by
+    /// another process.
+    Destroyed,
+    /// The session is ending, and the process is about to be terminated.
+    SessionEnding,
+    /// The thread running the window exited unexpectedly, such as if it
+    /// panicked.
+    ThreadError,
+}
+
+/// The reason the session is ending, as reported by [`Event::EndSession`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum EndSessionReason {
+    /// The system is shutting down or restarting.
+    Shutdown,
+    /// The user is logging off.
+    Logoff,
+    /// The application is being closed so that it can be updated, such as by
+    /// an installer.
+    CloseApp,
 }
 
 /// An event emitted by the event loop.
@@ -77,14 +209,56 @@ pub enum ClipboardEvent {
 #[non_exhaustive]
 pub enum Event {
     /// Window has been shut down.
-    Shutdown {},
+    ///
+    /// The reason can be used to decide whether to build the window again or
+    /// to exit the process.
+    Shutdown {
+        /// The reason the window was shut down.
+        reason: ShutdownReason,
+    },
+    /// A shutdown has been scheduled using [`Sender::shutdown_after`].
+    ///
+    /// [`Sender::shutdown_after`]: crate::Sender::shutdown_after
+    ShutdownScheduled {
+        /// The instant at which the window will shut down.
+        at: Instant,
+    },
+    /// A scheduled shutdown has been cancelled using
+    /// [`Sender::cancel_scheduled_shutdown`].
+    ///
+    /// [`Sender::cancel_scheduled_shutdown`]: crate::Sender::cancel_scheduled_shutdown
+    ShutdownCancelled {},
     /// The menu item identified by [`ItemId`] has been clicked.
+    ///
+    /// The area the menu item belongs to is available through
+    /// [`ItemId::area_id`].
     MenuItemClicked {
         /// The item that was clicked.
         item_id: ItemId,
+        /// The text of the menu item at the time it was clicked.
+        text: String,
+        /// The action associated with the menu item, if any.
+        ///
+        /// See [`PopupMenu::push_entry_id`].
+        ///
+        /// [`PopupMenu::push_entry_id`]: crate::PopupMenu::push_entry_id
+        action: Option<MenuAction>,
         /// The generated event.
         event: MouseEvent,
     },
+    /// The popup menu of an area is about to be shown.
+    ///
+    /// This can be used to refresh the state of the menu right before the
+    /// user sees it.
+    MenuOpened {
+        /// The area whose popup menu was opened.
+        area_id: AreaId,
+    },
+    /// The popup menu of an area has been closed.
+    MenuClosed {
+        /// The area whose popup menu was closed.
+        area_id: AreaId,
+    },
     /// An icon has been clicked.
     IconClicked {
         /// The area that was clicked.
@@ -92,6 +266,23 @@ pub enum Event {
         /// The generated event.
         event: MouseEvent,
     },
+    /// An icon has been double clicked.
+    ///
+    /// Note that the clicks making up the double click are also reported
+    /// through [`Event::IconClicked`].
+    IconDoubleClicked {
+        /// The area that was double clicked.
+        area_id: AreaId,
+        /// The generated event.
+        event: MouseEvent,
+    },
+    /// The cursor entered or left the icon of an area.
+    IconHovered {
+        /// The area that was hovered.
+        area_id: AreaId,
+        /// Whether the cursor entered the icon, or `false` if it left.
+        entered: bool,
+    },
     /// Indicates that the notification with the associated token has been clicked.
     NotificationClicked {
         /// The area the notification belonged to.
@@ -112,6 +303,15 @@ pub enum Event {
     Clipboard {
         /// The generated clipboard event.
         event: ClipboardEvent,
+        /// The sequence number of the clipboard when the event was generated.
+        ///
+        /// This can be compared against the sequence number after writing to
+        /// the clipboard to ignore events caused by the application itself, or
+        /// against the current [sequence number] to detect whether the
+        /// clipboard has been modified since.
+        ///
+        /// [sequence number]: crate::clipboard::sequence_number
+        sequence: u32,
     },
     /// Data was copied to the current process remotely using
     /// [`Window::copy_data`].
@@ -122,10 +322,143 @@ pub enum Event {
         ty: usize,
         /// The data.
         data: Vec<u8>,
+        /// The process which sent the data, if the sender passed along a
+        /// window it owns as is the convention.
+        peer: Option<Peer>,
+        /// A handle to reply with if the data was sent as a request using
+        /// [`Window::request`].
+        ///
+        /// [`Window::request`]: crate::Window::request
+        reply: Option<Reply>,
+    },
+    /// A chunk of data copied using [`Window::copy_data_chunked`] has been
+    /// received.
+    ///
+    /// Once all data has been received it is delivered as an
+    /// [`Event::CopyData`] event.
+    ///
+    /// [`Window::copy_data_chunked`]: crate::Window::copy_data_chunked
+    CopyDataProgress {
+        /// The type parameter passed when sending the data.
+        ty: usize,
+        /// The number of bytes received so far.
+        received: usize,
+        /// The total number of bytes being copied.
+        total: usize,
+    },
+    /// A typed message was sent to the current process remotely using
+    /// [`Window::send_message`].
+    ///
+    /// [`Window::send_message`]: crate::Window::send_message
+    #[cfg(feature = "serde")]
+    Message {
+        /// The tag the message was sent with.
+        tag: String,
+        /// The message, which can be decoded using [`Message::decode`].
+        ///
+        /// [`Message::decode`]: crate::window::Message::decode
+        message: crate::window::Message,
+    },
+    /// Arguments were forwarded by another instance of the application using
+    /// [`tools::single_instance`].
+    ///
+    /// [`tools::single_instance`]: crate::tools::single_instance
+    Arguments {
+        /// The forwarded arguments, excluding the name of the program.
+        args: Vec<OsString>,
+    },
+    /// A message registered through [`CreateWindow::register_message`] was
+    /// received.
+    ///
+    /// [`CreateWindow::register_message`]: crate::CreateWindow::register_message
+    AppMessage {
+        /// The identifier of the registered message.
+        id: u32,
+        /// The first parameter of the message.
+        wparam: usize,
+        /// The second parameter of the message.
+        lparam: isize,
+    },
+    /// The session is about to end, because the system is shutting down or
+    /// the user is logging off.
+    ///
+    /// The process might be terminated shortly after this event has been
+    /// emitted. To make sure there is time to flush state, block the session
+    /// from ending ahead of time using [`Sender::block_shutdown`] and unblock
+    /// it once done.
+    ///
+    /// [`Sender::block_shutdown`]: crate::Sender::block_shutdown
+    EndSession {
+        /// The reason the session is ending.
+        reason: EndSessionReason,
+        /// Whether the session is being ended forcibly, in which case it can't
+        /// be blocked.
+        critical: bool,
+    },
+    /// Ending the session has been cancelled, either by the user or by
+    /// another application.
+    EndSessionCancelled {},
+    /// A timer started using [`Sender::set_timer`] has elapsed.
+    ///
+    /// [`Sender::set_timer`]: crate::Sender::set_timer
+    Timer {
+        /// The identifier of the timer.
+        id: u16,
+    },
+    /// The close button of the window created using
+    /// [`CreateWindow::visible`] was pressed.
+    ///
+    /// The window isn't closed automatically, so it should be hidden using
+    /// [`Sender::hide`] or the application shut down.
+    ///
+    /// [`CreateWindow::visible`]: crate::CreateWindow::visible
+    /// [`Sender::hide`]: crate::Sender::hide
+    WindowClosed {},
+    /// Files were dragged and dropped onto the window created using
+    /// [`CreateWindow::visible`].
+    ///
+    /// [`CreateWindow::visible`]: crate::CreateWindow::visible
+    FilesDropped {
+        /// The paths of the dropped files and directories.
+        paths: Vec<PathBuf>,
+        /// The position the files were dropped at, in client coordinates of
+        /// the window.
+        position: (i32, i32),
+    },
+    /// The user has been idle for at least the threshold configured using
+    /// [`CreateWindow::idle_threshold`].
+    ///
+    /// [`CreateWindow::idle_threshold`]: crate::CreateWindow::idle_threshold
+    Idle {
+        /// How long the user has been idle.
+        duration: Duration,
+    },
+    /// The user is active again after [`Event::Idle`] was emitted.
+    Active {},
+    /// The window created using [`CreateWindow::visible`] was hidden because
+    /// it was minimized or closed, as enabled using
+    /// [`CreateWindow::hide_to_tray`].
+    ///
+    /// [`CreateWindow::visible`]: crate::CreateWindow::visible
+    /// [`CreateWindow::hide_to_tray`]: crate::CreateWindow::hide_to_tray
+    WindowHidden {},
+    /// The window created using [`CreateWindow::visible`] was resized.
+    ///
+    /// [`CreateWindow::visible`]: crate::CreateWindow::visible
+    Resized {
+        /// The new width of the client area.
+        width: u32,
+        /// The new height of the client area.
+        height: u32,
     },
     /// A non-fatal error has been reported.
+    ///
+    /// Failing to show or update a notification is reported this way, after
+    /// which the event loop carries on with the next notification.
     Error {
         /// The reported error.
         error: Error,
+        /// The notification the error is associated with, if any.
+        notification_id: Option<NotificationId>,
     },
 }
@@ -1,14 +1,23 @@
-use std::collections::VecDeque;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::future::Future;
+use std::pin::pin;
+use std::ptr;
+use std::task::{Context, Poll, RawWaker, RawWakerVTable, Waker};
+use std::time::{Duration, Instant, SystemTime};
 
 use tokio::sync::mpsc;
 
-use crate::error::Error;
-use crate::error::ErrorKind::*;
+use crate::create_window::{add_area, build_area, validate_icon, validate_menu_item};
+use crate::description::AreaDescription;
+use crate::error::ErrorRepr::{self, *};
+use crate::error::{Error, ValidateError};
+use crate::event::ShutdownReason;
 use crate::item_id::ItemId;
+use crate::window::{args, Reply, ARGS_TY};
 use crate::window_loop::IconHandle;
-use crate::window_loop::{WindowEvent, WindowLoop};
-use crate::NotificationId;
-use crate::{AreaId, Event, InputEvent, Notification, Result};
+use crate::window_loop::{CopiedData, Hotkey, WindowEvent, WindowLoop};
+use crate::{AreaId, Event, Handler, InputEvent, MenuAction, Notification, Result};
+use crate::{ModifyArea, NotificationId, WindowDescription};
 
 /// The event loop being run.
 #[repr(C)]
@@ -16,8 +25,21 @@ pub struct EventLoop {
     events_rx: mpsc::UnboundedReceiver<InputEvent>,
     window_loop: WindowLoop,
     icons: Vec<IconHandle>,
-    visible: Option<(AreaId, NotificationId)>,
+    /// Identifier of the collection of icons of the window.
+    icons_id: u32,
+    visible: Option<(AreaId, NotificationId, Notification)>,
     pending: VecDeque<(AreaId, NotificationId, Notification)>,
+    /// Scheduled notifications, ordered by when they should be shown.
+    scheduled: Vec<(SystemTime, AreaId, NotificationId, Notification)>,
+    scheduled_shutdown: Option<Instant>,
+    description: WindowDescription,
+    /// Events which are emitted before any further input is processed.
+    deferred: VecDeque<Event>,
+    /// Actions associated with menu items.
+    actions: HashMap<ItemId, MenuAction>,
+    /// The current state of each notification area, which is used to add
+    /// them again if Explorer restarts.
+    area_state: HashMap<AreaId, ModifyArea>,
 }
 
 impl EventLoop {
@@ -25,83 +47,769 @@ impl EventLoop {
         events_rx: mpsc::UnboundedReceiver<InputEvent>,
         window_loop: WindowLoop,
         icons: Vec<IconHandle>,
+        icons_id: u32,
+        description: WindowDescription,
+        actions: HashMap<ItemId, MenuAction>,
+        area_state: HashMap<AreaId, ModifyArea>,
     ) -> Self {
         Self {
             events_rx,
             window_loop,
             icons,
+            icons_id,
             visible: None,
             pending: VecDeque::new(),
+            scheduled: Vec::new(),
+            scheduled_shutdown: None,
+            description,
+            deferred: VecDeque::new(),
+            actions,
+            area_state,
+        }
+    }
+
+    /// Add all notification areas again with their current state, since they
+    /// are lost when the taskbar is created again such as when Explorer
+    /// restarts.
+    fn readd_areas(&mut self) {
+        for (&area_id, modify) in &self.area_state {
+            let result = self
+                .window_loop
+                .window
+                .readd_notification(area_id)
+                .map_err(AddNotification)
+                .and_then(|()| {
+                    self.window_loop
+                        .window
+                        .modify_notification(area_id, modify, &self.icons)
+                        .map_err(ModifyNotification)
+                });
+
+            if let Err(kind) = result {
+                self.deferred.push_back(Event::Error {
+                    error: Error::new(kind),
+                    notification_id: None,
+                });
+            }
         }
     }
 
     fn take_notification(&mut self) -> Result<(AreaId, NotificationId)> {
-        let (area_id, id) = self.visible.take().ok_or(MissingNotification)?;
+        let (area_id, id, _) = self.visible.take().ok_or(MissingNotification)?;
+
+        while let Some((area_id, id, n)) = self.pending.pop_front() {
+            if self.send_notification(area_id, id, n) {
+                break;
+            }
+        }
+
+        Ok((area_id, id))
+    }
+
+    /// Send a notification and make it the visible one.
+    ///
+    /// Failing to send is reported as an error event for the notification
+    /// instead of terminating the event loop, in which case `false` is
+    /// returned.
+    fn send_notification(&mut self, area_id: AreaId, id: NotificationId, n: Notification) -> bool {
+        if let Err(error) = self
+            .window_loop
+            .window
+            .send_notification(area_id, &n, &self.icons)
+        {
+            self.notification_error(id, SendNotification(error));
+            return false;
+        }
+
+        self.visible = Some((area_id, id, n));
+        true
+    }
+
+    /// Report an error associated with the given notification.
+    fn notification_error(&mut self, id: NotificationId, kind: ErrorRepr) {
+        self.deferred.push_back(Event::Error {
+            error: Error::new(kind),
+            notification_id: Some(id),
+        });
+    }
+
+    /// Show a notification, or queue it if another notification is visible.
+    fn show_notification(&mut self, area_id: AreaId, id: NotificationId, n: Notification) {
+        if self.visible.is_some() {
+            self.pending.push_back((area_id, id, n));
+            return;
+        }
+
+        self.send_notification(area_id, id, n);
+    }
+
+    /// Remove any notification which is replaced by the given one because it
+    /// has the same tag, emitting a dismissed event for it.
+    ///
+    /// If possible the replaced notification is swapped out in place, otherwise
+    /// the given notification is returned so that it can be shown as usual.
+    fn replace_tagged(
+        &mut self,
+        area_id: AreaId,
+        id: NotificationId,
+        n: Notification,
+        immediate: bool,
+    ) -> Result<Option<Notification>> {
+        if n.tag.is_none() {
+            return Ok(Some(n));
+        }
+
+        if let Some(index) = self
+            .pending
+            .iter()
+            .position(|(_, _, p)| p.is_replaced_by(&n))
+        {
+            let (old_area_id, old_id, n) = if immediate {
+                let (a, i, _) = std::mem::replace(&mut self.pending[index], (area_id, id, n));
+                (a, i, None)
+            } else {
+                let Some((a, i, _)) = self.pending.remove(index) else {
+                    return Ok(Some(n));
+                };
+
+                (a, i, Some(n))
+            };
+
+            self.deferred.push_back(Event::NotificationDismissed {
+                area_id: old_area_id,
+                id: old_id,
+            });
+
+            return Ok(n);
+        }
+
+        if let Some(index) = self
+            .scheduled
+            .iter()
+            .position(|(_, _, _, s)| s.is_replaced_by(&n))
+        {
+            let (_, area_id, id, _) = self.scheduled.remove(index);
+            self.deferred
+                .push_back(Event::NotificationDismissed { area_id, id });
+            return Ok(Some(n));
+        }
 
-        if let Some((area_id, id, n)) = self.pending.pop_front() {
-            self.visible = Some((area_id, id));
+        let Some((visible_area_id, visible_id, visible)) = &self.visible else {
+            return Ok(Some(n));
+        };
+
+        if !visible.is_replaced_by(&n) {
+            return Ok(Some(n));
+        }
+
+        let (visible_area_id, visible_id) = (*visible_area_id, *visible_id);
+
+        // NB: The visible notification can only be modified in place if it
+        // belongs to the same area.
+        if immediate && visible_area_id == area_id {
+            if !self.send_notification(area_id, id, n) {
+                return Ok(None);
+            }
+
+            self.deferred.push_back(Event::NotificationDismissed {
+                area_id: visible_area_id,
+                id: visible_id,
+            });
+            return Ok(None);
+        }
+
+        self.window_loop
+            .window
+            .clear_notification(visible_area_id)
+            .map_err(DismissNotification)?;
+        let (area_id, id) = self.take_notification()?;
+        self.deferred
+            .push_back(Event::NotificationDismissed { area_id, id });
+        Ok(Some(n))
+    }
+
+    /// Show scheduled notifications which are due, and schedule a wakeup for
+    /// the next one.
+    fn release_scheduled(&mut self) -> Result<()> {
+        let now = SystemTime::now();
+        let due = self.scheduled.partition_point(|&(at, ..)| at <= now);
+
+        for (_, area_id, id, n) in self.scheduled.drain(..due).collect::<Vec<_>>() {
+            self.show_notification(area_id, id, n);
+        }
+
+        if let Some(&(at, ..)) = self.scheduled.first() {
+            let delay = at.duration_since(now).unwrap_or_default();
             self.window_loop
                 .window
-                .send_notification(area_id, n)
-                .map_err(SendNotification)?;
+                .schedule_notifications(delay)
+                .map_err(ScheduleNotification)?;
         }
 
-        Ok((area_id, id))
+        Ok(())
+    }
+
+    /// Mark the event loop as terminated and join the window.
+    ///
+    /// This closes the input channel so that any inputs sent afterwards are
+    /// rejected, and drops inputs which are already queued since they will
+    /// never be processed.
+    fn terminate(&mut self) -> Result<()> {
+        close_inputs(&mut self.events_rx);
+        self.pending.clear();
+        self.scheduled.clear();
+        self.deferred.clear();
+        self.visible = None;
+        self.scheduled_shutdown = None;
+        self.window_loop.join()
+    }
+
+    /// Drive the event loop until the window has shut down, dispatching each
+    /// event to the callbacks registered in the given [`Handler`].
+    ///
+    /// This is an alternative to calling [`EventLoop::tick`] in a loop.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use winctx::{CreateWindow, Handler};
+    ///
+    /// # async fn test() -> winctx::Result<()> {
+    /// let window = CreateWindow::new("se.tedro.Example").clipboard_events(true);
+    /// let (_, mut event_loop) = window.build().await?;
+    ///
+    /// let handler = Handler::new().on_clipboard(|event| println!("{event:?}"));
+    /// event_loop.run(handler).await?;
+    /// # Ok(()) }
+    /// ```
+    pub async fn run(&mut self, mut handler: Handler<'_>) -> Result<()> {
+        loop {
+            let event = self.tick().await?;
+            let shutdown = matches!(event, Event::Shutdown { .. });
+            handler.dispatch(event);
+
+            if shutdown {
+                return Ok(());
+            }
+        }
+    }
+
+    /// Check that every input in a batch refers to areas, menu items and icons
+    /// which exist, so that a batch which can't be applied is rejected before
+    /// any of it is.
+    fn validate_batch(&self, inputs: &[InputEvent]) -> Result<()> {
+        let mut inserted = HashSet::new();
+
+        for input in inputs {
+            match input {
+                InputEvent::ModifyArea { area_id, modify } => {
+                    if self.window_loop.area(*area_id).is_none() {
+                        return Err(Error::new(Validate(ValidateError::UnknownArea(*area_id))));
+                    }
+
+                    if let Some(Some(icon)) = modify.icon {
+                        validate_icon(icon, self.icons_id).map_err(Validate)?;
+                    }
+                }
+                InputEvent::ModifyMenuItem { item_id, .. } => {
+                    // NB: Modifying items of areas without a popup menu does
+                    // nothing, so only items missing from a menu are errors.
+                    let popup_menu = self
+                        .window_loop
+                        .area(item_id.area_id())
+                        .and_then(|area| area.popup_menu.as_ref());
+
+                    if let Some(popup_menu) = popup_menu {
+                        if !popup_menu.contains(item_id.id()) && !inserted.contains(item_id) {
+                            return Err(Error::new(Validate(ValidateError::UnknownMenuItem(
+                                *item_id,
+                            ))));
+                        }
+                    }
+                }
+                InputEvent::InsertMenuItem { item, .. } => {
+                    validate_menu_item(item, self.icons_id).map_err(Validate)?;
+
+                    if let (Some(shortcut), true) = (&item.shortcut, item.global_hotkey) {
+                        Hotkey::parse(shortcut).map_err(InsertMenuItem)?;
+                    }
+
+                    inserted.insert(item.item_id);
+                }
+                _ => {}
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Handle a single input sent through a [`Sender`], returning an event if
+    /// one should be emitted.
+    ///
+    /// [`Sender`]: crate::Sender
+    fn handle_input(&mut self, input: InputEvent) -> Result<Option<Event>> {
+        match input {
+            InputEvent::ModifyArea { area_id, modify } => {
+                self.window_loop
+                    .window
+                    .modify_notification(area_id, &modify, &self.icons)
+                    .map_err(ModifyNotification)?;
+                self.description.modify_area(area_id.id(), &modify);
+                self.area_state.entry(area_id).or_default().merge(modify);
+            }
+            InputEvent::ModifyMenuItem { item_id, modify } => {
+                let Some(menu) = self.window_loop.area(item_id.area_id()) else {
+                    return Ok(None);
+                };
+
+                let Some(popup_menu) = &menu.popup_menu else {
+                    return Ok(None);
+                };
+
+                popup_menu
+                    .modify_menu_item(item_id.id(), &modify)
+                    .map_err(ModifyMenuItem)?;
+                self.description
+                    .modify_menu_item(item_id.area_id().id(), item_id.id(), &modify);
+            }
+            InputEvent::ModifyGroup { group_id, modify } => {
+                let Some(menu) = self.window_loop.area(group_id.area_id()) else {
+                    return Ok(None);
+                };
+
+                let Some(popup_menu) = &menu.popup_menu else {
+                    return Ok(None);
+                };
+
+                for &item_id in popup_menu.group(group_id.id()) {
+                    popup_menu
+                        .modify_menu_item(item_id, &modify)
+                        .map_err(ModifyMenuItem)?;
+                    self.description
+                        .modify_menu_item(group_id.area_id().id(), item_id, &modify);
+                }
+            }
+            InputEvent::InsertMenuItem { position, item } => {
+                validate_menu_item(&item, self.icons_id).map_err(Validate)?;
+
+                let Some(menu) = self.window_loop.area_mut(item.item_id.area_id()) else {
+                    return Ok(None);
+                };
+
+                let Some(popup_menu) = &mut menu.popup_menu else {
+                    return Ok(None);
+                };
+
+                popup_menu
+                    .insert_item(position, &item, false, &self.icons)
+                    .map_err(InsertMenuItem)?;
+                let hotkey = popup_menu.hotkey(item.item_id.id());
+                self.description.insert_menu_item(position, &item);
+
+                if let Some(action) = &item.action {
+                    self.actions.insert(item.item_id, action.clone());
+                }
+
+                if let Some(hotkey) = hotkey {
+                    self.window_loop
+                        .window
+                        .add_hotkey(item.item_id.area_id(), item.item_id.id(), hotkey)
+                        .map_err(RegisterHotkey)?;
+                }
+            }
+            InputEvent::RemoveMenuItem { item_id } => {
+                let Some(menu) = self.window_loop.area_mut(item_id.area_id()) else {
+                    return Ok(None);
+                };
+
+                let Some(popup_menu) = &mut menu.popup_menu else {
+                    return Ok(None);
+                };
+
+                let hotkey = popup_menu.hotkey(item_id.id());
+                popup_menu
+                    .remove_menu_item(item_id.id())
+                    .map_err(RemoveMenuItem)?;
+                self.description
+                    .remove_menu_item(item_id.area_id().id(), item_id.id());
+                self.actions.remove(&item_id);
+
+                if hotkey.is_some() {
+                    self.window_loop
+                        .window
+                        .remove_hotkey(item_id.area_id(), item_id.id())
+                        .map_err(RemoveMenuItem)?;
+                }
+            }
+            InputEvent::Notification {
+                area_id,
+                notification_id,
+                notification,
+            } => {
+                let persist = notification.persist;
+                let at = notification.schedule.filter(|&at| at > SystemTime::now());
+
+                if let Some(notification) =
+                    self.replace_tagged(area_id, notification_id, notification, at.is_none())?
+                {
+                    match at {
+                        Some(at) => {
+                            let index = self.scheduled.partition_point(|&(a, ..)| a <= at);
+                            self.scheduled
+                                .insert(index, (at, area_id, notification_id, notification));
+                            self.release_scheduled()?;
+                        }
+                        None => {
+                            self.show_notification(area_id, notification_id, notification);
+                        }
+                    }
+                }
+
+                if persist {
+                    self.notification_error(notification_id, PersistNotificationUnsupported);
+                }
+            }
+            InputEvent::RemoveFromCenter { notification_id } => {
+                self.notification_error(notification_id, RemoveFromCenterUnsupported);
+            }
+            InputEvent::DismissNotification { notification_id } => {
+                if let Some(index) = self
+                    .pending
+                    .iter()
+                    .position(|&(_, id, _)| id == notification_id)
+                {
+                    if let Some((area_id, id, _)) = self.pending.remove(index) {
+                        return Ok(Some(Event::NotificationDismissed { area_id, id }));
+                    }
+                }
+
+                if let Some(index) = self
+                    .scheduled
+                    .iter()
+                    .position(|&(_, _, id, _)| id == notification_id)
+                {
+                    let (_, area_id, id, _) = self.scheduled.remove(index);
+                    return Ok(Some(Event::NotificationDismissed { area_id, id }));
+                }
+
+                let Some((area_id, id, _)) = &self.visible else {
+                    return Ok(None);
+                };
+
+                if *id != notification_id {
+                    return Ok(None);
+                }
+
+                self.window_loop
+                    .window
+                    .clear_notification(*area_id)
+                    .map_err(DismissNotification)?;
+                let (area_id, id) = self.take_notification()?;
+                return Ok(Some(Event::NotificationDismissed { area_id, id }));
+            }
+            InputEvent::UpdateNotification {
+                notification_id,
+                update,
+            } => {
+                let queued = self.pending.iter_mut().map(|(_, id, n)| (id, n));
+                let scheduled = self.scheduled.iter_mut().map(|(_, _, id, n)| (id, n));
+
+                if let Some((_, n)) = queued
+                    .chain(scheduled)
+                    .find(|(id, _)| **id == notification_id)
+                {
+                    update.apply(n);
+                    return Ok(None);
+                }
+
+                let Some((area_id, id, n)) = &mut self.visible else {
+                    return Ok(None);
+                };
+
+                if *id != notification_id {
+                    return Ok(None);
+                }
+
+                update.apply(n);
+                // NB: The sound has already played when the
+                // notification was first shown.
+                n.no_sound();
+
+                if let Err(error) =
+                    self.window_loop
+                        .window
+                        .send_notification(*area_id, n, &self.icons)
+                {
+                    self.notification_error(notification_id, SendNotification(error));
+                }
+            }
+            InputEvent::Describe { redact, tx } => {
+                let mut description = self.description.clone();
+
+                if redact {
+                    description.redact();
+                }
+
+                _ = tx.send(description);
+            }
+            InputEvent::AreaRect { area_id, tx } => {
+                _ = tx.send(self.window_loop.window.area_rect(area_id));
+            }
+            InputEvent::AddArea { area } => {
+                let description = AreaDescription::new(&area);
+                let actions = area.menu_actions().collect::<Vec<_>>();
+                let (handle, modify) =
+                    build_area(area, &self.icons, self.icons_id, self.window_loop.rtl)?;
+                let area_id = handle.area_id;
+                self.window_loop.add_area(handle).map_err(AddArea)?;
+                add_area(&mut self.window_loop, area_id, &modify, &self.icons)?;
+                self.description.add_area(description);
+                self.actions.extend(actions);
+                self.area_state.insert(area_id, modify);
+            }
+            InputEvent::RemoveArea { area_id } => {
+                if !self.window_loop.remove_area(area_id).map_err(RemoveArea)? {
+                    return Ok(None);
+                }
+
+                self.description.remove_area(area_id.id());
+                self.area_state.remove(&area_id);
+                self.actions
+                    .retain(|item_id, _| item_id.area_id() != area_id);
+                self.pending.retain(|(a, _, _)| *a != area_id);
+                self.scheduled.retain(|(_, a, _, _)| *a != area_id);
+
+                // NB: A notification which is visible in the
+                // removed area will never be clicked or time out.
+                if matches!(self.visible, Some((a, _, _)) if a == area_id) {
+                    let (area_id, id) = self.take_notification()?;
+                    return Ok(Some(Event::NotificationDismissed { area_id, id }));
+                }
+            }
+            InputEvent::CopyData { hwnd, ty, bytes } => {
+                self.window_loop
+                    .window
+                    .copy_data(hwnd, ty, bytes)
+                    .map_err(CopyData)?;
+            }
+            InputEvent::OpenMenu { area_id, position } => {
+                self.window_loop
+                    .window
+                    .open_menu(area_id, position)
+                    .map_err(OpenMenu)?;
+            }
+            InputEvent::BlockShutdown { reason } => {
+                self.window_loop
+                    .window
+                    .block_shutdown(&reason)
+                    .map_err(BlockShutdown)?;
+            }
+            InputEvent::UnblockShutdown => {
+                self.window_loop
+                    .window
+                    .unblock_shutdown()
+                    .map_err(BlockShutdown)?;
+            }
+            InputEvent::TaskbarProgress { value, state } => {
+                self.window_loop
+                    .window
+                    .set_taskbar_progress(value, state)
+                    .map_err(TaskbarProgress)?;
+            }
+            InputEvent::Show { show } => {
+                self.window_loop.window.show(show).map_err(PostMessage)?;
+            }
+            InputEvent::TaskbarBadge { badge } => {
+                self.window_loop
+                    .window
+                    .set_taskbar_badge(badge)
+                    .map_err(PostMessage)?;
+            }
+            InputEvent::RequestAttention { attention } => {
+                self.window_loop.window.request_attention(attention);
+            }
+            InputEvent::SetTimer { id, period } => {
+                self.window_loop
+                    .window
+                    .set_timer(id, period)
+                    .map_err(SetTimer)?;
+            }
+            InputEvent::CancelTimer { id } => {
+                self.window_loop.window.kill_timer(id).map_err(SetTimer)?;
+            }
+            InputEvent::Batch { inputs } => {
+                self.validate_batch(&inputs)?;
+
+                for input in inputs {
+                    if let Some(event) = self.handle_input(input)? {
+                        self.deferred.push_back(event);
+                    }
+                }
+            }
+            InputEvent::Shutdown => {
+                self.terminate()?;
+                return Ok(Some(Event::Shutdown {
+                    reason: ShutdownReason::Requested,
+                }));
+            }
+            InputEvent::ScheduleShutdown { duration } => {
+                let duration = self
+                    .window_loop
+                    .window
+                    .schedule_shutdown(duration)
+                    .map_err(ScheduleShutdown)?;
+                let at = Instant::now() + duration;
+                self.scheduled_shutdown = Some(at);
+                return Ok(Some(Event::ShutdownScheduled { at }));
+            }
+            InputEvent::CancelScheduledShutdown => {
+                if self.scheduled_shutdown.take().is_none() {
+                    return Ok(None);
+                }
+
+                self.window_loop
+                    .window
+                    .cancel_scheduled_shutdown()
+                    .map_err(CancelScheduledShutdown)?;
+                return Ok(Some(Event::ShutdownCancelled {}));
+            }
+            InputEvent::Await { input, tx } => {
+                // NB: The outcome is reported to the sender instead of ending
+                // the event loop.
+                return match self.handle_input(*input) {
+                    Ok(event) => {
+                        _ = tx.send(Ok(()));
+                        Ok(event)
+                    }
+                    Err(error) => {
+                        _ = tx.send(Err(error));
+                        Ok(None)
+                    }
+                };
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// Tick the event loop without waiting, returning `None` if no event is
+    /// ready.
+    ///
+    /// This processes any inputs which are queued, so it can be called
+    /// periodically from an external scheduler instead of awaiting
+    /// [`EventLoop::tick`].
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use winctx::CreateWindow;
+    ///
+    /// # async fn test() -> winctx::Result<()> {
+    /// let (_, mut event_loop) = CreateWindow::new("se.tedro.Example").build().await?;
+    ///
+    /// while let Some(event) = event_loop.try_tick()? {
+    ///     println!("{event:?}");
+    /// }
+    /// # Ok(()) }
+    /// ```
+    pub fn try_tick(&mut self) -> Result<Option<Event>> {
+        let waker = noop_waker();
+        let mut cx = Context::from_waker(&waker);
+
+        // NB: Ticking is cancel safe, so it's fine to drop the future if it's
+        // not ready.
+        match pin!(self.tick()).poll(&mut cx) {
+            Poll::Ready(result) => result.map(Some),
+            Poll::Pending => Ok(None),
+        }
+    }
+
+    /// Tick the event loop, returning `None` if no event is received within
+    /// the given timeout.
+    ///
+    /// This requires the time driver of the Tokio runtime to be enabled.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use std::time::Duration;
+    ///
+    /// use winctx::CreateWindow;
+    ///
+    /// # async fn test() -> winctx::Result<()> {
+    /// let (_, mut event_loop) = CreateWindow::new("se.tedro.Example").build().await?;
+    ///
+    /// loop {
+    ///     match event_loop.tick_timeout(Duration::from_millis(100)).await? {
+    ///         Some(event) => println!("{event:?}"),
+    ///         None => { /* do other work */ }
+    ///     }
+    /// }
+    /// # Ok(()) }
+    /// ```
+    pub async fn tick_timeout(&mut self, timeout: Duration) -> Result<Option<Event>> {
+        match tokio::time::timeout(timeout, self.tick()).await {
+            Ok(result) => result.map(Some),
+            Err(..) => Ok(None),
+        }
     }
 
     /// Tick the event loop.
+    ///
+    /// This is cancel safe, so no events are lost if the returned future is
+    /// dropped before it completes.
     pub async fn tick(&mut self) -> Result<Event> {
         if self.window_loop.is_closed() {
             return Err(Error::new(WindowClosed));
         };
 
         loop {
-            tokio::select! {
-                Some(event) = self.events_rx.recv() => {
-                    match event {
-                        InputEvent::ModifyArea { area_id, modify } => {
-                            let icon = modify.icon.and_then(|icon| self.icons.get(icon.as_usize()));
-                            self.window_loop.window.modify_notification(area_id, icon, modify.tooltip.as_deref()).map_err(ModifyNotification)?;
-                        }
-                        InputEvent::ModifyMenuItem { item_id, modify } => {
-                            let Some(menu) = self.window_loop.areas.get(item_id.area_id().id() as usize) else {
-                                continue;
-                            };
-
-                            let Some(popup_menu) = &menu.popup_menu else {
-                                continue;
-                            };
+            if let Some(event) = self.deferred.pop_front() {
+                return Ok(event);
+            }
 
-                            popup_menu.modify_menu_item(item_id.id(), &modify).map_err(ModifyMenuItem)?;
-                        }
-                        InputEvent::Notification { area_id, notification_id, notification } => {
-                            if self.visible.is_some() {
-                                self.pending.push_back((area_id, notification_id, notification));
-                            } else {
-                                self.visible = Some((area_id, notification_id));
-                                self.window_loop.window.send_notification(area_id, notification).map_err(SendNotification)?;
-                            }
-                        }
-                        InputEvent::Shutdown => {
-                            self.window_loop.join()?;
-                            return Ok(Event::Shutdown {});
-                        }
+            tokio::select! {
+                Some(input) = self.events_rx.recv() => {
+                    if let Some(event) = self.handle_input(input)? {
+                        return Ok(event);
                     }
                 }
                 e = self.window_loop.tick() => {
                     match e {
                         WindowEvent::MenuItemClicked(area_id, idx, event) => {
+                            let text = self
+                                .description
+                                .menu_item_text(area_id.id(), idx)
+                                .unwrap_or_default()
+                                .to_owned();
+
+                            let item_id = ItemId::new(area_id.id(), idx);
+
                             return Ok(Event::MenuItemClicked {
-                                item_id: ItemId::new(area_id.id(), idx),
+                                item_id,
+                                text,
+                                action: self.actions.get(&item_id).cloned(),
                                 event,
                             });
                         },
-                        WindowEvent::Clipboard(event) => {
-                            return Ok(Event::Clipboard { event });
+                        WindowEvent::MenuOpened(area_id) => {
+                            return Ok(Event::MenuOpened { area_id });
+                        }
+                        WindowEvent::MenuClosed(area_id) => {
+                            return Ok(Event::MenuClosed { area_id });
+                        }
+                        WindowEvent::Clipboard(sequence, event) => {
+                            return Ok(Event::Clipboard { event, sequence });
                         }
                         WindowEvent::IconClicked(area_id, event) => {
                             return Ok(Event::IconClicked { area_id, event });
                         }
+                        WindowEvent::IconDoubleClicked(area_id, event) => {
+                            return Ok(Event::IconDoubleClicked { area_id, event });
+                        }
+                        WindowEvent::IconHovered(area_id, entered) => {
+                            return Ok(Event::IconHovered { area_id, entered });
+                        }
                         WindowEvent::NotificationClicked(actual_menu_id, event) => {
                             let (area_id, id) = self.take_notification()?;
                             debug_assert_eq!(actual_menu_id, area_id);
@@ -116,15 +824,69 @@ impl EventLoop {
                             debug_assert_eq!(actual_menu_id, area_id);
                             return Ok(Event::NotificationDismissed { area_id, id });
                         }
-                        WindowEvent::CopyData(ty, data) => {
-                            return Ok(Event::CopyData { ty, data });
+                        WindowEvent::NotificationTimer => {
+                            self.release_scheduled()?;
+                        }
+                        WindowEvent::CopyData(CopiedData { ty: ARGS_TY, data, .. }) => {
+                            return Ok(Event::Arguments { args: args::decode(&data) });
+                        }
+                        #[cfg(feature = "serde")]
+                        WindowEvent::CopyData(CopiedData { ty: crate::window::MESSAGE_TY, data, .. }) => {
+                            let Some((tag, message)) = crate::window::Message::from_bytes(&data) else {
+                                continue;
+                            };
+
+                            return Ok(Event::Message { tag, message });
+                        }
+                        WindowEvent::AppMessage(id, wparam, lparam) => {
+                            return Ok(Event::AppMessage { id, wparam, lparam });
+                        }
+                        WindowEvent::EndSession(reason, critical) => {
+                            return Ok(Event::EndSession { reason, critical });
+                        }
+                        WindowEvent::EndSessionCancelled => {
+                            return Ok(Event::EndSessionCancelled {});
+                        }
+                        WindowEvent::Timer(id) => {
+                            return Ok(Event::Timer { id });
+                        }
+                        WindowEvent::WindowClosed => {
+                            return Ok(Event::WindowClosed {});
+                        }
+                        WindowEvent::FilesDropped(paths, position) => {
+                            return Ok(Event::FilesDropped { paths, position });
+                        }
+                        WindowEvent::Idle(duration) => {
+                            return Ok(Event::Idle { duration });
+                        }
+                        WindowEvent::Active => {
+                            return Ok(Event::Active {});
+                        }
+                        WindowEvent::WindowHidden => {
+                            return Ok(Event::WindowHidden {});
+                        }
+                        WindowEvent::Resized(width, height) => {
+                            return Ok(Event::Resized { width, height });
+                        }
+                        WindowEvent::Filtered(event) => {
+                            return Ok(event);
+                        }
+                        WindowEvent::CopyDataProgress(ty, received, total) => {
+                            return Ok(Event::CopyDataProgress { ty, received, total });
+                        }
+                        WindowEvent::CopyData(CopiedData { ty, data, peer, reply }) => {
+                            let reply = reply.map(Reply::new);
+                            return Ok(Event::CopyData { ty, data, peer, reply });
                         }
                         WindowEvent::Error(error) => {
-                            return Ok(Event::Error { error });
+                            return Ok(Event::Error { error, notification_id: None });
+                        }
+                        WindowEvent::TaskbarCreated => {
+                            self.readd_areas();
                         }
-                        WindowEvent::Shutdown => {
-                            self.window_loop.join()?;
-                            return Ok(Event::Shutdown {});
+                        WindowEvent::Shutdown(reason) => {
+                            self.terminate()?;
+                            return Ok(Event::Shutdown { reason });
                         }
                     }
                 }
@@ -133,8 +895,171 @@ impl EventLoop {
     }
 }
 
+/// The event loop can be used as a stream of events when the `futures-core`
+/// feature is enabled.
+///
+/// The stream ends once the window has shut down, after
+/// [`Event::Shutdown`] has been emitted.
+///
+/// # Examples
+///
+/// ```no_run
+/// use futures::StreamExt;
+/// use winctx::CreateWindow;
+///
+/// # async fn test() -> winctx::Result<()> {
+/// let (_, mut event_loop) = CreateWindow::new("se.tedro.Example").build().await?;
+///
+/// while let Some(event) = event_loop.next().await {
+///     println!("{:?}", event?);
+/// }
+/// # Ok(()) }
+/// ```
+#[cfg(feature = "futures-core")]
+impl futures_core::Stream for EventLoop {
+    type Item = Result<Event>;
+
+    fn poll_next(self: std::pin::Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+
+        if this.window_loop.is_closed() {
+            return Poll::Ready(None);
+        }
+
+        // NB: Ticking is cancel safe, so a new tick can be started each time
+        // the stream is polled.
+        pin!(this.tick()).poll(cx).map(Some)
+    }
+}
+
+/// The window of the event loop can be passed to other crates which need a
+/// parent window, such as native dialogs.
+///
+/// The handle is unavailable once the event loop has shut down.
+///
+/// # Examples
+///
+/// ```no_run
+/// use raw_window_handle::HasWindowHandle;
+/// use winctx::CreateWindow;
+///
+/// # async fn test() -> winctx::Result<()> {
+/// let (_, event_loop) = CreateWindow::new("se.tedro.Example").build().await?;
+/// let handle = event_loop.window_handle();
+/// # Ok(()) }
+/// ```
+#[cfg(feature = "raw-window-handle")]
+impl raw_window_handle::HasWindowHandle for EventLoop {
+    fn window_handle(
+        &self,
+    ) -> Result<raw_window_handle::WindowHandle<'_>, raw_window_handle::HandleError> {
+        if self.window_loop.is_closed() {
+            return Err(raw_window_handle::HandleError::Unavailable);
+        }
+
+        let Some(hwnd) = std::num::NonZeroIsize::new(self.window_loop.window.hwnd()) else {
+            return Err(raw_window_handle::HandleError::Unavailable);
+        };
+
+        let handle = raw_window_handle::Win32WindowHandle::new(hwnd);
+
+        // SAFETY: The window thread only destroys the window once the event
+        // loop has asked it to, which happens when it terminates and requires
+        // a mutable borrow of the event loop. Requests to close or destroy the
+        // window from the outside are only reported to the event loop. The
+        // thread itself keeps running for as long as the event loop holds on
+        // to it. So the window stays alive for as long as it's borrowed.
+        unsafe {
+            Ok(raw_window_handle::WindowHandle::borrow_raw(
+                raw_window_handle::RawWindowHandle::Win32(handle),
+            ))
+        }
+    }
+}
+
+#[cfg(feature = "raw-window-handle")]
+impl raw_window_handle::HasDisplayHandle for EventLoop {
+    fn display_handle(
+        &self,
+    ) -> Result<raw_window_handle::DisplayHandle<'_>, raw_window_handle::HandleError> {
+        Ok(raw_window_handle::DisplayHandle::windows())
+    }
+}
+
 impl Drop for EventLoop {
     fn drop(&mut self) {
-        _ = self.window_loop.join();
+        _ = self.terminate();
+    }
+}
+
+/// Construct a waker which does nothing when woken.
+fn noop_waker() -> Waker {
+    unsafe fn clone(_: *const ()) -> RawWaker {
+        RawWaker::new(ptr::null(), &VTABLE)
+    }
+
+    unsafe fn noop(_: *const ()) {}
+
+    static VTABLE: RawWakerVTable = RawWakerVTable::new(clone, noop, noop, noop);
+
+    // SAFETY: The vtable doesn't use the data pointer.
+    unsafe { Waker::from_raw(RawWaker::new(ptr::null(), &VTABLE)) }
+}
+
+/// Close the input channel and drop any inputs which are already queued.
+///
+/// Dropping an input drops any reply channel it holds, so that callers waiting
+/// for a reply resolve with an error instead of hanging.
+fn close_inputs(events_rx: &mut mpsc::UnboundedReceiver<InputEvent>) {
+    events_rx.close();
+    while events_rx.try_recv().is_ok() {}
+}
+
+#[cfg(test)]
+mod tests {
+    use std::pin::pin;
+
+    use tokio::sync::mpsc;
+
+    use crate::window::Window;
+    use crate::{AreaId, ErrorKind, Sender};
+
+    use super::close_inputs;
+
+    #[tokio::test]
+    async fn pending_replies_resolve_after_close() {
+        let (tx, mut events_rx) = mpsc::unbounded_channel();
+        let sender = Sender::new(tx, 0, 1, Window::new(0));
+
+        let mut describe = pin!(sender.describe(false));
+        let mut modify = pin!(sender.modify_area(AreaId::new(0)).send_await());
+
+        // Poll both futures once so that their inputs are queued.
+        assert!(futures::poll!(describe.as_mut()).is_pending());
+        assert!(futures::poll!(modify.as_mut()).is_pending());
+
+        close_inputs(&mut events_rx);
+
+        let error = describe.await.unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::WindowClosed);
+        let error = modify.await.unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::WindowClosed);
+    }
+
+    #[tokio::test]
+    async fn inputs_rejected_after_close() {
+        let (tx, mut events_rx) = mpsc::unbounded_channel();
+        let sender = Sender::new(tx, 0, 1, Window::new(0));
+
+        close_inputs(&mut events_rx);
+
+        let error = sender.describe(false).await.unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::WindowClosed);
+
+        let modify = sender.modify_area(AreaId::new(0)).send_await();
+        assert_eq!(modify.await.unwrap_err().kind(), ErrorKind::WindowClosed);
+
+        let error = sender.modify_area(AreaId::new(0)).send().unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::WindowClosed);
     }
 }
@@ -0,0 +1,41 @@
+use std::fmt;
+
+use crate::AreaId;
+
+/// An identifier for a group of menu items.
+///
+/// This is constructed through [`PopupMenu::group`].
+///
+/// [`PopupMenu::group`]: crate::PopupMenu::group
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct GroupId {
+    area_id: u32,
+    id: u32,
+}
+
+impl GroupId {
+    #[inline]
+    pub(crate) fn new(area_id: u32, id: u32) -> Self {
+        Self { area_id, id }
+    }
+
+    #[inline]
+    pub(crate) const fn area_id(&self) -> AreaId {
+        AreaId::new(self.area_id)
+    }
+
+    #[inline]
+    pub(crate) const fn id(&self) -> u32 {
+        self.id
+    }
+}
+
+impl fmt::Debug for GroupId {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("GroupId")
+            .field(&self.area_id)
+            .field(&self.id)
+            .finish()
+    }
+}
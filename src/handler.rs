@@ -0,0 +1,181 @@
+use crate::event::{ClipboardEvent, MouseEvent};
+use crate::{AreaId, Error, Event, ItemId, NotificationId};
+
+type Callback<'a, T> = Option<Box<dyn FnMut(T) + 'a>>;
+
+/// A collection of callbacks which are invoked by [`EventLoop::run`].
+///
+/// Events without a dedicated callback are passed to the callback registered
+/// with [`Handler::on_event`], if any.
+///
+/// [`EventLoop::run`]: crate::EventLoop::run
+///
+/// # Examples
+///
+/// ```no_run
+/// use winctx::{CreateWindow, Handler};
+///
+/// # async fn test() -> winctx::Result<()> {
+/// let mut window = CreateWindow::new("se.tedro.Example");
+/// let menu = window.new_area().popup_menu();
+/// let quit = menu.push_entry("Quit").id();
+///
+/// let (sender, mut event_loop) = window.build().await?;
+///
+/// let handler = Handler::new()
+///     .on_menu_item(|item_id, _| {
+///         if item_id == quit {
+///             _ = sender.shutdown();
+///         }
+///     })
+///     .on_shutdown(|| println!("Window shut down"));
+///
+/// event_loop.run(handler).await?;
+/// # Ok(()) }
+/// ```
+#[derive(Default)]
+pub struct Handler<'a> {
+    pub(crate) on_menu_item: Callback<'a, (ItemId, MouseEvent)>,
+    pub(crate) on_icon_clicked: Callback<'a, (AreaId, MouseEvent)>,
+    pub(crate) on_notification_clicked: Callback<'a, (AreaId, NotificationId)>,
+    pub(crate) on_clipboard: Callback<'a, ClipboardEvent>,
+    pub(crate) on_copy_data: Callback<'a, (usize, Vec<u8>)>,
+    pub(crate) on_error: Callback<'a, Error>,
+    pub(crate) on_shutdown: Callback<'a, ()>,
+    pub(crate) on_event: Callback<'a, Event>,
+}
+
+impl<'a> Handler<'a> {
+    /// Construct a handler without any callbacks.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Call the given closure when a menu item has been clicked.
+    pub fn on_menu_item<F>(self, mut f: F) -> Self
+    where
+        F: FnMut(ItemId, MouseEvent) + 'a,
+    {
+        Self {
+            on_menu_item: Some(Box::new(move |(item_id, event)| f(item_id, event))),
+            ..self
+        }
+    }
+
+    /// Call the given closure when the icon of an area has been clicked.
+    pub fn on_icon_clicked<F>(self, mut f: F) -> Self
+    where
+        F: FnMut(AreaId, MouseEvent) + 'a,
+    {
+        Self {
+            on_icon_clicked: Some(Box::new(move |(area_id, event)| f(area_id, event))),
+            ..self
+        }
+    }
+
+    /// Call the given closure when a notification has been clicked.
+    pub fn on_notification_clicked<F>(self, mut f: F) -> Self
+    where
+        F: FnMut(AreaId, NotificationId) + 'a,
+    {
+        Self {
+            on_notification_clicked: Some(Box::new(move |(area_id, id)| f(area_id, id))),
+            ..self
+        }
+    }
+
+    /// Call the given closure when the system clipboard has been modified.
+    pub fn on_clipboard<F>(self, f: F) -> Self
+    where
+        F: FnMut(ClipboardEvent) + 'a,
+    {
+        Self {
+            on_clipboard: Some(Box::new(f)),
+            ..self
+        }
+    }
+
+    /// Call the given closure with the type and the data when data has been
+    /// copied to the window.
+    pub fn on_copy_data<F>(self, mut f: F) -> Self
+    where
+        F: FnMut(usize, Vec<u8>) + 'a,
+    {
+        Self {
+            on_copy_data: Some(Box::new(move |(ty, data)| f(ty, data))),
+            ..self
+        }
+    }
+
+    /// Call the given closure when a non-fatal error has been reported.
+    pub fn on_error<F>(self, f: F) -> Self
+    where
+        F: FnMut(Error) + 'a,
+    {
+        Self {
+            on_error: Some(Box::new(f)),
+            ..self
+        }
+    }
+
+    /// Call the given closure once the window has shut down.
+    pub fn on_shutdown<F>(self, mut f: F) -> Self
+    where
+        F: FnMut() + 'a,
+    {
+        Self {
+            on_shutdown: Some(Box::new(move |()| f())),
+            ..self
+        }
+    }
+
+    /// Call the given closure with any event which doesn't have a dedicated
+    /// callback.
+    pub fn on_event<F>(self, f: F) -> Self
+    where
+        F: FnMut(Event) + 'a,
+    {
+        Self {
+            on_event: Some(Box::new(f)),
+            ..self
+        }
+    }
+
+    /// Dispatch an event to the matching callback.
+    pub(crate) fn dispatch(&mut self, event: Event) {
+        let event = match event {
+            Event::MenuItemClicked { item_id, event, .. } if self.on_menu_item.is_some() => {
+                return call(&mut self.on_menu_item, (item_id, event));
+            }
+            Event::IconClicked { area_id, event } if self.on_icon_clicked.is_some() => {
+                return call(&mut self.on_icon_clicked, (area_id, event));
+            }
+            Event::NotificationClicked { area_id, id, .. }
+                if self.on_notification_clicked.is_some() =>
+            {
+                return call(&mut self.on_notification_clicked, (area_id, id));
+            }
+            Event::Clipboard { event, .. } if self.on_clipboard.is_some() => {
+                return call(&mut self.on_clipboard, event);
+            }
+            Event::CopyData { ty, data, .. } if self.on_copy_data.is_some() => {
+                return call(&mut self.on_copy_data, (ty, data));
+            }
+            Event::Error { error, .. } if self.on_error.is_some() => {
+                return call(&mut self.on_error, error);
+            }
+            Event::Shutdown { .. } if self.on_shutdown.is_some() => {
+                return call(&mut self.on_shutdown, ());
+            }
+            event => event,
+        };
+
+        call(&mut self.on_event, event);
+    }
+}
+
+fn call<T>(callback: &mut Callback<'_, T>, value: T) {
+    if let Some(callback) = callback {
+        callback(value);
+    }
+}
@@ -6,16 +6,26 @@ mod stock_icon;
 
 /// A reference to an icon.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-pub struct IconId(u32);
+pub struct IconId {
+    /// The collection the icon belongs to.
+    icons: u32,
+    id: u32,
+}
 
 impl IconId {
     #[inline]
-    pub(crate) fn new(id: u32) -> Self {
-        Self(id)
+    pub(crate) fn new(icons: u32, id: u32) -> Self {
+        Self { icons, id }
+    }
+
+    /// Test if the icon belongs to the given collection of icons.
+    #[inline]
+    pub(crate) fn belongs_to(self, icons: u32) -> bool {
+        self.icons == icons
     }
 
     #[inline]
     pub(crate) fn as_usize(self) -> usize {
-        self.0 as usize
+        self.id as usize
     }
 }
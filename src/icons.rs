@@ -1,7 +1,12 @@
 //! Type used to interact with an icons collection.
 
+use std::sync::atomic::{AtomicU32, Ordering};
+
 use crate::{IconBuffer, IconId};
 
+/// Identifier of the next collection of icons to be constructed.
+static NEXT_ID: AtomicU32 = AtomicU32::new(0);
+
 /// A collection of notification icons.
 ///
 /// This defines the various icons that an application using winctx can use.
@@ -9,8 +14,10 @@ use crate::{IconBuffer, IconId};
 /// This is returned by [`CreateWindow::icons`].
 ///
 /// [`CreateWindow::icons`]: crate::CreateWindow::icons
-#[derive(Default)]
 pub struct Icons {
+    /// Identifier of the collection, which is used to detect icons from
+    /// other collections.
+    pub(super) id: u32,
     pub(super) icons: Vec<IconBuffer>,
 }
 
@@ -18,7 +25,10 @@ impl Icons {
     /// Construct a new empty collection of notification icons.
     #[inline]
     pub fn new() -> Self {
-        Self::default()
+        Self {
+            id: NEXT_ID.fetch_add(1, Ordering::Relaxed),
+            icons: Vec::new(),
+        }
     }
 
     /// Push an icon from a buffer and return a handle to it.
@@ -38,9 +48,16 @@ impl Icons {
     where
         T: AsRef<[u8]>,
     {
-        let icon = IconId::new(self.icons.len() as u32);
+        let icon = IconId::new(self.id, self.icons.len() as u32);
         self.icons
             .push(IconBuffer::from_buffer(buffer, width, height));
         icon
     }
 }
+
+impl Default for Icons {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -46,7 +46,7 @@ use crate::AreaId;
 /// loop {
 ///     let event = tokio::select! {
 ///         _ = ctrl_c.as_mut(), if !shutdown => {
-///             sender.shutdown();
+///             sender.shutdown()?;
 ///             shutdown = true;
 ///             continue;
 ///         }
@@ -63,7 +63,7 @@ use crate::AreaId;
 ///                     assert_eq!(item_id, first);
 ///                 }
 ///                 winctx::item_id!(0, 1) => {
-///                     sender.shutdown();
+///                     sender.shutdown()?;
 ///                 }
 ///                 winctx::item_id!(1, 0) => {
 ///                     println!("Item clicked in second area");
@@ -105,8 +105,23 @@ impl ItemId {
         Self { area_id, id }
     }
 
+    /// Get the identifier of the area whose popup menu the menu item belongs
+    /// to.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use winctx::CreateWindow;
+    ///
+    /// let mut window = CreateWindow::new("se.tedro.Example");
+    /// let area = window.new_area();
+    /// let area_id = area.id();
+    /// let item_id = area.popup_menu().push_entry("Quit").id();
+    ///
+    /// assert_eq!(item_id.area_id(), area_id);
+    /// ```
     #[inline]
-    pub(crate) const fn area_id(&self) -> AreaId {
+    pub const fn area_id(&self) -> AreaId {
         AreaId::new(self.area_id)
     }
 
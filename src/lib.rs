@@ -82,7 +82,7 @@
 //! loop {
 //!     let event = tokio::select! {
 //!         _ = ctrl_c.as_mut(), if !shutdown => {
-//!             sender.shutdown();
+//!             sender.shutdown()?;
 //!             shutdown = true;
 //!             continue;
 //!         }
@@ -96,7 +96,7 @@
 //!             println!("Menu entry clicked: {item_id:?}");
 //!
 //!             if item_id == quit {
-//!                 sender.shutdown();
+//!                 sender.shutdown()?;
 //!             }
 //!         }
 //!         Event::Shutdown { .. } => {
@@ -109,6 +109,22 @@
 //! # Ok(()) }
 //! ```
 //!
+//! <br>
+//!
+//! ## Features
+//!
+//! The following optional cargo features are available:
+//!
+//! * `serde` - Adds serialization of [window descriptions] and [badges], loading
+//!   and storing values in the [registry] through [`RegistryKey::load`] and
+//!   [`RegistryKey::store`], and sending typed messages to other processes using
+//!   [`Window::send_message`].
+//! * `raw-window-handle` - Implements the traits of the [raw-window-handle] crate
+//!   for the [`EventLoop`], so that the window can be used as the parent of
+//!   native dialogs provided by other crates.
+//! * `futures-core` - Implements [`Stream`] for the [`EventLoop`], producing
+//!   events until the window has shut down.
+//!
 //! [window]: https://learn.microsoft.com/en-us/windows/win32/learnwin32/creating-a-window
 //! [Event]: https://docs.rs/winctx/latest/winctx/enum.Event.html
 //! [clipboard]: https://github.com/udoprog/winctx/blob/main/examples/clipboard.rs
@@ -116,6 +132,14 @@
 //! [registry]: https://github.com/udoprog/winctx/blob/main/examples/registry.rs
 //! [showcase]: https://github.com/udoprog/winctx/blob/main/examples/showcase.rs
 //! [started automatically]: https://docs.rs/winctx/latest/winctx/struct.AutoStart.html
+//! [window descriptions]: https://docs.rs/winctx/latest/winctx/description/index.html
+//! [badges]: https://docs.rs/winctx/latest/winctx/enum.Badge.html
+//! [`RegistryKey::load`]: https://docs.rs/winctx/latest/winctx/registry/struct.RegistryKey.html#method.load
+//! [`RegistryKey::store`]: https://docs.rs/winctx/latest/winctx/registry/struct.RegistryKey.html#method.store
+//! [`Window::send_message`]: https://docs.rs/winctx/latest/winctx/window/struct.Window.html#method.send_message
+//! [raw-window-handle]: https://docs.rs/raw-window-handle
+//! [`EventLoop`]: https://docs.rs/winctx/latest/winctx/struct.EventLoop.html
+//! [`Stream`]: https://docs.rs/futures-core/latest/futures_core/stream/trait.Stream.html
 
 #![allow(clippy::module_inception)]
 #![deny(missing_docs)]
@@ -123,12 +147,21 @@
 /// Convenient result alias for this crate.
 pub type Result<T, E = Error> = core::result::Result<T, E>;
 
-mod clipboard;
+#[doc(inline)]
+pub use self::clipboard::ClipboardFormat;
+pub mod clipboard;
+
+#[doc(inline)]
+pub use self::clipboard_options::ClipboardOptions;
+mod clipboard_options;
+
+mod com;
+
 mod convert;
 
 #[doc(inline)]
 pub use self::registry::{OpenRegistryKey, RegistryKey};
-mod registry;
+pub mod registry;
 
 #[doc(inline)]
 pub use self::window::Window;
@@ -138,24 +171,37 @@ mod window_loop;
 
 #[doc(inline)]
 use self::notification::Notification;
+#[doc(inline)]
+pub use self::notification::NotificationSound;
 mod notification;
 
 #[doc(inline)]
-pub use self::error::Error;
+pub use self::error::{Error, ErrorKind};
 mod error;
 
 #[doc(inline)]
 pub use self::item_id::ItemId;
 mod item_id;
 
+#[doc(inline)]
+pub use self::group_id::GroupId;
+mod group_id;
+
 #[doc(inline)]
 pub use self::notification_id::NotificationId;
 mod notification_id;
 
+#[doc(inline)]
+pub use self::notification_backend::NotificationBackend;
+mod notification_backend;
+
 #[doc(inline)]
 pub use self::area_id::AreaId;
 mod area_id;
 
+#[doc(inline)]
+pub use self::window_loop::MessageThread;
+
 #[doc(inline)]
 pub use self::event_loop::EventLoop;
 mod event_loop;
@@ -164,27 +210,53 @@ mod event_loop;
 pub use self::event::Event;
 pub mod event;
 
+#[doc(inline)]
+pub use self::handler::Handler;
+mod handler;
+
 #[doc(inline)]
 pub use self::create_window::CreateWindow;
 mod create_window;
 
+#[doc(inline)]
+pub use self::description::WindowDescription;
+pub mod description;
+
 pub mod area;
 pub mod icons;
 
 #[doc(inline)]
-pub use self::popup_menu::PopupMenu;
+pub use self::popup_menu::{HorizontalAlign, MenuAnimation, PopupMenu, VerticalAlign};
 mod popup_menu;
 
+#[doc(inline)]
+pub use self::menu_action::MenuAction;
+mod menu_action;
+
 #[doc(inline)]
 use self::icon_buffer::IconBuffer;
 mod icon_buffer;
 
 #[doc(inline)]
-pub use self::autostart::AutoStart;
+pub use self::autostart::{AutoStart, AutoStartEntry, AutoStartStatus};
 mod autostart;
 
 pub mod tools;
 
+pub mod shm;
+
+#[doc(inline)]
+pub use self::taskbar_state::TaskbarState;
+mod taskbar_state;
+
+#[doc(inline)]
+pub use self::attention::Attention;
+mod attention;
+
+#[doc(inline)]
+pub use self::badge::Badge;
+mod badge;
+
 #[doc(inline)]
 pub use self::named_mutex::NamedMutex;
 mod named_mutex;
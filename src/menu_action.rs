@@ -0,0 +1,75 @@
+use std::any::Any;
+use std::fmt;
+use std::sync::Arc;
+
+/// A user-defined value associated with a menu item, such as a variant of an
+/// enum of actions.
+///
+/// This is set using [`PopupMenu::push_entry_id`] and handed back in
+/// [`Event::MenuItemClicked`], so that menu items can be matched on without
+/// keeping track of their [`ItemId`].
+///
+/// [`PopupMenu::push_entry_id`]: crate::PopupMenu::push_entry_id
+/// [`Event::MenuItemClicked`]: crate::Event::MenuItemClicked
+/// [`ItemId`]: crate::ItemId
+#[derive(Clone)]
+pub struct MenuAction {
+    value: Arc<dyn Any + Send + Sync>,
+}
+
+impl MenuAction {
+    pub(crate) fn new<A>(action: A) -> Self
+    where
+        A: Any + Send + Sync,
+    {
+        Self {
+            value: Arc::new(action),
+        }
+    }
+
+    /// Get the action if it's of type `A`.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use winctx::{CreateWindow, Event};
+    ///
+    /// #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    /// enum Action {
+    ///     Settings,
+    ///     Quit,
+    /// }
+    ///
+    /// # async fn test() -> winctx::Result<()> {
+    /// let mut window = CreateWindow::new("se.tedro.Example");
+    /// let menu = window.new_area().popup_menu();
+    /// menu.push_entry_id(Action::Settings, "Settings");
+    /// menu.push_entry_id(Action::Quit, "Quit");
+    ///
+    /// let (sender, mut event_loop) = window.build().await?;
+    ///
+    /// loop {
+    ///     if let Event::MenuItemClicked { action: Some(action), .. } = event_loop.tick().await? {
+    ///         match action.get::<Action>() {
+    ///             Some(Action::Settings) => println!("Opening settings"),
+    ///             Some(Action::Quit) => sender.shutdown()?,
+    ///             None => {}
+    ///         }
+    ///     }
+    /// }
+    /// # Ok(()) }
+    /// ```
+    pub fn get<A>(&self) -> Option<&A>
+    where
+        A: Any,
+    {
+        self.value.downcast_ref()
+    }
+}
+
+impl fmt::Debug for MenuAction {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MenuAction").finish_non_exhaustive()
+    }
+}
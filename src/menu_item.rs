@@ -1,7 +1,10 @@
 //! Types related to menu construction.
 
-use crate::{ItemId, ModifyMenuItem};
+use std::any::Any;
 
+use crate::{GroupId, IconId, ItemId, MenuAction, ModifyMenuItem};
+
+#[derive(Debug)]
 pub(super) enum MenuItemKind {
     Separator,
     String { text: String },
@@ -12,10 +15,18 @@ pub(super) enum MenuItemKind {
 /// This is constructed through:
 /// * [`MenuItem::separator`].
 /// * [`MenuItem::entry`].
+#[derive(Debug)]
 pub struct MenuItem {
     pub(crate) item_id: ItemId,
     pub(crate) kind: MenuItemKind,
     pub(crate) initial: ModifyMenuItem,
+    pub(crate) groups: Vec<GroupId>,
+    pub(crate) icon: Option<IconId>,
+    pub(crate) checked_icon: Option<IconId>,
+    pub(crate) unchecked_icon: Option<IconId>,
+    pub(crate) shortcut: Option<String>,
+    pub(crate) global_hotkey: bool,
+    pub(crate) action: Option<MenuAction>,
 }
 
 impl MenuItem {
@@ -24,6 +35,13 @@ impl MenuItem {
             item_id,
             kind,
             initial: ModifyMenuItem::default(),
+            groups: Vec::new(),
+            icon: None,
+            checked_icon: None,
+            unchecked_icon: None,
+            shortcut: None,
+            global_hotkey: false,
+            action: None,
         }
     }
 
@@ -67,4 +85,194 @@ impl MenuItem {
         self.initial.highlight(highlight);
         self
     }
+
+    /// Set whether the menu item is enabled.
+    ///
+    /// Disabled menu items are grayed out and cannot be clicked. Menu items are
+    /// enabled by default.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use winctx::CreateWindow;
+    ///
+    /// let mut window = CreateWindow::new("se.tedro.Example");;
+    /// let area = window.new_area();
+    ///
+    /// let mut menu = area.popup_menu();
+    /// menu.push_entry("Upload").enabled(false);
+    /// ```
+    pub fn enabled(&mut self, enabled: bool) -> &mut Self {
+        self.initial.enabled(enabled);
+        self
+    }
+
+    /// Set the icon which is displayed next to the text of the menu item.
+    ///
+    /// The icon is scaled to the size of small icons and has no effect on
+    /// separators.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use winctx::CreateWindow;
+    ///
+    /// # macro_rules! include_bytes { ($path:literal) => { &[] } }
+    /// const ICON: &[u8] = include_bytes!("tokio.ico");
+    ///
+    /// let mut window = CreateWindow::new("se.tedro.Example");
+    /// let icon = window.icons().insert_buffer(ICON, 16, 16);
+    /// let area = window.new_area();
+    ///
+    /// let mut menu = area.popup_menu();
+    /// menu.push_entry("Example Application").icon(icon);
+    /// ```
+    pub fn icon(&mut self, icon: IconId) -> &mut Self {
+        self.icon = Some(icon);
+        self
+    }
+
+    /// Set the icon used in place of the standard check mark when the menu
+    /// item is checked.
+    ///
+    /// The icon is scaled to the size of menu check marks.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use winctx::CreateWindow;
+    ///
+    /// # macro_rules! include_bytes { ($path:literal) => { &[] } }
+    /// const CONNECTED: &[u8] = include_bytes!("connected.ico");
+    /// const DISCONNECTED: &[u8] = include_bytes!("disconnected.ico");
+    ///
+    /// let mut window = CreateWindow::new("se.tedro.Example");
+    /// let connected = window.icons().insert_buffer(CONNECTED, 16, 16);
+    /// let disconnected = window.icons().insert_buffer(DISCONNECTED, 16, 16);
+    /// let area = window.new_area();
+    ///
+    /// let mut menu = area.popup_menu();
+    /// menu.push_entry("Server")
+    ///     .checked_icon(connected)
+    ///     .unchecked_icon(disconnected);
+    /// ```
+    pub fn checked_icon(&mut self, icon: IconId) -> &mut Self {
+        self.checked_icon = Some(icon);
+        self
+    }
+
+    /// Set the icon which is displayed in place of the check mark when the
+    /// menu item isn't checked.
+    ///
+    /// See [`MenuItem::checked_icon`].
+    pub fn unchecked_icon(&mut self, icon: IconId) -> &mut Self {
+        self.unchecked_icon = Some(icon);
+        self
+    }
+
+    /// Set the shortcut hint which is displayed right-aligned next to the text
+    /// of the menu item, such as `Ctrl+Q`.
+    ///
+    /// By default this is only a hint, use [`MenuItem::global_hotkey`] to have
+    /// the shortcut click the menu item. It has no effect on separators.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use winctx::CreateWindow;
+    ///
+    /// let mut window = CreateWindow::new("se.tedro.Example");
+    /// let area = window.new_area();
+    ///
+    /// let mut menu = area.popup_menu();
+    /// menu.push_entry("Quit").shortcut("Ctrl+Q");
+    /// ```
+    pub fn shortcut<S>(&mut self, shortcut: S) -> &mut Self
+    where
+        S: AsRef<str>,
+    {
+        self.shortcut = Some(shortcut.as_ref().to_owned());
+        self
+    }
+
+    /// Register the [shortcut] of the menu item as a global hotkey.
+    ///
+    /// Pressing the hotkey anywhere in the system emits
+    /// [`Event::MenuItemClicked`] for the menu item, even if the popup menu
+    /// isn't shown. The shortcut consists of any number of the modifiers
+    /// `Ctrl`, `Alt`, `Shift` and `Win` followed by a letter, a digit, a
+    /// function key like `F5`, or one of `Space`, `Enter`, `Esc`, `Tab`,
+    /// `Backspace`, `Delete`, `Insert`, `Home`, `End`, `PageUp`, `PageDown`,
+    /// `Up`, `Down`, `Left`, `Right`, `Plus` or `Minus`, all separated by `+`.
+    /// The plus key can also be written as `+`, such as in `Ctrl++`.
+    ///
+    /// Since a global hotkey takes the key away from every other application,
+    /// it needs at least one modifier unless it's a function key.
+    ///
+    /// Building the window fails if the shortcut can't be parsed. Since
+    /// hotkeys are shared with all other applications, failing to register
+    /// one because it's already in use is reported through [`Event::Error`].
+    ///
+    /// [shortcut]: MenuItem::shortcut
+    /// [`Event::MenuItemClicked`]: crate::Event::MenuItemClicked
+    /// [`Event::Error`]: crate::Event::Error
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use winctx::CreateWindow;
+    ///
+    /// let mut window = CreateWindow::new("se.tedro.Example");
+    /// let area = window.new_area();
+    ///
+    /// let mut menu = area.popup_menu();
+    /// menu.push_entry("Take screenshot")
+    ///     .shortcut("Ctrl+Shift+S")
+    ///     .global_hotkey(true);
+    /// ```
+    pub fn global_hotkey(&mut self, global_hotkey: bool) -> &mut Self {
+        self.global_hotkey = global_hotkey;
+        self
+    }
+
+    /// Add the menu item to the given group.
+    ///
+    /// A menu item can be a member of multiple groups. Adding the menu item
+    /// to a group belonging to a different popup menu causes building the
+    /// window to fail.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use winctx::CreateWindow;
+    ///
+    /// let mut window = CreateWindow::new("se.tedro.Example");;
+    /// let area = window.new_area();
+    ///
+    /// let mut menu = area.popup_menu();
+    /// let transfers = menu.group("transfers");
+    /// menu.push_entry("Upload").group(transfers);
+    /// menu.push_entry("Download").group(transfers);
+    /// ```
+    pub fn group(&mut self, group: GroupId) -> &mut Self {
+        if !self.groups.contains(&group) {
+            self.groups.push(group);
+        }
+
+        self
+    }
+    /// Associate a user-defined action with the menu item, which is handed
+    /// back in [`Event::MenuItemClicked`] when it's clicked.
+    ///
+    /// See [`PopupMenu::push_entry_id`].
+    ///
+    /// [`Event::MenuItemClicked`]: crate::Event::MenuItemClicked
+    /// [`PopupMenu::push_entry_id`]: crate::PopupMenu::push_entry_id
+    pub fn action<A>(&mut self, action: A) -> &mut Self
+    where
+        A: Any + Send + Sync,
+    {
+        self.action = Some(MenuAction::new(action));
+        self
+    }
 }
@@ -1,18 +1,26 @@
 use std::fmt;
 
-use crate::IconId;
+use crate::{Badge, IconId};
 
 /// A message sent to modify a notification area.
 #[derive(Default, Debug)]
 pub(crate) struct ModifyArea {
-    pub(super) icon: Option<IconId>,
-    pub(super) tooltip: Option<Box<str>>,
+    pub(super) icon: Option<Option<IconId>>,
+    pub(super) tooltip: Option<Option<Box<str>>>,
+    pub(super) hidden: Option<bool>,
+    pub(super) show_tooltip: Option<bool>,
+    pub(super) badge: Option<Option<Badge>>,
 }
 
 impl ModifyArea {
     /// Set the icon of the notification area.
     pub(crate) fn icon(&mut self, icon: IconId) {
-        self.icon = Some(icon);
+        self.icon = Some(Some(icon));
+    }
+
+    /// Clear the icon of the notification area.
+    pub(crate) fn clear_icon(&mut self) {
+        self.icon = Some(None);
     }
 
     /// Set the tooltip of the notification area.
@@ -20,6 +28,36 @@ impl ModifyArea {
     where
         T: fmt::Display,
     {
-        self.tooltip = Some(tooltip.to_string().into());
+        self.tooltip = Some(Some(tooltip.to_string().into()));
+    }
+
+    /// Clear the tooltip of the notification area.
+    pub(crate) fn clear_tooltip(&mut self) {
+        self.tooltip = Some(None);
+    }
+
+    /// Set whether the notification area is hidden.
+    pub(crate) fn hidden(&mut self, hidden: bool) {
+        self.hidden = Some(hidden);
+    }
+
+    /// Set whether the standard tooltip is shown.
+    pub(crate) fn show_tooltip(&mut self, show_tooltip: bool) {
+        self.show_tooltip = Some(show_tooltip);
+    }
+
+    /// Set or clear the badge drawn on the icon.
+    pub(crate) fn badge(&mut self, badge: Option<Badge>) {
+        self.badge = Some(badge);
+    }
+
+    /// Merge a later modification into this one, so that both can be applied
+    /// at once.
+    pub(crate) fn merge(&mut self, other: ModifyArea) {
+        self.icon = other.icon.or(self.icon);
+        self.tooltip = other.tooltip.or(self.tooltip.take());
+        self.hidden = other.hidden.or(self.hidden);
+        self.show_tooltip = other.show_tooltip.or(self.show_tooltip);
+        self.badge = other.badge.or(self.badge);
     }
 }
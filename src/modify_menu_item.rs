@@ -3,6 +3,7 @@
 pub(super) struct ModifyMenuItem {
     pub(super) checked: Option<bool>,
     pub(super) highlight: Option<bool>,
+    pub(super) enabled: Option<bool>,
 }
 
 impl ModifyMenuItem {
@@ -15,4 +16,9 @@ impl ModifyMenuItem {
     pub(super) fn highlight(&mut self, highlight: bool) {
         self.highlight = Some(highlight);
     }
+
+    /// Set whether the menu item is enabled.
+    pub(super) fn enabled(&mut self, enabled: bool) {
+        self.enabled = Some(enabled);
+    }
 }
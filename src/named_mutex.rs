@@ -7,7 +7,7 @@ use windows_sys::Win32::Foundation::{GetLastError, ERROR_ALREADY_EXISTS, TRUE};
 use windows_sys::Win32::System::Threading::CreateMutexW;
 
 use crate::convert::ToWide;
-use crate::error::ErrorKind::*;
+use crate::error::ErrorRepr::*;
 use crate::windows::{FromRawHandle, OwnedHandle};
 use crate::Result;
 
@@ -1,11 +1,13 @@
 //! Types related to notifications.
 
 use std::fmt;
-use std::time::Duration;
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime};
 
 use windows_sys::Win32::UI::Shell::{self, NIIF_LARGE_ICON, NIIF_NOSOUND, NIIF_RESPECT_QUIET_TIME};
 
 use crate::icon::StockIcon;
+use crate::IconId;
 
 /// Indicates the [standard icon] that Windows should use for the notification.
 ///
@@ -21,6 +23,92 @@ pub(super) enum NotificationIcon {
     Error,
     /// A stock icon icon.
     StockIcon(StockIcon),
+    /// An icon registered with the window.
+    Custom(IconId),
+}
+
+/// The sound played when a notification is shown.
+///
+/// Apart from [`NotificationSound::File`], these correspond to the
+/// notification sounds which can be configured in the sound settings of the
+/// system.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum NotificationSound {
+    /// The default notification sound.
+    Default,
+    /// The sound used for instant messages.
+    InstantMessage,
+    /// The sound used for new mail.
+    Mail,
+    /// The sound used for reminders.
+    Reminder,
+    /// The sound used for text messages.
+    Sms,
+    /// The sound used for alarms.
+    Alarm,
+    /// The sound used for incoming calls.
+    Call,
+    /// A custom sound loaded from the given WAV file.
+    File(PathBuf),
+}
+
+impl NotificationSound {
+    /// The sound alias of the system sound.
+    pub(super) fn alias(&self) -> Option<&'static str> {
+        let alias = match self {
+            NotificationSound::Default => "Notification.Default",
+            NotificationSound::InstantMessage => "Notification.IM",
+            NotificationSound::Mail => "Notification.Mail",
+            NotificationSound::Reminder => "Notification.Reminder",
+            NotificationSound::Sms => "Notification.SMS",
+            NotificationSound::Alarm => "Notification.Looping.Alarm",
+            NotificationSound::Call => "Notification.Looping.Call",
+            NotificationSound::File(..) => return None,
+        };
+
+        Some(alias)
+    }
+}
+
+/// Progress displayed in a notification.
+#[derive(Debug)]
+pub(super) struct Progress {
+    title: String,
+    value: f32,
+    status: String,
+}
+
+impl Progress {
+    pub(super) fn new<T, S>(title: T, value: f32, status: S) -> Self
+    where
+        T: fmt::Display,
+        S: fmt::Display,
+    {
+        Self {
+            title: title.to_string(),
+            value: value.clamp(0.0, 1.0),
+            status: status.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for Progress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let percent = (self.value * 100.0).round() as u32;
+
+        if self.title.is_empty() {
+            write!(f, "{percent}%")?;
+        } else {
+            write!(f, "{}: {percent}%", self.title)?;
+        }
+
+        if !self.status.is_empty() {
+            write!(f, "\n{}", self.status)?;
+        }
+
+        Ok(())
+    }
 }
 
 /// A single notification.
@@ -28,10 +116,16 @@ pub(super) enum NotificationIcon {
 pub(super) struct Notification {
     pub(super) title: Option<String>,
     pub(super) message: Option<String>,
+    pub(super) progress: Option<Progress>,
+    pub(super) sound: Option<NotificationSound>,
     pub(super) icon: Option<NotificationIcon>,
     pub(super) timeout: Option<Duration>,
     pub(super) options: u32,
     pub(super) stock_icon_opts: u32,
+    pub(super) persist: bool,
+    pub(super) schedule: Option<SystemTime>,
+    pub(super) tag: Option<String>,
+    pub(super) group: Option<String>,
 }
 
 impl Notification {
@@ -40,10 +134,16 @@ impl Notification {
         Self {
             message: None,
             title: None,
+            progress: None,
+            sound: None,
             icon: None,
             timeout: Some(Duration::from_secs(1)),
             options: 0,
             stock_icon_opts: 0,
+            persist: false,
+            schedule: None,
+            tag: None,
+            group: None,
         }
     }
 
@@ -61,10 +161,33 @@ impl Notification {
         self.title = Some(title.to_string());
     }
 
+    pub(super) fn progress(&mut self, progress: Progress) {
+        self.progress = Some(progress);
+    }
+
+    /// The text displayed in the body of the notification.
+    ///
+    /// Balloon notifications can't display a progress bar, so progress is
+    /// rendered as text following the message.
+    pub(super) fn body(&self) -> Option<String> {
+        let Some(progress) = &self.progress else {
+            return self.message.clone();
+        };
+
+        match &self.message {
+            Some(message) => Some(format!("{message}\n{progress}")),
+            None => Some(progress.to_string()),
+        }
+    }
+
     pub(super) fn icon(&mut self, icon: NotificationIcon) {
         self.icon = Some(icon);
     }
 
+    pub(super) fn sound(&mut self, sound: NotificationSound) {
+        self.sound = Some(sound);
+    }
+
     pub(super) fn no_sound(&mut self) {
         self.options |= NIIF_NOSOUND;
     }
@@ -77,6 +200,34 @@ impl Notification {
         self.options |= NIIF_RESPECT_QUIET_TIME;
     }
 
+    pub(super) fn persist(&mut self, persist: bool) {
+        self.persist = persist;
+    }
+
+    pub(super) fn schedule(&mut self, at: SystemTime) {
+        self.schedule = Some(at);
+    }
+
+    pub(super) fn tag<T>(&mut self, tag: T)
+    where
+        T: fmt::Display,
+    {
+        self.tag = Some(tag.to_string());
+    }
+
+    pub(super) fn group<T>(&mut self, group: T)
+    where
+        T: fmt::Display,
+    {
+        self.group = Some(group.to_string());
+    }
+
+    /// Test if this notification is replaced by the given one, which is the
+    /// case if they have the same tag and group.
+    pub(super) fn is_replaced_by(&self, other: &Notification) -> bool {
+        other.tag.is_some() && self.tag == other.tag && self.group == other.group
+    }
+
     pub(crate) fn icon_selected(&mut self) {
         self.stock_icon_opts |= Shell::SHGSI_SELECTED;
     }
@@ -85,3 +236,46 @@ impl Notification {
         self.stock_icon_opts |= Shell::SHGSI_LINKOVERLAY;
     }
 }
+
+/// An update to a notification which has already been sent.
+#[derive(Debug, Default)]
+pub(super) struct UpdateNotification {
+    title: Option<String>,
+    message: Option<String>,
+    progress: Option<Progress>,
+}
+
+impl UpdateNotification {
+    pub(super) fn title<M>(&mut self, title: M)
+    where
+        M: fmt::Display,
+    {
+        self.title = Some(title.to_string());
+    }
+
+    pub(super) fn message<M>(&mut self, message: M)
+    where
+        M: fmt::Display,
+    {
+        self.message = Some(message.to_string());
+    }
+
+    pub(super) fn progress(&mut self, progress: Progress) {
+        self.progress = Some(progress);
+    }
+
+    /// Apply the update to a notification.
+    pub(super) fn apply(self, notification: &mut Notification) {
+        if let Some(title) = self.title {
+            notification.title = Some(title);
+        }
+
+        if let Some(message) = self.message {
+            notification.message = Some(message);
+        }
+
+        if let Some(progress) = self.progress {
+            notification.progress = Some(progress);
+        }
+    }
+}
@@ -0,0 +1,11 @@
+/// The backend used to show a notification, as reported by
+/// [`NotificationId::backend`].
+///
+/// [`NotificationId::backend`]: crate::NotificationId::backend
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum NotificationBackend {
+    /// The notification is shown as a balloon through the notification area
+    /// of the window.
+    Balloon,
+}
@@ -1,3 +1,5 @@
+use crate::NotificationBackend;
+
 /// An identifier for a notification.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct NotificationId(u32);
@@ -7,4 +9,29 @@ impl NotificationId {
     pub(crate) fn new(id: u32) -> Self {
         Self(id)
     }
+
+    /// Get the backend used to show the notification.
+    ///
+    /// Notifications are currently always shown as balloons, which is what
+    /// any other backend falls back to if it isn't supported.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use winctx::{CreateWindow, NotificationBackend};
+    ///
+    /// # async fn test() -> winctx::Result<()> {
+    /// let mut window = CreateWindow::new("se.tedro.Example");
+    /// let area = window.new_area().id();
+    ///
+    /// let (sender, _) = window.build().await?;
+    ///
+    /// let id = sender.notification(area).message("Hello").send()?;
+    /// assert_eq!(id.backend(), NotificationBackend::Balloon);
+    /// # Ok(()) }
+    /// ```
+    #[inline]
+    pub fn backend(&self) -> NotificationBackend {
+        NotificationBackend::Balloon
+    }
 }
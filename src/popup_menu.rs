@@ -1,17 +1,36 @@
+use std::any::Any;
 use std::fmt;
 
+use windows_sys::Win32::UI::WindowsAndMessaging as winuser;
+
 use crate::event::{MouseButton, MouseButtons};
 use crate::menu_item::MenuItemKind;
-use crate::{AreaId, ItemId, MenuItem};
+use crate::{AreaId, GroupId, ItemId, MenuItem};
 
 /// The structure of a popup menu.
+#[derive(Debug)]
 pub struct PopupMenu {
     area_id: AreaId,
     pub(super) menu: Vec<MenuItem>,
+    /// The identifier of the next menu item, which is independent of the
+    /// position of items.
+    pub(super) next_id: u32,
+    /// The names of groups in the menu.
+    pub(super) groups: Vec<Box<str>>,
     /// The default item in the menu.
-    pub(super) default: Option<u32>,
+    pub(super) default: Option<ItemId>,
     /// Mouse buttons which will be accepted to open the menu.
     pub(super) open_menu: MouseButtons,
+    /// Horizontal alignment of the menu.
+    horizontal_align: HorizontalAlign,
+    /// Vertical alignment of the menu.
+    vertical_align: VerticalAlign,
+    /// Animation used when the menu is shown.
+    animation: MenuAnimation,
+    /// Whether items can be selected with the right mouse button.
+    right_button: bool,
+    /// Whether the menu is owner drawn to follow the system theme.
+    pub(super) dark_mode: bool,
 }
 
 impl PopupMenu {
@@ -20,15 +39,24 @@ impl PopupMenu {
         Self {
             area_id,
             menu: Vec::new(),
+            next_id: 0,
+            groups: Vec::new(),
             default: None,
             open_menu: MouseButtons::RIGHT,
+            horizontal_align: HorizontalAlign::Left,
+            vertical_align: VerticalAlign::Bottom,
+            animation: MenuAnimation::Default,
+            right_button: false,
+            dark_mode: false,
         }
     }
 
     /// Specify a collection of mouse buttons which will be accepted to open the
     /// context menu.
     ///
-    /// By default this is [`MouseButton::Right`].
+    /// By default this is [`MouseButton::Right`]. Any combination of
+    /// [`MouseButton::Left`], [`MouseButton::Right`], and
+    /// [`MouseButton::Middle`] can be used.
     ///
     /// # Examples
     ///
@@ -79,14 +107,76 @@ impl PopupMenu {
     where
         T: fmt::Display,
     {
-        let menu_id = ItemId::new(self.area_id.id(), self.menu.len() as u32);
-        self.menu.push(MenuItem::new(
-            menu_id,
+        self.insert_entry(self.menu.len(), text)
+    }
+
+    /// Construct a menu entry associated with a user-defined action.
+    ///
+    /// The action is handed back in [`Event::MenuItemClicked`], so that menu
+    /// items can be matched on without keeping track of their [`ItemId`]. See
+    /// [`MenuAction`].
+    ///
+    /// [`Event::MenuItemClicked`]: crate::Event::MenuItemClicked
+    /// [`MenuAction`]: crate::MenuAction
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use winctx::CreateWindow;
+    ///
+    /// enum Action {
+    ///     Settings,
+    ///     Quit,
+    /// }
+    ///
+    /// let mut window = CreateWindow::new("se.tedro.Example");
+    /// let area = window.new_area();
+    ///
+    /// let menu = area.popup_menu();
+    /// menu.push_entry_id(Action::Settings, "Settings");
+    /// menu.push_separator();
+    /// menu.push_entry_id(Action::Quit, "Quit");
+    /// ```
+    pub fn push_entry_id<A, T>(&mut self, action: A, text: T) -> &mut MenuItem
+    where
+        A: Any + Send + Sync,
+        T: fmt::Display,
+    {
+        self.push_entry(text).action(action)
+    }
+
+    /// Construct a menu entry which is inserted at the given index.
+    ///
+    /// The entry is inserted before the entry currently at `index`, or appended
+    /// if `index` is past the end of the menu. The identifier of the entry is
+    /// allocated when it's constructed and doesn't change as other entries are
+    /// inserted, so menus can be composed in any order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use winctx::CreateWindow;
+    ///
+    /// let mut window = CreateWindow::new("se.tedro.Example");
+    /// let area = window.new_area();
+    ///
+    /// let menu = area.popup_menu();
+    /// let quit = menu.push_entry("Quit").id();
+    /// let settings = menu.insert_entry(0, "Settings").id();
+    /// menu.insert_separator(1);
+    ///
+    /// assert_ne!(quit, settings);
+    /// ```
+    pub fn insert_entry<T>(&mut self, index: usize, text: T) -> &mut MenuItem
+    where
+        T: fmt::Display,
+    {
+        self.insert(
+            index,
             MenuItemKind::String {
                 text: text.to_string(),
             },
-        ));
-        self.menu.last_mut().unwrap()
+        )
     }
 
     /// Construct a menu separator.
@@ -103,14 +193,73 @@ impl PopupMenu {
     /// menu.push_separator();
     /// ```
     pub fn push_separator(&mut self) -> &mut MenuItem {
-        let menu_id = ItemId::new(self.area_id.id(), self.menu.len() as u32);
-        self.menu
-            .push(MenuItem::new(menu_id, MenuItemKind::Separator));
-        self.menu.last_mut().unwrap()
+        self.insert_separator(self.menu.len())
+    }
+
+    /// Construct a menu separator which is inserted at the given index.
+    ///
+    /// See [`PopupMenu::insert_entry`] for how `index` is interpreted.
+    pub fn insert_separator(&mut self, index: usize) -> &mut MenuItem {
+        self.insert(index, MenuItemKind::Separator)
+    }
+
+    fn insert(&mut self, index: usize, kind: MenuItemKind) -> &mut MenuItem {
+        let menu_id = ItemId::new(self.area_id.id(), self.next_id);
+        self.next_id += 1;
+
+        let index = index.min(self.menu.len());
+        self.menu.insert(index, MenuItem::new(menu_id, kind));
+        &mut self.menu[index]
+    }
+
+    /// Get or construct a group of menu items with the given name.
+    ///
+    /// Menu items are added to the group by calling `group` on the item
+    /// returned when it is pushed, after which the whole group can be modified
+    /// at once using [`Sender::modify_group`].
+    ///
+    /// Calling this multiple times with the same name returns the same group.
+    ///
+    /// [`Sender::modify_group`]: crate::Sender::modify_group
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use winctx::CreateWindow;
+    ///
+    /// let mut window = CreateWindow::new("se.tedro.Example");;
+    /// let area = window.new_area();
+    ///
+    /// let menu = area.popup_menu();
+    /// let transfers = menu.group("transfers");
+    /// menu.push_entry("Upload").group(transfers);
+    /// menu.push_entry("Download").group(transfers);
+    /// menu.push_separator();
+    /// menu.push_entry("Quit");
+    ///
+    /// assert_eq!(transfers, menu.group("transfers"));
+    /// ```
+    pub fn group<N>(&mut self, name: N) -> GroupId
+    where
+        N: fmt::Display,
+    {
+        let name = name.to_string();
+
+        let id = match self.groups.iter().position(|g| **g == *name) {
+            Some(id) => id,
+            None => {
+                self.groups.push(name.into());
+                self.groups.len() - 1
+            }
+        };
+
+        GroupId::new(self.area_id.id(), id as u32)
     }
 
     /// Set the default item in the menu.
     ///
+    /// Building the window fails if the item isn't in this menu.
+    ///
     /// # Examples
     ///
     /// ```no_run
@@ -126,8 +275,157 @@ impl PopupMenu {
     /// menu.set_default(first);
     /// ```
     pub fn set_default(&mut self, menu_item_id: ItemId) {
-        if self.area_id == menu_item_id.area_id() {
-            self.default = Some(menu_item_id.id());
+        self.default = Some(menu_item_id);
+    }
+
+    /// Set how the menu is horizontally aligned relative to the position it's
+    /// opened at.
+    ///
+    /// By default this is [`HorizontalAlign::Left`], which means that the
+    /// left edge of the menu is at the position it's opened at.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use winctx::{CreateWindow, HorizontalAlign, VerticalAlign};
+    ///
+    /// let mut window = CreateWindow::new("se.tedro.Example");
+    /// let area = window.new_area();
+    ///
+    /// // Suitable for a taskbar which is docked to the top right of the
+    /// // screen.
+    /// let menu = area
+    ///     .popup_menu()
+    ///     .horizontal_align(HorizontalAlign::Right)
+    ///     .vertical_align(VerticalAlign::Top);
+    /// menu.push_entry("Quit");
+    /// ```
+    pub fn horizontal_align(&mut self, align: HorizontalAlign) -> &mut Self {
+        self.horizontal_align = align;
+        self
+    }
+
+    /// Set how the menu is vertically aligned relative to the position it's
+    /// opened at.
+    ///
+    /// By default this is [`VerticalAlign::Bottom`], which means that the
+    /// bottom edge of the menu is at the position it's opened at.
+    pub fn vertical_align(&mut self, align: VerticalAlign) -> &mut Self {
+        self.vertical_align = align;
+        self
+    }
+
+    /// Set the animation used when the menu is shown.
+    ///
+    /// By default the animation is decided by the system.
+    pub fn animation(&mut self, animation: MenuAnimation) -> &mut Self {
+        self.animation = animation;
+        self
+    }
+
+    /// Set whether menu items can be selected with the right mouse button in
+    /// addition to the left one.
+    pub fn right_button(&mut self, right_button: bool) -> &mut Self {
+        self.right_button = right_button;
+        self
+    }
+
+    /// Set whether the menu should follow the dark theme of the system.
+    ///
+    /// Standard popup menus are always light, which stands out when the rest
+    /// of the system uses a dark theme. With this enabled the menu is drawn by
+    /// this library instead, using dark colors whenever applications are
+    /// configured to use the dark theme.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use winctx::CreateWindow;
+    ///
+    /// let mut window = CreateWindow::new("se.tedro.Example");
+    /// let area = window.new_area();
+    ///
+    /// let menu = area.popup_menu().dark_mode(true);
+    /// menu.push_entry("Example Application");
+    /// menu.push_separator();
+    /// menu.push_entry("Quit");
+    /// ```
+    pub fn dark_mode(&mut self, dark_mode: bool) -> &mut Self {
+        self.dark_mode = dark_mode;
+        self
+    }
+
+    /// The flags used when showing the menu with `TrackPopupMenu`.
+    pub(crate) fn track_flags(&self) -> u32 {
+        let mut flags = match self.horizontal_align {
+            HorizontalAlign::Left => winuser::TPM_LEFTALIGN,
+            HorizontalAlign::Center => winuser::TPM_CENTERALIGN,
+            HorizontalAlign::Right => winuser::TPM_RIGHTALIGN,
+        };
+
+        flags |= match self.vertical_align {
+            VerticalAlign::Top => winuser::TPM_TOPALIGN,
+            VerticalAlign::Center => winuser::TPM_VCENTERALIGN,
+            VerticalAlign::Bottom => winuser::TPM_BOTTOMALIGN,
+        };
+
+        flags |= match self.animation {
+            MenuAnimation::Default => 0,
+            MenuAnimation::None => winuser::TPM_NOANIMATION,
+            MenuAnimation::LeftToRight => winuser::TPM_HORPOSANIMATION,
+            MenuAnimation::RightToLeft => winuser::TPM_HORNEGANIMATION,
+            MenuAnimation::TopToBottom => winuser::TPM_VERPOSANIMATION,
+            MenuAnimation::BottomToTop => winuser::TPM_VERNEGANIMATION,
+        };
+
+        if self.right_button {
+            flags |= winuser::TPM_RIGHTBUTTON;
         }
+
+        flags
     }
 }
+
+/// How a popup menu is horizontally aligned relative to the position it's
+/// opened at.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum HorizontalAlign {
+    /// The left edge of the menu is at the position.
+    Left,
+    /// The menu is horizontally centered on the position.
+    Center,
+    /// The right edge of the menu is at the position.
+    Right,
+}
+
+/// How a popup menu is vertically aligned relative to the position it's
+/// opened at.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum VerticalAlign {
+    /// The top edge of the menu is at the position.
+    Top,
+    /// The menu is vertically centered on the position.
+    Center,
+    /// The bottom edge of the menu is at the position.
+    Bottom,
+}
+
+/// The animation used when a popup menu is shown.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum MenuAnimation {
+    /// Let the system decide how the menu is animated.
+    Default,
+    /// Show the menu without animating it.
+    None,
+    /// Animate the menu from left to right.
+    LeftToRight,
+    /// Animate the menu from right to left.
+    RightToLeft,
+    /// Animate the menu from top to bottom.
+    TopToBottom,
+    /// Animate the menu from bottom to top.
+    BottomToTop,
+}
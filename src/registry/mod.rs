@@ -0,0 +1,16 @@
+//! Types related to reading and modifying the registry.
+
+pub use self::registry::{OpenRegistryKey, RegistryKey, Subkeys, Values};
+mod registry;
+
+pub use self::registry_value::RegistryValue;
+mod registry_value;
+
+pub use self::value_info::ValueInfo;
+mod value_info;
+
+pub use self::value_type::ValueType;
+mod value_type;
+
+#[cfg(feature = "serde")]
+mod settings;
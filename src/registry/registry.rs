@@ -0,0 +1,722 @@
+use std::ffi::{OsStr, OsString};
+use std::io;
+use std::mem::MaybeUninit;
+use std::ptr;
+use std::time::{Duration, SystemTime};
+
+use windows_sys::Win32::Foundation::{
+    ERROR_MORE_DATA, ERROR_NO_MORE_ITEMS, ERROR_SUCCESS, FILETIME,
+};
+use windows_sys::Win32::System::Registry::{self as winreg, HKEY};
+
+use crate::convert::{FromWide, ToWide};
+
+use super::{RegistryValue, ValueInfo, ValueType};
+
+/// The maximum length of the name of a key, including the null terminator.
+const MAX_KEY_NAME: usize = 256;
+
+/// The maximum length of the name of a value, including the null terminator.
+const MAX_VALUE_NAME: usize = 16384;
+
+/// The number of 100-nanosecond intervals between the Windows epoch
+/// (1601-01-01) and the Unix epoch.
+const UNIX_EPOCH_INTERVALS: u64 = 116_444_736_000_000_000;
+
+/// An open registry key.
+///
+/// This is constructed using [`OpenRegistryKey`].
+pub struct RegistryKey(winreg::HKEY);
+
+unsafe impl Sync for RegistryKey {}
+unsafe impl Send for RegistryKey {}
+
+/// Helper to open a registry key with the ability to specify desired
+/// permissions.
+pub struct OpenRegistryKey {
+    key: HKEY,
+    desired: u32,
+}
+
+impl OpenRegistryKey {
+    /// Open the given key in the `HKEY_CURRENT_USER` registry.
+    pub fn current_user() -> Self {
+        Self {
+            key: winreg::HKEY_CURRENT_USER,
+            desired: winreg::KEY_READ | winreg::KEY_WOW64_64KEY,
+        }
+    }
+
+    /// Open the given key in the `HKEY_LOCAL_MACHINE` registry.
+    pub fn local_machine() -> Self {
+        Self {
+            key: winreg::HKEY_LOCAL_MACHINE,
+            desired: winreg::KEY_READ,
+        }
+    }
+
+    /// Enable the `KEY_SET_VALUE` desired access mode.
+    pub fn set_value(mut self) -> Self {
+        self.desired |= winreg::KEY_SET_VALUE;
+        self
+    }
+
+    /// Internal open implementation.
+    pub fn open<K>(self, key: K) -> io::Result<RegistryKey>
+    where
+        K: AsRef<OsStr>,
+    {
+        let key = key.to_wide_null();
+        self.open_inner(&key)
+    }
+
+    fn open_inner(&self, key: &[u16]) -> io::Result<RegistryKey> {
+        unsafe {
+            let mut hkey = MaybeUninit::uninit();
+
+            let status =
+                winreg::RegOpenKeyExW(self.key, key.as_ptr(), 0, self.desired, hkey.as_mut_ptr());
+
+            if status != ERROR_SUCCESS {
+                return Err(io::Error::from_raw_os_error(status as i32));
+            }
+
+            Ok(RegistryKey(hkey.assume_init()))
+        }
+    }
+}
+
+impl RegistryKey {
+    /// Open the given key in the HKEY_CURRENT_USER scope.
+    #[inline]
+    pub fn current_user<K>(key: K) -> io::Result<RegistryKey>
+    where
+        K: AsRef<OsStr>,
+    {
+        OpenRegistryKey::current_user().open(key)
+    }
+
+    /// Open the given key in the HKEY_LOCAL_MACHINE scope.
+    pub fn local_machine<K>(key: K) -> io::Result<RegistryKey>
+    where
+        K: AsRef<OsStr>,
+    {
+        OpenRegistryKey::local_machine().open(key)
+    }
+
+    /// Iterate over the names of the subkeys of this key.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use winctx::RegistryKey;
+    ///
+    /// let key = RegistryKey::current_user("Software")?;
+    ///
+    /// for name in key.subkeys() {
+    ///     println!("{}", name?.to_string_lossy());
+    /// }
+    /// # Ok::<_, std::io::Error>(())
+    /// ```
+    pub fn subkeys(&self) -> Subkeys<'_> {
+        Subkeys {
+            key: self,
+            index: 0,
+            name: vec![0; MAX_KEY_NAME],
+        }
+    }
+
+    /// Iterate over the names and types of the values of this key.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use winctx::RegistryKey;
+    ///
+    /// let key = RegistryKey::current_user("Software\\Microsoft\\Windows\\CurrentVersion\\Run")?;
+    ///
+    /// for value in key.values() {
+    ///     let (name, ty) = value?;
+    ///     println!("{}: {ty:?}", name.to_string_lossy());
+    /// }
+    /// # Ok::<_, std::io::Error>(())
+    /// ```
+    pub fn values(&self) -> Values<'_> {
+        Values {
+            key: self,
+            index: 0,
+            name: vec![0; MAX_VALUE_NAME],
+        }
+    }
+
+    /// Get the time at which this key, or any of its values, was last
+    /// modified.
+    ///
+    /// Note that modifications to subkeys don't update the time of their
+    /// parent.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use std::time::SystemTime;
+    ///
+    /// use winctx::RegistryKey;
+    ///
+    /// let key = RegistryKey::current_user("Software\\Example")?;
+    /// let modified = key.last_write_time()?;
+    ///
+    /// if modified > SystemTime::now() {
+    ///     println!("Modified in the future");
+    /// }
+    /// # Ok::<_, std::io::Error>(())
+    /// ```
+    pub fn last_write_time(&self) -> io::Result<SystemTime> {
+        let mut time = FILETIME {
+            dwLowDateTime: 0,
+            dwHighDateTime: 0,
+        };
+
+        let status = unsafe {
+            winreg::RegQueryInfoKeyW(
+                self.0,
+                ptr::null_mut(),
+                ptr::null_mut(),
+                ptr::null(),
+                ptr::null_mut(),
+                ptr::null_mut(),
+                ptr::null_mut(),
+                ptr::null_mut(),
+                ptr::null_mut(),
+                ptr::null_mut(),
+                ptr::null_mut(),
+                &mut time,
+            )
+        };
+
+        if status != ERROR_SUCCESS {
+            return Err(io::Error::from_raw_os_error(status as i32));
+        }
+
+        let intervals = u64::from(time.dwHighDateTime) << 32 | u64::from(time.dwLowDateTime);
+
+        let time = if intervals >= UNIX_EPOCH_INTERVALS {
+            SystemTime::UNIX_EPOCH + intervals_to_duration(intervals - UNIX_EPOCH_INTERVALS)
+        } else {
+            SystemTime::UNIX_EPOCH - intervals_to_duration(UNIX_EPOCH_INTERVALS - intervals)
+        };
+
+        Ok(time)
+    }
+
+    /// Get the given value along with its type.
+    ///
+    /// Strings are not expanded, so values of type `REG_EXPAND_SZ` are
+    /// returned as they are stored.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use winctx::RegistryKey;
+    /// use winctx::registry::RegistryValue;
+    ///
+    /// let key = RegistryKey::current_user("Environment")?;
+    ///
+    /// match key.get_value("Path")? {
+    ///     RegistryValue::Sz(path) | RegistryValue::ExpandSz(path) => {
+    ///         println!("{}", path.to_string_lossy());
+    ///     }
+    ///     value => {
+    ///         println!("Unexpected value type {:?}", value.value_type());
+    ///     }
+    /// }
+    /// # Ok::<_, std::io::Error>(())
+    /// ```
+    pub fn get_value<N>(&self, name: N) -> io::Result<RegistryValue>
+    where
+        N: AsRef<OsStr>,
+    {
+        let name = name.to_wide_null();
+        let (ty, data) = self.get_raw(&name)?;
+        Ok(RegistryValue::decode(ty, data))
+    }
+
+    /// Get the type and length of the given value without reading it.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use winctx::RegistryKey;
+    /// use winctx::registry::ValueType;
+    ///
+    /// let key = RegistryKey::current_user("Environment")?;
+    /// let info = key.value_info("Path")?;
+    ///
+    /// if info.value_type == ValueType::ExpandSz && info.len < 1 << 16 {
+    ///     println!("{:?}", key.get_value("Path")?);
+    /// }
+    /// # Ok::<_, std::io::Error>(())
+    /// ```
+    pub fn value_info<N>(&self, name: N) -> io::Result<ValueInfo>
+    where
+        N: AsRef<OsStr>,
+    {
+        let name = name.to_wide_null();
+        let mut ty = 0;
+        let mut len = 0;
+
+        let status = unsafe {
+            winreg::RegQueryValueExW(
+                self.0,
+                name.as_ptr(),
+                ptr::null(),
+                &mut ty,
+                ptr::null_mut(),
+                &mut len,
+            )
+        };
+
+        if status != ERROR_SUCCESS {
+            return Err(io::Error::from_raw_os_error(status as i32));
+        }
+
+        Ok(ValueInfo {
+            value_type: ValueType::from_raw(ty),
+            len: len as usize,
+        })
+    }
+
+    fn get_raw(&self, name: &[u16]) -> io::Result<(ValueType, Vec<u8>)> {
+        let flags = winreg::RRF_RT_ANY | winreg::RRF_NOEXPAND;
+        let mut data = Vec::<u8>::new();
+
+        loop {
+            let mut ty = 0;
+            let mut len = data.len() as u32;
+
+            let status = unsafe {
+                winreg::RegGetValueW(
+                    self.0,
+                    ptr::null(),
+                    name.as_ptr(),
+                    flags,
+                    &mut ty,
+                    if data.is_empty() {
+                        ptr::null_mut()
+                    } else {
+                        data.as_mut_ptr().cast()
+                    },
+                    &mut len,
+                )
+            };
+
+            match status {
+                // NB: Querying without a buffer succeeds with the size of the
+                // value, which might grow before we read it.
+                ERROR_SUCCESS if !data.is_empty() || len == 0 => {
+                    data.truncate(len as usize);
+                    return Ok((ValueType::from_raw(ty), data));
+                }
+                ERROR_SUCCESS | ERROR_MORE_DATA => {
+                    data.resize(len as usize, 0);
+                }
+                status => return Err(io::Error::from_raw_os_error(status as i32)),
+            }
+        }
+    }
+
+    /// Set the given value, replacing any existing value.
+    ///
+    /// The key must have been opened with [`OpenRegistryKey::set_value`].
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use winctx::OpenRegistryKey;
+    /// use winctx::registry::RegistryValue;
+    ///
+    /// let key = OpenRegistryKey::current_user().set_value().open("Software\\Example")?;
+    /// key.set_value("Count", &RegistryValue::Dword(42))?;
+    /// # Ok::<_, std::io::Error>(())
+    /// ```
+    pub fn set_value<N>(&self, name: N, value: &RegistryValue) -> io::Result<()>
+    where
+        N: AsRef<OsStr>,
+    {
+        let name = name.to_wide_null();
+        self.set_value_inner(&name, value)
+    }
+
+    fn set_value_inner(&self, name: &[u16], value: &RegistryValue) -> io::Result<()> {
+        let data = value.encode();
+
+        let len = u32::try_from(data.len())
+            .map_err(|_| io::Error::new(io::ErrorKind::Other, "Value size overflow"))?;
+
+        let status = unsafe {
+            winreg::RegSetValueExW(
+                self.0,
+                name.as_ptr(),
+                0,
+                value.value_type().into_raw(),
+                data.as_ptr(),
+                len,
+            )
+        };
+
+        if status != ERROR_SUCCESS {
+            return Err(io::Error::from_raw_os_error(status as i32));
+        }
+
+        Ok(())
+    }
+
+    /// Get the default value of this key, which is the value without a name.
+    ///
+    /// Shell integrations such as file associations are often registered
+    /// through the default value of a key.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use winctx::RegistryKey;
+    ///
+    /// let key = RegistryKey::current_user("Software\\Classes\\.txt")?;
+    /// println!("{:?}", key.default_value()?);
+    /// # Ok::<_, std::io::Error>(())
+    /// ```
+    pub fn default_value(&self) -> io::Result<RegistryValue> {
+        let (ty, data) = self.get_raw(&[0])?;
+        Ok(RegistryValue::decode(ty, data))
+    }
+
+    /// Set the default value of this key, which is the value without a name.
+    ///
+    /// The key must have been opened with [`OpenRegistryKey::set_value`].
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use winctx::OpenRegistryKey;
+    /// use winctx::registry::RegistryValue;
+    ///
+    /// let key = OpenRegistryKey::current_user()
+    ///     .set_value()
+    ///     .open("Software\\Classes\\.example")?;
+    ///
+    /// key.set_default_value(&RegistryValue::Sz("Example.File".into()))?;
+    /// # Ok::<_, std::io::Error>(())
+    /// ```
+    pub fn set_default_value(&self, value: &RegistryValue) -> io::Result<()> {
+        self.set_value_inner(&[0], value)
+    }
+
+    /// Load a value from the values of this key, where each field of the
+    /// value is read from the value with the same name.
+    ///
+    /// Strings, numbers and booleans are read from their natural registry
+    /// representation, and any other fields are decoded from the JSON stored
+    /// by [`RegistryKey::store`]. Optional fields which have no value are
+    /// `None`.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use serde::Deserialize;
+    /// use winctx::RegistryKey;
+    ///
+    /// #[derive(Deserialize)]
+    /// struct Settings {
+    ///     theme: String,
+    ///     volume: u32,
+    ///     muted: bool,
+    /// }
+    ///
+    /// let key = RegistryKey::current_user("Software\\Example")?;
+    /// let settings = key.load::<Settings>()?;
+    /// # Ok::<_, std::io::Error>(())
+    /// ```
+    #[cfg(feature = "serde")]
+    pub fn load<T>(&self) -> io::Result<T>
+    where
+        T: serde::de::DeserializeOwned,
+    {
+        let mut values = Vec::new();
+
+        for value in self.values() {
+            let (name, _) = value?;
+
+            // NB: Names which are not valid UTF-8 can't correspond to a field.
+            let Some(field) = name.to_str() else {
+                continue;
+            };
+
+            values.push((field.to_owned(), self.get_value(&name)?));
+        }
+
+        super::settings::decode(values)
+    }
+
+    /// Store a value as the values of this key, where each field of the value
+    /// is written to the value with the same name.
+    ///
+    /// Strings are stored as `REG_SZ`, booleans and non-negative numbers as
+    /// `REG_DWORD` or `REG_QWORD` depending on their size, and any other
+    /// fields as JSON in `REG_BINARY` values. Fields which are `None` have
+    /// their value removed, and values which don't correspond to a field are
+    /// left as they are.
+    ///
+    /// The key must have been opened with [`OpenRegistryKey::set_value`].
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use serde::Serialize;
+    /// use winctx::OpenRegistryKey;
+    ///
+    /// #[derive(Serialize)]
+    /// struct Settings {
+    ///     theme: String,
+    ///     volume: u32,
+    ///     muted: bool,
+    /// }
+    ///
+    /// let settings = Settings {
+    ///     theme: String::from("dark"),
+    ///     volume: 80,
+    ///     muted: false,
+    /// };
+    ///
+    /// let key = OpenRegistryKey::current_user().set_value().open("Software\\Example")?;
+    /// key.store(&settings)?;
+    /// # Ok::<_, std::io::Error>(())
+    /// ```
+    #[cfg(feature = "serde")]
+    pub fn store<T>(&self, value: &T) -> io::Result<()>
+    where
+        T: ?Sized + serde::Serialize,
+    {
+        for (name, value) in super::settings::encode(value)? {
+            match value {
+                Some(value) => self.set_value(&name, &value)?,
+                None => self.delete_value(&name.to_wide_null())?,
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Delete the given value, succeeding if it doesn't exist.
+    #[cfg(feature = "serde")]
+    fn delete_value(&self, name: &[u16]) -> io::Result<()> {
+        use windows_sys::Win32::Foundation::ERROR_FILE_NOT_FOUND;
+
+        let status = unsafe { winreg::RegDeleteValueW(self.0, name.as_ptr()) };
+
+        match status {
+            ERROR_SUCCESS | ERROR_FILE_NOT_FOUND => Ok(()),
+            status => Err(io::Error::from_raw_os_error(status as i32)),
+        }
+    }
+
+    /// Get the given value as a string.
+    pub fn get_string<N>(&self, name: N) -> io::Result<OsString>
+    where
+        N: AsRef<OsStr>,
+    {
+        let name = name.to_wide_null();
+        let bytes = self.get_wide(&name, winreg::RRF_RT_REG_SZ)?;
+        // Skip the terminating null.
+        Ok(OsString::from_wide(&bytes[..bytes.len().saturating_sub(1)]))
+    }
+
+    fn get_wide(&self, name: &[u16], flags: u32) -> io::Result<Vec<u16>> {
+        let mut len = 0;
+
+        unsafe {
+            let status = winreg::RegGetValueW(
+                self.0,
+                ptr::null_mut(),
+                name.as_ptr(),
+                flags,
+                ptr::null_mut(),
+                ptr::null_mut(),
+                &mut len,
+            );
+
+            if status != ERROR_SUCCESS {
+                return Err(io::Error::from_raw_os_error(status as i32));
+            }
+
+            debug_assert!(len % 2 == 0);
+            let mut value = vec![0u16; (len / 2) as usize];
+
+            let status = winreg::RegGetValueW(
+                self.0,
+                ptr::null_mut(),
+                name.as_ptr(),
+                flags,
+                ptr::null_mut(),
+                value.as_mut_ptr().cast(),
+                &mut len,
+            );
+
+            if status != ERROR_SUCCESS {
+                return Err(io::Error::from_raw_os_error(status as i32));
+            }
+
+            debug_assert!(len % 2 == 0);
+
+            // Length reported *including* wide null terminator.
+            value.truncate((len / 2) as usize);
+            Ok(value)
+        }
+    }
+
+    /// Set the given value.
+    pub fn set<N>(&self, name: N, value: impl AsRef<OsStr>) -> io::Result<()>
+    where
+        N: AsRef<OsStr>,
+    {
+        let name = name.to_wide_null();
+        let value = value.to_wide_null();
+        self.set_inner(&name, &value)
+    }
+
+    fn set_inner(&self, name: &[u16], value: &[u16]) -> io::Result<()> {
+        let value_len = value
+            .len()
+            .checked_mul(2)
+            .and_then(|n| u32::try_from(n).ok())
+            .ok_or_else(|| io::Error::new(io::ErrorKind::Other, "Value size overflow"))?;
+
+        let status = unsafe {
+            winreg::RegSetValueExW(
+                self.0,
+                name.as_ptr(),
+                0,
+                winreg::REG_SZ,
+                value.as_ptr().cast(),
+                value_len,
+            )
+        };
+
+        if status != 0 {
+            return Err(io::Error::last_os_error());
+        }
+
+        Ok(())
+    }
+
+    /// Delete the given registry key.
+    pub fn delete<N>(&self, name: N) -> io::Result<()>
+    where
+        N: AsRef<OsStr>,
+    {
+        let name = name.to_wide_null();
+        self.delete_inner(&name)
+    }
+
+    fn delete_inner(&self, name: &[u16]) -> io::Result<()> {
+        let status = unsafe { winreg::RegDeleteKeyValueW(self.0, ptr::null_mut(), name.as_ptr()) };
+
+        if status != 0 {
+            return Err(io::Error::last_os_error());
+        }
+
+        Ok(())
+    }
+}
+
+/// An iterator over the names of the subkeys of a registry key, as returned by
+/// [`RegistryKey::subkeys`].
+pub struct Subkeys<'a> {
+    key: &'a RegistryKey,
+    index: u32,
+    name: Vec<u16>,
+}
+
+impl Iterator for Subkeys<'_> {
+    type Item = io::Result<OsString>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut len = self.name.len() as u32;
+
+        let status = unsafe {
+            winreg::RegEnumKeyExW(
+                self.key.0,
+                self.index,
+                self.name.as_mut_ptr(),
+                &mut len,
+                ptr::null(),
+                ptr::null_mut(),
+                ptr::null_mut(),
+                ptr::null_mut(),
+            )
+        };
+
+        match status {
+            ERROR_SUCCESS => {}
+            ERROR_NO_MORE_ITEMS => return None,
+            status => return Some(Err(io::Error::from_raw_os_error(status as i32))),
+        }
+
+        self.index += 1;
+        Some(Ok(OsString::from_wide(&self.name[..len as usize])))
+    }
+}
+
+/// An iterator over the names and types of the values of a registry key, as
+/// returned by [`RegistryKey::values`].
+pub struct Values<'a> {
+    key: &'a RegistryKey,
+    index: u32,
+    name: Vec<u16>,
+}
+
+impl Iterator for Values<'_> {
+    type Item = io::Result<(OsString, ValueType)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut len = self.name.len() as u32;
+        let mut ty = 0;
+
+        let status = unsafe {
+            winreg::RegEnumValueW(
+                self.key.0,
+                self.index,
+                self.name.as_mut_ptr(),
+                &mut len,
+                ptr::null(),
+                &mut ty,
+                ptr::null_mut(),
+                ptr::null_mut(),
+            )
+        };
+
+        match status {
+            ERROR_SUCCESS => {}
+            ERROR_NO_MORE_ITEMS => return None,
+            status => return Some(Err(io::Error::from_raw_os_error(status as i32))),
+        }
+
+        self.index += 1;
+        let name = OsString::from_wide(&self.name[..len as usize]);
+        Some(Ok((name, ValueType::from_raw(ty))))
+    }
+}
+
+/// Convert a number of 100-nanosecond intervals into a duration.
+fn intervals_to_duration(intervals: u64) -> Duration {
+    Duration::new(
+        intervals / 10_000_000,
+        (intervals % 10_000_000) as u32 * 100,
+    )
+}
+
+impl Drop for RegistryKey {
+    fn drop(&mut self) {
+        unsafe {
+            winreg::RegCloseKey(self.0);
+        }
+    }
+}
@@ -0,0 +1,117 @@
+use std::ffi::OsString;
+
+use crate::convert::{FromWide, ToWide};
+
+use super::ValueType;
+
+/// A typed registry value, as returned by [`RegistryKey::get_value`].
+///
+/// [`RegistryKey::get_value`]: super::RegistryKey::get_value
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum RegistryValue {
+    /// A string (`REG_SZ`).
+    Sz(OsString),
+    /// A string which contains unexpanded references to environment
+    /// variables (`REG_EXPAND_SZ`).
+    ExpandSz(OsString),
+    /// A sequence of strings (`REG_MULTI_SZ`).
+    MultiSz(Vec<OsString>),
+    /// A 32-bit number (`REG_DWORD`).
+    Dword(u32),
+    /// A 64-bit number (`REG_QWORD`).
+    Qword(u64),
+    /// Binary data in any form (`REG_BINARY`).
+    Binary(Vec<u8>),
+    /// A value of any other type, or a value which is malformed for its
+    /// type, in its raw form.
+    Other(ValueType, Vec<u8>),
+}
+
+impl RegistryValue {
+    /// Get the type of the value.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use winctx::registry::{RegistryValue, ValueType};
+    ///
+    /// assert_eq!(RegistryValue::Dword(42).value_type(), ValueType::Dword);
+    /// ```
+    pub fn value_type(&self) -> ValueType {
+        match self {
+            Self::Sz(..) => ValueType::Sz,
+            Self::ExpandSz(..) => ValueType::ExpandSz,
+            Self::MultiSz(..) => ValueType::MultiSz,
+            Self::Dword(..) => ValueType::Dword,
+            Self::Qword(..) => ValueType::Qword,
+            Self::Binary(..) => ValueType::Binary,
+            Self::Other(ty, ..) => *ty,
+        }
+    }
+
+    /// Decode a value from its raw type and data.
+    pub(crate) fn decode(ty: ValueType, data: Vec<u8>) -> Self {
+        match ty {
+            ValueType::Sz => Self::Sz(decode_string(&data)),
+            ValueType::ExpandSz => Self::ExpandSz(decode_string(&data)),
+            ValueType::MultiSz => Self::MultiSz(decode_multi_string(&data)),
+            ValueType::Dword => match <[u8; 4]>::try_from(&data[..]) {
+                Ok(bytes) => Self::Dword(u32::from_le_bytes(bytes)),
+                Err(..) => Self::Other(ty, data),
+            },
+            ValueType::Qword => match <[u8; 8]>::try_from(&data[..]) {
+                Ok(bytes) => Self::Qword(u64::from_le_bytes(bytes)),
+                Err(..) => Self::Other(ty, data),
+            },
+            ValueType::Binary => Self::Binary(data),
+            ty => Self::Other(ty, data),
+        }
+    }
+
+    /// Encode the value into its raw data.
+    pub(crate) fn encode(&self) -> Vec<u8> {
+        match self {
+            Self::Sz(string) | Self::ExpandSz(string) => wide_bytes(&string.to_wide_null()),
+            Self::MultiSz(strings) => {
+                let mut wide = Vec::new();
+
+                for string in strings {
+                    wide.extend(string.to_wide_null());
+                }
+
+                wide.push(0);
+                wide_bytes(&wide)
+            }
+            Self::Dword(value) => value.to_le_bytes().to_vec(),
+            Self::Qword(value) => value.to_le_bytes().to_vec(),
+            Self::Binary(data) | Self::Other(_, data) => data.clone(),
+        }
+    }
+}
+
+fn wide_bytes(wide: &[u16]) -> Vec<u8> {
+    wide.iter().flat_map(|c| c.to_ne_bytes()).collect()
+}
+
+fn decode_wide(data: &[u8]) -> Vec<u16> {
+    data.chunks_exact(2)
+        .map(|c| u16::from_ne_bytes([c[0], c[1]]))
+        .collect()
+}
+
+/// Decode a string, which might be missing its null terminator.
+fn decode_string(data: &[u8]) -> OsString {
+    let wide = decode_wide(data);
+    let end = wide.iter().position(|&c| c == 0).unwrap_or(wide.len());
+    OsString::from_wide(&wide[..end])
+}
+
+/// Decode a sequence of strings, which is terminated by an empty string.
+fn decode_multi_string(data: &[u8]) -> Vec<OsString> {
+    decode_wide(data)
+        .split(|&c| c == 0)
+        .take_while(|s| !s.is_empty())
+        .map(OsString::from_wide)
+        .collect()
+}
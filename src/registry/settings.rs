@@ -0,0 +1,387 @@
+use std::ffi::OsString;
+use std::io;
+
+use serde::de::value::{MapDeserializer, SeqDeserializer};
+use serde::de::{self, DeserializeOwned, IntoDeserializer, Visitor};
+use serde::{forward_to_deserialize_any, Deserializer, Serialize};
+use serde_json::Value;
+
+use super::RegistryValue;
+
+/// Encode the fields of a value into registry values.
+///
+/// Fields which are `None` map to no value, so that they are removed.
+pub(super) fn encode<T>(value: &T) -> io::Result<Vec<(String, Option<RegistryValue>)>>
+where
+    T: ?Sized + Serialize,
+{
+    let Value::Object(fields) = serde_json::to_value(value)? else {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "Only values which serialize as maps can be stored in the registry",
+        ));
+    };
+
+    let mut values = Vec::with_capacity(fields.len());
+
+    for (name, value) in fields {
+        let value = match value {
+            Value::Null => None,
+            Value::Bool(value) => Some(RegistryValue::Dword(u32::from(value))),
+            Value::String(value) => Some(RegistryValue::Sz(value.into())),
+            Value::Number(n) => match n.as_u64() {
+                Some(n) => Some(match u32::try_from(n) {
+                    Ok(n) => RegistryValue::Dword(n),
+                    Err(..) => RegistryValue::Qword(n),
+                }),
+                None => Some(RegistryValue::Binary(serde_json::to_vec(&n)?)),
+            },
+            // NB: Anything without a natural registry representation is stored
+            // as JSON.
+            value => Some(RegistryValue::Binary(serde_json::to_vec(&value)?)),
+        };
+
+        values.push((name, value));
+    }
+
+    Ok(values)
+}
+
+/// Decode a value from the values of a registry key.
+pub(super) fn decode<T>(values: Vec<(String, RegistryValue)>) -> io::Result<T>
+where
+    T: DeserializeOwned,
+{
+    let values = values
+        .into_iter()
+        .map(|(name, value)| (name, ValueDeserializer(value)));
+
+    Ok(T::deserialize(
+        MapDeserializer::<_, serde_json::Error>::new(values),
+    )?)
+}
+
+/// Deserializer for a single registry value.
+struct ValueDeserializer(RegistryValue);
+
+impl<'de> IntoDeserializer<'de, serde_json::Error> for ValueDeserializer {
+    type Deserializer = Self;
+
+    #[inline]
+    fn into_deserializer(self) -> Self::Deserializer {
+        self
+    }
+}
+
+impl<'de> Deserializer<'de> for ValueDeserializer {
+    type Error = serde_json::Error;
+
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        match self.0 {
+            RegistryValue::Sz(string) | RegistryValue::ExpandSz(string) => {
+                visitor.visit_string(into_string(string)?)
+            }
+            RegistryValue::MultiSz(strings) => {
+                let strings = strings
+                    .into_iter()
+                    .map(into_string)
+                    .collect::<Result<Vec<_>, _>>()?;
+
+                visitor.visit_seq(SeqDeserializer::new(strings.into_iter()))
+            }
+            RegistryValue::Dword(n) => visitor.visit_u32(n),
+            RegistryValue::Qword(n) => visitor.visit_u64(n),
+            RegistryValue::Binary(data) => {
+                serde_json::from_slice::<Value>(&data)?.deserialize_any(visitor)
+            }
+            RegistryValue::Other(ty, ..) => Err(de::Error::custom(format_args!(
+                "Unsupported registry value type {ty:?}"
+            ))),
+        }
+    }
+
+    fn deserialize_bool<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        match self.0 {
+            RegistryValue::Dword(n) => visitor.visit_bool(n != 0),
+            _ => self.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_option<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        // NB: Missing values are handled as missing fields.
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_newtype_struct<V>(
+        self,
+        _: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V>(
+        self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        match self.0 {
+            RegistryValue::Sz(string) => {
+                visitor.visit_enum(into_string(string)?.into_deserializer())
+            }
+            RegistryValue::Binary(data) => {
+                serde_json::from_slice::<Value>(&data)?.deserialize_enum(name, variants, visitor)
+            }
+            _ => self.deserialize_any(visitor),
+        }
+    }
+
+    forward_to_deserialize_any! {
+        i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf unit unit_struct seq tuple
+        tuple_struct map struct identifier ignored_any
+    }
+}
+
+fn into_string(string: OsString) -> Result<String, serde_json::Error> {
+    string
+        .into_string()
+        .map_err(|_| de::Error::custom("Registry string is not valid UTF-8"))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ffi::OsString;
+
+    use serde::{Deserialize, Serialize};
+
+    use super::{decode, encode, RegistryValue};
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    enum Theme {
+        Light,
+        Dark,
+        Custom(String),
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Settings {
+        enabled: bool,
+        small: u32,
+        large: u64,
+        negative: i32,
+        ratio: f64,
+        name: String,
+        theme: Theme,
+        custom: Theme,
+        paths: Vec<String>,
+        missing: Option<u32>,
+        present: Option<String>,
+    }
+
+    fn settings() -> Settings {
+        Settings {
+            enabled: true,
+            small: 42,
+            large: 1 << 40,
+            negative: -5,
+            ratio: 1.5,
+            name: String::from("winctx"),
+            theme: Theme::Dark,
+            custom: Theme::Custom(String::from("solarized")),
+            paths: vec![String::from("a"), String::from("b")],
+            missing: None,
+            present: Some(String::from("here")),
+        }
+    }
+
+    fn sz(string: &str) -> RegistryValue {
+        RegistryValue::Sz(OsString::from(string))
+    }
+
+    fn json(json: &str) -> RegistryValue {
+        RegistryValue::Binary(json.as_bytes().to_vec())
+    }
+
+    /// Store values as they would be in the registry, where `None` means the
+    /// value is removed.
+    fn store(values: Vec<(String, Option<RegistryValue>)>) -> Vec<(String, RegistryValue)> {
+        values
+            .into_iter()
+            .filter_map(|(name, value)| Some((name, value?)))
+            .collect()
+    }
+
+    #[test]
+    fn encode_values() {
+        let mut values = encode(&settings()).unwrap();
+
+        let expected = [
+            ("enabled", Some(RegistryValue::Dword(1))),
+            ("small", Some(RegistryValue::Dword(42))),
+            ("large", Some(RegistryValue::Qword(1 << 40))),
+            ("negative", Some(json("-5"))),
+            ("ratio", Some(json("1.5"))),
+            ("name", Some(sz("winctx"))),
+            ("theme", Some(sz("Dark"))),
+            ("custom", Some(json(r#"{"Custom":"solarized"}"#))),
+            ("paths", Some(json(r#"["a","b"]"#))),
+            ("missing", None),
+            ("present", Some(sz("here"))),
+        ];
+
+        let mut expected = expected
+            .into_iter()
+            .map(|(name, value)| (name.to_owned(), value))
+            .collect::<Vec<_>>();
+
+        // NB: Fields are not necessarily encoded in declaration order.
+        values.sort_by(|a, b| a.0.cmp(&b.0));
+        expected.sort_by(|a, b| a.0.cmp(&b.0));
+        assert_eq!(values, expected);
+    }
+
+    #[test]
+    fn round_trip() {
+        let values = store(encode(&settings()).unwrap());
+        assert!(values.iter().all(|(name, _)| name != "missing"));
+
+        let decoded = decode::<Settings>(values).unwrap();
+        assert_eq!(decoded, settings());
+    }
+
+    #[test]
+    fn boundaries() {
+        #[derive(Debug, PartialEq, Serialize, Deserialize)]
+        struct Numbers {
+            max_dword: u64,
+            min_qword: u64,
+            max_qword: u64,
+            zero: bool,
+        }
+
+        let numbers = Numbers {
+            max_dword: u64::from(u32::MAX),
+            min_qword: u64::from(u32::MAX) + 1,
+            max_qword: u64::MAX,
+            zero: false,
+        };
+
+        let values = store(encode(&numbers).unwrap());
+
+        let value = |name: &str| {
+            let (_, value) = values.iter().find(|(n, _)| n == name).unwrap();
+            value.clone()
+        };
+
+        assert_eq!(value("max_dword"), RegistryValue::Dword(u32::MAX));
+        assert_eq!(value("min_qword"), RegistryValue::Qword(1 << 32));
+        assert_eq!(value("max_qword"), RegistryValue::Qword(u64::MAX));
+        assert_eq!(value("zero"), RegistryValue::Dword(0));
+        assert_eq!(decode::<Numbers>(values).unwrap(), numbers);
+    }
+
+    #[test]
+    fn decode_registry_values() {
+        #[derive(Debug, PartialEq, Deserialize)]
+        struct Values {
+            flag: bool,
+            expand: String,
+            multi: Vec<String>,
+            theme: Theme,
+            optional: Option<u32>,
+        }
+
+        let values = vec![
+            (String::from("flag"), RegistryValue::Dword(2)),
+            (
+                String::from("expand"),
+                RegistryValue::ExpandSz("%HOME%".into()),
+            ),
+            (
+                String::from("multi"),
+                RegistryValue::MultiSz(vec!["a".into(), "b".into()]),
+            ),
+            (String::from("theme"), sz("Light")),
+            (String::from("optional"), RegistryValue::Dword(7)),
+        ];
+
+        let expected = Values {
+            flag: true,
+            expand: String::from("%HOME%"),
+            multi: vec![String::from("a"), String::from("b")],
+            theme: Theme::Light,
+            optional: Some(7),
+        };
+
+        assert_eq!(decode::<Values>(values).unwrap(), expected);
+    }
+
+    #[test]
+    fn missing_optional_values() {
+        #[derive(Debug, PartialEq, Deserialize)]
+        struct Values {
+            optional: Option<u32>,
+        }
+
+        let values = decode::<Values>(Vec::new()).unwrap();
+        assert_eq!(values, Values { optional: None });
+    }
+
+    #[test]
+    fn invalid() {
+        #[derive(Debug, Deserialize)]
+        #[allow(unused)]
+        struct Values {
+            value: u32,
+        }
+
+        assert!(encode(&42u32).is_err());
+        assert!(decode::<Values>(Vec::new()).is_err());
+        assert!(decode::<Values>(vec![(String::from("value"), sz("42"))]).is_err());
+
+        let other = RegistryValue::Other(crate::registry::ValueType::None, Vec::new());
+        assert!(decode::<Values>(vec![(String::from("value"), other)]).is_err());
+    }
+
+    #[test]
+    fn non_utf8_strings() {
+        #[derive(Debug, Deserialize)]
+        #[allow(unused)]
+        struct Values {
+            value: String,
+        }
+
+        #[cfg(windows)]
+        let string = {
+            use std::os::windows::ffi::OsStringExt;
+            OsString::from_wide(&[0xd800])
+        };
+
+        #[cfg(unix)]
+        let string = {
+            use std::os::unix::ffi::OsStringExt;
+            OsString::from_vec(vec![0xff])
+        };
+
+        let error =
+            decode::<Values>(vec![(String::from("value"), RegistryValue::Sz(string))]).unwrap_err();
+        assert!(error.to_string().contains("not valid UTF-8"));
+    }
+}
@@ -0,0 +1,15 @@
+use super::ValueType;
+
+/// Information about a registry value, as returned by
+/// [`RegistryKey::value_info`].
+///
+/// [`RegistryKey::value_info`]: super::RegistryKey::value_info
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct ValueInfo {
+    /// The type of the value.
+    pub value_type: ValueType,
+    /// The length of the value in bytes, including the null terminator of
+    /// strings if it was stored.
+    pub len: usize,
+}
@@ -0,0 +1,64 @@
+use windows_sys::Win32::System::Registry as winreg;
+
+/// The type of a registry value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ValueType {
+    /// No defined value type (`REG_NONE`).
+    None,
+    /// A null-terminated string (`REG_SZ`).
+    Sz,
+    /// A null-terminated string which contains unexpanded references to
+    /// environment variables (`REG_EXPAND_SZ`).
+    ExpandSz,
+    /// Binary data in any form (`REG_BINARY`).
+    Binary,
+    /// A 32-bit little-endian number (`REG_DWORD`).
+    Dword,
+    /// A 32-bit big-endian number (`REG_DWORD_BIG_ENDIAN`).
+    DwordBigEndian,
+    /// A symbolic link to another key (`REG_LINK`).
+    Link,
+    /// A sequence of null-terminated strings, terminated by an empty string
+    /// (`REG_MULTI_SZ`).
+    MultiSz,
+    /// A 64-bit little-endian number (`REG_QWORD`).
+    Qword,
+    /// A value type which is not known by winctx, such as the resource
+    /// descriptors used by drivers.
+    Other(u32),
+}
+
+impl ValueType {
+    /// Convert from a raw value type.
+    pub(crate) fn from_raw(ty: u32) -> Self {
+        match ty {
+            winreg::REG_NONE => Self::None,
+            winreg::REG_SZ => Self::Sz,
+            winreg::REG_EXPAND_SZ => Self::ExpandSz,
+            winreg::REG_BINARY => Self::Binary,
+            winreg::REG_DWORD => Self::Dword,
+            winreg::REG_DWORD_BIG_ENDIAN => Self::DwordBigEndian,
+            winreg::REG_LINK => Self::Link,
+            winreg::REG_MULTI_SZ => Self::MultiSz,
+            winreg::REG_QWORD => Self::Qword,
+            ty => Self::Other(ty),
+        }
+    }
+
+    /// Convert into a raw value type.
+    pub(crate) fn into_raw(self) -> u32 {
+        match self {
+            Self::None => winreg::REG_NONE,
+            Self::Sz => winreg::REG_SZ,
+            Self::ExpandSz => winreg::REG_EXPAND_SZ,
+            Self::Binary => winreg::REG_BINARY,
+            Self::Dword => winreg::REG_DWORD,
+            Self::DwordBigEndian => winreg::REG_DWORD_BIG_ENDIAN,
+            Self::Link => winreg::REG_LINK,
+            Self::MultiSz => winreg::REG_MULTI_SZ,
+            Self::Qword => winreg::REG_QWORD,
+            Self::Other(ty) => ty,
+        }
+    }
+}
@@ -1,18 +1,48 @@
 //! Types related to modifying the window context.
 
+use std::any::Any;
 use std::fmt;
+use std::io;
 use std::sync::atomic::AtomicU32;
 use std::sync::Arc;
+use std::time::{Duration, SystemTime};
 
-use tokio::sync::mpsc;
+use tokio::sync::{mpsc, oneshot};
+use windows_sys::Win32::Foundation::HWND;
 
+use crate::area::{Area, Rect};
+use crate::error::Error;
+use crate::error::ErrorRepr::*;
 use crate::icon::StockIcon;
-use crate::notification::NotificationIcon;
-use crate::{AreaId, IconId, ItemId, ModifyArea, ModifyMenuItem, Notification, NotificationId};
+use crate::menu_item::{MenuItem, MenuItemKind};
+use crate::notification::{NotificationIcon, Progress, UpdateNotification};
+use crate::window::Window;
+use crate::{
+    AreaId, GroupId, IconId, ItemId, ModifyArea, ModifyMenuItem, Notification, NotificationId,
+    PopupMenu, Result, WindowDescription,
+};
+use crate::{Attention, Badge, NotificationSound, TaskbarState};
 
 #[derive(Debug)]
 pub(super) enum InputEvent {
+    /// Process the input and report its outcome through the channel.
+    Await {
+        input: Box<InputEvent>,
+        tx: oneshot::Sender<Result<()>>,
+    },
+    /// Process several inputs at once.
+    Batch {
+        inputs: Vec<InputEvent>,
+    },
     Shutdown,
+    ScheduleShutdown {
+        duration: Duration,
+    },
+    CancelScheduledShutdown,
+    Describe {
+        redact: bool,
+        tx: oneshot::Sender<WindowDescription>,
+    },
     ModifyArea {
         area_id: AreaId,
         modify: ModifyArea,
@@ -21,34 +51,209 @@ pub(super) enum InputEvent {
         item_id: ItemId,
         modify: ModifyMenuItem,
     },
+    ModifyGroup {
+        group_id: GroupId,
+        modify: ModifyMenuItem,
+    },
     Notification {
         area_id: AreaId,
         notification_id: NotificationId,
         notification: Notification,
     },
+    DismissNotification {
+        notification_id: NotificationId,
+    },
+    RemoveFromCenter {
+        notification_id: NotificationId,
+    },
+    UpdateNotification {
+        notification_id: NotificationId,
+        update: UpdateNotification,
+    },
+    InsertMenuItem {
+        position: u32,
+        item: MenuItem,
+    },
+    RemoveMenuItem {
+        item_id: ItemId,
+    },
+    AreaRect {
+        area_id: AreaId,
+        tx: oneshot::Sender<io::Result<Rect>>,
+    },
+    AddArea {
+        area: Area,
+    },
+    RemoveArea {
+        area_id: AreaId,
+    },
+    OpenMenu {
+        area_id: AreaId,
+        position: Option<(i32, i32)>,
+    },
+    CopyData {
+        hwnd: HWND,
+        ty: usize,
+        bytes: Vec<u8>,
+    },
+    BlockShutdown {
+        reason: String,
+    },
+    UnblockShutdown,
+    TaskbarProgress {
+        value: f32,
+        state: TaskbarState,
+    },
+    RequestAttention {
+        attention: Attention,
+    },
+    TaskbarBadge {
+        badge: Option<Badge>,
+    },
+    Show {
+        show: bool,
+    },
+    SetTimer {
+        id: u16,
+        period: Duration,
+    },
+    CancelTimer {
+        id: u16,
+    },
 }
 
 struct Inner {
     notifications: AtomicU32,
+    menu_items: AtomicU32,
+    areas: AtomicU32,
     tx: mpsc::UnboundedSender<InputEvent>,
+    window: Window,
+}
+
+impl Inner {
+    fn send(&self, input: InputEvent) -> Result<()> {
+        send(&self.tx, input)
+    }
 }
 
 /// Handle used to interact with the system integration.
+///
+/// Requests sent through the handle are processed by the [`EventLoop`], and
+/// return an error if it has shut down. See [`Sender::is_closed`].
+///
+/// [`EventLoop`]: crate::EventLoop
 #[derive(Clone)]
 pub struct Sender {
     inner: Arc<Inner>,
 }
 
 impl Sender {
-    pub(crate) fn new(tx: mpsc::UnboundedSender<InputEvent>) -> Self {
+    pub(crate) fn new(
+        tx: mpsc::UnboundedSender<InputEvent>,
+        menu_items: u32,
+        areas: u32,
+        window: Window,
+    ) -> Self {
         Self {
             inner: Arc::new(Inner {
                 notifications: AtomicU32::new(0),
+                menu_items: AtomicU32::new(menu_items),
+                areas: AtomicU32::new(areas),
                 tx,
+                window,
             }),
         }
     }
 
+    /// Get a handle to the window which was built.
+    ///
+    /// The window is hidden unless it was built using
+    /// [`CreateWindow::visible`], and only exists while the event loop is
+    /// alive. To pass the window to other crates which need a parent window,
+    /// such as native dialogs, borrow it from the [`EventLoop`] instead.
+    ///
+    /// [`CreateWindow::visible`]: crate::CreateWindow::visible
+    /// [`EventLoop`]: crate::EventLoop
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use winctx::CreateWindow;
+    ///
+    /// # async fn test() -> winctx::Result<()> {
+    /// let (sender, _) = CreateWindow::new("se.tedro.Example").build().await?;
+    /// let window = sender.window();
+    /// # Ok(()) }
+    /// ```
+    pub fn window(&self) -> &Window {
+        &self.inner.window
+    }
+
+    /// Test if the event loop has shut down.
+    ///
+    /// Once closed, anything sent through the sender returns an error, so
+    /// long-lived components holding on to a sender can use this to detect
+    /// that it's no longer useful.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use winctx::CreateWindow;
+    ///
+    /// # async fn test() -> winctx::Result<()> {
+    /// let (sender, mut event_loop) = CreateWindow::new("se.tedro.Example").build().await?;
+    ///
+    /// sender.shutdown()?;
+    /// event_loop.tick().await?;
+    ///
+    /// assert!(sender.is_closed());
+    /// assert!(sender.shutdown().is_err());
+    /// # Ok(()) }
+    /// ```
+    pub fn is_closed(&self) -> bool {
+        self.inner.tx.is_closed()
+    }
+
+    /// Start a batch of modifications which are applied together.
+    ///
+    /// Modifications to the same area are merged, so that for example
+    /// changing both the icon and the tooltip of an area doesn't result in an
+    /// intermediate state where only the icon has changed. The batch is sent
+    /// as a single request using [`BatchBuilder::send`], and is checked before
+    /// any of it is applied. See [`BatchBuilder::send_await`].
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use winctx::CreateWindow;
+    ///
+    /// # macro_rules! include_bytes { ($path:literal) => { &[] } }
+    /// const ICON: &[u8] = include_bytes!("tokio.ico");
+    ///
+    /// # async fn test() -> winctx::Result<()> {
+    /// let mut window = CreateWindow::new("se.tedro.Example");
+    /// let busy = window.icons().insert_buffer(ICON, 22, 22);
+    /// let area = window.new_area();
+    /// let area_id = area.id();
+    /// let upload = area.popup_menu().push_entry("Upload").id();
+    ///
+    /// let (sender, _) = window.build().await?;
+    ///
+    /// sender
+    ///     .batch()
+    ///     .push(sender.modify_area(area_id).icon(busy))
+    ///     .push(sender.modify_area(area_id).tooltip("Uploading"))
+    ///     .push(sender.modify_menu_item(upload).enabled(false))
+    ///     .send()?;
+    /// # Ok(()) }
+    /// ```
+    pub fn batch(&self) -> BatchBuilder<'_> {
+        BatchBuilder {
+            tx: &self.inner.tx,
+            inputs: Vec::new(),
+        }
+    }
+
     /// Start a modify area request.
     ///
     /// This needs to be send using [`ModifyAreaBuilder::send`] to actually
@@ -70,6 +275,198 @@ impl Sender {
         }
     }
 
+    /// Modify every menu item in a group.
+    ///
+    /// Groups are constructed using [`PopupMenu::group`].
+    ///
+    /// [`PopupMenu::group`]: crate::PopupMenu::group
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use winctx::CreateWindow;
+    ///
+    /// # async fn test() -> winctx::Result<()> {
+    /// let mut window = CreateWindow::new("se.tedro.Example");
+    /// let menu = window.new_area().popup_menu();
+    ///
+    /// let transfers = menu.group("transfers");
+    /// menu.push_entry("Upload").group(transfers);
+    /// menu.push_entry("Download").group(transfers);
+    ///
+    /// let (sender, _) = window.build().await?;
+    ///
+    /// sender.modify_group(transfers).enabled(false).send()?;
+    /// # Ok(()) }
+    /// ```
+    pub fn modify_group(&self, group_id: GroupId) -> ModifyGroupBuilder<'_> {
+        ModifyGroupBuilder {
+            tx: &self.inner.tx,
+            group_id,
+            modify: ModifyMenuItem::default(),
+        }
+    }
+
+    /// Insert a menu entry into the popup menu of the given area.
+    ///
+    /// The entry is inserted before the item currently at `position`, or
+    /// appended if `position` is past the end of the menu. The identifier
+    /// returned by [`InsertMenuItemBuilder::send`] stays the same regardless of
+    /// other items being inserted or removed.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use winctx::CreateWindow;
+    ///
+    /// # async fn test() -> winctx::Result<()> {
+    /// let mut window = CreateWindow::new("se.tedro.Example");
+    /// let area = window.new_area();
+    /// let area_id = area.id();
+    ///
+    /// let menu = area.popup_menu();
+    /// menu.push_separator();
+    /// menu.push_entry("Quit");
+    ///
+    /// let (sender, _) = window.build().await?;
+    ///
+    /// let recent = sender.insert_menu_item(area_id, 0, "recent.txt").send()?;
+    /// sender.remove_menu_item(recent)?;
+    /// # Ok(()) }
+    /// ```
+    pub fn insert_menu_item<T>(
+        &self,
+        area_id: AreaId,
+        position: u32,
+        text: T,
+    ) -> InsertMenuItemBuilder<'_>
+    where
+        T: fmt::Display,
+    {
+        self.insert(
+            area_id,
+            position,
+            MenuItemKind::String {
+                text: text.to_string(),
+            },
+        )
+    }
+
+    /// Insert a menu separator into the popup menu of the given area.
+    ///
+    /// See [`Sender::insert_menu_item`] for how `position` is interpreted.
+    pub fn insert_menu_separator(
+        &self,
+        area_id: AreaId,
+        position: u32,
+    ) -> InsertMenuItemBuilder<'_> {
+        self.insert(area_id, position, MenuItemKind::Separator)
+    }
+
+    fn insert(
+        &self,
+        area_id: AreaId,
+        position: u32,
+        kind: MenuItemKind,
+    ) -> InsertMenuItemBuilder<'_> {
+        let id = self
+            .inner
+            .menu_items
+            .fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+
+        InsertMenuItemBuilder {
+            tx: &self.inner.tx,
+            position,
+            item: MenuItem::new(ItemId::new(area_id.id(), id), kind),
+        }
+    }
+
+    /// Remove a menu item from its popup menu.
+    ///
+    /// This can be used both with menu items inserted using
+    /// [`Sender::insert_menu_item`] and those pushed when constructing the
+    /// window.
+    pub fn remove_menu_item(&self, item_id: ItemId) -> Result<()> {
+        self.inner.send(InputEvent::RemoveMenuItem { item_id })
+    }
+
+    /// Add a new notification area to the window.
+    ///
+    /// The area is configured through the returned builder in the same way as
+    /// areas added with [`CreateWindow::new_area`], and is added once
+    /// [`NewAreaBuilder::send`] is called.
+    ///
+    /// [`CreateWindow::new_area`]: crate::CreateWindow::new_area
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use winctx::CreateWindow;
+    ///
+    /// # async fn test() -> winctx::Result<()> {
+    /// let window = CreateWindow::new("se.tedro.Example");
+    /// let (sender, _) = window.build().await?;
+    ///
+    /// let mut area = sender.new_area();
+    /// area.tooltip("john@example.com");
+    /// area.popup_menu().push_entry("Sign out");
+    /// let area_id = area.send()?;
+    ///
+    /// sender.remove_area(area_id)?;
+    /// # Ok(()) }
+    /// ```
+    pub fn new_area(&self) -> NewAreaBuilder<'_> {
+        let id = self
+            .inner
+            .areas
+            .fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+
+        NewAreaBuilder {
+            tx: &self.inner.tx,
+            menu_items: &self.inner.menu_items,
+            area: Area::new(AreaId::new(id)),
+        }
+    }
+
+    /// Remove a notification area from the window.
+    ///
+    /// This can be used both with areas added using [`Sender::new_area`] and
+    /// those added when constructing the window.
+    pub fn remove_area(&self, area_id: AreaId) -> Result<()> {
+        self.inner.send(InputEvent::RemoveArea { area_id })
+    }
+
+    /// Open the popup menu of the given area.
+    ///
+    /// By default the menu is opened next to the icon of the area, which can
+    /// be changed using [`OpenMenuBuilder::position`]. Unlike when the icon is
+    /// clicked, the menu is opened regardless of which mouse buttons are
+    /// configured to open it.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use winctx::CreateWindow;
+    ///
+    /// # async fn test() -> winctx::Result<()> {
+    /// let mut window = CreateWindow::new("se.tedro.Example");
+    /// let area = window.new_area();
+    /// let area_id = area.id();
+    /// area.popup_menu().push_entry("Quit");
+    ///
+    /// let (sender, _) = window.build().await?;
+    ///
+    /// sender.open_menu(area_id).send()?;
+    /// # Ok(()) }
+    /// ```
+    pub fn open_menu(&self, area_id: AreaId) -> OpenMenuBuilder<'_> {
+        OpenMenuBuilder {
+            tx: &self.inner.tx,
+            area_id,
+            position: None,
+        }
+    }
+
     /// Send the given notification.
     pub fn notification(&self, area_id: AreaId) -> NotificationBuilder<'_> {
         let id = self
@@ -85,72 +482,1205 @@ impl Sender {
         }
     }
 
-    /// Cause the window to shut down.
-    pub fn shutdown(&self) {
-        _ = self.inner.tx.send(InputEvent::Shutdown);
+    /// Update the contents of a notification which was sent using
+    /// [`Sender::notification`].
+    ///
+    /// If the notification is queued the update is applied before it's shown,
+    /// and if it's visible it's shown again with the updated contents without
+    /// playing a sound. Nothing happens if the notification has already been
+    /// clicked or dismissed.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use winctx::CreateWindow;
+    ///
+    /// # async fn test() -> winctx::Result<()> {
+    /// let mut window = CreateWindow::new("se.tedro.Example");
+    /// let area = window.new_area().id();
+    ///
+    /// let (sender, _) = window.build().await?;
+    ///
+    /// let id = sender.notification(area)
+    ///     .title("Downloading")
+    ///     .progress("update.zip", 0.0, "Starting")
+    ///     .send()?;
+    ///
+    /// sender.update_notification(id)
+    ///     .progress("update.zip", 0.5, "12 MB of 24 MB")
+    ///     .send()?;
+    /// # Ok(()) }
+    /// ```
+    pub fn update_notification(
+        &self,
+        notification_id: NotificationId,
+    ) -> UpdateNotificationBuilder<'_> {
+        UpdateNotificationBuilder {
+            tx: &self.inner.tx,
+            notification_id,
+            update: UpdateNotification::default(),
+        }
+    }
+
+    /// Dismiss a notification which was sent using [`Sender::notification`].
+    ///
+    /// If the notification is queued behind another one it's removed from the
+    /// queue, and if it's currently visible it's removed from the screen,
+    /// allowing the next queued notification to be shown. In both cases
+    /// [`Event::NotificationDismissed`] is emitted for it. Nothing happens if
+    /// the notification has already been clicked or dismissed.
+    ///
+    /// [`Event::NotificationDismissed`]: crate::Event::NotificationDismissed
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use winctx::CreateWindow;
+    ///
+    /// # async fn test() -> winctx::Result<()> {
+    /// let mut window = CreateWindow::new("se.tedro.Example");
+    /// let area = window.new_area().id();
+    ///
+    /// let (sender, _) = window.build().await?;
+    ///
+    /// let id = sender.notification(area)
+    ///     .message("Uploading...")
+    ///     .send()?;
+    ///
+    /// sender.dismiss_notification(id)?;
+    /// # Ok(()) }
+    /// ```
+    pub fn dismiss_notification(&self, notification_id: NotificationId) -> Result<()> {
+        self.inner
+            .send(InputEvent::DismissNotification { notification_id })
+    }
+
+    /// Remove a notification from the notification center, such as one which
+    /// was sent with [`NotificationBuilder::persist`] once the condition it
+    /// reports has cleared.
+    ///
+    /// Balloon notifications are never retained by the notification center,
+    /// so this currently emits an [`Event::Error`] associated with the
+    /// notification to indicate that it is unsupported.
+    ///
+    /// [`Event::Error`]: crate::Event::Error
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use winctx::CreateWindow;
+    ///
+    /// # async fn test() -> winctx::Result<()> {
+    /// let mut window = CreateWindow::new("se.tedro.Example");
+    /// let area = window.new_area().id();
+    ///
+    /// let (sender, _) = window.build().await?;
+    ///
+    /// let id = sender.notification(area)
+    ///     .message("Disk is almost full")
+    ///     .persist(true)
+    ///     .send()?;
+    ///
+    /// sender.remove_from_center(id)?;
+    /// # Ok(()) }
+    /// ```
+    pub fn remove_from_center(&self, notification_id: NotificationId) -> Result<()> {
+        self.inner
+            .send(InputEvent::RemoveFromCenter { notification_id })
+    }
+
+    /// Copy bytes to another window, such as a sibling process.
+    ///
+    /// The data is sent from the thread running the window, with the window
+    /// passed along as the sender. If the target is a window constructed by
+    /// winctx it is received as an [`Event::CopyData`] event. Failing to copy
+    /// the data is reported through [`Event::Error`].
+    ///
+    /// [`Event::CopyData`]: crate::Event::CopyData
+    /// [`Event::Error`]: crate::Event::Error
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use winctx::CreateWindow;
+    /// use winctx::window::FindWindow;
+    ///
+    /// # async fn test() -> winctx::Result<()> {
+    /// let (sender, _) = CreateWindow::new("se.tedro.Example").build().await?;
+    ///
+    /// if let Ok(Some(window)) = FindWindow::new().class("se.tedro.Sibling").find() {
+    ///     sender.copy_data_to(&window, 42, b"foobar")?;
+    /// }
+    /// # Ok(()) }
+    /// ```
+    pub fn copy_data_to(&self, target: &Window, ty: usize, bytes: &[u8]) -> Result<()> {
+        self.inner.send(InputEvent::CopyData {
+            hwnd: target.hwnd(),
+            ty,
+            bytes: bytes.to_vec(),
+        })
+    }
+
+    /// Cause the window to shut down.
+    pub fn shutdown(&self) -> Result<()> {
+        self.inner.send(InputEvent::Shutdown)
+    }
+
+    /// Cause the window to shut down after the given duration has elapsed.
+    ///
+    /// The shutdown is driven by a timer on the window thread and goes through
+    /// the same path as [`Sender::shutdown`]. Scheduling a new shutdown
+    /// replaces any previously scheduled one.
+    ///
+    /// Once scheduled, the event loop emits [`Event::ShutdownScheduled`].
+    ///
+    /// [`Event::ShutdownScheduled`]: crate::Event::ShutdownScheduled
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use std::time::Duration;
+    ///
+    /// use winctx::CreateWindow;
+    ///
+    /// # async fn test() -> winctx::Result<()> {
+    /// let window = CreateWindow::new("se.tedro.Example");
+    /// let (sender, _) = window.build().await?;
+    ///
+    /// sender.shutdown_after(Duration::from_secs(60 * 60))?;
+    /// # Ok(()) }
+    /// ```
+    pub fn shutdown_after(&self, duration: Duration) -> Result<()> {
+        self.inner.send(InputEvent::ScheduleShutdown { duration })
+    }
+
+    /// Cancel a shutdown scheduled with [`Sender::shutdown_after`].
+    ///
+    /// If a shutdown was scheduled, the event loop emits
+    /// [`Event::ShutdownCancelled`].
+    ///
+    /// [`Event::ShutdownCancelled`]: crate::Event::ShutdownCancelled
+    pub fn cancel_scheduled_shutdown(&self) -> Result<()> {
+        self.inner.send(InputEvent::CancelScheduledShutdown)
+    }
+
+    /// Post a raw message to the window.
+    ///
+    /// The message is handled on the window thread. Messages which winctx
+    /// doesn't handle itself can be intercepted using
+    /// [`CreateWindow::message_filter`], otherwise they are passed on to the
+    /// default window procedure.
+    ///
+    /// [`CreateWindow::message_filter`]: crate::CreateWindow::message_filter
+    ///
+    /// # Safety
+    ///
+    /// The window uses messages in the range `WM_USER..WM_APP` internally,
+    /// some of which carry pointers. The caller must ensure that the message
+    /// isn't one of these, and that any other message is valid for the given
+    /// parameters.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use winctx::CreateWindow;
+    ///
+    /// const WM_APP: u32 = 0x8000;
+    ///
+    /// # async fn test() -> winctx::Result<()> {
+    /// let (sender, _) = CreateWindow::new("se.tedro.Example").build().await?;
+    ///
+    /// // SAFETY: The message is not used by winctx.
+    /// unsafe {
+    ///     sender.post_raw(WM_APP + 1, 0, 0)?;
+    /// }
+    /// # Ok(()) }
+    /// ```
+    pub unsafe fn post_raw(&self, msg: u32, wparam: usize, lparam: isize) -> Result<()> {
+        self.inner
+            .window
+            .post_message(msg, wparam, lparam)
+            .map_err(PostMessage)?;
+        Ok(())
+    }
+
+    /// Start a timer which elapses periodically, emitting [`Event::Timer`]
+    /// with the given identifier each time.
+    ///
+    /// The timer runs on the window thread until it's cancelled using
+    /// [`Sender::cancel_timer`]. Setting a timer with an identifier which is
+    /// already in use replaces it.
+    ///
+    /// [`Event::Timer`]: crate::Event::Timer
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use std::time::Duration;
+    ///
+    /// use winctx::{CreateWindow, Event};
+    ///
+    /// const REFRESH: u16 = 1;
+    ///
+    /// # async fn test() -> winctx::Result<()> {
+    /// let (sender, mut event_loop) = CreateWindow::new("se.tedro.Example").build().await?;
+    ///
+    /// sender.set_timer(REFRESH, Duration::from_secs(10))?;
+    ///
+    /// loop {
+    ///     if let Event::Timer { id: REFRESH } = event_loop.tick().await? {
+    ///         /* refresh */
+    ///     }
+    /// }
+    /// # Ok(()) }
+    /// ```
+    pub fn set_timer(&self, id: u16, period: Duration) -> Result<()> {
+        self.inner.send(InputEvent::SetTimer { id, period })
+    }
+
+    /// Cancel a timer started using [`Sender::set_timer`].
+    pub fn cancel_timer(&self, id: u16) -> Result<()> {
+        self.inner.send(InputEvent::CancelTimer { id })
+    }
+
+    /// Block the session from ending, such as when the system is shutting down
+    /// or the user is logging off.
+    ///
+    /// The reason is shown to the user while the session is blocked, and the
+    /// event loop emits [`Event::EndSession`] when the session is about to
+    /// end. Calling this again replaces the reason. Note that the session
+    /// can't be blocked from ending forcibly.
+    ///
+    /// [`Event::EndSession`]: crate::Event::EndSession
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use winctx::{CreateWindow, Event};
+    ///
+    /// # async fn test() -> winctx::Result<()> {
+    /// let (sender, mut event_loop) = CreateWindow::new("se.tedro.Example").build().await?;
+    ///
+    /// sender.block_shutdown("Saving changes")?;
+    ///
+    /// loop {
+    ///     if let Event::EndSession { .. } = event_loop.tick().await? {
+    ///         /* flush state */
+    ///         sender.unblock_shutdown()?;
+    ///     }
+    /// }
+    /// # Ok(()) }
+    /// ```
+    pub fn block_shutdown<R>(&self, reason: R) -> Result<()>
+    where
+        R: fmt::Display,
+    {
+        self.inner.send(InputEvent::BlockShutdown {
+            reason: reason.to_string(),
+        })
+    }
+
+    /// Stop blocking the session from ending after it was blocked using
+    /// [`Sender::block_shutdown`].
+    pub fn unblock_shutdown(&self) -> Result<()> {
+        self.inner.send(InputEvent::UnblockShutdown)
+    }
+
+    /// Set the progress shown on the taskbar button of the window, where the
+    /// value is between `0.0` and `1.0`.
+    ///
+    /// This only has an effect if the window has a taskbar button, which
+    /// requires it to be visible. The value is ignored for
+    /// [`TaskbarState::None`] and [`TaskbarState::Indeterminate`].
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use winctx::{CreateWindow, TaskbarState};
+    ///
+    /// # async fn test() -> winctx::Result<()> {
+    /// let (sender, _) = CreateWindow::new("se.tedro.Example").build().await?;
+    ///
+    /// sender.set_taskbar_progress(0.5, TaskbarState::Normal)?;
+    /// /* once done */
+    /// sender.set_taskbar_progress(0.0, TaskbarState::None)?;
+    /// # Ok(()) }
+    /// ```
+    pub fn set_taskbar_progress(&self, value: f32, state: TaskbarState) -> Result<()> {
+        self.inner
+            .send(InputEvent::TaskbarProgress { value, state })
+    }
+
+    /// Set or clear a badge shown over the taskbar button of the window.
+    ///
+    /// This only has an effect if the window has a taskbar button, which
+    /// requires it to be visible. To show a badge on a notification area, use
+    /// [`ModifyAreaBuilder::badge`].
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use winctx::{Badge, CreateWindow};
+    ///
+    /// # async fn test() -> winctx::Result<()> {
+    /// let (sender, _) = CreateWindow::new("se.tedro.Example")
+    ///     .visible(400, 300)
+    ///     .build()
+    ///     .await?;
+    ///
+    /// sender.set_taskbar_badge(Some(Badge::Dot))?;
+    /// # Ok(()) }
+    /// ```
+    pub fn set_taskbar_badge(&self, badge: Option<Badge>) -> Result<()> {
+        self.inner.send(InputEvent::TaskbarBadge { badge })
+    }
+
+    /// Show the window created using [`CreateWindow::visible`], restoring it
+    /// if it's minimized and bringing it to the foreground.
+    ///
+    /// This has no effect on windows which aren't visible.
+    ///
+    /// [`CreateWindow::visible`]: crate::CreateWindow::visible
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use winctx::{CreateWindow, Event};
+    ///
+    /// # async fn test() -> winctx::Result<()> {
+    /// let mut window = CreateWindow::new("se.tedro.Example").visible(400, 300);
+    /// window.new_area();
+    ///
+    /// let (sender, mut event_loop) = window.build().await?;
+    ///
+    /// loop {
+    ///     match event_loop.tick().await? {
+    ///         Event::IconClicked { .. } => sender.show()?,
+    ///         Event::WindowClosed { .. } => sender.hide()?,
+    ///         _ => {}
+    ///     }
+    /// }
+    /// # Ok(()) }
+    /// ```
+    pub fn show(&self) -> Result<()> {
+        self.inner.send(InputEvent::Show { show: true })
+    }
+
+    /// Hide the window created using [`CreateWindow::visible`].
+    ///
+    /// This has no effect on windows which aren't visible.
+    ///
+    /// [`CreateWindow::visible`]: crate::CreateWindow::visible
+    pub fn hide(&self) -> Result<()> {
+        self.inner.send(InputEvent::Show { show: false })
+    }
+
+    /// Flash the window and its taskbar button to request the attention of
+    /// the user.
+    ///
+    /// This is a lighter alternative to a notification, but only has a
+    /// visible effect if the window has a taskbar button.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use winctx::{Attention, CreateWindow};
+    ///
+    /// # async fn test() -> winctx::Result<()> {
+    /// let (sender, _) = CreateWindow::new("se.tedro.Example").build().await?;
+    ///
+    /// sender.request_attention(Attention::UntilForeground)?;
+    /// # Ok(()) }
+    /// ```
+    pub fn request_attention(&self, attention: Attention) -> Result<()> {
+        self.inner.send(InputEvent::RequestAttention { attention })
+    }
+
+    /// Describe the current configuration of the window, including any
+    /// modifications made at runtime.
+    ///
+    /// If `redact` is set, text which might be sensitive such as tooltips and
+    /// the text of menu items is replaced.
+    ///
+    /// This resolves once the event loop has processed the request, so the
+    /// event loop has to be ticked concurrently. An error is returned if the
+    /// window has been closed.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use winctx::CreateWindow;
+    ///
+    /// # async fn test() -> winctx::Result<()> {
+    /// let mut window = CreateWindow::new("se.tedro.Example");
+    /// let area = window.new_area().id();
+    ///
+    /// let (sender, mut event_loop) = window.build().await?;
+    /// sender.modify_area(area).tooltip("Updated").send()?;
+    ///
+    /// let description = tokio::select! {
+    ///     description = sender.describe(true) => description?,
+    ///     event = event_loop.tick() => panic!("unexpected event: {:?}", event?),
+    /// };
+    ///
+    /// assert_eq!(description.areas.len(), 1);
+    /// # Ok(()) }
+    /// ```
+    pub async fn describe(&self, redact: bool) -> Result<WindowDescription> {
+        let (tx, rx) = oneshot::channel();
+
+        if self
+            .inner
+            .tx
+            .send(InputEvent::Describe { redact, tx })
+            .is_err()
+        {
+            return Err(Error::new(WindowClosed));
+        }
+
+        rx.await.map_err(|_| Error::new(WindowClosed))
+    }
+
+    /// Get the bounding rectangle of the icon of the given area in screen
+    /// coordinates.
+    ///
+    /// This can be used to position custom popup windows next to the icon.
+    /// Like [`Sender::describe`], this resolves once the event loop has
+    /// processed the request.
+    ///
+    /// An error is returned if the window has been closed or if the location
+    /// of the icon can't be determined, for example because it's hidden in the
+    /// notification overflow area.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use winctx::CreateWindow;
+    ///
+    /// # async fn test() -> winctx::Result<()> {
+    /// let mut window = CreateWindow::new("se.tedro.Example");
+    /// let area = window.new_area().id();
+    ///
+    /// let (sender, mut event_loop) = window.build().await?;
+    ///
+    /// let rect = tokio::select! {
+    ///     rect = sender.area_rect(area) => rect?,
+    ///     event = event_loop.tick() => panic!("unexpected event: {:?}", event?),
+    /// };
+    ///
+    /// println!("Icon is {}x{} pixels", rect.width(), rect.height());
+    /// # Ok(()) }
+    /// ```
+    pub async fn area_rect(&self, area_id: AreaId) -> Result<Rect> {
+        let (tx, rx) = oneshot::channel();
+
+        if self
+            .inner
+            .tx
+            .send(InputEvent::AreaRect { area_id, tx })
+            .is_err()
+        {
+            return Err(Error::new(WindowClosed));
+        }
+
+        let rect = rx.await.map_err(|_| Error::new(WindowClosed))?;
+        Ok(rect.map_err(AreaRect)?)
+    }
+}
+
+/// A builder returned by [`Sender::modify_area`].
+#[must_use = "Must call `send()` to apply changes"]
+pub struct ModifyAreaBuilder<'a> {
+    tx: &'a mpsc::UnboundedSender<InputEvent>,
+    area_id: AreaId,
+    modify: ModifyArea,
+}
+
+impl ModifyAreaBuilder<'_> {
+    /// Set the icon of the notification area.
+    pub fn icon(mut self, icon: IconId) -> Self {
+        self.modify.icon(icon);
+        self
+    }
+
+    /// Set the tooltip of the notification area.
+    pub fn tooltip<T>(mut self, tooltip: T) -> Self
+    where
+        T: fmt::Display,
+    {
+        self.modify.tooltip(tooltip);
+        self
+    }
+
+    /// Clear the icon of the notification area, leaving an empty space in
+    /// the notification tray.
+    ///
+    /// A badge set on the area is still drawn.
+    pub fn clear_icon(mut self) -> Self {
+        self.modify.clear_icon();
+        self
+    }
+
+    /// Clear the tooltip of the notification area.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use winctx::CreateWindow;
+    ///
+    /// # async fn test() -> winctx::Result<()> {
+    /// let mut window = CreateWindow::new("se.tedro.Example");
+    /// let area = window.new_area().tooltip("Uploading").id();
+    ///
+    /// let (sender, _) = window.build().await?;
+    ///
+    /// sender.modify_area(area).clear_tooltip().send()?;
+    /// # Ok(()) }
+    /// ```
+    pub fn clear_tooltip(mut self) -> Self {
+        self.modify.clear_tooltip();
+        self
+    }
+
+    /// Set whether the notification area is hidden.
+    ///
+    /// A hidden area is removed from the notification tray, but keeps its
+    /// icon, tooltip, and popup menu so that it can be shown again later.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use winctx::CreateWindow;
+    ///
+    /// # async fn test() -> winctx::Result<()> {
+    /// let mut window = CreateWindow::new("se.tedro.Example");
+    /// let area = window.new_area().id();
+    ///
+    /// let (sender, _) = window.build().await?;
+    ///
+    /// sender.modify_area(area).hidden(true).send()?;
+    /// # Ok(()) }
+    /// ```
+    pub fn hidden(mut self, hidden: bool) -> Self {
+        self.modify.hidden(hidden);
+        self
+    }
+
+    /// Set whether the standard tooltip is shown when hovering the icon.
+    ///
+    /// See [`Area::show_tooltip`].
+    ///
+    /// [`Area::show_tooltip`]: crate::area::Area::show_tooltip
+    pub fn show_tooltip(mut self, show_tooltip: bool) -> Self {
+        self.modify.show_tooltip(show_tooltip);
+        self
+    }
+
+    /// Set or clear a badge drawn in the corner of the icon, such as a count
+    /// of unread items.
+    ///
+    /// The badge is drawn on top of the current icon of the area, and is kept
+    /// when the icon is changed.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use winctx::{Badge, CreateWindow};
+    ///
+    /// # async fn test() -> winctx::Result<()> {
+    /// let mut window = CreateWindow::new("se.tedro.Example");
+    /// let area = window.new_area().id();
+    ///
+    /// let (sender, _) = window.build().await?;
+    ///
+    /// sender.modify_area(area).badge(Some(Badge::Count(3))).send()?;
+    /// /* once read */
+    /// sender.modify_area(area).badge(None).send()?;
+    /// # Ok(()) }
+    /// ```
+    pub fn badge(mut self, badge: Option<Badge>) -> Self {
+        self.modify.badge(badge);
+        self
+    }
+
+    /// Send the modification.
+    pub fn send(self) -> Result<()> {
+        send(
+            self.tx,
+            InputEvent::ModifyArea {
+                area_id: self.area_id,
+                modify: self.modify,
+            },
+        )
+    }
+
+    /// Send the modification and wait for it to be applied, returning the
+    /// outcome.
+    ///
+    /// Unlike [`ModifyAreaBuilder::send`], a failure is returned to the
+    /// caller instead of being raised by [`EventLoop::tick`], which must be
+    /// driven concurrently for this to complete.
+    ///
+    /// [`EventLoop::tick`]: crate::EventLoop::tick
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use winctx::CreateWindow;
+    ///
+    /// # async fn test() -> winctx::Result<()> {
+    /// let mut window = CreateWindow::new("se.tedro.Example");
+    /// let area = window.new_area().id();
+    ///
+    /// let (sender, mut event_loop) = window.build().await?;
+    ///
+    /// tokio::select! {
+    ///     result = sender.modify_area(area).tooltip("Updated").send_await() => {
+    ///         if let Err(error) = result {
+    ///             println!("Failed to update tooltip: {error}");
+    ///         }
+    ///     }
+    ///     event = event_loop.tick() => panic!("unexpected event: {:?}", event?),
+    /// }
+    /// # Ok(()) }
+    /// ```
+    pub async fn send_await(self) -> Result<()> {
+        let input = InputEvent::ModifyArea {
+            area_id: self.area_id,
+            modify: self.modify,
+        };
+
+        send_await(self.tx, input).await
+    }
+}
+
+/// A builder returned by [`Sender::new_area`].
+#[must_use = "Must call `send()` to add the area"]
+pub struct NewAreaBuilder<'a> {
+    tx: &'a mpsc::UnboundedSender<InputEvent>,
+    menu_items: &'a AtomicU32,
+    area: Area,
+}
+
+impl NewAreaBuilder<'_> {
+    /// Get the identifier of the area being added.
+    pub fn id(&self) -> AreaId {
+        self.area.id()
+    }
+
+    /// Set the icon of the notification area.
+    pub fn icon(&mut self, icon: IconId) -> &mut Self {
+        self.area.icon(icon);
+        self
+    }
+
+    /// Set the tooltip of the notification area.
+    pub fn tooltip<T>(&mut self, tooltip: T) -> &mut Self
+    where
+        T: fmt::Display,
+    {
+        self.area.tooltip(tooltip);
+        self
+    }
+
+    /// Set whether the window should be brought to the foreground when the
+    /// popup menu is opened.
+    ///
+    /// See [`Area::take_foreground`].
+    ///
+    /// [`Area::take_foreground`]: crate::area::Area::take_foreground
+    pub fn take_foreground(&mut self, take_foreground: bool) -> &mut Self {
+        self.area.take_foreground(take_foreground);
+        self
+    }
+
+    /// Set whether double clicking the icon should click the default item of
+    /// the popup menu.
+    ///
+    /// See [`Area::double_click_default`].
+    ///
+    /// [`Area::double_click_default`]: crate::area::Area::double_click_default
+    pub fn double_click_default(&mut self, double_click_default: bool) -> &mut Self {
+        self.area.double_click_default(double_click_default);
+        self
+    }
+
+    /// Set up a popup menu for the notification area.
+    pub fn popup_menu(&mut self) -> &mut PopupMenu {
+        self.area.popup_menu()
+    }
+
+    /// Send the area to be added and return its identifier.
+    pub fn send(self) -> Result<AreaId> {
+        let area_id = self.area.id();
+
+        // NB: Make sure that identifiers of menu items inserted later are
+        // allocated after the ones used by the new menu.
+        self.menu_items.fetch_max(
+            self.area.menu_item_ids(),
+            std::sync::atomic::Ordering::SeqCst,
+        );
+
+        send(self.tx, InputEvent::AddArea { area: self.area })?;
+        Ok(area_id)
+    }
+
+    /// Send the area to be added and wait for it to be added, returning its
+    /// identifier.
+    ///
+    /// See [`ModifyAreaBuilder::send_await`].
+    pub async fn send_await(self) -> Result<AreaId> {
+        let area_id = self.area.id();
+
+        self.menu_items.fetch_max(
+            self.area.menu_item_ids(),
+            std::sync::atomic::Ordering::SeqCst,
+        );
+
+        send_await(self.tx, InputEvent::AddArea { area: self.area }).await?;
+        Ok(area_id)
+    }
+}
+
+/// A builder returned by [`Sender::open_menu`].
+#[must_use = "Must call `send()` to open the menu"]
+pub struct OpenMenuBuilder<'a> {
+    tx: &'a mpsc::UnboundedSender<InputEvent>,
+    area_id: AreaId,
+    position: Option<(i32, i32)>,
+}
+
+impl OpenMenuBuilder<'_> {
+    /// Open the menu at the given position in screen coordinates instead of
+    /// next to the icon of the area.
+    pub fn position(mut self, x: i32, y: i32) -> Self {
+        self.position = Some((x, y));
+        self
+    }
+
+    /// Send the request to open the menu.
+    pub fn send(self) -> Result<()> {
+        send(
+            self.tx,
+            InputEvent::OpenMenu {
+                area_id: self.area_id,
+                position: self.position,
+            },
+        )
+    }
+
+    /// Send the request to open the menu and wait for it to be processed.
+    ///
+    /// Since the menu is opened by the window thread, only failing to pass
+    /// the request on to it is reported.
+    ///
+    /// See [`ModifyAreaBuilder::send_await`].
+    pub async fn send_await(self) -> Result<()> {
+        let input = InputEvent::OpenMenu {
+            area_id: self.area_id,
+            position: self.position,
+        };
+
+        send_await(self.tx, input).await
+    }
+}
+
+/// A builder returned by [`Sender::modify_menu_item`].
+#[must_use = "Must call `send()` to apply changes"]
+pub struct ModifyMenuItemBuilder<'a> {
+    tx: &'a mpsc::UnboundedSender<InputEvent>,
+    item_id: ItemId,
+    modify: ModifyMenuItem,
+}
+
+impl ModifyMenuItemBuilder<'_> {
+    /// Set the checked state of the menu item.
+    pub fn checked(mut self, checked: bool) -> Self {
+        self.modify.checked(checked);
+        self
+    }
+
+    /// Set that the menu item should be highlighted.
+    pub fn highlight(mut self, highlight: bool) -> Self {
+        self.modify.highlight(highlight);
+        self
+    }
+
+    /// Set whether the menu item is enabled.
+    ///
+    /// Disabled menu items are grayed out and cannot be clicked.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use winctx::CreateWindow;
+    ///
+    /// # async fn test() -> winctx::Result<()> {
+    /// let mut window = CreateWindow::new("se.tedro.Example");
+    /// let area = window.new_area();
+    /// let upload = area.popup_menu().push_entry("Upload").id();
+    ///
+    /// let (sender, _) = window.build().await?;
+    ///
+    /// sender.modify_menu_item(upload).enabled(false).send()?;
+    /// # Ok(()) }
+    /// ```
+    pub fn enabled(mut self, enabled: bool) -> Self {
+        self.modify.enabled(enabled);
+        self
+    }
+
+    /// Send the modification.
+    pub fn send(self) -> Result<()> {
+        send(
+            self.tx,
+            InputEvent::ModifyMenuItem {
+                item_id: self.item_id,
+                modify: self.modify,
+            },
+        )
+    }
+
+    /// Send the modification and wait for it to be applied.
+    ///
+    /// See [`ModifyAreaBuilder::send_await`].
+    pub async fn send_await(self) -> Result<()> {
+        let input = InputEvent::ModifyMenuItem {
+            item_id: self.item_id,
+            modify: self.modify,
+        };
+
+        send_await(self.tx, input).await
+    }
+}
+
+/// A builder returned by [`Sender::insert_menu_item`] and
+/// [`Sender::insert_menu_separator`].
+#[must_use = "Must call `send()` to insert the menu item"]
+pub struct InsertMenuItemBuilder<'a> {
+    tx: &'a mpsc::UnboundedSender<InputEvent>,
+    position: u32,
+    item: MenuItem,
+}
+
+impl InsertMenuItemBuilder<'_> {
+    /// Get the identifier of the menu item being inserted.
+    ///
+    /// This is the same identifier which is returned by
+    /// [`InsertMenuItemBuilder::send`], which makes it possible to refer to
+    /// the menu item after it has been inserted as part of a batch.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use winctx::CreateWindow;
+    ///
+    /// # async fn test() -> winctx::Result<()> {
+    /// let mut window = CreateWindow::new("se.tedro.Example");
+    /// let area = window.new_area();
+    /// let area_id = area.id();
+    /// area.popup_menu().push_entry("Quit");
+    ///
+    /// let (sender, _) = window.build().await?;
+    ///
+    /// let insert = sender.insert_menu_item(area_id, 0, "recent.txt");
+    /// let recent = insert.id();
+    ///
+    /// sender.batch().push(insert).send()?;
+    /// sender.remove_menu_item(recent)?;
+    /// # Ok(()) }
+    /// ```
+    pub fn id(&self) -> ItemId {
+        self.item.item_id
+    }
+
+    /// Set the checked state of the menu item.
+    pub fn checked(mut self, checked: bool) -> Self {
+        self.item.checked(checked);
+        self
+    }
+
+    /// Set that the menu item should be highlighted.
+    pub fn highlight(mut self, highlight: bool) -> Self {
+        self.item.highlight(highlight);
+        self
+    }
+
+    /// Set whether the menu item is enabled.
+    pub fn enabled(mut self, enabled: bool) -> Self {
+        self.item.enabled(enabled);
+        self
+    }
+
+    /// Set the icon which is displayed next to the text of the menu item.
+    pub fn icon(mut self, icon: IconId) -> Self {
+        self.item.icon(icon);
+        self
+    }
+
+    /// Set the icon used in place of the check mark when the menu item is
+    /// checked.
+    pub fn checked_icon(mut self, icon: IconId) -> Self {
+        self.item.checked_icon(icon);
+        self
+    }
+
+    /// Set the icon used in place of the check mark when the menu item isn't
+    /// checked.
+    pub fn unchecked_icon(mut self, icon: IconId) -> Self {
+        self.item.unchecked_icon(icon);
+        self
     }
-}
 
-/// A builder returned by [`Sender::modify_area`].
-#[must_use = "Must call `send()` to apply changes"]
-pub struct ModifyAreaBuilder<'a> {
-    tx: &'a mpsc::UnboundedSender<InputEvent>,
-    area_id: AreaId,
-    modify: ModifyArea,
-}
+    /// Set the shortcut hint which is displayed right-aligned next to the text
+    /// of the menu item, such as `Ctrl+Q`.
+    pub fn shortcut<S>(mut self, shortcut: S) -> Self
+    where
+        S: AsRef<str>,
+    {
+        self.item.shortcut(shortcut);
+        self
+    }
 
-impl ModifyAreaBuilder<'_> {
-    /// Set the icon of the notification area.
-    pub fn icon(mut self, icon: IconId) -> Self {
-        self.modify.icon(icon);
+    /// Register the shortcut of the menu item as a global hotkey, which clicks
+    /// the menu item when pressed.
+    ///
+    /// Inserting the menu item fails if the shortcut can't be parsed.
+    pub fn global_hotkey(mut self, global_hotkey: bool) -> Self {
+        self.item.global_hotkey(global_hotkey);
         self
     }
 
-    /// Set the tooltip of the notification area.
-    pub fn tooltip<T>(mut self, tooltip: T) -> Self
+    /// Add the menu item to the given group.
+    pub fn group(mut self, group: GroupId) -> Self {
+        self.item.group(group);
+        self
+    }
+
+    /// Associate a user-defined action with the menu item.
+    ///
+    /// See [`PopupMenu::push_entry_id`].
+    pub fn action<A>(mut self, action: A) -> Self
     where
-        T: fmt::Display,
+        A: Any + Send + Sync,
     {
-        self.modify.tooltip(tooltip);
+        self.item.action(action);
         self
     }
 
-    /// Send the modification.
-    pub fn send(self) {
-        _ = self.tx.send(InputEvent::ModifyArea {
-            area_id: self.area_id,
-            modify: self.modify,
-        });
+    /// Send the insertion and return the identifier of the inserted menu
+    /// item.
+    pub fn send(self) -> Result<ItemId> {
+        let item_id = self.item.item_id;
+
+        send(
+            self.tx,
+            InputEvent::InsertMenuItem {
+                position: self.position,
+                item: self.item,
+            },
+        )?;
+
+        Ok(item_id)
+    }
+
+    /// Send the insertion and wait for it to be applied, returning the
+    /// identifier of the inserted menu item.
+    ///
+    /// See [`ModifyAreaBuilder::send_await`].
+    pub async fn send_await(self) -> Result<ItemId> {
+        let item_id = self.item.item_id;
+
+        let input = InputEvent::InsertMenuItem {
+            position: self.position,
+            item: self.item,
+        };
+
+        send_await(self.tx, input).await?;
+        Ok(item_id)
     }
 }
 
-/// A builder returned by [`Sender::modify_menu_item`].
+/// A builder returned by [`Sender::modify_group`].
 #[must_use = "Must call `send()` to apply changes"]
-pub struct ModifyMenuItemBuilder<'a> {
+pub struct ModifyGroupBuilder<'a> {
     tx: &'a mpsc::UnboundedSender<InputEvent>,
-    item_id: ItemId,
+    group_id: GroupId,
     modify: ModifyMenuItem,
 }
 
-impl ModifyMenuItemBuilder<'_> {
-    /// Set the checked state of the menu item.
+impl ModifyGroupBuilder<'_> {
+    /// Set the checked state of every menu item in the group.
     pub fn checked(mut self, checked: bool) -> Self {
         self.modify.checked(checked);
         self
     }
 
-    /// Set that the menu item should be highlighted.
+    /// Set that every menu item in the group should be highlighted.
     pub fn highlight(mut self, highlight: bool) -> Self {
         self.modify.highlight(highlight);
         self
     }
 
+    /// Set whether every menu item in the group is enabled.
+    ///
+    /// Disabled menu items are grayed out and cannot be clicked.
+    pub fn enabled(mut self, enabled: bool) -> Self {
+        self.modify.enabled(enabled);
+        self
+    }
+
     /// Send the modification.
-    pub fn send(self) {
-        _ = self.tx.send(InputEvent::ModifyMenuItem {
-            item_id: self.item_id,
+    pub fn send(self) -> Result<()> {
+        send(
+            self.tx,
+            InputEvent::ModifyGroup {
+                group_id: self.group_id,
+                modify: self.modify,
+            },
+        )
+    }
+
+    /// Send the modification and wait for it to be applied.
+    ///
+    /// See [`ModifyAreaBuilder::send_await`].
+    pub async fn send_await(self) -> Result<()> {
+        let input = InputEvent::ModifyGroup {
+            group_id: self.group_id,
             modify: self.modify,
-        });
+        };
+
+        send_await(self.tx, input).await
+    }
+}
+
+/// A modification which can be added to a batch using [`BatchBuilder::push`].
+///
+/// This is constructed from the builders returned by [`Sender::modify_area`],
+/// [`Sender::modify_menu_item`], [`Sender::modify_group`], and
+/// [`Sender::insert_menu_item`].
+///
+/// The identifier of an inserted menu item can be retrieved with
+/// [`InsertMenuItemBuilder::id`] before it's added to the batch.
+pub struct BatchItem {
+    input: InputEvent,
+}
+
+impl From<ModifyAreaBuilder<'_>> for BatchItem {
+    #[inline]
+    fn from(builder: ModifyAreaBuilder<'_>) -> Self {
+        Self {
+            input: InputEvent::ModifyArea {
+                area_id: builder.area_id,
+                modify: builder.modify,
+            },
+        }
+    }
+}
+
+impl From<ModifyMenuItemBuilder<'_>> for BatchItem {
+    #[inline]
+    fn from(builder: ModifyMenuItemBuilder<'_>) -> Self {
+        Self {
+            input: InputEvent::ModifyMenuItem {
+                item_id: builder.item_id,
+                modify: builder.modify,
+            },
+        }
+    }
+}
+
+impl From<ModifyGroupBuilder<'_>> for BatchItem {
+    #[inline]
+    fn from(builder: ModifyGroupBuilder<'_>) -> Self {
+        Self {
+            input: InputEvent::ModifyGroup {
+                group_id: builder.group_id,
+                modify: builder.modify,
+            },
+        }
+    }
+}
+
+impl From<InsertMenuItemBuilder<'_>> for BatchItem {
+    #[inline]
+    fn from(builder: InsertMenuItemBuilder<'_>) -> Self {
+        Self {
+            input: InputEvent::InsertMenuItem {
+                position: builder.position,
+                item: builder.item,
+            },
+        }
+    }
+}
+
+/// A builder returned by [`Sender::batch`].
+#[must_use = "Must call `send()` to apply changes"]
+pub struct BatchBuilder<'a> {
+    tx: &'a mpsc::UnboundedSender<InputEvent>,
+    inputs: Vec<InputEvent>,
+}
+
+impl BatchBuilder<'_> {
+    /// Add a modification to the batch.
+    ///
+    /// Modifications are applied in the order they're added, except that
+    /// modifications of the same area are merged into the first one.
+    pub fn push<T>(mut self, item: T) -> Self
+    where
+        T: Into<BatchItem>,
+    {
+        let input = item.into().input;
+
+        if let InputEvent::ModifyArea { area_id, modify } = input {
+            for input in &mut self.inputs {
+                if let InputEvent::ModifyArea {
+                    area_id: existing,
+                    modify: existing_modify,
+                } = input
+                {
+                    if *existing == area_id {
+                        existing_modify.merge(modify);
+                        return self;
+                    }
+                }
+            }
+
+            self.inputs.push(InputEvent::ModifyArea { area_id, modify });
+        } else {
+            self.inputs.push(input);
+        }
+
+        self
+    }
+
+    /// Send the batch.
+    pub fn send(self) -> Result<()> {
+        send(
+            self.tx,
+            InputEvent::Batch {
+                inputs: self.inputs,
+            },
+        )
+    }
+
+    /// Send the batch and wait for it to be applied.
+    ///
+    /// Before anything is applied, the batch is checked for modifications of
+    /// areas or menu items which don't exist and for icons which aren't from
+    /// the icons of the window, in which case none of it is applied and the
+    /// error is returned. If the system fails to apply a modification, the
+    /// ones before it remain applied and the ones following it are not.
+    ///
+    /// See [`ModifyAreaBuilder::send_await`].
+    pub async fn send_await(self) -> Result<()> {
+        send_await(
+            self.tx,
+            InputEvent::Batch {
+                inputs: self.inputs,
+            },
+        )
+        .await
     }
 }
 
@@ -179,7 +1709,7 @@ impl NotificationBuilder<'_> {
     ///
     /// let id = sender.notification(area)
     ///     .message("This is a body")
-    ///     .send();
+    ///     .send()?;
     /// # Ok(()) }
     /// ```
     pub fn message<M>(mut self, message: M) -> Self
@@ -206,7 +1736,7 @@ impl NotificationBuilder<'_> {
     /// let id = sender.notification(area)
     ///     .title("This is a title")
     ///     .message("This is a body")
-    ///     .send();
+    ///     .send()?;
     /// # Ok(()) }
     /// ```
     pub fn title<M>(mut self, title: M) -> Self
@@ -236,7 +1766,7 @@ impl NotificationBuilder<'_> {
     /// let id = sender.notification(area)
     ///     .info()
     ///     .message("Something normal")
-    ///     .send();
+    ///     .send()?;
     /// # Ok(()) }
     /// ```
     pub fn info(mut self) -> Self {
@@ -262,7 +1792,7 @@ impl NotificationBuilder<'_> {
     /// let id = sender.notification(area)
     ///     .warning()
     ///     .message("Something strange")
-    ///     .send();
+    ///     .send()?;
     /// # Ok(()) }
     /// ```
     pub fn warning(mut self) -> Self {
@@ -288,7 +1818,7 @@ impl NotificationBuilder<'_> {
     /// let id = sender.notification(area)
     ///     .error()
     ///     .message("Something broken")
-    ///     .send();
+    ///     .send()?;
     /// # Ok(()) }
     /// ```
     pub fn error(mut self) -> Self {
@@ -313,7 +1843,7 @@ impl NotificationBuilder<'_> {
     /// let id = sender.notification(area)
     ///     .error()
     ///     .message("Something broken")
-    ///     .send();
+    ///     .send()?;
     /// # Ok(()) }
     /// ```
     pub fn stock_icon(mut self, stock_icon: StockIcon) -> Self {
@@ -322,6 +1852,107 @@ impl NotificationBuilder<'_> {
         self
     }
 
+    /// Use an icon registered through [`CreateWindow::icons`] as the
+    /// notification icon.
+    ///
+    /// The icon is scaled to the size used by notifications, so to avoid it
+    /// being blurry it should be at least 16x16 pixels, or 32x32 pixels if
+    /// combined with [`NotificationBuilder::large_icon`]. If the icon doesn't
+    /// exist the notification is shown without one.
+    ///
+    /// [`CreateWindow::icons`]: crate::CreateWindow::icons
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use winctx::CreateWindow;
+    ///
+    /// # macro_rules! include_bytes { ($path:literal) => { &[] } }
+    /// const ICON: &[u8] = include_bytes!("tokio.ico");
+    ///
+    /// # async fn test() -> winctx::Result<()> {
+    /// let mut window = CreateWindow::new("se.tedro.Example");
+    /// let icon = window.icons().insert_buffer(ICON, 32, 32);
+    /// let area = window.new_area().icon(icon).id();
+    ///
+    /// let (mut sender, _) = window.build().await?;
+    ///
+    /// let id = sender.notification(area)
+    ///     .icon(icon)
+    ///     .large_icon()
+    ///     .message("Upload finished")
+    ///     .send()?;
+    /// # Ok(()) }
+    /// ```
+    pub fn icon(mut self, icon: IconId) -> Self {
+        self.notification.icon(NotificationIcon::Custom(icon));
+        self
+    }
+
+    /// Display progress in the notification, such as that of a download.
+    ///
+    /// The `value` is clamped to the range `0.0` to `1.0`. Balloon
+    /// notifications can't display a progress bar, so the progress is rendered
+    /// as a line of text with the `title` and the value as a percentage,
+    /// followed by the `status` on a separate line. Use
+    /// [`Sender::update_notification`] to advance it.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use winctx::CreateWindow;
+    ///
+    /// # async fn test() -> winctx::Result<()> {
+    /// let mut window = CreateWindow::new("se.tedro.Example");
+    /// let area = window.new_area().id();
+    ///
+    /// let (sender, _) = window.build().await?;
+    ///
+    /// let id = sender.notification(area)
+    ///     .title("Installing update")
+    ///     .progress("Version 2.0", 0.25, "Extracting files")
+    ///     .send()?;
+    /// # Ok(()) }
+    /// ```
+    pub fn progress<T, S>(mut self, title: T, value: f32, status: S) -> Self
+    where
+        T: fmt::Display,
+        S: fmt::Display,
+    {
+        self.notification
+            .progress(Progress::new(title, value, status));
+        self
+    }
+
+    /// Select the sound which is played when the notification is shown.
+    ///
+    /// Sounds are played asynchronously by the application rather than by the
+    /// shell, so they're played even if the shell decides to show the
+    /// notification quietly, such as during quiet time. Combining this with
+    /// [`NotificationBuilder::no_sound`] plays no sound.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use winctx::{CreateWindow, NotificationSound};
+    ///
+    /// # async fn test() -> winctx::Result<()> {
+    /// let mut window = CreateWindow::new("se.tedro.Example");
+    /// let area = window.new_area().id();
+    ///
+    /// let (sender, _) = window.build().await?;
+    ///
+    /// let id = sender.notification(area)
+    ///     .message("Time for a break")
+    ///     .sound(NotificationSound::Reminder)
+    ///     .send()?;
+    /// # Ok(()) }
+    /// ```
+    pub fn sound(mut self, sound: NotificationSound) -> Self {
+        self.notification.sound(sound);
+        self
+    }
+
     /// Do not play the sound associated with a notification.
     ///
     /// # Examples
@@ -339,7 +1970,7 @@ impl NotificationBuilder<'_> {
     ///     .warning()
     ///     .message("Something dangerous")
     ///     .no_sound()
-    ///     .send();
+    ///     .send()?;
     /// # Ok(()) }
     /// ```
     pub fn no_sound(mut self) -> Self {
@@ -367,7 +1998,7 @@ impl NotificationBuilder<'_> {
     ///     .warning()
     ///     .message("Something dangerous")
     ///     .large_icon()
-    ///     .send();
+    ///     .send()?;
     /// # Ok(()) }
     /// ```
     pub fn large_icon(mut self) -> Self {
@@ -397,7 +2028,7 @@ impl NotificationBuilder<'_> {
     ///     .message("Something dangerous")
     ///     .stock_icon(StockIcon::FOLDER)
     ///     .icon_selected()
-    ///     .send();
+    ///     .send()?;
     /// # Ok(()) }
     /// ```
     pub fn icon_selected(mut self) -> Self {
@@ -427,7 +2058,7 @@ impl NotificationBuilder<'_> {
     ///     .message("Something dangerous")
     ///     .stock_icon(StockIcon::FOLDER)
     ///     .icon_link_overlay()
-    ///     .send();
+    ///     .send()?;
     /// # Ok(()) }
     /// ```
     pub fn icon_link_overlay(mut self) -> Self {
@@ -452,7 +2083,7 @@ impl NotificationBuilder<'_> {
     ///     .warning()
     ///     .message("Something dangerous")
     ///     .respect_quiet_time()
-    ///     .send();
+    ///     .send()?;
     /// # Ok(()) }
     /// ```
     pub fn respect_quiet_time(mut self) -> Self {
@@ -460,14 +2091,212 @@ impl NotificationBuilder<'_> {
         self
     }
 
+    /// Indicates that the notification should persist in the notification
+    /// center after it has been dismissed.
+    ///
+    /// Balloon notifications cannot be persisted, so the notification is shown
+    /// as usual and an [`Event::Error`] is emitted to indicate that this
+    /// option is unsupported.
+    ///
+    /// [`Event::Error`]: crate::Event::Error
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use winctx::CreateWindow;
+    ///
+    /// # async fn test() -> winctx::Result<()> {
+    /// let mut window = CreateWindow::new("se.tedro.Example");
+    /// let area = window.new_area().id();
+    ///
+    /// let (sender, _) = window.build().await?;
+    ///
+    /// let id = sender.notification(area)
+    ///     .message("Something important")
+    ///     .persist(true)
+    ///     .send()?;
+    /// # Ok(()) }
+    /// ```
+    pub fn persist(mut self, persist: bool) -> Self {
+        self.notification.persist(persist);
+        self
+    }
+
+    /// Schedule the notification to be shown at the given time.
+    ///
+    /// Until then the notification is held by the event loop, where it can be
+    /// updated or dismissed like a queued notification. Notifications
+    /// scheduled in the past are shown immediately. Scheduled notifications
+    /// are discarded if the window shuts down before they're shown.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use std::time::{Duration, SystemTime};
+    ///
+    /// use winctx::CreateWindow;
+    ///
+    /// # async fn test() -> winctx::Result<()> {
+    /// let mut window = CreateWindow::new("se.tedro.Example");
+    /// let area = window.new_area().id();
+    ///
+    /// let (sender, _) = window.build().await?;
+    ///
+    /// let id = sender.notification(area)
+    ///     .message("Stand up and stretch")
+    ///     .schedule(SystemTime::now() + Duration::from_secs(30 * 60))
+    ///     .send()?;
+    /// # Ok(()) }
+    /// ```
+    pub fn schedule(mut self, at: SystemTime) -> Self {
+        self.notification.schedule(at);
+        self
+    }
+
+    /// Set the tag of the notification.
+    ///
+    /// Sending a notification with the same tag and [group] as an earlier one
+    /// which is still visible, queued, or scheduled replaces it, emitting
+    /// [`Event::NotificationDismissed`] for the replaced notification. This
+    /// avoids piling up notifications which are superseded, such as the
+    /// status of the same download.
+    ///
+    /// [group]: NotificationBuilder::group
+    /// [`Event::NotificationDismissed`]: crate::Event::NotificationDismissed
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use winctx::CreateWindow;
+    ///
+    /// # async fn test() -> winctx::Result<()> {
+    /// let mut window = CreateWindow::new("se.tedro.Example");
+    /// let area = window.new_area().id();
+    ///
+    /// let (sender, _) = window.build().await?;
+    ///
+    /// sender.notification(area)
+    ///     .message("Download 42 started")
+    ///     .tag("download-42")
+    ///     .group("downloads")
+    ///     .send()?;
+    ///
+    /// // Replaces the notification above.
+    /// sender.notification(area)
+    ///     .message("Download 42 finished")
+    ///     .tag("download-42")
+    ///     .group("downloads")
+    ///     .send()?;
+    /// # Ok(()) }
+    /// ```
+    pub fn tag<T>(mut self, tag: T) -> Self
+    where
+        T: fmt::Display,
+    {
+        self.notification.tag(tag);
+        self
+    }
+
+    /// Set the group of the notification.
+    ///
+    /// Groups are namespaces for [tags], so that notifications with the same
+    /// tag only replace each other if they're in the same group. A group has
+    /// no effect on a notification without a tag.
+    ///
+    /// [tags]: NotificationBuilder::tag
+    pub fn group<T>(mut self, group: T) -> Self
+    where
+        T: fmt::Display,
+    {
+        self.notification.group(group);
+        self
+    }
+
     /// Send the modification and return the identifier of the sent
     /// notification.
-    pub fn send(self) -> NotificationId {
-        _ = self.tx.send(InputEvent::Notification {
-            area_id: self.area_id,
-            notification_id: self.id,
-            notification: self.notification,
-        });
-        self.id
+    pub fn send(self) -> Result<NotificationId> {
+        send(
+            self.tx,
+            InputEvent::Notification {
+                area_id: self.area_id,
+                notification_id: self.id,
+                notification: self.notification,
+            },
+        )?;
+
+        Ok(self.id)
+    }
+}
+
+/// A builder returned by [`Sender::update_notification`].
+#[must_use = "Must call `send()` to update the notification"]
+pub struct UpdateNotificationBuilder<'a> {
+    tx: &'a mpsc::UnboundedSender<InputEvent>,
+    notification_id: NotificationId,
+    update: UpdateNotification,
+}
+
+impl UpdateNotificationBuilder<'_> {
+    /// Set the title of the notification.
+    pub fn title<M>(mut self, title: M) -> Self
+    where
+        M: fmt::Display,
+    {
+        self.update.title(title);
+        self
+    }
+
+    /// Set the message of the notification.
+    pub fn message<M>(mut self, message: M) -> Self
+    where
+        M: fmt::Display,
+    {
+        self.update.message(message);
+        self
+    }
+
+    /// Set the progress displayed in the notification.
+    ///
+    /// See [`NotificationBuilder::progress`].
+    pub fn progress<T, S>(mut self, title: T, value: f32, status: S) -> Self
+    where
+        T: fmt::Display,
+        S: fmt::Display,
+    {
+        self.update.progress(Progress::new(title, value, status));
+        self
+    }
+
+    /// Send the update.
+    pub fn send(self) -> Result<()> {
+        send(
+            self.tx,
+            InputEvent::UpdateNotification {
+                notification_id: self.notification_id,
+                update: self.update,
+            },
+        )
     }
 }
+
+/// Send the given input, returning an error if the event loop has shut down.
+fn send(tx: &mpsc::UnboundedSender<InputEvent>, input: InputEvent) -> Result<()> {
+    if tx.send(input).is_err() {
+        return Err(Error::new(WindowClosed));
+    }
+
+    Ok(())
+}
+
+/// Send the given input and wait for the event loop to report its outcome.
+async fn send_await(tx: &mpsc::UnboundedSender<InputEvent>, input: InputEvent) -> Result<()> {
+    let (reply, rx) = oneshot::channel();
+
+    let input = InputEvent::Await {
+        input: Box::new(input),
+        tx: reply,
+    };
+
+    send(tx, input)?;
+    rx.await.map_err(|_| Error::new(WindowClosed))?
+}
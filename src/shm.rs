@@ -0,0 +1,401 @@
+//! Shared memory channels for high-throughput communication between local
+//! processes.
+//!
+//! A [`Channel`] is a ring buffer in a named file mapping which carries
+//! length-prefixed messages from a single writer to a single reader. After
+//! writing, the writer posts a registered window message to the reader as a
+//! doorbell, which is received as an [`Event::AppMessage`] and signals that
+//! messages are ready to be read.
+//!
+//! [`Event::AppMessage`]: crate::Event::AppMessage
+//!
+//! # Examples
+//!
+//! The receiving end, which owns the window:
+//!
+//! ```no_run
+//! use winctx::{CreateWindow, Event};
+//! use winctx::shm::Channel;
+//!
+//! # async fn test() -> winctx::Result<()> {
+//! let mut window = CreateWindow::new("se.tedro.Example");
+//! let doorbell = window.register_message("se.tedro.Example.Doorbell")?;
+//! let (_sender, mut event_loop) = window.build().await?;
+//!
+//! let channel = Channel::create("se.tedro.Example.Channel", 1 << 20).unwrap();
+//!
+//! loop {
+//!     match event_loop.tick().await? {
+//!         Event::AppMessage { id, .. } if id == doorbell => {
+//!             while let Some(message) = channel.pop().unwrap() {
+//!                 println!("{} bytes", message.len());
+//!             }
+//!         }
+//!         Event::Shutdown { .. } => break,
+//!         _ => {}
+//!     }
+//! }
+//! # Ok(()) }
+//! ```
+//!
+//! The sending end:
+//!
+//! ```no_run
+//! use winctx::shm::Channel;
+//! use winctx::window::{self, FindWindow};
+//!
+//! let doorbell = window::register_message("se.tedro.Example.Doorbell")?;
+//!
+//! let Some(window) = FindWindow::new().class("se.tedro.Example").find()? else {
+//!     println!("Could not find window");
+//!     return Ok(());
+//! };
+//!
+//! let channel = Channel::open("se.tedro.Example.Channel")?;
+//! channel.send(&window, doorbell, b"Hello World")?;
+//! # Ok::<_, std::io::Error>(())
+//! ```
+
+use std::ffi::OsStr;
+use std::io;
+use std::mem::{size_of, MaybeUninit};
+use std::ptr;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use windows_sys::Win32::Foundation::{
+    CloseHandle, GetLastError, ERROR_ALREADY_EXISTS, FALSE, HANDLE, INVALID_HANDLE_VALUE,
+};
+use windows_sys::Win32::System::Memory::{
+    CreateFileMappingW, MapViewOfFile, OpenFileMappingW, UnmapViewOfFile, VirtualQuery,
+    FILE_MAP_ALL_ACCESS, MEMORY_BASIC_INFORMATION, MEMORY_MAPPED_VIEW_ADDRESS, PAGE_READWRITE,
+};
+
+use crate::convert::ToWide;
+use crate::window::Window;
+
+/// The length of the prefix of each message.
+const PREFIX_LEN: usize = size_of::<u32>();
+
+/// The header at the start of the mapping.
+///
+/// Positions are the total number of bytes which have been written and read,
+/// and only ever increase.
+#[repr(C)]
+struct Header {
+    /// The capacity of the ring buffer following the header.
+    capacity: u64,
+    /// The position of the writer.
+    write: AtomicU64,
+    /// The position of the reader.
+    read: AtomicU64,
+}
+
+/// A ring buffer in shared memory which is used to transfer messages from a
+/// single writer to a single reader.
+///
+/// See the [module level documentation](self) for more details.
+pub struct Channel {
+    handle: HANDLE,
+    view: MEMORY_MAPPED_VIEW_ADDRESS,
+    capacity: usize,
+}
+
+// SAFETY: The mapping is owned by the channel and may be used from any thread.
+unsafe impl Send for Channel {}
+
+impl Channel {
+    /// Create a new named channel which can hold up to `capacity` bytes of
+    /// messages at a time.
+    ///
+    /// Each message takes up four bytes in addition to its length.
+    ///
+    /// # Errors
+    ///
+    /// Errors with [`io::ErrorKind::AlreadyExists`] if a mapping with the
+    /// given name already exists.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use winctx::shm::Channel;
+    ///
+    /// let channel = Channel::create("se.tedro.Example.Channel", 1 << 20)?;
+    /// # Ok::<_, std::io::Error>(())
+    /// ```
+    pub fn create<N>(name: N, capacity: usize) -> io::Result<Self>
+    where
+        N: AsRef<OsStr>,
+    {
+        let name = name.as_ref().to_wide_null();
+
+        let Some(len) = capacity.checked_add(size_of::<Header>()) else {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "Channel capacity is too large",
+            ));
+        };
+
+        // SAFETY: The mapping is backed by the page file, and the header is
+        // initialized before the channel is returned.
+        unsafe {
+            let len = len as u64;
+
+            let handle = CreateFileMappingW(
+                INVALID_HANDLE_VALUE,
+                ptr::null(),
+                PAGE_READWRITE,
+                (len >> 32) as u32,
+                len as u32,
+                name.as_ptr(),
+            );
+
+            if handle == 0 {
+                return Err(io::Error::last_os_error());
+            }
+
+            // NB: Resetting the header of an existing channel would corrupt
+            // it for anyone using it.
+            if GetLastError() == ERROR_ALREADY_EXISTS {
+                CloseHandle(handle);
+                return Err(io::ErrorKind::AlreadyExists.into());
+            }
+
+            let mut channel = Self::map(handle)?;
+            ptr::addr_of_mut!((*channel.view.Value.cast::<Header>()).capacity)
+                .write(capacity as u64);
+            channel.header().write.store(0, Ordering::Relaxed);
+            channel.header().read.store(0, Ordering::Relaxed);
+            channel.capacity = capacity;
+            Ok(channel)
+        }
+    }
+
+    /// Open an existing named channel.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use winctx::shm::Channel;
+    ///
+    /// let channel = Channel::open("se.tedro.Example.Channel")?;
+    /// # Ok::<_, std::io::Error>(())
+    /// ```
+    pub fn open<N>(name: N) -> io::Result<Self>
+    where
+        N: AsRef<OsStr>,
+    {
+        let name = name.as_ref().to_wide_null();
+
+        // SAFETY: The capacity declared by the creator is checked against the
+        // size of the mapped view before it's used.
+        unsafe {
+            let handle = OpenFileMappingW(FILE_MAP_ALL_ACCESS, FALSE, name.as_ptr());
+
+            if handle == 0 {
+                return Err(io::Error::last_os_error());
+            }
+
+            let mut channel = Self::map(handle)?;
+
+            let mut info = MaybeUninit::<MEMORY_BASIC_INFORMATION>::zeroed();
+
+            if VirtualQuery(
+                channel.view.Value,
+                info.as_mut_ptr(),
+                size_of::<MEMORY_BASIC_INFORMATION>(),
+            ) == 0
+            {
+                return Err(io::Error::last_os_error());
+            }
+
+            let region = info.assume_init().RegionSize;
+            let capacity = usize::try_from(channel.header().capacity).unwrap_or(usize::MAX);
+
+            if region < size_of::<Header>() || capacity > region - size_of::<Header>() {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "Channel capacity exceeds the size of the mapping",
+                ));
+            }
+
+            channel.capacity = capacity;
+            Ok(channel)
+        }
+    }
+
+    /// Map a view of the given file mapping, taking ownership of the handle.
+    unsafe fn map(handle: HANDLE) -> io::Result<Self> {
+        let view = MapViewOfFile(handle, FILE_MAP_ALL_ACCESS, 0, 0, 0);
+
+        if view.Value.is_null() {
+            let error = io::Error::last_os_error();
+            CloseHandle(handle);
+            return Err(error);
+        }
+
+        Ok(Self {
+            handle,
+            view,
+            capacity: 0,
+        })
+    }
+
+    /// The number of bytes of messages the channel can hold at a time.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Write a message to the channel.
+    ///
+    /// # Errors
+    ///
+    /// Errors with [`io::ErrorKind::WouldBlock`] if there currently isn't
+    /// enough room in the channel for the message, and with
+    /// [`io::ErrorKind::InvalidInput`] if the message can never fit.
+    pub fn push(&self, bytes: &[u8]) -> io::Result<()> {
+        let needed = PREFIX_LEN + bytes.len();
+
+        if needed > self.capacity || u32::try_from(bytes.len()).is_err() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "Message does not fit in channel",
+            ));
+        }
+
+        let header = self.header();
+        let read = header.read.load(Ordering::Acquire);
+        let write = header.write.load(Ordering::Relaxed);
+        let used = write.wrapping_sub(read);
+
+        if used > self.capacity as u64 || self.capacity as u64 - used < needed as u64 {
+            return Err(io::ErrorKind::WouldBlock.into());
+        }
+
+        // SAFETY: The region between the writer and reader is owned by the
+        // writer, and there is room for the message.
+        unsafe {
+            self.write_at(write, &(bytes.len() as u32).to_le_bytes());
+            self.write_at(write + PREFIX_LEN as u64, bytes);
+        }
+
+        header.write.store(write + needed as u64, Ordering::Release);
+        Ok(())
+    }
+
+    /// Write a message to the channel and ring the doorbell of the given
+    /// window.
+    ///
+    /// The doorbell should be a message registered through
+    /// [`window::register_message`] which the receiving window also registered
+    /// through [`CreateWindow::register_message`].
+    ///
+    /// [`window::register_message`]: crate::window::register_message
+    /// [`CreateWindow::register_message`]: crate::CreateWindow::register_message
+    ///
+    /// # Errors
+    ///
+    /// See [`Channel::push`].
+    pub fn send(&self, window: &Window, doorbell: u32, bytes: &[u8]) -> io::Result<()> {
+        self.push(bytes)?;
+        window.post_message(doorbell, 0, 0)
+    }
+
+    /// Read the next message from the channel, if there is one.
+    ///
+    /// # Errors
+    ///
+    /// Errors with [`io::ErrorKind::InvalidData`] if the channel has been
+    /// corrupted.
+    pub fn pop(&self) -> io::Result<Option<Vec<u8>>> {
+        let header = self.header();
+        let write = header.write.load(Ordering::Acquire);
+        let read = header.read.load(Ordering::Relaxed);
+        let available = write.wrapping_sub(read);
+
+        if available == 0 {
+            return Ok(None);
+        }
+
+        if available < PREFIX_LEN as u64 || available > self.capacity as u64 {
+            return Err(corrupt());
+        }
+
+        let mut prefix = [0; PREFIX_LEN];
+
+        // SAFETY: The region between the reader and writer is owned by the
+        // reader, and has been checked to hold the prefix.
+        unsafe {
+            self.read_at(read, &mut prefix);
+        }
+
+        let len = u32::from_le_bytes(prefix) as usize;
+
+        if len as u64 > available - PREFIX_LEN as u64 {
+            return Err(corrupt());
+        }
+
+        let mut message = vec![0; len];
+
+        // SAFETY: The length of the message has been checked against the
+        // region owned by the reader.
+        unsafe {
+            self.read_at(read + PREFIX_LEN as u64, &mut message);
+        }
+
+        header
+            .read
+            .store(read + (PREFIX_LEN + len) as u64, Ordering::Release);
+
+        Ok(Some(message))
+    }
+
+    fn header(&self) -> &Header {
+        // SAFETY: The view is at least as large as the header and is
+        // page-aligned.
+        unsafe { &*self.view.Value.cast::<Header>() }
+    }
+
+    /// Get a pointer to the start of the ring buffer.
+    fn data(&self) -> *mut u8 {
+        // SAFETY: The ring buffer follows the header in the view.
+        unsafe { self.view.Value.cast::<u8>().add(size_of::<Header>()) }
+    }
+
+    /// Write bytes at the given position, wrapping around the end of the ring
+    /// buffer.
+    unsafe fn write_at(&self, pos: u64, bytes: &[u8]) {
+        let start = (pos % self.capacity as u64) as usize;
+        let first = bytes.len().min(self.capacity - start);
+
+        ptr::copy_nonoverlapping(bytes.as_ptr(), self.data().add(start), first);
+        ptr::copy_nonoverlapping(bytes[first..].as_ptr(), self.data(), bytes.len() - first);
+    }
+
+    /// Read bytes at the given position, wrapping around the end of the ring
+    /// buffer.
+    unsafe fn read_at(&self, pos: u64, bytes: &mut [u8]) {
+        let start = (pos % self.capacity as u64) as usize;
+        let first = bytes.len().min(self.capacity - start);
+
+        ptr::copy_nonoverlapping(self.data().add(start), bytes.as_mut_ptr(), first);
+        ptr::copy_nonoverlapping(
+            self.data(),
+            bytes[first..].as_mut_ptr(),
+            bytes.len() - first,
+        );
+    }
+}
+
+impl Drop for Channel {
+    fn drop(&mut self) {
+        // SAFETY: The view and handle are owned by the channel.
+        unsafe {
+            UnmapViewOfFile(self.view);
+            CloseHandle(self.handle);
+        }
+    }
+}
+
+fn corrupt() -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, "Channel is corrupt")
+}
@@ -0,0 +1,33 @@
+use windows_sys::Win32::UI::Shell as shellapi;
+
+/// The state of the progress shown on the taskbar button of a window, as set
+/// using [`Sender::set_taskbar_progress`].
+///
+/// [`Sender::set_taskbar_progress`]: crate::Sender::set_taskbar_progress
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum TaskbarState {
+    /// No progress is shown.
+    None,
+    /// Progress is shown as ongoing without a known value.
+    Indeterminate,
+    /// Progress is shown normally.
+    Normal,
+    /// Progress is shown as having failed.
+    Error,
+    /// Progress is shown as paused.
+    Paused,
+}
+
+impl TaskbarState {
+    /// Get the raw progress flags of the state.
+    pub(crate) fn flags(self) -> shellapi::TBPFLAG {
+        match self {
+            TaskbarState::None => shellapi::TBPF_NOPROGRESS,
+            TaskbarState::Indeterminate => shellapi::TBPF_INDETERMINATE,
+            TaskbarState::Normal => shellapi::TBPF_NORMAL,
+            TaskbarState::Error => shellapi::TBPF_ERROR,
+            TaskbarState::Paused => shellapi::TBPF_PAUSED,
+        }
+    }
+}
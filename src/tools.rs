@@ -1,13 +1,26 @@
 //! Minor tools made available for convenience.
 
+use std::env;
 use std::ffi::OsStr;
 use std::io;
+use std::mem::size_of;
 use std::ptr;
 
-use windows_sys::Win32::UI::Shell::ShellExecuteW;
-use windows_sys::Win32::UI::WindowsAndMessaging::SW_SHOW;
+use windows_sys::Win32::Foundation::{BOOL, FALSE, LPARAM, RECT, TRUE};
+use windows_sys::Win32::Graphics::Gdi::{
+    EnumDisplayMonitors, GetMonitorInfoW, HDC, HMONITOR, MONITORINFO,
+};
+use windows_sys::Win32::UI::HiDpi::{GetDpiForMonitor, MDT_EFFECTIVE_DPI};
+use windows_sys::Win32::UI::Shell::{
+    SHQueryUserNotificationState, ShellExecuteW, QUNS_ACCEPTS_NOTIFICATIONS, QUNS_APP,
+};
+use windows_sys::Win32::UI::WindowsAndMessaging::{MONITORINFOF_PRIMARY, SW_SHOW};
 
+use crate::area::Rect;
 use crate::convert::ToWide;
+use crate::error::ErrorRepr::*;
+use crate::window::FindWindow;
+use crate::{Error, NamedMutex};
 
 /// Open the given directory using the default file manager, which on windows
 /// would most likely be Explorer.
@@ -40,3 +53,212 @@ where
 
     Ok(result as usize > 32)
 }
+
+/// Test if quiet hours are active, in which case notifications are not
+/// expected to be shown to the user.
+///
+/// This covers the quiet time after a fresh install, but also when the user is
+/// away, is running a full screen application or is presenting. Applications
+/// can use this to defer notifications which are not critical before even
+/// sending them.
+///
+/// Note that this does not detect Focus Assist (or *Do not disturb*). Windows
+/// doesn't report its priority only or alarms only modes through
+/// [`SHQueryUserNotificationState`], and there is no documented API to query
+/// them. Notifications sent while Focus Assist is active are still delivered
+/// to the action center, but are not shown as a banner.
+///
+/// [`SHQueryUserNotificationState`]: https://learn.microsoft.com/en-us/windows/win32/api/shellapi/nf-shellapi-shqueryusernotificationstate
+///
+/// # Examples
+///
+/// ```no_run
+/// use winctx::tools;
+///
+/// if !tools::quiet_hours_active()? {
+///     // Send notification.
+/// }
+/// # Ok::<_, std::io::Error>(())
+/// ```
+pub fn quiet_hours_active() -> io::Result<bool> {
+    let mut state = 0;
+
+    let result = unsafe { SHQueryUserNotificationState(&mut state) };
+
+    if result < 0 {
+        return Err(io::Error::from_raw_os_error(result));
+    }
+
+    Ok(!matches!(state, QUNS_ACCEPTS_NOTIFICATIONS | QUNS_APP))
+}
+
+/// Ensure that only a single instance of the application is running.
+///
+/// This acquires a [`NamedMutex`] named after the given window class name. If
+/// it's acquired the mutex is returned, and it should be kept alive for as
+/// long as the application is running.
+///
+/// If it's already held by another instance, the window of that instance is
+/// looked up using the class name and the arguments of the current process are
+/// forwarded to it, after which `None` is returned and the current process
+/// should exit. The running instance receives them as an
+/// [`Event::Arguments`] event.
+///
+/// [`Event::Arguments`]: crate::Event::Arguments
+///
+/// # Examples
+///
+/// ```no_run
+/// use winctx::{tools, CreateWindow};
+///
+/// # async fn test() -> winctx::Result<()> {
+/// const CLASS_NAME: &str = "se.tedro.Example";
+///
+/// let Some(_mutex) = tools::single_instance(CLASS_NAME)? else {
+///     return Ok(());
+/// };
+///
+/// let (sender, mut event_loop) = CreateWindow::new(CLASS_NAME).build().await?;
+/// # Ok(()) }
+/// ```
+pub fn single_instance<N>(class_name: N) -> crate::Result<Option<NamedMutex>>
+where
+    N: AsRef<str>,
+{
+    let class_name = class_name.as_ref();
+
+    if let Some(mutex) = NamedMutex::create_acquired(class_name)? {
+        return Ok(Some(mutex));
+    }
+
+    let window = FindWindow::new()
+        .class(class_name)
+        .find()
+        .map_err(ForwardArguments)?;
+
+    let Some(window) = window else {
+        return Err(Error::new(ForwardArguments(io::Error::new(
+            io::ErrorKind::NotFound,
+            "Could not find window of running instance",
+        ))));
+    };
+
+    window
+        .forward_args(env::args_os().skip(1))
+        .map_err(ForwardArguments)?;
+
+    Ok(None)
+}
+
+/// A display monitor, as returned by [`monitors`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Monitor {
+    /// The area of the monitor in virtual screen coordinates.
+    pub rect: Rect,
+    /// The work area of the monitor, which excludes the taskbar and other
+    /// docked toolbars.
+    pub work_area: Rect,
+    /// The effective DPI of the monitor, where `96` corresponds to a scale of
+    /// 100%.
+    pub dpi: u32,
+    /// Whether this is the primary monitor.
+    pub primary: bool,
+}
+
+/// Enumerate the display monitors connected to the system.
+///
+/// This can be used to position windows next to the notification area, such
+/// as by clamping them to the work area of the monitor containing the
+/// rectangle returned by [`Sender::area_rect`].
+///
+/// [`Sender::area_rect`]: crate::Sender::area_rect
+///
+/// # Examples
+///
+/// ```no_run
+/// use winctx::tools;
+///
+/// for monitor in tools::monitors()? {
+///     println!("{:?} at {} dpi", monitor.work_area, monitor.dpi);
+/// }
+/// # Ok::<_, std::io::Error>(())
+/// ```
+pub fn monitors() -> io::Result<Vec<Monitor>> {
+    unsafe extern "system" fn callback(
+        monitor: HMONITOR,
+        _: HDC,
+        _: *mut RECT,
+        data: LPARAM,
+    ) -> BOOL {
+        let handles = &mut *(data as *mut Vec<HMONITOR>);
+        handles.push(monitor);
+        TRUE
+    }
+
+    let mut handles = Vec::<HMONITOR>::new();
+
+    let result = unsafe {
+        EnumDisplayMonitors(
+            0,
+            ptr::null(),
+            Some(callback),
+            &mut handles as *mut Vec<HMONITOR> as LPARAM,
+        )
+    };
+
+    if result == FALSE {
+        return Err(io::Error::last_os_error());
+    }
+
+    let mut monitors = Vec::with_capacity(handles.len());
+
+    for handle in handles {
+        let mut info = MONITORINFO {
+            cbSize: size_of::<MONITORINFO>() as u32,
+            rcMonitor: RECT {
+                left: 0,
+                top: 0,
+                right: 0,
+                bottom: 0,
+            },
+            rcWork: RECT {
+                left: 0,
+                top: 0,
+                right: 0,
+                bottom: 0,
+            },
+            dwFlags: 0,
+        };
+
+        if unsafe { GetMonitorInfoW(handle, &mut info) } == FALSE {
+            return Err(io::Error::last_os_error());
+        }
+
+        let mut dpi_x = 0;
+        let mut dpi_y = 0;
+
+        // NB: Monitors which don't report their DPI are assumed to be
+        // unscaled.
+        let result = unsafe { GetDpiForMonitor(handle, MDT_EFFECTIVE_DPI, &mut dpi_x, &mut dpi_y) };
+        let dpi = if result < 0 { 96 } else { dpi_x };
+
+        monitors.push(Monitor {
+            rect: rect(&info.rcMonitor),
+            work_area: rect(&info.rcWork),
+            dpi,
+            primary: info.dwFlags & MONITORINFOF_PRIMARY != 0,
+        });
+    }
+
+    Ok(monitors)
+}
+
+fn rect(rect: &RECT) -> Rect {
+    Rect {
+        left: rect.left,
+        top: rect.top,
+        right: rect.right,
+        bottom: rect.bottom,
+    }
+}
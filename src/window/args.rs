@@ -0,0 +1,41 @@
+use std::ffi::{OsStr, OsString};
+
+use crate::convert::{FromWide, ToWide};
+
+/// Encode arguments as a sequence of null-terminated wide strings.
+pub(crate) fn encode<I>(args: I) -> Vec<u8>
+where
+    I: IntoIterator,
+    I::Item: AsRef<OsStr>,
+{
+    let mut bytes = Vec::new();
+
+    for arg in args {
+        for c in arg.as_ref().to_wide_null() {
+            bytes.extend_from_slice(&c.to_ne_bytes());
+        }
+    }
+
+    bytes
+}
+
+/// Decode arguments encoded using [`encode`].
+pub(crate) fn decode(bytes: &[u8]) -> Vec<OsString> {
+    let wide = bytes
+        .chunks_exact(2)
+        .map(|c| u16::from_ne_bytes([c[0], c[1]]))
+        .collect::<Vec<_>>();
+
+    let mut args = Vec::new();
+
+    for arg in wide.split_inclusive(|&c| c == 0) {
+        let arg = match arg {
+            [head @ .., 0] => head,
+            rest => rest,
+        };
+
+        args.push(OsString::from_wide(arg));
+    }
+
+    args
+}
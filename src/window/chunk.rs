@@ -0,0 +1,360 @@
+use std::collections::HashMap;
+use std::mem::size_of;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::time::{Duration, Instant};
+
+/// The size of the payload of each chunk.
+pub(crate) const CHUNK_SIZE: usize = 1 << 16;
+
+/// The maximum total length of a transfer.
+pub(crate) const MAX_TRANSFER_LEN: u64 = 1 << 28;
+
+/// The maximum number of transfers which are reassembled at the same time.
+const MAX_TRANSFERS: usize = 8;
+
+/// Transfers which haven't received a chunk for this long are considered
+/// abandoned by their sender.
+const TRANSFER_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// The length of an encoded chunk header.
+const HEADER_LEN: usize = size_of::<u64>() * 3 + size_of::<u32>();
+
+/// Counter used to generate transfer identifiers.
+static TRANSFER: AtomicU32 = AtomicU32::new(0);
+
+/// The header of a chunk.
+///
+/// This is encoded in little-endian byte order in front of the payload of each
+/// chunk.
+pub(crate) struct ChunkHeader {
+    /// The identifier of the transfer the chunk belongs to.
+    pub(crate) id: u64,
+    /// The index of the chunk, starting at zero.
+    pub(crate) index: u32,
+    /// The copy data type of the whole transfer.
+    pub(crate) ty: usize,
+    /// The total length of the transfer.
+    pub(crate) total: u64,
+}
+
+impl ChunkHeader {
+    /// Generate a new transfer identifier, which is unique across processes.
+    pub(crate) fn new_id() -> u64 {
+        let process = u64::from(std::process::id());
+        let transfer = u64::from(TRANSFER.fetch_add(1, Ordering::Relaxed));
+        process << 32 | transfer
+    }
+
+    /// Encode the header followed by the given chunk.
+    pub(crate) fn encode(&self, chunk: &[u8]) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(HEADER_LEN + chunk.len());
+        bytes.extend_from_slice(&self.id.to_le_bytes());
+        bytes.extend_from_slice(&self.index.to_le_bytes());
+        bytes.extend_from_slice(&(self.ty as u64).to_le_bytes());
+        bytes.extend_from_slice(&self.total.to_le_bytes());
+        bytes.extend_from_slice(chunk);
+        bytes
+    }
+
+    /// Decode a header, returning it along with the chunk which follows it.
+    pub(crate) fn decode(bytes: &[u8]) -> Option<(Self, &[u8])> {
+        let header = bytes.get(..HEADER_LEN)?;
+        let chunk = &bytes[HEADER_LEN..];
+
+        let id = u64::from_le_bytes(header[0..8].try_into().ok()?);
+        let index = u32::from_le_bytes(header[8..12].try_into().ok()?);
+        let ty = u64::from_le_bytes(header[12..20].try_into().ok()?);
+        let total = u64::from_le_bytes(header[20..28].try_into().ok()?);
+
+        let header = Self {
+            id,
+            index,
+            ty: usize::try_from(ty).ok()?,
+            total,
+        };
+
+        Some((header, chunk))
+    }
+}
+
+/// The outcome of receiving a chunk.
+pub(crate) enum Received {
+    /// The chunk was rejected, and the transfer it belongs to abandoned.
+    Rejected,
+    /// The chunk was added to the transfer, which has received the given
+    /// number of bytes so far out of the given total.
+    Progress {
+        ty: usize,
+        received: u64,
+        total: u64,
+    },
+    /// The chunk completed the transfer.
+    Complete { ty: usize, data: Vec<u8> },
+}
+
+/// A chunked transfer which is being reassembled.
+struct Transfer {
+    ty: usize,
+    total: u64,
+    next: u32,
+    data: Vec<u8>,
+    /// When the transfer last received a chunk.
+    updated: Instant,
+}
+
+/// Chunked transfers which are being reassembled.
+///
+/// Since chunks can be sent by any process, the number and length of transfers
+/// are limited, and transfers which are abandoned by their sender are evicted.
+#[derive(Default)]
+pub(crate) struct Transfers {
+    transfers: HashMap<u64, Transfer>,
+}
+
+impl Transfers {
+    /// Test if the given chunk completes the transfer it belongs to.
+    pub(crate) fn is_last(&self, header: &ChunkHeader, chunk: &[u8]) -> bool {
+        let received = match self.transfers.get(&header.id) {
+            Some(transfer) if header.index != 0 => transfer.data.len(),
+            _ => 0,
+        };
+
+        received as u64 + chunk.len() as u64 >= header.total
+    }
+
+    /// Add a chunk to the transfer it belongs to.
+    pub(crate) fn receive(&mut self, header: ChunkHeader, chunk: &[u8], now: Instant) -> Received {
+        self.transfers.retain(|_, transfer| {
+            now.saturating_duration_since(transfer.updated) < TRANSFER_TIMEOUT
+        });
+
+        if header.index == 0 {
+            // NB: A new transfer replaces any transfer with the same
+            // identifier which was abandoned by its sender.
+            self.transfers.remove(&header.id);
+
+            if header.total > MAX_TRANSFER_LEN {
+                return Received::Rejected;
+            }
+
+            if self.transfers.len() >= MAX_TRANSFERS {
+                let oldest = self
+                    .transfers
+                    .iter()
+                    .min_by_key(|(_, transfer)| transfer.updated)
+                    .map(|(&id, _)| id);
+
+                if let Some(id) = oldest {
+                    self.transfers.remove(&id);
+                }
+            }
+
+            self.transfers.insert(
+                header.id,
+                Transfer {
+                    ty: header.ty,
+                    total: header.total,
+                    next: 0,
+                    data: Vec::new(),
+                    updated: now,
+                },
+            );
+        }
+
+        let Some(transfer) = self.transfers.get_mut(&header.id) else {
+            return Received::Rejected;
+        };
+
+        let received = transfer.data.len() as u64 + chunk.len() as u64;
+
+        if transfer.next != header.index || transfer.ty != header.ty || received > transfer.total {
+            self.transfers.remove(&header.id);
+            return Received::Rejected;
+        }
+
+        transfer.next += 1;
+        transfer.updated = now;
+        transfer.data.extend_from_slice(chunk);
+
+        if received < transfer.total {
+            return Received::Progress {
+                ty: transfer.ty,
+                received,
+                total: transfer.total,
+            };
+        }
+
+        match self.transfers.remove(&header.id) {
+            Some(transfer) => Received::Complete {
+                ty: transfer.ty,
+                data: transfer.data,
+            },
+            None => Received::Rejected,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use super::{
+        ChunkHeader, Received, Transfers, MAX_TRANSFERS, MAX_TRANSFER_LEN, TRANSFER_TIMEOUT,
+    };
+
+    fn header(id: u64, index: u32, total: u64) -> ChunkHeader {
+        ChunkHeader {
+            id,
+            index,
+            ty: 42,
+            total,
+        }
+    }
+
+    fn is_rejected(received: Received) -> bool {
+        matches!(received, Received::Rejected)
+    }
+
+    #[test]
+    fn encode_decode() {
+        let header = ChunkHeader {
+            id: 0x0102_0304_0506_0708,
+            index: 7,
+            ty: 0xdead_beef,
+            total: 1 << 40,
+        };
+
+        let bytes = header.encode(b"hello");
+        let (decoded, chunk) = ChunkHeader::decode(&bytes).expect("decoding header");
+
+        assert_eq!(decoded.id, header.id);
+        assert_eq!(decoded.index, header.index);
+        assert_eq!(decoded.ty, header.ty);
+        assert_eq!(decoded.total, header.total);
+        assert_eq!(chunk, b"hello");
+    }
+
+    #[test]
+    fn decode_empty_chunk() {
+        let bytes = header(1, 0, 0).encode(&[]);
+        let (decoded, chunk) = ChunkHeader::decode(&bytes).expect("decoding header");
+        assert_eq!(decoded.total, 0);
+        assert!(chunk.is_empty());
+    }
+
+    #[test]
+    fn decode_truncated() {
+        let bytes = header(1, 0, 10).encode(&[]);
+
+        for len in 0..bytes.len() {
+            assert!(ChunkHeader::decode(&bytes[..len]).is_none());
+        }
+    }
+
+    #[test]
+    fn reassemble() {
+        let now = Instant::now();
+        let mut transfers = Transfers::default();
+
+        assert!(!transfers.is_last(&header(1, 0, 6), b"abc"));
+
+        assert!(matches!(
+            transfers.receive(header(1, 0, 6), b"abc", now),
+            Received::Progress {
+                ty: 42,
+                received: 3,
+                total: 6
+            }
+        ));
+
+        assert!(transfers.is_last(&header(1, 1, 6), b"def"));
+
+        let Received::Complete { ty, data } = transfers.receive(header(1, 1, 6), b"def", now)
+        else {
+            panic!("expected transfer to complete");
+        };
+
+        assert_eq!(ty, 42);
+        assert_eq!(data, b"abcdef");
+        assert!(transfers.transfers.is_empty());
+    }
+
+    #[test]
+    fn reject_invalid_chunks() {
+        let now = Instant::now();
+        let mut transfers = Transfers::default();
+
+        // Unknown transfer.
+        assert!(is_rejected(transfers.receive(header(1, 1, 6), b"abc", now)));
+
+        // Out of order.
+        transfers.receive(header(1, 0, 9), b"abc", now);
+        assert!(is_rejected(transfers.receive(header(1, 2, 9), b"def", now)));
+        assert!(is_rejected(transfers.receive(header(1, 1, 9), b"def", now)));
+
+        // Past the total.
+        transfers.receive(header(2, 0, 4), b"abc", now);
+        assert!(is_rejected(transfers.receive(header(2, 1, 4), b"def", now)));
+
+        // Mismatched type.
+        transfers.receive(header(3, 0, 6), b"abc", now);
+        let mut other = header(3, 1, 6);
+        other.ty = 43;
+        assert!(is_rejected(transfers.receive(other, b"def", now)));
+
+        assert!(transfers.transfers.is_empty());
+    }
+
+    #[test]
+    fn reject_oversized() {
+        let now = Instant::now();
+        let mut transfers = Transfers::default();
+
+        assert!(is_rejected(transfers.receive(
+            header(1, 0, MAX_TRANSFER_LEN + 1),
+            b"abc",
+            now
+        )));
+
+        assert!(transfers.transfers.is_empty());
+    }
+
+    #[test]
+    fn evict_abandoned() {
+        let now = Instant::now();
+        let mut transfers = Transfers::default();
+
+        transfers.receive(header(1, 0, 6), b"abc", now);
+
+        let later = now + TRANSFER_TIMEOUT + Duration::from_secs(1);
+        transfers.receive(header(2, 0, 6), b"abc", later);
+
+        assert!(!transfers.transfers.contains_key(&1));
+        assert!(is_rejected(transfers.receive(
+            header(1, 1, 6),
+            b"def",
+            later
+        )));
+    }
+
+    #[test]
+    fn limit_concurrent() {
+        let now = Instant::now();
+        let mut transfers = Transfers::default();
+
+        for id in 0..MAX_TRANSFERS as u64 {
+            transfers.receive(header(id, 0, 6), b"abc", now + Duration::from_millis(id));
+        }
+
+        let later = now + Duration::from_secs(1);
+
+        // NB: Updating the first transfer makes the second the oldest one.
+        transfers.receive(header(0, 1, 6), b"d", later);
+        transfers.receive(header(100, 0, 6), b"abc", later);
+
+        assert_eq!(transfers.transfers.len(), MAX_TRANSFERS);
+        assert!(transfers.transfers.contains_key(&0));
+        assert!(!transfers.transfers.contains_key(&1));
+        assert!(transfers.transfers.contains_key(&100));
+    }
+}
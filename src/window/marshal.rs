@@ -0,0 +1,44 @@
+use std::io;
+use std::sync::mpsc;
+use std::sync::{Mutex, OnceLock};
+use std::thread;
+
+use super::Window;
+
+/// Data which is waiting to be copied by the marshalling thread.
+struct Posted {
+    window: Window,
+    ty: usize,
+    bytes: Vec<u8>,
+}
+
+/// The queue of the marshalling thread, which is started on first use.
+static QUEUE: OnceLock<Mutex<mpsc::Sender<Posted>>> = OnceLock::new();
+
+/// Queue data to be copied to the given window by the marshalling thread.
+pub(crate) fn post(window: Window, ty: usize, bytes: Vec<u8>) -> io::Result<()> {
+    let queue = match QUEUE.get() {
+        Some(queue) => queue,
+        None => {
+            let (tx, rx) = mpsc::channel::<Posted>();
+
+            thread::Builder::new()
+                .name(String::from("winctx-marshal"))
+                .spawn(move || {
+                    for posted in rx {
+                        _ = posted.window.copy_data(posted.ty, &posted.bytes);
+                    }
+                })?;
+
+            // NB: If another thread won the race its queue is used instead,
+            // and the thread we started exits once `tx` is dropped.
+            QUEUE.get_or_init(|| Mutex::new(tx))
+        }
+    };
+
+    let queue = queue.lock().unwrap_or_else(|error| error.into_inner());
+
+    queue
+        .send(Posted { window, ty, bytes })
+        .map_err(|_| io::Error::new(io::ErrorKind::BrokenPipe, "Marshalling thread has exited"))
+}
@@ -0,0 +1,81 @@
+use std::io;
+use std::mem::size_of;
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+/// A typed message sent using [`Window::send_message`].
+///
+/// This is received as an [`Event::Message`] event, and the payload is
+/// decoded on demand using [`Message::decode`] once the tag identifies its
+/// type.
+///
+/// [`Window::send_message`]: crate::window::Window::send_message
+/// [`Event::Message`]: crate::Event::Message
+#[derive(Debug, Clone)]
+pub struct Message {
+    payload: Vec<u8>,
+}
+
+impl Message {
+    /// Decode the payload of the message.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use winctx::{CreateWindow, Event};
+    ///
+    /// # async fn test() -> winctx::Result<()> {
+    /// let (sender, mut event_loop) = CreateWindow::new("se.tedro.Example").build().await?;
+    ///
+    /// loop {
+    ///     if let Event::Message { tag, message } = event_loop.tick().await? {
+    ///         if tag == "args" {
+    ///             let args = message.decode::<Vec<String>>();
+    ///             println!("Arguments: {args:?}");
+    ///         }
+    ///     }
+    /// }
+    /// # Ok(()) }
+    /// ```
+    pub fn decode<T>(&self) -> io::Result<T>
+    where
+        T: DeserializeOwned,
+    {
+        Ok(serde_json::from_slice(&self.payload)?)
+    }
+
+    /// Split encoded data into the tag and the message.
+    ///
+    /// The encoding consists of the length of the tag as a little-endian
+    /// `u32`, followed by the tag and the serialized payload.
+    pub(crate) fn from_bytes(bytes: &[u8]) -> Option<(String, Self)> {
+        let len = bytes.get(..size_of::<u32>())?;
+        let len = u32::from_le_bytes(len.try_into().ok()?) as usize;
+        let rest = &bytes[size_of::<u32>()..];
+        let tag = std::str::from_utf8(rest.get(..len)?).ok()?;
+        let payload = &rest[len..];
+
+        Some((
+            tag.to_owned(),
+            Self {
+                payload: payload.to_vec(),
+            },
+        ))
+    }
+}
+
+/// Encode a message with the given tag.
+pub(super) fn encode<T>(tag: &str, value: &T) -> io::Result<Vec<u8>>
+where
+    T: ?Sized + Serialize,
+{
+    let len = u32::try_from(tag.len())
+        .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "Message tag is too long"))?;
+
+    let mut bytes = Vec::new();
+    bytes.extend_from_slice(&len.to_le_bytes());
+    bytes.extend_from_slice(tag.as_bytes());
+    serde_json::to_writer(&mut bytes, value)?;
+    Ok(bytes)
+}
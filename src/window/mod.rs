@@ -1,4 +1,61 @@
 //! Types related to finding and manipulating windows.
 
-pub use self::window::{FindWindow, Window};
+pub use self::window::{register_message, FindAll, FindWindow, Window};
 mod window;
+
+pub use self::peer_info::PeerInfo;
+mod peer_info;
+
+pub use self::reply::Reply;
+mod reply;
+
+pub use self::peer::Peer;
+mod peer;
+
+pub use self::raw_message::RawMessage;
+mod raw_message;
+
+pub(crate) mod args;
+pub(crate) mod chunk;
+mod marshal;
+
+#[cfg(feature = "serde")]
+pub use self::message::Message;
+#[cfg(feature = "serde")]
+mod message;
+
+/// The first copy data type which is reserved for use by winctx.
+///
+/// Data copied to a window constructed by winctx using a type in the range
+/// `RESERVED_TY..` is handled internally and is never delivered as an
+/// [`Event::CopyData`].
+///
+/// [`Event::CopyData`]: crate::Event::CopyData
+pub const RESERVED_TY: usize = usize::MAX - 0xff;
+
+/// The reserved copy data type used to query [`PeerInfo`] through
+/// [`Window::query_info`].
+pub const INFO_TY: usize = RESERVED_TY;
+
+/// The reserved copy data type used to send a request through
+/// [`Window::request`].
+pub const REQUEST_TY: usize = RESERVED_TY + 1;
+
+/// The reserved copy data type used to respond to a request through
+/// [`Reply::send`].
+pub const REPLY_TY: usize = RESERVED_TY + 2;
+
+/// The reserved copy data type used to send a typed message through
+/// [`Window::send_message`].
+#[cfg(feature = "serde")]
+pub const MESSAGE_TY: usize = RESERVED_TY + 3;
+
+/// The reserved copy data type used to forward arguments to a running
+/// instance through [`tools::single_instance`].
+///
+/// [`tools::single_instance`]: crate::tools::single_instance
+pub const ARGS_TY: usize = RESERVED_TY + 4;
+
+/// The reserved copy data type used to send a chunk of data through
+/// [`Window::copy_data_chunked`].
+pub const CHUNK_TY: usize = RESERVED_TY + 5;
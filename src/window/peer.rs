@@ -0,0 +1,75 @@
+use std::ffi::OsString;
+use std::path::PathBuf;
+
+use windows_sys::Win32::Foundation::{CloseHandle, FALSE, HWND};
+use windows_sys::Win32::System::Threading::{
+    OpenProcess, QueryFullProcessImageNameW, PROCESS_NAME_WIN32, PROCESS_QUERY_LIMITED_INFORMATION,
+};
+use windows_sys::Win32::UI::WindowsAndMessaging::GetWindowThreadProcessId;
+
+use crate::convert::FromWide;
+
+use super::Window;
+
+/// The process which copied data to the current process, as provided through
+/// [`Event::CopyData`].
+///
+/// Note that the window is provided by the sender, so this can be used to
+/// reject data from processes which are not trusted but not to prove where
+/// the data came from.
+///
+/// [`Event::CopyData`]: crate::Event::CopyData
+#[derive(Debug)]
+#[non_exhaustive]
+pub struct Peer {
+    /// The window which sent the data.
+    pub window: Window,
+    /// The identifier of the process owning the window.
+    pub process_id: u32,
+    /// The path to the executable of the process, if it could be determined.
+    pub executable: Option<PathBuf>,
+}
+
+impl Peer {
+    /// Resolve the process owning the given window.
+    ///
+    /// # Safety
+    ///
+    /// This must be called while the window is guaranteed to be alive, such
+    /// as while processing a message sent by it.
+    pub(crate) unsafe fn from_hwnd(hwnd: HWND) -> Option<Self> {
+        let mut process_id = 0;
+
+        if GetWindowThreadProcessId(hwnd, &mut process_id) == 0 {
+            return None;
+        }
+
+        Some(Self {
+            window: Window::new(hwnd),
+            process_id,
+            executable: executable(process_id),
+        })
+    }
+}
+
+/// Get the path to the executable of the given process.
+unsafe fn executable(process_id: u32) -> Option<PathBuf> {
+    let handle = OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, FALSE, process_id);
+
+    if handle == 0 {
+        return None;
+    }
+
+    let mut path = vec![0u16; 1024];
+    let mut len = path.len() as u32;
+
+    let result =
+        QueryFullProcessImageNameW(handle, PROCESS_NAME_WIN32, path.as_mut_ptr(), &mut len);
+    CloseHandle(handle);
+
+    if result == FALSE {
+        return None;
+    }
+
+    Some(PathBuf::from(OsString::from_wide(&path[..len as usize])))
+}
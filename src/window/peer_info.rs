@@ -0,0 +1,185 @@
+use std::str;
+
+/// Magic prefix of an encoded [`PeerInfo`].
+const MAGIC: [u8; 4] = *b"wctx";
+/// The version of the encoding.
+const FORMAT: u32 = 1;
+/// Flag set if the peer listens to clipboard events.
+const CLIPBOARD_EVENTS: u32 = 0x1;
+
+/// Information about a window constructed by winctx, as returned by
+/// [`Window::query_info`].
+///
+/// [`Window::query_info`]: crate::window::Window::query_info
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct PeerInfo {
+    /// The version of winctx used by the peer.
+    pub crate_version: String,
+    /// The version of the application as specified using
+    /// [`CreateWindow::app_version`].
+    ///
+    /// [`CreateWindow::app_version`]: crate::CreateWindow::app_version
+    pub app_version: Option<String>,
+    /// The notify icon version negotiated with the shell.
+    pub notify_icon_version: u32,
+    /// Whether the peer listens to clipboard events.
+    pub clipboard_events: bool,
+}
+
+impl PeerInfo {
+    /// Encode the peer information.
+    pub(crate) fn encode(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        out.extend_from_slice(&MAGIC);
+        out.extend_from_slice(&FORMAT.to_le_bytes());
+        encode_str(&mut out, Some(&self.crate_version));
+        encode_str(&mut out, self.app_version.as_deref());
+        out.extend_from_slice(&self.notify_icon_version.to_le_bytes());
+
+        let mut flags = 0;
+
+        if self.clipboard_events {
+            flags |= CLIPBOARD_EVENTS;
+        }
+
+        out.extend_from_slice(&flags.to_le_bytes());
+        out
+    }
+
+    /// Decode peer information, returning `None` if it is malformed.
+    pub(crate) fn decode(mut data: &[u8]) -> Option<Self> {
+        if take(&mut data, MAGIC.len())? != MAGIC || decode_u32(&mut data)? != FORMAT {
+            return None;
+        }
+
+        let crate_version = decode_str(&mut data)?.unwrap_or_default();
+        let app_version = decode_str(&mut data)?;
+        let notify_icon_version = decode_u32(&mut data)?;
+        let flags = decode_u32(&mut data)?;
+
+        Some(Self {
+            crate_version,
+            app_version,
+            notify_icon_version,
+            clipboard_events: flags & CLIPBOARD_EVENTS != 0,
+        })
+    }
+}
+
+fn encode_str(out: &mut Vec<u8>, string: Option<&str>) {
+    let Some(string) = string else {
+        out.extend_from_slice(&u32::MAX.to_le_bytes());
+        return;
+    };
+
+    out.extend_from_slice(&(string.len() as u32).to_le_bytes());
+    out.extend_from_slice(string.as_bytes());
+}
+
+fn decode_str(data: &mut &[u8]) -> Option<Option<String>> {
+    let len = decode_u32(data)?;
+
+    if len == u32::MAX {
+        return Some(None);
+    }
+
+    let string = str::from_utf8(take(data, len as usize)?).ok()?;
+    Some(Some(string.to_owned()))
+}
+
+fn decode_u32(data: &mut &[u8]) -> Option<u32> {
+    Some(u32::from_le_bytes(take(data, 4)?.try_into().ok()?))
+}
+
+fn take<'a>(data: &mut &'a [u8], n: usize) -> Option<&'a [u8]> {
+    if data.len() < n {
+        return None;
+    }
+
+    let (head, tail) = data.split_at(n);
+    *data = tail;
+    Some(head)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::PeerInfo;
+
+    fn info(app_version: Option<&str>) -> PeerInfo {
+        PeerInfo {
+            crate_version: String::from("0.0.19"),
+            app_version: app_version.map(str::to_owned),
+            notify_icon_version: 4,
+            clipboard_events: true,
+        }
+    }
+
+    #[test]
+    fn round_trip() {
+        for info in [info(Some("1.2.3")), info(Some("")), info(None)] {
+            assert_eq!(PeerInfo::decode(&info.encode()), Some(info));
+        }
+
+        let info = PeerInfo {
+            clipboard_events: false,
+            ..info(None)
+        };
+
+        assert_eq!(PeerInfo::decode(&info.encode()), Some(info));
+    }
+
+    #[test]
+    fn wire_format() {
+        // NB: This must not change, since peers might be built with different
+        // versions of winctx.
+        let expected = [
+            b'w', b'c', b't', b'x', // magic
+            1, 0, 0, 0, // format
+            6, 0, 0, 0, b'0', b'.', b'0', b'.', b'1', b'9', // crate version
+            0xff, 0xff, 0xff, 0xff, // no app version
+            4, 0, 0, 0, // notify icon version
+            1, 0, 0, 0, // flags
+        ];
+
+        assert_eq!(info(None).encode(), expected);
+    }
+
+    #[test]
+    fn trailing_data() {
+        // Fields appended by future versions are ignored.
+        let mut data = info(Some("1.0")).encode();
+        data.extend_from_slice(&[1, 2, 3, 4]);
+        assert_eq!(PeerInfo::decode(&data), Some(info(Some("1.0"))));
+    }
+
+    #[test]
+    fn truncated() {
+        let data = info(Some("1.0")).encode();
+
+        for len in 0..data.len() {
+            assert_eq!(PeerInfo::decode(&data[..len]), None, "length {len}");
+        }
+    }
+
+    #[test]
+    fn malformed() {
+        let data = info(Some("1.0")).encode();
+
+        let mut bad_magic = data.clone();
+        bad_magic[0] = b'W';
+        assert_eq!(PeerInfo::decode(&bad_magic), None);
+
+        let mut bad_format = data.clone();
+        bad_format[4] = 2;
+        assert_eq!(PeerInfo::decode(&bad_format), None);
+
+        let mut bad_utf8 = data.clone();
+        bad_utf8[12] = 0xff;
+        assert_eq!(PeerInfo::decode(&bad_utf8), None);
+
+        let mut bad_len = data;
+        bad_len[8..12].copy_from_slice(&(u32::MAX - 1).to_le_bytes());
+        assert_eq!(PeerInfo::decode(&bad_len), None);
+    }
+}
@@ -0,0 +1,16 @@
+/// A raw window message received by a window constructed by winctx, as passed
+/// to the filter installed with [`CreateWindow::message_filter`].
+///
+/// [`CreateWindow::message_filter`]: crate::CreateWindow::message_filter
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct RawMessage {
+    /// The handle of the window which received the message.
+    pub hwnd: isize,
+    /// The identifier of the message, such as `WM_POWERBROADCAST`.
+    pub message: u32,
+    /// The first parameter of the message.
+    pub wparam: usize,
+    /// The second parameter of the message.
+    pub lparam: isize,
+}
@@ -0,0 +1,95 @@
+use std::fmt;
+use std::io;
+use std::ptr;
+
+use windows_sys::Win32::Foundation::{GetLastError, ERROR_TIMEOUT, HWND, LPARAM};
+use windows_sys::Win32::System::DataExchange::COPYDATASTRUCT;
+use windows_sys::Win32::UI::WindowsAndMessaging as winuser;
+
+use super::REPLY_TY;
+
+/// Timeout in milliseconds used when sending a reply.
+const REPLY_TIMEOUT: u32 = 1000;
+
+/// A handle used to reply to a request sent through [`Window::request`].
+///
+/// This is provided through [`Event::CopyData`].
+///
+/// [`Window::request`]: crate::window::Window::request
+/// [`Event::CopyData`]: crate::Event::CopyData
+pub struct Reply {
+    hwnd: HWND,
+}
+
+impl Reply {
+    #[inline]
+    pub(crate) fn new(hwnd: HWND) -> Self {
+        Self { hwnd }
+    }
+
+    /// Send a reply to the process which sent the request.
+    ///
+    /// This fails if the requesting process has stopped waiting for the
+    /// reply, such as if the request timed out.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use winctx::{CreateWindow, Event};
+    ///
+    /// # async fn test() -> winctx::Result<()> {
+    /// let (sender, mut event_loop) = CreateWindow::new("se.tedro.Example").build().await?;
+    ///
+    /// loop {
+    ///     if let Event::CopyData { data, reply: Some(reply), .. } = event_loop.tick().await? {
+    ///         if let Err(error) = reply.send(&data) {
+    ///             println!("Failed to reply: {error}");
+    ///         }
+    ///     }
+    /// }
+    /// # Ok(()) }
+    /// ```
+    pub fn send(self, bytes: &[u8]) -> io::Result<()> {
+        // SAFETY: All arguments are correctly populated, and the data is
+        // copied by the system before this returns.
+        unsafe {
+            let data = COPYDATASTRUCT {
+                dwData: REPLY_TY,
+                cbData: bytes.len() as u32,
+                lpData: bytes.as_ptr() as *mut _,
+            };
+
+            let result = winuser::SendMessageTimeoutW(
+                self.hwnd,
+                winuser::WM_COPYDATA,
+                0,
+                &data as *const _ as LPARAM,
+                winuser::SMTO_NORMAL,
+                REPLY_TIMEOUT,
+                ptr::null_mut(),
+            );
+
+            if result == 0 {
+                let code = GetLastError();
+
+                if code == 0 || code == ERROR_TIMEOUT {
+                    return Err(io::Error::new(
+                        io::ErrorKind::TimedOut,
+                        "Timed out sending reply",
+                    ));
+                }
+
+                return Err(io::Error::from_raw_os_error(code as i32));
+            }
+
+            Ok(())
+        }
+    }
+}
+
+impl fmt::Debug for Reply {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Reply").field("hwnd", &self.hwnd).finish()
+    }
+}
@@ -1,17 +1,30 @@
+use std::cell::OnceCell;
 use std::ffi::OsStr;
 use std::fmt;
 use std::io;
+use std::mem::{size_of, MaybeUninit};
 use std::ptr;
+use std::slice;
+use std::time::{Duration, Instant};
 
 use windows_sys::Win32::Foundation::GetLastError;
-use windows_sys::Win32::Foundation::HWND;
+use windows_sys::Win32::Foundation::{
+    ERROR_CLASS_ALREADY_EXISTS, ERROR_TIMEOUT, FALSE, HWND, LPARAM, LRESULT, WAIT_FAILED, WPARAM,
+};
 use windows_sys::Win32::System::DataExchange::COPYDATASTRUCT;
+use windows_sys::Win32::UI::WindowsAndMessaging as winuser;
 use windows_sys::Win32::UI::WindowsAndMessaging::FindWindowExW;
 use windows_sys::Win32::UI::WindowsAndMessaging::SendMessageW;
 use windows_sys::Win32::UI::WindowsAndMessaging::WM_COPYDATA;
 
 use crate::convert::ToWide;
 
+use super::chunk::{ChunkHeader, CHUNK_SIZE, MAX_TRANSFER_LEN};
+use super::{PeerInfo, CHUNK_TY, INFO_TY, REPLY_TY, REQUEST_TY};
+
+/// The class of the temporary window used to receive replies.
+const REPLY_CLASS: &str = "se.tedro.winctx.Reply";
+
 /// Helper to find windows by title or class.
 #[derive(Default)]
 pub struct FindWindow {
@@ -75,7 +88,7 @@ impl FindWindow {
         self
     }
 
-    /// Construct an iterator over all matching windows.
+    /// Find the first matching window.
     ///
     /// # Examples
     ///
@@ -86,11 +99,38 @@ impl FindWindow {
     /// # Ok::<_, std::io::Error>(())
     /// ```
     pub fn find(&self) -> io::Result<Option<Window>> {
+        self.find_after(0)
+    }
+
+    /// Construct an iterator over all matching windows.
+    ///
+    /// This can be used to broadcast to every running instance of an
+    /// application.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use winctx::window::FindWindow;
+    ///
+    /// for window in FindWindow::new().class("se.tedro.Example").find_all() {
+    ///     window?.copy_data(42, b"foobar")?;
+    /// }
+    /// # Ok::<_, std::io::Error>(())
+    /// ```
+    pub fn find_all(&self) -> FindAll<'_> {
+        FindAll {
+            find: self,
+            after: Some(0),
+        }
+    }
+
+    /// Find the first matching window after the given window.
+    fn find_after(&self, after: HWND) -> io::Result<Option<Window>> {
         // SAFETY: All arguments are correctly popuplated by this builder.
         unsafe {
             let hwnd = FindWindowExW(
                 0,
-                0,
+                after,
                 self.class.as_ref().map_or(ptr::null(), |c| c.as_ptr()),
                 self.title.as_ref().map_or(ptr::null(), |c| c.as_ptr()),
             );
@@ -110,17 +150,146 @@ impl FindWindow {
     }
 }
 
+/// An iterator over all matching windows, as returned by
+/// [`FindWindow::find_all`].
+pub struct FindAll<'a> {
+    find: &'a FindWindow,
+    after: Option<HWND>,
+}
+
+impl Iterator for FindAll<'_> {
+    type Item = io::Result<Window>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let after = self.after.take()?;
+
+        match self.find.find_after(after) {
+            Ok(Some(window)) => {
+                self.after = Some(window.hwnd);
+                Some(Ok(window))
+            }
+            Ok(None) => None,
+            Err(error) => Some(Err(error)),
+        }
+    }
+}
+
 /// Handle to a window on the system.
 pub struct Window {
     hwnd: HWND,
 }
 
+/// Register a window message with the given name.
+///
+/// The same identifier is returned for the same name by every process in the
+/// current session, so it can be used to exchange lightweight signals through
+/// [`Window::post_message`] without copying any data. A window constructed by
+/// winctx only receives messages registered through
+/// [`CreateWindow::register_message`].
+///
+/// [`CreateWindow::register_message`]: crate::CreateWindow::register_message
+///
+/// # Examples
+///
+/// ```no_run
+/// use winctx::window;
+///
+/// let ping = window::register_message("se.tedro.Example.Ping")?;
+/// # Ok::<_, std::io::Error>(())
+/// ```
+pub fn register_message<N>(name: N) -> io::Result<u32>
+where
+    N: AsRef<OsStr>,
+{
+    let name = name.as_ref().to_wide_null();
+
+    // SAFETY: The name is a valid null-terminated wide string.
+    let id = unsafe { winuser::RegisterWindowMessageW(name.as_ptr()) };
+
+    if id == 0 {
+        return Err(io::Error::last_os_error());
+    }
+
+    Ok(id)
+}
+
 impl Window {
+    #[inline]
+    pub(crate) fn new(hwnd: HWND) -> Self {
+        Self { hwnd }
+    }
+
+    /// Get the raw `HWND` of the window.
+    ///
+    /// This is intended for interoperability with existing Win32 code. The
+    /// handle is only valid for as long as the window exists, and for windows
+    /// built by winctx it must not be used to destroy the window or to change
+    /// its window procedure or user data, since winctx relies on them.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use winctx::CreateWindow;
+    ///
+    /// # async fn test() -> winctx::Result<()> {
+    /// let (sender, _) = CreateWindow::new("se.tedro.Example").build().await?;
+    /// let hwnd = sender.window().hwnd();
+    /// # Ok(()) }
+    /// ```
+    #[inline]
+    pub fn hwnd(&self) -> isize {
+        self.hwnd
+    }
+
+    /// Post a message registered through [`register_message`] to the window.
+    ///
+    /// The message is received as an [`Event::AppMessage`] event by a window
+    /// which registered the same message through
+    /// [`CreateWindow::register_message`].
+    ///
+    /// [`register_message`]: super::register_message
+    /// [`Event::AppMessage`]: crate::Event::AppMessage
+    /// [`CreateWindow::register_message`]: crate::CreateWindow::register_message
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use winctx::window::{self, FindWindow};
+    ///
+    /// let ping = window::register_message("se.tedro.Example.Ping")?;
+    ///
+    /// let Some(window) = FindWindow::new().class("se.tedro.Example").find()? else {
+    ///     println!("Could not find window");
+    ///     return Ok(());
+    /// };
+    ///
+    /// window.post_message(ping, 0, 0)?;
+    /// # Ok::<_, std::io::Error>(())
+    /// ```
+    pub fn post_message(&self, id: u32, wparam: usize, lparam: isize) -> io::Result<()> {
+        // SAFETY: Posting a message is safe for any window handle, and fails
+        // if the window no longer exists.
+        unsafe {
+            if winuser::PostMessageW(self.hwnd, id, wparam, lparam) == FALSE {
+                return Err(io::Error::last_os_error());
+            }
+        }
+
+        Ok(())
+    }
+
     /// Copy bytes to the given process.
     ///
-    /// Data is received as an [`Event::CopyData`] event.
+    /// Data is received as an [`Event::CopyData`] event. Types starting at
+    /// [`RESERVED_TY`] are reserved for use by winctx and will not be
+    /// delivered.
+    ///
+    /// The data is sent along with a message-only window owned by the current
+    /// thread, which allows the receiver to identify the sending process. The
+    /// window is created on first use and reused by later calls.
     ///
     /// [`Event::CopyData`]: crate::Event::CopyData
+    /// [`RESERVED_TY`]: super::RESERVED_TY
     ///
     /// # Examples
     ///
@@ -136,7 +305,10 @@ impl Window {
     /// # Ok::<_, std::io::Error>(())
     /// ```
     pub fn copy_data(&self, ty: usize, bytes: &[u8]) -> io::Result<()> {
-        // SAFETY: All arguments are correctly popuplated by this builder.
+        let sender = sender_window()?;
+
+        // SAFETY: All arguments are correctly popuplated by this builder, and
+        // the sender window is owned by and only used on the current thread.
         unsafe {
             let data = COPYDATASTRUCT {
                 dwData: ty,
@@ -144,10 +316,480 @@ impl Window {
                 lpData: (bytes.as_ptr() as *mut u8).cast(),
             };
 
-            SendMessageW(self.hwnd, WM_COPYDATA, 0, &data as *const _ as isize);
+            SendMessageW(
+                self.hwnd,
+                WM_COPYDATA,
+                sender as WPARAM,
+                &data as *const _ as isize,
+            );
+
+            Ok(())
+        }
+    }
+
+    /// Copy bytes to the given process without blocking.
+    ///
+    /// This is a fire-and-forget variant of [`Window::copy_data`] which never
+    /// stalls the caller, even if the receiving window is busy. The data is
+    /// handed off to a marshalling thread owned by winctx which copies it in
+    /// the order it was posted, and any errors raised while copying are
+    /// discarded.
+    ///
+    /// # Errors
+    ///
+    /// Errors if the marshalling thread could not be started.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use winctx::window::FindWindow;
+    ///
+    /// let Some(window) = FindWindow::new().class("se.tedro.Example").find()? else {
+    ///     println!("Could not find window");
+    ///     return Ok(());
+    /// };
+    ///
+    /// window.post_data(42, b"foobar")?;
+    /// # Ok::<_, std::io::Error>(())
+    /// ```
+    pub fn post_data(&self, ty: usize, bytes: &[u8]) -> io::Result<()> {
+        super::marshal::post(Window::new(self.hwnd), ty, bytes.to_vec())
+    }
+
+    /// Copy bytes to the given process, split into chunks.
+    ///
+    /// This should be used for large payloads, which are otherwise copied in a
+    /// single message. The chunks are reassembled by the receiving window,
+    /// which reports progress through [`Event::CopyDataProgress`] and then
+    /// delivers the whole payload as an [`Event::CopyData`] event once it has
+    /// been received.
+    ///
+    /// Payloads can be at most 256 MiB long. An error is returned if the
+    /// payload is longer than that, or if the receiver rejects a chunk such as
+    /// if it's not a window constructed by winctx.
+    ///
+    /// [`Event::CopyDataProgress`]: crate::Event::CopyDataProgress
+    /// [`Event::CopyData`]: crate::Event::CopyData
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use winctx::window::FindWindow;
+    ///
+    /// let Some(window) = FindWindow::new().class("se.tedro.Example").find()? else {
+    ///     println!("Could not find window");
+    ///     return Ok(());
+    /// };
+    ///
+    /// let data = std::fs::read("large.bin")?;
+    /// window.copy_data_chunked(42, &data)?;
+    /// # Ok::<_, std::io::Error>(())
+    /// ```
+    pub fn copy_data_chunked(&self, ty: usize, bytes: &[u8]) -> io::Result<()> {
+        if bytes.len() as u64 > MAX_TRANSFER_LEN {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "Payload is too large to be transferred",
+            ));
+        }
+
+        let id = ChunkHeader::new_id();
+        let sender = sender_window()?;
+
+        // SAFETY: All arguments are correctly populated, and the sender window
+        // is owned by and only used on the current thread.
+        unsafe {
+            // NB: An empty payload is still sent as a single empty chunk.
+            let chunks = bytes
+                .chunks(CHUNK_SIZE)
+                .chain(bytes.is_empty().then_some(&[][..]));
+
+            for (index, chunk) in chunks.enumerate() {
+                let header = ChunkHeader {
+                    id,
+                    index: index as u32,
+                    ty,
+                    total: bytes.len() as u64,
+                };
+
+                let mut chunk = header.encode(chunk);
+
+                let data = COPYDATASTRUCT {
+                    dwData: CHUNK_TY,
+                    cbData: chunk.len() as u32,
+                    lpData: chunk.as_mut_ptr().cast(),
+                };
+
+                let result = SendMessageW(
+                    self.hwnd,
+                    WM_COPYDATA,
+                    sender as WPARAM,
+                    &data as *const _ as LPARAM,
+                );
+
+                if result == 0 {
+                    return Err(io::Error::new(
+                        io::ErrorKind::ConnectionAborted,
+                        "Chunk was rejected by window",
+                    ));
+                }
+            }
+
             Ok(())
         }
     }
+
+    /// Send a typed message to the given process.
+    ///
+    /// The message is serialized and sent along with the given tag, which the
+    /// receiver uses to determine the type of the message. It is received as
+    /// an [`Event::Message`] event.
+    ///
+    /// [`Event::Message`]: crate::Event::Message
+    ///
+    /// # Examples
+    ///
+    /// Forward the arguments of the current process to a running instance:
+    ///
+    /// ```no_run
+    /// use winctx::window::FindWindow;
+    ///
+    /// let Some(window) = FindWindow::new().class("se.tedro.Example").find()? else {
+    ///     println!("Could not find window");
+    ///     return Ok(());
+    /// };
+    ///
+    /// let args = std::env::args().collect::<Vec<_>>();
+    /// window.send_message("args", &args)?;
+    /// # Ok::<_, std::io::Error>(())
+    /// ```
+    #[cfg(feature = "serde")]
+    pub fn send_message<T>(&self, tag: &str, message: &T) -> io::Result<()>
+    where
+        T: ?Sized + serde::Serialize,
+    {
+        let bytes = super::message::encode(tag, message)?;
+        self.copy_data(super::MESSAGE_TY, &bytes)
+    }
+
+    /// Forward the given arguments to the process, which receives them as an
+    /// [`Event::Arguments`] event.
+    ///
+    /// [`Event::Arguments`]: crate::Event::Arguments
+    pub(crate) fn forward_args<I>(&self, args: I) -> io::Result<()>
+    where
+        I: IntoIterator,
+        I::Item: AsRef<OsStr>,
+    {
+        self.copy_data(super::ARGS_TY, &super::args::encode(args))
+    }
+
+    /// Copy bytes to the given process and wait for it to reply.
+    ///
+    /// The request is received as an [`Event::CopyData`] event which carries a
+    /// [`Reply`] handle, and the bytes passed to [`Reply::send`] are returned
+    /// from this function. If no reply is received within the given timeout
+    /// an error of kind [`io::ErrorKind::TimedOut`] is returned.
+    ///
+    /// [`Event::CopyData`]: crate::Event::CopyData
+    /// [`Reply`]: super::Reply
+    /// [`Reply::send`]: super::Reply::send
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use std::time::Duration;
+    ///
+    /// use winctx::window::FindWindow;
+    ///
+    /// let Some(window) = FindWindow::new().class("se.tedro.Example").find()? else {
+    ///     println!("Could not find window");
+    ///     return Ok(());
+    /// };
+    ///
+    /// let response = window.request(42, b"ping", Duration::from_secs(5))?;
+    /// println!("Response: {response:?}");
+    /// # Ok::<_, std::io::Error>(())
+    /// ```
+    pub fn request(&self, ty: usize, bytes: &[u8], timeout: Duration) -> io::Result<Vec<u8>> {
+        let deadline = Instant::now() + timeout;
+
+        // NB: The type of the request is sent as a prefix to the data.
+        let mut payload = Vec::with_capacity(size_of::<usize>() + bytes.len());
+        payload.extend_from_slice(&ty.to_ne_bytes());
+        payload.extend_from_slice(bytes);
+
+        // SAFETY: The reply window is owned by and only used on the current
+        // thread, and the reply storage outlives it.
+        unsafe {
+            let mut reply = None::<Vec<u8>>;
+            let window = ReplyWindow::new(&mut reply)?;
+
+            let data = COPYDATASTRUCT {
+                dwData: REQUEST_TY,
+                cbData: payload.len() as u32,
+                lpData: payload.as_mut_ptr().cast(),
+            };
+
+            let result = winuser::SendMessageTimeoutW(
+                self.hwnd,
+                WM_COPYDATA,
+                window.hwnd as WPARAM,
+                &data as *const _ as LPARAM,
+                winuser::SMTO_NORMAL,
+                millis(timeout),
+                ptr::null_mut(),
+            );
+
+            if result == 0 {
+                let code = GetLastError();
+
+                if code == 0 || code == ERROR_TIMEOUT {
+                    return Err(io::Error::new(
+                        io::ErrorKind::TimedOut,
+                        "Timed out waiting for window",
+                    ));
+                }
+
+                return Err(io::Error::from_raw_os_error(code as i32));
+            }
+
+            // The reply is sent to the reply window once the peer has
+            // processed the request, so wait for sent messages and dispatch
+            // them until it arrives.
+            while reply.is_none() {
+                let remaining = deadline.saturating_duration_since(Instant::now());
+
+                if remaining.is_zero() {
+                    return Err(io::Error::new(
+                        io::ErrorKind::TimedOut,
+                        "Timed out waiting for reply",
+                    ));
+                }
+
+                let result = winuser::MsgWaitForMultipleObjects(
+                    0,
+                    ptr::null(),
+                    FALSE,
+                    millis(remaining),
+                    winuser::QS_SENDMESSAGE,
+                );
+
+                if result == WAIT_FAILED {
+                    return Err(io::Error::last_os_error());
+                }
+
+                let mut msg = MaybeUninit::zeroed();
+
+                winuser::PeekMessageW(
+                    msg.as_mut_ptr(),
+                    window.hwnd,
+                    0,
+                    0,
+                    winuser::PM_REMOVE | winuser::PM_QS_SENDMESSAGE,
+                );
+            }
+
+            drop(window);
+            Ok(reply.unwrap_or_default())
+        }
+    }
+
+    /// Query information about a window constructed by winctx, such as the
+    /// version of the application running it.
+    ///
+    /// This performs a handshake using the reserved [`INFO_TY`] copy data
+    /// type which is answered by winctx itself without involving the event
+    /// loop of the peer. If the peer doesn't answer within the given timeout
+    /// an error of kind [`io::ErrorKind::TimedOut`] is returned.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use std::time::Duration;
+    ///
+    /// use winctx::window::FindWindow;
+    ///
+    /// let Some(window) = FindWindow::new().class("se.tedro.Example").find()? else {
+    ///     println!("Could not find window");
+    ///     return Ok(());
+    /// };
+    ///
+    /// let info = window.query_info(Duration::from_secs(1))?;
+    /// println!("Running version: {:?}", info.app_version);
+    /// # Ok::<_, std::io::Error>(())
+    /// ```
+    pub fn query_info(&self, timeout: Duration) -> io::Result<PeerInfo> {
+        let timeout = millis(timeout);
+
+        // SAFETY: The reply window is owned by and only used on the current
+        // thread, and the reply storage outlives it.
+        unsafe {
+            let mut reply = None::<Vec<u8>>;
+            let window = ReplyWindow::new(&mut reply)?;
+
+            let data = COPYDATASTRUCT {
+                dwData: INFO_TY,
+                cbData: 0,
+                lpData: ptr::null_mut(),
+            };
+
+            // While blocked, the current thread still processes messages sent
+            // to the reply window.
+            let result = winuser::SendMessageTimeoutW(
+                self.hwnd,
+                WM_COPYDATA,
+                window.hwnd as WPARAM,
+                &data as *const _ as LPARAM,
+                winuser::SMTO_NORMAL,
+                timeout,
+                ptr::null_mut(),
+            );
+
+            let code = GetLastError();
+            drop(window);
+
+            if result == 0 {
+                if code == 0 || code == ERROR_TIMEOUT {
+                    return Err(io::Error::new(
+                        io::ErrorKind::TimedOut,
+                        "Timed out waiting for window",
+                    ));
+                }
+
+                return Err(io::Error::from_raw_os_error(code as i32));
+            }
+
+            let Some(reply) = reply else {
+                return Err(io::Error::new(
+                    io::ErrorKind::Unsupported,
+                    "Window did not respond to the handshake",
+                ));
+            };
+
+            PeerInfo::decode(&reply).ok_or_else(|| {
+                io::Error::new(io::ErrorKind::InvalidData, "Malformed handshake response")
+            })
+        }
+    }
+}
+
+/// A temporary message-only window used to receive a reply.
+struct ReplyWindow {
+    hwnd: HWND,
+}
+
+impl ReplyWindow {
+    unsafe fn new(reply: &mut Option<Vec<u8>>) -> io::Result<Self> {
+        let window = Self::create()?;
+        winuser::SetWindowLongPtrW(
+            window.hwnd,
+            winuser::GWLP_USERDATA,
+            reply as *mut _ as isize,
+        );
+        Ok(window)
+    }
+
+    /// Create a window which doesn't receive replies.
+    unsafe fn create() -> io::Result<Self> {
+        let class_name = REPLY_CLASS.to_wide_null();
+
+        let class = winuser::WNDCLASSW {
+            style: 0,
+            lpfnWndProc: Some(reply_window_proc),
+            cbClsExtra: 0,
+            cbWndExtra: 0,
+            hInstance: 0,
+            hIcon: 0,
+            hCursor: 0,
+            hbrBackground: 0,
+            lpszMenuName: ptr::null(),
+            lpszClassName: class_name.as_ptr(),
+        };
+
+        if winuser::RegisterClassW(&class) == 0 && GetLastError() != ERROR_CLASS_ALREADY_EXISTS {
+            return Err(io::Error::last_os_error());
+        }
+
+        let hwnd = winuser::CreateWindowExW(
+            0,
+            class_name.as_ptr(),
+            ptr::null(),
+            0,
+            0,
+            0,
+            0,
+            0,
+            winuser::HWND_MESSAGE,
+            0,
+            0,
+            ptr::null(),
+        );
+
+        if hwnd == 0 {
+            return Err(io::Error::last_os_error());
+        }
+
+        Ok(Self { hwnd })
+    }
+}
+
+/// Get the window of the current thread which is sent along with data which
+/// doesn't expect a reply, so that the receiver can identify the sender.
+fn sender_window() -> io::Result<HWND> {
+    thread_local! {
+        static SENDER: OnceCell<ReplyWindow> = const { OnceCell::new() };
+    }
+
+    SENDER.with(|sender| {
+        if let Some(window) = sender.get() {
+            return Ok(window.hwnd);
+        }
+
+        // SAFETY: The window is owned by the current thread, and is destroyed
+        // by the same thread when it exits.
+        let window = unsafe { ReplyWindow::create()? };
+        Ok(sender.get_or_init(|| window).hwnd)
+    })
+}
+
+impl Drop for ReplyWindow {
+    fn drop(&mut self) {
+        unsafe {
+            winuser::SetWindowLongPtrW(self.hwnd, winuser::GWLP_USERDATA, 0);
+            winuser::DestroyWindow(self.hwnd);
+        }
+    }
+}
+
+unsafe extern "system" fn reply_window_proc(
+    hwnd: HWND,
+    msg: u32,
+    w_param: WPARAM,
+    l_param: LPARAM,
+) -> LRESULT {
+    if msg == WM_COPYDATA {
+        let reply =
+            winuser::GetWindowLongPtrW(hwnd, winuser::GWLP_USERDATA) as *mut Option<Vec<u8>>;
+        let data = &*(l_param as *const COPYDATASTRUCT);
+
+        if let (Some(reply), INFO_TY | REPLY_TY) = (reply.as_mut(), data.dwData) {
+            let bytes = if data.cbData == 0 {
+                &[][..]
+            } else {
+                slice::from_raw_parts(data.lpData.cast::<u8>(), data.cbData as usize)
+            };
+
+            *reply = Some(bytes.to_vec());
+            return 1;
+        }
+    }
+
+    winuser::DefWindowProcW(hwnd, msg, w_param, l_param)
+}
+
+fn millis(duration: Duration) -> u32 {
+    u32::try_from(duration.as_millis()).unwrap_or(u32::MAX)
 }
 
 impl fmt::Debug for Window {
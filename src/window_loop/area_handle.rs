@@ -1,19 +1,42 @@
 use crate::AreaId;
 
-use super::PopupMenuHandle;
+use super::{AreaMenu, PopupMenuHandle};
 
 #[repr(C)]
 pub(crate) struct AreaHandle {
     pub(crate) area_id: AreaId,
     pub(crate) popup_menu: Option<PopupMenuHandle>,
+    pub(crate) take_foreground: bool,
+    pub(crate) double_click_default: bool,
 }
 
 impl AreaHandle {
     /// Construct a new menu handle.
-    pub(crate) fn new(area_id: AreaId, popup_menu: Option<PopupMenuHandle>) -> Self {
+    pub(crate) fn new(
+        area_id: AreaId,
+        popup_menu: Option<PopupMenuHandle>,
+        take_foreground: bool,
+        double_click_default: bool,
+    ) -> Self {
         Self {
             area_id,
             popup_menu,
+            take_foreground,
+            double_click_default,
         }
     }
+
+    /// Construct the state of the popup menu which is used by the window
+    /// thread.
+    pub(super) fn menu(&self) -> Option<AreaMenu> {
+        let popup_menu = self.popup_menu.as_ref()?;
+
+        Some(AreaMenu {
+            hmenu: popup_menu.hmenu,
+            open_menu: popup_menu.open_menu.copy_data(),
+            track_flags: popup_menu.track_flags,
+            take_foreground: self.take_foreground,
+            double_click_default: self.double_click_default,
+        })
+    }
 }
@@ -0,0 +1,375 @@
+use std::ffi::c_void;
+use std::io;
+use std::mem::{size_of, MaybeUninit};
+use std::ptr;
+use std::slice;
+
+use windows_sys::Win32::Foundation::{FALSE, RECT, SIZE, TRUE};
+use windows_sys::Win32::Graphics::Gdi as gdi;
+use windows_sys::Win32::UI::WindowsAndMessaging as winuser;
+
+use crate::convert::ToWide;
+use crate::Badge;
+
+use super::IconHandle;
+
+/// The color of the badge as `0xRRGGBB`.
+const BADGE_COLOR: u32 = 0xd13438;
+/// The color of the text in the badge as `0xRRGGBB`.
+const TEXT_COLOR: u32 = 0xffffff;
+
+/// Render a badge in the bottom right corner of the given icon, or of an
+/// empty small icon if none is given.
+pub(super) fn render(base: Option<&IconHandle>, badge: Badge) -> io::Result<IconHandle> {
+    unsafe {
+        let (width, height, mut pixels) = match base {
+            Some(icon) => read_icon(icon)?,
+            None => {
+                let width = winuser::GetSystemMetrics(winuser::SM_CXSMICON);
+                let height = winuser::GetSystemMetrics(winuser::SM_CYSMICON);
+                (width, height, vec![0; (width * height) as usize])
+            }
+        };
+
+        let mut canvas = Canvas {
+            width,
+            height,
+            pixels: &mut pixels,
+        };
+
+        match badge.text() {
+            Some(text) => draw_text_badge(&mut canvas, &text)?,
+            None => {
+                let r = height as f32 * 0.25;
+                let x = width as f32 - r;
+                let y = height as f32 - r;
+                canvas.fill_pill(x, x, y, r, BADGE_COLOR);
+            }
+        }
+
+        create_icon(width, height, &pixels)
+    }
+}
+
+/// A buffer of pixels in the `0xAARRGGBB` format with straight alpha, which
+/// is what 32-bit icons use.
+struct Canvas<'a> {
+    width: i32,
+    height: i32,
+    pixels: &'a mut [u32],
+}
+
+impl Canvas<'_> {
+    /// Fill a horizontal pill shape, which is the area within `r` of the
+    /// line between `x0` and `x1` at `y`.
+    fn fill_pill(&mut self, x0: f32, x1: f32, y: f32, r: f32, color: u32) {
+        for py in 0..self.height {
+            for px in 0..self.width {
+                let cx = px as f32 + 0.5;
+                let cy = py as f32 + 0.5;
+
+                let dx = if cx < x0 {
+                    x0 - cx
+                } else if cx > x1 {
+                    cx - x1
+                } else {
+                    0.0
+                };
+
+                let dy = cy - y;
+                let coverage = r - (dx * dx + dy * dy).sqrt() + 0.5;
+                self.blend(px, py, color, coverage);
+            }
+        }
+    }
+
+    /// Blend the given color onto a pixel with the given coverage.
+    fn blend(&mut self, x: i32, y: i32, color: u32, coverage: f32) {
+        if x < 0 || y < 0 || x >= self.width || y >= self.height {
+            return;
+        }
+
+        let sa = coverage.clamp(0.0, 1.0);
+
+        if sa <= 0.0 {
+            return;
+        }
+
+        let pixel = &mut self.pixels[(y * self.width + x) as usize];
+        let da = (*pixel >> 24) as f32 / 255.0;
+        let oa = sa + da * (1.0 - sa);
+
+        let channel = |shift: u32| {
+            let s = ((color >> shift) & 0xff) as f32;
+            let d = ((*pixel >> shift) & 0xff) as f32;
+            ((s * sa + d * da * (1.0 - sa)) / oa).round() as u32
+        };
+
+        *pixel =
+            ((oa * 255.0).round() as u32) << 24 | channel(16) << 16 | channel(8) << 8 | channel(0);
+    }
+}
+
+/// Draw a badge containing text in the bottom right corner of the canvas.
+///
+/// Text drawn by GDI doesn't preserve the alpha channel, so the text is drawn
+/// white on black into a separate bitmap which is used as its coverage.
+unsafe fn draw_text_badge(canvas: &mut Canvas<'_>, text: &str) -> io::Result<()> {
+    let text = text.encode_utf16().collect::<Vec<u16>>();
+    let badge_height = ((canvas.height as f32 * 0.6).round() as i32).max(1);
+
+    let hdc = gdi::CreateCompatibleDC(0);
+
+    if hdc == 0 {
+        return Err(io::Error::last_os_error());
+    }
+
+    let face = "Segoe UI".to_wide_null();
+
+    let font = gdi::CreateFontW(
+        -badge_height,
+        0,
+        0,
+        0,
+        gdi::FW_BOLD as i32,
+        0,
+        0,
+        0,
+        gdi::DEFAULT_CHARSET as u32,
+        gdi::OUT_DEFAULT_PRECIS as u32,
+        gdi::CLIP_DEFAULT_PRECIS as u32,
+        gdi::ANTIALIASED_QUALITY as u32,
+        gdi::DEFAULT_PITCH as u32,
+        face.as_ptr(),
+    );
+
+    if font == 0 {
+        let error = io::Error::last_os_error();
+        gdi::DeleteDC(hdc);
+        return Err(error);
+    }
+
+    let old_font = gdi::SelectObject(hdc, font);
+
+    let mut size = SIZE { cx: 0, cy: 0 };
+    gdi::GetTextExtentPoint32W(hdc, text.as_ptr(), text.len() as i32, &mut size);
+
+    let badge_width = (size.cx + badge_height / 2)
+        .max(badge_height)
+        .min(canvas.width);
+
+    let info = bitmap_info(badge_width, badge_height);
+    let mut bits = ptr::null_mut();
+    let hbitmap = gdi::CreateDIBSection(hdc, &info, gdi::DIB_RGB_COLORS, &mut bits, 0, 0);
+
+    if hbitmap == 0 {
+        let error = io::Error::last_os_error();
+        gdi::SelectObject(hdc, old_font);
+        gdi::DeleteObject(font);
+        gdi::DeleteDC(hdc);
+        return Err(error);
+    }
+
+    let coverage =
+        slice::from_raw_parts_mut(bits.cast::<u32>(), (badge_width * badge_height) as usize);
+
+    coverage.fill(0);
+
+    let old_bitmap = gdi::SelectObject(hdc, hbitmap);
+
+    gdi::SetTextColor(hdc, 0xffffff);
+    gdi::SetBkMode(hdc, gdi::TRANSPARENT as i32);
+
+    let mut rect = RECT {
+        left: 0,
+        top: 0,
+        right: badge_width,
+        bottom: badge_height,
+    };
+
+    gdi::DrawTextW(
+        hdc,
+        text.as_ptr(),
+        text.len() as i32,
+        &mut rect,
+        gdi::DT_CENTER | gdi::DT_VCENTER | gdi::DT_SINGLELINE | gdi::DT_NOPREFIX,
+    );
+
+    gdi::GdiFlush();
+
+    let left = canvas.width - badge_width;
+    let top = canvas.height - badge_height;
+    let r = badge_height as f32 / 2.0;
+
+    canvas.fill_pill(
+        left as f32 + r,
+        canvas.width as f32 - r,
+        top as f32 + r,
+        r,
+        BADGE_COLOR,
+    );
+
+    for y in 0..badge_height {
+        for x in 0..badge_width {
+            let value = coverage[(y * badge_width + x) as usize];
+            let value = ((value >> 8) & 0xff) as f32 / 255.0;
+            canvas.blend(left + x, top + y, TEXT_COLOR, value);
+        }
+    }
+
+    gdi::SelectObject(hdc, old_bitmap);
+    gdi::SelectObject(hdc, old_font);
+    gdi::DeleteObject(hbitmap);
+    gdi::DeleteObject(font);
+    gdi::DeleteDC(hdc);
+    Ok(())
+}
+
+/// Read the size and pixels of an icon.
+unsafe fn read_icon(icon: &IconHandle) -> io::Result<(i32, i32, Vec<u32>)> {
+    let mut info: winuser::ICONINFO = MaybeUninit::zeroed().assume_init();
+
+    if winuser::GetIconInfo(icon.hicon, &mut info) == FALSE {
+        return Err(io::Error::last_os_error());
+    }
+
+    let result = read_icon_bitmaps(info.hbmColor, info.hbmMask);
+
+    // NB: The bitmaps returned are copies owned by the caller.
+    if info.hbmColor != 0 {
+        gdi::DeleteObject(info.hbmColor);
+    }
+
+    gdi::DeleteObject(info.hbmMask);
+    result
+}
+
+unsafe fn read_icon_bitmaps(
+    color: gdi::HBITMAP,
+    mask: gdi::HBITMAP,
+) -> io::Result<(i32, i32, Vec<u32>)> {
+    if color == 0 {
+        return Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "Badges can't be drawn on monochrome icons",
+        ));
+    }
+
+    let mut bitmap: gdi::BITMAP = MaybeUninit::zeroed().assume_init();
+
+    let result = gdi::GetObjectW(
+        color,
+        size_of::<gdi::BITMAP>() as i32,
+        (&mut bitmap as *mut gdi::BITMAP).cast::<c_void>(),
+    );
+
+    if result == 0 {
+        return Err(io::Error::last_os_error());
+    }
+
+    let width = bitmap.bmWidth;
+    let height = bitmap.bmHeight;
+    let mut pixels = read_bits(color, width, height)?;
+
+    // NB: Icons without an alpha channel use their mask for transparency.
+    if pixels.iter().all(|pixel| pixel >> 24 == 0) {
+        let mask = read_bits(mask, width, height)?;
+
+        for (pixel, mask) in pixels.iter_mut().zip(mask) {
+            if mask & 0xffffff == 0 {
+                *pixel |= 0xff000000;
+            }
+        }
+    }
+
+    Ok((width, height, pixels))
+}
+
+/// Read the pixels of a bitmap as 32-bit pixels.
+unsafe fn read_bits(hbitmap: gdi::HBITMAP, width: i32, height: i32) -> io::Result<Vec<u32>> {
+    let hdc = gdi::CreateCompatibleDC(0);
+
+    if hdc == 0 {
+        return Err(io::Error::last_os_error());
+    }
+
+    let mut info = bitmap_info(width, height);
+    let mut pixels = vec![0u32; (width * height) as usize];
+
+    let result = gdi::GetDIBits(
+        hdc,
+        hbitmap,
+        0,
+        height as u32,
+        pixels.as_mut_ptr().cast(),
+        &mut info,
+        gdi::DIB_RGB_COLORS,
+    );
+
+    let error = (result == 0).then(io::Error::last_os_error);
+    gdi::DeleteDC(hdc);
+
+    if let Some(error) = error {
+        return Err(error);
+    }
+
+    Ok(pixels)
+}
+
+/// Create an icon from 32-bit pixels.
+unsafe fn create_icon(width: i32, height: i32, pixels: &[u32]) -> io::Result<IconHandle> {
+    let info = bitmap_info(width, height);
+    let mut bits = ptr::null_mut();
+    let color = gdi::CreateDIBSection(0, &info, gdi::DIB_RGB_COLORS, &mut bits, 0, 0);
+
+    if color == 0 {
+        return Err(io::Error::last_os_error());
+    }
+
+    ptr::copy_nonoverlapping(pixels.as_ptr(), bits.cast::<u32>(), pixels.len());
+
+    // NB: The mask is ignored for icons with an alpha channel, but it still
+    // has to be provided. Rows are aligned to 16 bits.
+    let mask_bits = vec![0u8; (((width + 15) / 16) * 2 * height) as usize];
+    let mask = gdi::CreateBitmap(width, height, 1, 1, mask_bits.as_ptr().cast());
+
+    if mask == 0 {
+        let error = io::Error::last_os_error();
+        gdi::DeleteObject(color);
+        return Err(error);
+    }
+
+    let info = winuser::ICONINFO {
+        fIcon: TRUE,
+        xHotspot: 0,
+        yHotspot: 0,
+        hbmMask: mask,
+        hbmColor: color,
+    };
+
+    let hicon = winuser::CreateIconIndirect(&info);
+    let error = (hicon == 0).then(io::Error::last_os_error);
+
+    // NB: The icon holds copies of the bitmaps.
+    gdi::DeleteObject(color);
+    gdi::DeleteObject(mask);
+
+    if let Some(error) = error {
+        return Err(error);
+    }
+
+    Ok(IconHandle { hicon })
+}
+
+/// Construct the header of a top-down 32-bit bitmap.
+fn bitmap_info(width: i32, height: i32) -> gdi::BITMAPINFO {
+    // SAFETY: The bitmap info is a plain structure.
+    let mut info: gdi::BITMAPINFO = unsafe { MaybeUninit::zeroed().assume_init() };
+    info.bmiHeader.biSize = size_of::<gdi::BITMAPINFOHEADER>() as u32;
+    info.bmiHeader.biWidth = width;
+    info.bmiHeader.biHeight = -height;
+    info.bmiHeader.biPlanes = 1;
+    info.bmiHeader.biBitCount = 32;
+    info.bmiHeader.biCompression = gdi::BI_RGB;
+    info
+}
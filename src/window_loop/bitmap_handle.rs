@@ -0,0 +1,104 @@
+use std::io;
+use std::mem::{size_of, MaybeUninit};
+use std::ptr;
+
+use windows_sys::Win32::Foundation::FALSE;
+use windows_sys::Win32::Graphics::Gdi as gdi;
+use windows_sys::Win32::UI::WindowsAndMessaging as winuser;
+
+use super::IconHandle;
+
+/// An owned bitmap, used to display icons in popup menus.
+pub(crate) struct BitmapHandle {
+    pub(super) hbitmap: gdi::HBITMAP,
+}
+
+impl BitmapHandle {
+    /// Render the given icon into a 32-bit bitmap sized for use in menus.
+    pub(crate) fn from_icon(icon: &IconHandle) -> io::Result<Self> {
+        unsafe {
+            let width = winuser::GetSystemMetrics(winuser::SM_CXSMICON);
+            let height = winuser::GetSystemMetrics(winuser::SM_CYSMICON);
+            Self::render(icon, width, height)
+        }
+    }
+
+    /// Render the given icon into a 32-bit bitmap sized for use as a menu
+    /// check mark.
+    pub(crate) fn check_mark(icon: &IconHandle) -> io::Result<Self> {
+        unsafe {
+            let width = winuser::GetSystemMetrics(winuser::SM_CXMENUCHECK);
+            let height = winuser::GetSystemMetrics(winuser::SM_CYMENUCHECK);
+            Self::render(icon, width, height)
+        }
+    }
+
+    /// Render the given icon into a 32-bit bitmap of the given size.
+    ///
+    /// The bitmap is a top-down device-independent bitmap, which preserves the
+    /// alpha channel of the icon when it's drawn in a menu.
+    fn render(icon: &IconHandle, width: i32, height: i32) -> io::Result<Self> {
+        unsafe {
+            let hdc = gdi::CreateCompatibleDC(0);
+
+            if hdc == 0 {
+                return Err(io::Error::last_os_error());
+            }
+
+            let mut info: gdi::BITMAPINFO = MaybeUninit::zeroed().assume_init();
+            info.bmiHeader.biSize = size_of::<gdi::BITMAPINFOHEADER>() as u32;
+            info.bmiHeader.biWidth = width;
+            info.bmiHeader.biHeight = -height;
+            info.bmiHeader.biPlanes = 1;
+            info.bmiHeader.biBitCount = 32;
+            info.bmiHeader.biCompression = gdi::BI_RGB;
+
+            let mut bits = ptr::null_mut();
+
+            let hbitmap = gdi::CreateDIBSection(hdc, &info, gdi::DIB_RGB_COLORS, &mut bits, 0, 0);
+
+            if hbitmap == 0 {
+                let error = io::Error::last_os_error();
+                gdi::DeleteDC(hdc);
+                return Err(error);
+            }
+
+            // Construct the handle here so that the bitmap is freed on error.
+            let bitmap = Self { hbitmap };
+
+            let old = gdi::SelectObject(hdc, hbitmap);
+
+            let result = winuser::DrawIconEx(
+                hdc,
+                0,
+                0,
+                icon.hicon,
+                width,
+                height,
+                0,
+                0,
+                winuser::DI_NORMAL,
+            );
+
+            let error = (result == FALSE).then(io::Error::last_os_error);
+
+            gdi::SelectObject(hdc, old);
+            gdi::DeleteDC(hdc);
+
+            if let Some(error) = error {
+                return Err(error);
+            }
+
+            Ok(bitmap)
+        }
+    }
+}
+
+impl Drop for BitmapHandle {
+    fn drop(&mut self) {
+        // SAFETY: bitmap handle is owned by this struct.
+        unsafe {
+            gdi::DeleteObject(self.hbitmap);
+        }
+    }
+}
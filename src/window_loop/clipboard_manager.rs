@@ -1,38 +1,51 @@
-use std::str;
-
 use tokio::sync::mpsc::UnboundedSender;
 use windows_sys::Win32::Foundation::HWND;
 use windows_sys::Win32::UI::WindowsAndMessaging as winuser;
 use windows_sys::Win32::UI::WindowsAndMessaging::MSG;
 
-use crate::clipboard::{Clipboard, ClipboardFormat};
-use crate::error::{ErrorKind, WindowError};
+use crate::clipboard::{decode_ansi, dib, Clipboard, ClipboardFormat};
+use crate::error::{ErrorRepr, WindowError};
 use crate::event::ClipboardEvent;
-use crate::Error;
+use crate::{ClipboardOptions, Error};
 
 use super::WindowEvent;
 
 const CLIPBOARD_RETRY_TIMER: usize = 1000;
-const RETRY_MILLIS: u32 = 25;
-const RETRY_MAX_ATTEMPTS: usize = 10;
 
 /// A timer used to debounce reacting to clipboard updates.
 ///
 /// We will only process updates again after this timer has been fired.
 const CLIPBOARD_DEBOUNCE_TIMER: usize = 1001;
-const DEBOUNCE_MILLIS: u32 = 25;
+
+/// The maximum number of formats reported in a metadata-only event.
+const MAX_FORMATS: usize = 64;
+
+/// Settings for clipboard monitoring.
+pub(crate) struct ClipboardSettings {
+    /// Deliver bitmaps without decoding them.
+    pub(crate) raw_bitmaps: bool,
+    /// Only report the available formats without copying any data.
+    pub(crate) metadata_only: bool,
+    /// Debounce and retry options.
+    pub(crate) options: ClipboardOptions,
+}
 
 /// Helper to manager clipboard polling state.
-pub(super) struct ClipboardManager<'a> {
-    events_tx: &'a UnboundedSender<WindowEvent>,
+pub(super) struct ClipboardManager {
+    events_tx: UnboundedSender<WindowEvent>,
+    settings: ClipboardSettings,
     attempts: usize,
     supported: Option<ClipboardFormat>,
 }
 
-impl<'a> ClipboardManager<'a> {
-    pub(super) fn new(events_tx: &'a UnboundedSender<WindowEvent>) -> Self {
+impl ClipboardManager {
+    pub(super) fn new(
+        events_tx: UnboundedSender<WindowEvent>,
+        settings: ClipboardSettings,
+    ) -> Self {
         Self {
             events_tx,
+            settings,
             attempts: 0,
             supported: None,
         }
@@ -42,7 +55,12 @@ impl<'a> ClipboardManager<'a> {
         match msg.message {
             winuser::WM_CLIPBOARDUPDATE => {
                 // Debounce incoming events.
-                winuser::SetTimer(msg.hwnd, CLIPBOARD_DEBOUNCE_TIMER, DEBOUNCE_MILLIS, None);
+                winuser::SetTimer(
+                    msg.hwnd,
+                    CLIPBOARD_DEBOUNCE_TIMER,
+                    self.settings.options.debounce_millis(),
+                    None,
+                );
                 true
             }
             winuser::WM_TIMER => match msg.wParam {
@@ -52,6 +70,21 @@ impl<'a> ClipboardManager<'a> {
                 }
                 CLIPBOARD_DEBOUNCE_TIMER => {
                     winuser::KillTimer(msg.hwnd, CLIPBOARD_DEBOUNCE_TIMER);
+
+                    if self.settings.metadata_only {
+                        let sequence = Clipboard::sequence_number();
+
+                        let clipboard_event = ClipboardEvent::Changed {
+                            formats: Clipboard::updated_formats::<MAX_FORMATS>().collect(),
+                            sequence,
+                        };
+
+                        _ = self
+                            .events_tx
+                            .send(WindowEvent::Clipboard(sequence, clipboard_event));
+                        return true;
+                    }
+
                     self.populate_formats();
 
                     // We need to incorporate a little delay to avoid "clobbering"
@@ -71,13 +104,20 @@ impl<'a> ClipboardManager<'a> {
                     // So as a best effort, we impose a minor timeout of
                     // INITIAL_MILLIS to hopefully avoid this.
                     let Ok(result) = self.poll_clipboard(msg.hwnd) else {
-                        winuser::SetTimer(msg.hwnd, CLIPBOARD_RETRY_TIMER, RETRY_MILLIS, None);
+                        winuser::SetTimer(
+                            msg.hwnd,
+                            CLIPBOARD_RETRY_TIMER,
+                            self.settings.options.retry_millis(),
+                            None,
+                        );
                         self.attempts = 1;
                         return true;
                     };
 
-                    if let Some(clipboard_event) = result {
-                        _ = self.events_tx.send(WindowEvent::Clipboard(clipboard_event));
+                    if let Some((sequence, clipboard_event)) = result {
+                        _ = self
+                            .events_tx
+                            .send(WindowEvent::Clipboard(sequence, clipboard_event));
                     }
 
                     true
@@ -93,7 +133,10 @@ impl<'a> ClipboardManager<'a> {
             for format in Clipboard::updated_formats::<16>() {
                 if matches!(
                     format,
-                    ClipboardFormat::DIBV5 | ClipboardFormat::TEXT | ClipboardFormat::UNICODETEXT
+                    ClipboardFormat::DIBV5
+                        | ClipboardFormat::HDROP
+                        | ClipboardFormat::TEXT
+                        | ClipboardFormat::UNICODETEXT
                 ) {
                     break 'out Some(format);
                 }
@@ -107,15 +150,20 @@ impl<'a> ClipboardManager<'a> {
         let result = match self.poll_clipboard(hwnd) {
             Ok(result) => result,
             Err(error) => {
-                if self.attempts >= RETRY_MAX_ATTEMPTS {
+                if self.attempts >= self.settings.options.max_attempts() {
                     winuser::KillTimer(hwnd, CLIPBOARD_RETRY_TIMER);
                     self.attempts = 0;
                     _ = self.events_tx.send(WindowEvent::Error(Error::new(
-                        ErrorKind::ClipboardPoll(error),
+                        ErrorRepr::ClipboardPoll(error),
                     )));
                 } else {
                     if self.attempts == 0 {
-                        winuser::SetTimer(hwnd, CLIPBOARD_RETRY_TIMER, RETRY_MILLIS, None);
+                        winuser::SetTimer(
+                            hwnd,
+                            CLIPBOARD_RETRY_TIMER,
+                            self.settings.options.retry_millis(),
+                            None,
+                        );
                     }
 
                     self.attempts += 1;
@@ -128,17 +176,29 @@ impl<'a> ClipboardManager<'a> {
         winuser::KillTimer(hwnd, CLIPBOARD_RETRY_TIMER);
         self.attempts = 0;
 
-        if let Some(clipboard_event) = result {
-            _ = self.events_tx.send(WindowEvent::Clipboard(clipboard_event));
+        if let Some((sequence, clipboard_event)) = result {
+            _ = self
+                .events_tx
+                .send(WindowEvent::Clipboard(sequence, clipboard_event));
         }
     }
 
+    /// Poll the clipboard, returning the event along with the sequence number
+    /// of the clipboard it was read from.
     pub(super) unsafe fn poll_clipboard(
         &mut self,
         hwnd: HWND,
-    ) -> Result<Option<ClipboardEvent>, WindowError> {
+    ) -> Result<Option<(u32, ClipboardEvent)>, WindowError> {
         let clipboard = Clipboard::new(hwnd).map_err(WindowError::OpenClipboard)?;
+        let sequence = Clipboard::sequence_number();
+        let event = self.read_clipboard(&clipboard)?;
+        Ok(event.map(|event| (sequence, event)))
+    }
 
+    fn read_clipboard(
+        &mut self,
+        clipboard: &Clipboard,
+    ) -> Result<Option<ClipboardEvent>, WindowError> {
         let Some(format) = self.supported else {
             return Ok(None);
         };
@@ -146,26 +206,55 @@ impl<'a> ClipboardManager<'a> {
         let data = clipboard
             .data(format)
             .map_err(WindowError::GetClipboardData)?;
+
+        // NB: A file list is queried through its handle rather than by locking
+        // the data.
+        if format == ClipboardFormat::HDROP {
+            self.supported = None;
+            return Ok(Some(ClipboardEvent::Files(data.files())));
+        }
+
         let data = data.lock().map_err(WindowError::LockClipboardData)?;
 
         // We've successfully locked the data, so take it from here.
         self.supported = None;
 
+        let size = data.as_slice().len();
+
+        if self.settings.options.is_too_large(size) {
+            return Ok(Some(ClipboardEvent::TooLarge { format, size }));
+        }
+
         let clipboard_event = match format {
-            ClipboardFormat::DIBV5 => ClipboardEvent::BitMap(data.as_slice().to_vec()),
-            ClipboardFormat::TEXT => {
+            ClipboardFormat::DIBV5 => {
                 let data = data.as_slice();
 
-                let data = match data {
-                    [head @ .., 0] => head,
-                    rest => rest,
+                let image = if self.settings.raw_bitmaps {
+                    None
+                } else {
+                    dib::decode(data)
                 };
 
-                let Ok(string) = str::from_utf8(data) else {
+                match image {
+                    Some(image) => ClipboardEvent::Image {
+                        width: image.width,
+                        height: image.height,
+                        rgba: image.rgba,
+                    },
+                    None => ClipboardEvent::BitMap(data.to_vec()),
+                }
+            }
+            ClipboardFormat::TEXT => {
+                let data = data.as_slice();
+
+                // NB: The data might be padded with more than one null.
+                let end = data.iter().position(|&b| b == 0).unwrap_or(data.len());
+
+                let Some(string) = decode_ansi(&data[..end], clipboard.text_code_page()) else {
                     return Ok(None);
                 };
 
-                ClipboardEvent::Text(string.to_owned())
+                ClipboardEvent::Text(string)
             }
             ClipboardFormat::UNICODETEXT => {
                 let data = data.as_wide_slice();
@@ -0,0 +1,211 @@
+use std::io;
+
+use windows_sys::Win32::UI::Input::KeyboardAndMouse as keyboard;
+
+/// A global hotkey parsed from the shortcut text of a menu item, such as
+/// `Ctrl+Shift+Q`.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Hotkey {
+    pub(super) modifiers: u32,
+    pub(super) vk: u32,
+}
+
+impl Hotkey {
+    /// Parse a hotkey.
+    ///
+    /// This consists of any number of modifiers followed by a single key,
+    /// separated by `+`. Names are matched case insensitively. Since the
+    /// hotkey is registered globally, keys other than function keys need at
+    /// least one modifier.
+    pub(crate) fn parse(shortcut: &str) -> io::Result<Self> {
+        let trimmed = shortcut.trim();
+
+        // NB: A trailing `+` is the plus key itself, such as in `Ctrl++`.
+        let (modifiers_part, key_part) = match trimmed.strip_suffix('+') {
+            Some(rest) => match rest.trim_end().strip_suffix('+') {
+                Some(rest) => (Some(rest), "+"),
+                None if rest.trim().is_empty() => (None, "+"),
+                None => return Err(invalid(shortcut)),
+            },
+            None => match trimmed.rsplit_once('+') {
+                Some((rest, key)) => (Some(rest), key),
+                None => (None, trimmed),
+            },
+        };
+
+        let mut modifiers = 0;
+
+        for part in modifiers_part.into_iter().flat_map(|m| m.split('+')) {
+            modifiers |= match part.trim().to_ascii_lowercase().as_str() {
+                "ctrl" | "control" => keyboard::MOD_CONTROL,
+                "alt" => keyboard::MOD_ALT,
+                "shift" => keyboard::MOD_SHIFT,
+                "win" => keyboard::MOD_WIN,
+                _ => return Err(invalid(shortcut)),
+            };
+        }
+
+        let vk = key(key_part.trim()).ok_or_else(|| invalid(shortcut))?;
+
+        if modifiers == 0 && !(keyboard::VK_F1..=keyboard::VK_F24).contains(&vk) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("`{shortcut}` needs a modifier to be used as a global hotkey"),
+            ));
+        }
+
+        Ok(Self {
+            // NB: Holding down the hotkey shouldn't repeatedly click the menu
+            // item.
+            modifiers: modifiers | keyboard::MOD_NOREPEAT,
+            vk: u32::from(vk),
+        })
+    }
+}
+
+/// A hotkey which clicks a menu item, as it's sent to the window thread.
+pub(super) struct HotkeyBinding {
+    pub(super) item_id: u32,
+    pub(super) hotkey: Hotkey,
+}
+
+fn invalid(shortcut: &str) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidInput,
+        format!("`{shortcut}` is not a valid shortcut"),
+    )
+}
+
+/// Parse the name of a key into its virtual-key code.
+fn key(name: &str) -> Option<keyboard::VIRTUAL_KEY> {
+    let mut chars = name.chars();
+
+    // Letters and digits map directly onto their virtual-key codes.
+    if let (Some(c), None) = (chars.next(), chars.next()) {
+        let c = c.to_ascii_uppercase();
+
+        if c.is_ascii_uppercase() || c.is_ascii_digit() {
+            return Some(c as keyboard::VIRTUAL_KEY);
+        }
+    }
+
+    let name = name.to_ascii_lowercase();
+
+    if let Some(n) = name.strip_prefix('f').and_then(|n| n.parse::<u16>().ok()) {
+        if (1..=24).contains(&n) {
+            return Some(keyboard::VK_F1 + n - 1);
+        }
+    }
+
+    let vk = match name.as_str() {
+        "space" => keyboard::VK_SPACE,
+        "enter" | "return" => keyboard::VK_RETURN,
+        "esc" | "escape" => keyboard::VK_ESCAPE,
+        "tab" => keyboard::VK_TAB,
+        "backspace" => keyboard::VK_BACK,
+        "del" | "delete" => keyboard::VK_DELETE,
+        "ins" | "insert" => keyboard::VK_INSERT,
+        "home" => keyboard::VK_HOME,
+        "end" => keyboard::VK_END,
+        "pgup" | "pageup" => keyboard::VK_PRIOR,
+        "pgdn" | "pagedown" => keyboard::VK_NEXT,
+        "up" => keyboard::VK_UP,
+        "down" => keyboard::VK_DOWN,
+        "left" => keyboard::VK_LEFT,
+        "right" => keyboard::VK_RIGHT,
+        "+" | "plus" => keyboard::VK_OEM_PLUS,
+        "-" | "minus" => keyboard::VK_OEM_MINUS,
+        _ => return None,
+    };
+
+    Some(vk)
+}
+
+#[cfg(test)]
+mod tests {
+    use windows_sys::Win32::UI::Input::KeyboardAndMouse as keyboard;
+
+    use super::Hotkey;
+
+    fn parse(shortcut: &str) -> Option<(u32, u32)> {
+        let hotkey = Hotkey::parse(shortcut).ok()?;
+        Some((hotkey.modifiers & !keyboard::MOD_NOREPEAT, hotkey.vk))
+    }
+
+    #[test]
+    fn valid() {
+        assert_eq!(
+            parse("Ctrl+Q"),
+            Some((keyboard::MOD_CONTROL, u32::from(b'Q')))
+        );
+        assert_eq!(
+            parse("Ctrl+Shift+S"),
+            Some((keyboard::MOD_CONTROL | keyboard::MOD_SHIFT, u32::from(b'S')))
+        );
+        assert_eq!(
+            parse("Win + Alt + 1"),
+            Some((keyboard::MOD_WIN | keyboard::MOD_ALT, u32::from(b'1')))
+        );
+        assert_eq!(
+            parse("Alt+Space"),
+            Some((keyboard::MOD_ALT, u32::from(keyboard::VK_SPACE)))
+        );
+        assert_eq!(
+            parse("Ctrl+PageDown"),
+            Some((keyboard::MOD_CONTROL, u32::from(keyboard::VK_NEXT)))
+        );
+        assert_eq!(
+            parse("Ctrl++"),
+            Some((keyboard::MOD_CONTROL, u32::from(keyboard::VK_OEM_PLUS)))
+        );
+        assert_eq!(
+            parse("Ctrl+Shift + +"),
+            Some((
+                keyboard::MOD_CONTROL | keyboard::MOD_SHIFT,
+                u32::from(keyboard::VK_OEM_PLUS)
+            ))
+        );
+        assert_eq!(
+            parse("Ctrl+-"),
+            Some((keyboard::MOD_CONTROL, u32::from(keyboard::VK_OEM_MINUS)))
+        );
+    }
+
+    #[test]
+    fn function_keys_without_modifiers() {
+        assert_eq!(parse("F1"), Some((0, u32::from(keyboard::VK_F1))));
+        assert_eq!(parse("F24"), Some((0, u32::from(keyboard::VK_F24))));
+        assert_eq!(
+            parse("Shift+F5"),
+            Some((keyboard::MOD_SHIFT, u32::from(keyboard::VK_F5)))
+        );
+    }
+
+    #[test]
+    fn case_insensitive() {
+        assert_eq!(parse("ctrl+q"), parse("Ctrl+Q"));
+        assert_eq!(parse("CONTROL+ALT+DELETE"), parse("Ctrl+Alt+Del"));
+        assert_eq!(parse("shift+f5"), parse("Shift+F5"));
+        assert_eq!(parse("ctrl+PLUS"), parse("Ctrl++"));
+    }
+
+    #[test]
+    fn invalid() {
+        assert_eq!(parse(""), None);
+        assert_eq!(parse("Ctrl"), None);
+        assert_eq!(parse("Ctrl+"), None);
+        assert_eq!(parse("Ctrl+Q+W"), None);
+        assert_eq!(parse("Ctrl+Hyper+Q"), None);
+        assert_eq!(parse("Ctrl+F25"), None);
+        assert_eq!(parse("Ctrl+Unknown"), None);
+        assert_eq!(parse("+Q"), None);
+    }
+
+    #[test]
+    fn requires_modifier() {
+        assert_eq!(parse("Q"), None);
+        assert_eq!(parse("Space"), None);
+        assert_eq!(parse("+"), None);
+        assert_eq!(parse("Enter"), None);
+    }
+}
@@ -0,0 +1,120 @@
+use std::collections::BTreeSet;
+use std::mem::MaybeUninit;
+
+use tokio::sync::mpsc::UnboundedSender;
+use windows_sys::Win32::Foundation::{FALSE, HWND};
+use windows_sys::Win32::Graphics::Gdi::PtInRect;
+use windows_sys::Win32::UI::Shell as shellapi;
+use windows_sys::Win32::UI::WindowsAndMessaging as winuser;
+use windows_sys::Win32::UI::WindowsAndMessaging::MSG;
+
+use crate::AreaId;
+
+use super::messages::{self, IconMessage};
+use super::{area_rect, WindowEvent};
+
+/// A timer used to detect when the cursor leaves a hovered icon.
+const HOVER_TIMER: usize = 1003;
+/// How often the cursor position is polled while an icon is hovered.
+const HOVER_POLL_MILLIS: u32 = 100;
+
+/// Helper to manage which notification areas are being hovered.
+///
+/// The shell reports when the cursor leaves an icon through `NIN_POPUPCLOSE`,
+/// but only after it has hovered long enough to send `NIN_POPUPOPEN`. So as a
+/// fallback the cursor is polled while an icon is hovered to detect when
+/// it leaves.
+pub(super) struct HoverManager {
+    events_tx: UnboundedSender<WindowEvent>,
+    hovered: BTreeSet<u32>,
+}
+
+impl HoverManager {
+    pub(super) fn new(events_tx: UnboundedSender<WindowEvent>) -> Self {
+        Self {
+            events_tx,
+            hovered: BTreeSet::new(),
+        }
+    }
+
+    pub(super) unsafe fn dispatch(&mut self, msg: &MSG) -> bool {
+        match msg.message {
+            messages::ICON_ID => {
+                let icon = IconMessage::decode(msg.wParam, msg.lParam);
+
+                match icon.event {
+                    winuser::WM_MOUSEMOVE | shellapi::NIN_POPUPOPEN => {
+                        self.enter(msg.hwnd, icon.id);
+                        true
+                    }
+                    shellapi::NIN_POPUPCLOSE => {
+                        self.leave(msg.hwnd, icon.id);
+                        true
+                    }
+                    _ => false,
+                }
+            }
+            winuser::WM_TIMER if msg.wParam == HOVER_TIMER => {
+                self.poll(msg.hwnd);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    unsafe fn enter(&mut self, hwnd: HWND, id: u32) {
+        if !self.hovered.insert(id) {
+            return;
+        }
+
+        _ = self
+            .events_tx
+            .send(WindowEvent::IconHovered(AreaId::new(id), true));
+
+        winuser::SetTimer(hwnd, HOVER_TIMER, HOVER_POLL_MILLIS, None);
+    }
+
+    unsafe fn leave(&mut self, hwnd: HWND, id: u32) {
+        if !self.hovered.remove(&id) {
+            return;
+        }
+
+        _ = self
+            .events_tx
+            .send(WindowEvent::IconHovered(AreaId::new(id), false));
+
+        if self.hovered.is_empty() {
+            winuser::KillTimer(hwnd, HOVER_TIMER);
+        }
+    }
+
+    unsafe fn poll(&mut self, hwnd: HWND) {
+        let mut p = MaybeUninit::zeroed();
+
+        if winuser::GetCursorPos(p.as_mut_ptr()) == FALSE {
+            return;
+        }
+
+        let p = p.assume_init();
+
+        let left = self
+            .hovered
+            .iter()
+            .copied()
+            .filter(|&id| {
+                // If the location of the icon can't be determined, for
+                // example because it's hidden in the overflow area, it's
+                // treated as no longer being hovered.
+                let Ok(rect) = area_rect(hwnd, id) else {
+                    return true;
+                };
+
+                PtInRect(&rect, p) == FALSE
+            })
+            .collect::<Vec<_>>();
+
+        for id in left {
+            self.leave(hwnd, id);
+        }
+    }
+}
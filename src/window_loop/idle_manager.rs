@@ -0,0 +1,78 @@
+use std::mem::size_of;
+use std::time::Duration;
+
+use tokio::sync::mpsc::UnboundedSender;
+use windows_sys::Win32::Foundation::{FALSE, HWND};
+use windows_sys::Win32::System::SystemInformation::GetTickCount;
+use windows_sys::Win32::UI::Input::KeyboardAndMouse::{GetLastInputInfo, LASTINPUTINFO};
+use windows_sys::Win32::UI::WindowsAndMessaging as winuser;
+use windows_sys::Win32::UI::WindowsAndMessaging::MSG;
+
+use super::WindowEvent;
+
+/// A timer used to poll for user input.
+const IDLE_TIMER: usize = 1005;
+/// How often the time of the last input is polled.
+const IDLE_POLL_MILLIS: u32 = 1000;
+
+/// Helper to detect when the user becomes idle or active.
+///
+/// The time of the last input is polled, since there's no notification for
+/// it short of installing low-level input hooks.
+pub(super) struct IdleManager {
+    events_tx: UnboundedSender<WindowEvent>,
+    threshold: Duration,
+    idle: bool,
+}
+
+impl IdleManager {
+    /// Start monitoring for the user being idle for at least the given
+    /// threshold.
+    pub(super) unsafe fn new(
+        hwnd: HWND,
+        events_tx: UnboundedSender<WindowEvent>,
+        threshold: Duration,
+    ) -> Self {
+        winuser::SetTimer(hwnd, IDLE_TIMER, IDLE_POLL_MILLIS, None);
+
+        Self {
+            events_tx,
+            threshold,
+            idle: false,
+        }
+    }
+
+    pub(super) unsafe fn dispatch(&mut self, msg: &MSG) -> bool {
+        match msg.message {
+            winuser::WM_TIMER if msg.wParam == IDLE_TIMER => {
+                self.poll();
+                true
+            }
+            _ => false,
+        }
+    }
+
+    unsafe fn poll(&mut self) {
+        let mut info = LASTINPUTINFO {
+            cbSize: size_of::<LASTINPUTINFO>() as u32,
+            dwTime: 0,
+        };
+
+        if GetLastInputInfo(&mut info) == FALSE {
+            return;
+        }
+
+        // NB: Tick counts wrap around after about 49 days.
+        let idle = Duration::from_millis(GetTickCount().wrapping_sub(info.dwTime) as u64);
+
+        if idle >= self.threshold {
+            if !self.idle {
+                self.idle = true;
+                _ = self.events_tx.send(WindowEvent::Idle(idle));
+            }
+        } else if self.idle {
+            self.idle = false;
+            _ = self.events_tx.send(WindowEvent::Active);
+        }
+    }
+}
@@ -1,8 +1,13 @@
+use std::cell::RefCell;
 use std::mem::MaybeUninit;
 use std::ptr;
+use std::rc::Rc;
 
 use tokio::sync::mpsc::UnboundedSender;
-use windows_sys::Win32::Foundation::FALSE;
+use windows_sys::Win32::Foundation::{HWND, POINT};
+use windows_sys::Win32::UI::Input::KeyboardAndMouse::{
+    GetAsyncKeyState, VK_CONTROL, VK_MENU, VK_SHIFT,
+};
 use windows_sys::Win32::UI::Shell as shellapi;
 use windows_sys::Win32::UI::WindowsAndMessaging as winuser;
 use windows_sys::Win32::UI::WindowsAndMessaging::{HMENU, MSG};
@@ -10,36 +15,152 @@ use windows_sys::Win32::UI::WindowsAndMessaging::{HMENU, MSG};
 use crate::event::MouseButton;
 use crate::event::MouseButtons;
 use crate::event::MouseEvent;
+use crate::event::{Modifier, Modifiers};
 use crate::AreaId;
 
-use super::messages;
+use super::messages::{self, IconMessage};
 use super::WindowEvent;
 
-/// Helper to manager clipboard polling state.
-pub(super) struct MenuManager<'a> {
-    events_tx: &'a UnboundedSender<WindowEvent>,
-    menus: &'a [Option<(winuser::HMENU, MouseButtons)>],
+/// The popup menu associated with an area.
+pub(super) struct AreaMenu {
+    pub(super) hmenu: HMENU,
+    /// Mouse buttons which open the menu.
+    pub(super) open_menu: MouseButtons,
+    /// Whether the window should be brought to the foreground when the menu
+    /// is opened.
+    pub(super) take_foreground: bool,
+    /// Flags used when showing the menu.
+    pub(super) track_flags: u32,
+    /// Whether double clicking the icon clicks the default menu item.
+    pub(super) double_click_default: bool,
 }
 
-impl<'a> MenuManager<'a> {
+/// How a popup menu should be shown.
+#[derive(Debug, PartialEq, Eq)]
+struct ShowMenu {
+    hmenu: HMENU,
+    take_foreground: bool,
+    track_flags: u32,
+}
+
+/// Decide if the given menu should be shown in response to a button being
+/// clicked, or unconditionally if no button is specified.
+///
+/// Nothing is shown if the area doesn't have a menu, or if the menu isn't
+/// opened by the given button. In that case the window must not be brought
+/// to the foreground either.
+fn show_menu(menu: Option<&AreaMenu>, button: Option<MouseButton>) -> Option<ShowMenu> {
+    let menu = menu?;
+
+    if let Some(button) = button {
+        if !menu.open_menu.test(button) {
+            return None;
+        }
+    }
+
+    Some(ShowMenu {
+        hmenu: menu.hmenu,
+        take_foreground: menu.take_foreground,
+        track_flags: menu.track_flags,
+    })
+}
+
+/// Helper to manager popup menu state.
+pub(super) struct MenuManager {
+    events_tx: UnboundedSender<WindowEvent>,
+    menus: Rc<RefCell<Vec<Option<AreaMenu>>>>,
+}
+
+impl MenuManager {
     pub(super) fn new(
-        events_tx: &'a UnboundedSender<WindowEvent>,
-        menus: &'a [Option<(winuser::HMENU, MouseButtons)>],
+        events_tx: UnboundedSender<WindowEvent>,
+        menus: Rc<RefCell<Vec<Option<AreaMenu>>>>,
     ) -> Self {
         Self { events_tx, menus }
     }
 
+    /// Open the popup menu of the given area at the given position.
+    ///
+    /// If a button is specified, the menu is only opened if it's configured to
+    /// be opened by that button.
+    unsafe fn open_menu(
+        &self,
+        hwnd: HWND,
+        area_id: AreaId,
+        button: Option<MouseButton>,
+        x: i32,
+        y: i32,
+    ) {
+        // NB: The borrow can't be held while the menu is shown, since areas
+        // might be added or removed.
+        let show = {
+            let menus = self.menus.borrow();
+            let menu = menus.get(area_id.id() as usize).and_then(Option::as_ref);
+            show_menu(menu, button)
+        };
+
+        let Some(ShowMenu {
+            hmenu,
+            take_foreground,
+            track_flags,
+        }) = show
+        else {
+            return;
+        };
+
+        // NB: Only take the foreground once we know that a menu will be shown.
+        // Without it the menu isn't dismissed when clicking outside of it.
+        if take_foreground {
+            winuser::SetForegroundWindow(hwnd);
+        }
+
+        // NB: The flags have to be passed as the second argument, the fifth one
+        // is reserved and must be zero.
+        winuser::TrackPopupMenu(hmenu, track_flags, x, y, 0, hwnd, ptr::null_mut());
+    }
+
+    /// Click the default item of the popup menu of the given area, if it's
+    /// configured to be clicked by double clicking the icon.
+    unsafe fn click_default(&self, area_id: AreaId, anchor: (i32, i32)) {
+        let hmenu = {
+            let menus = self.menus.borrow();
+
+            let Some(Some(menu)) = menus.get(area_id.id() as usize) else {
+                return;
+            };
+
+            if !menu.double_click_default {
+                return;
+            }
+
+            menu.hmenu
+        };
+
+        // NB: The default item is looked up from the menu since it might have
+        // been modified. Disabled items are skipped.
+        let item_id = winuser::GetMenuDefaultItem(hmenu, 0, 0);
+
+        if item_id == u32::MAX {
+            return;
+        }
+
+        let event = mouse_event(MouseButtons::from_iter([MouseButton::Left]), Some(anchor));
+
+        _ = self
+            .events_tx
+            .send(WindowEvent::MenuItemClicked(area_id, item_id, event));
+    }
+
     pub(super) unsafe fn dispatch(&mut self, msg: &MSG) -> bool {
         match msg.message {
             messages::ICON_ID => {
-                let area_id = AreaId::new(msg.wParam as u32);
+                let icon = IconMessage::decode(msg.wParam, msg.lParam);
+                let area_id = AreaId::new(icon.id);
 
-                match msg.lParam as u32 {
+                match icon.event {
                     // Balloon clicked.
                     shellapi::NIN_BALLOONUSERCLICK => {
-                        let event = MouseEvent {
-                            buttons: MouseButtons::empty(),
-                        };
+                        let event = mouse_event(MouseButtons::empty(), None);
 
                         _ = self
                             .events_tx
@@ -53,72 +174,82 @@ impl<'a> MenuManager<'a> {
                             .send(WindowEvent::NotificationDismissed(area_id));
                         return true;
                     }
-                    winuser::WM_LBUTTONUP | winuser::WM_RBUTTONUP => {
-                        let button = match msg.lParam as u32 {
-                            winuser::WM_LBUTTONUP => MouseButton::Left,
-                            winuser::WM_RBUTTONUP => MouseButton::Right,
-                            _ => return true,
-                        };
+                    winuser::WM_LBUTTONDBLCLK => {
+                        let event = mouse_event(
+                            MouseButtons::from_iter([MouseButton::Left]),
+                            Some(icon.anchor),
+                        );
 
-                        _ = self.events_tx.send(WindowEvent::IconClicked(
-                            area_id,
-                            MouseEvent {
-                                buttons: MouseButtons::from_iter([button]),
-                            },
-                        ));
+                        _ = self
+                            .events_tx
+                            .send(WindowEvent::IconDoubleClicked(area_id, event));
 
-                        let Some(Some((hmenu, open_menu))) = self.menus.get(area_id.id() as usize)
-                        else {
-                            return true;
+                        self.click_default(area_id, icon.anchor);
+                        return true;
+                    }
+                    // NB: The context menu is requested both by right clicking
+                    // and using the keyboard, and pressing enter or space on a
+                    // focused icon is treated like a left click. NIN_SELECT is
+                    // sent in addition to WM_LBUTTONUP when clicking, so it's
+                    // ignored to avoid reporting the click twice.
+                    winuser::WM_LBUTTONUP
+                    | winuser::WM_CONTEXTMENU
+                    | winuser::WM_MBUTTONUP
+                    | messages::NIN_KEYSELECT => {
+                        let button = match icon.event {
+                            winuser::WM_LBUTTONUP | messages::NIN_KEYSELECT => MouseButton::Left,
+                            winuser::WM_CONTEXTMENU => MouseButton::Right,
+                            winuser::WM_MBUTTONUP => MouseButton::Middle,
+                            _ => return true,
                         };
 
-                        if !open_menu.test(button) {
-                            return true;
-                        }
-
-                        let mut p = MaybeUninit::zeroed();
+                        let event =
+                            mouse_event(MouseButtons::from_iter([button]), Some(icon.anchor));
+                        let (x, y) = event.position;
 
-                        if winuser::GetCursorPos(p.as_mut_ptr()) == FALSE {
-                            return true;
-                        }
-
-                        let p = p.assume_init();
-
-                        winuser::SetForegroundWindow(msg.hwnd);
-
-                        winuser::TrackPopupMenu(
-                            *hmenu,
-                            0,
-                            p.x,
-                            p.y,
-                            (winuser::TPM_BOTTOMALIGN | winuser::TPM_LEFTALIGN) as i32,
-                            msg.hwnd,
-                            ptr::null_mut(),
-                        );
+                        _ = self
+                            .events_tx
+                            .send(WindowEvent::IconClicked(area_id, event));
 
+                        self.open_menu(msg.hwnd, area_id, Some(button), x, y);
                         return true;
                     }
                     _ => (),
                 }
             }
+            messages::OPEN_MENU_ID => {
+                let area_id = AreaId::new(msg.wParam as u32);
+                let x = i32::from(msg.lParam as u16 as i16);
+                let y = i32::from((msg.lParam >> 16) as u16 as i16);
+                self.open_menu(msg.hwnd, area_id, None, x, y);
+                return true;
+            }
             winuser::WM_MENUCOMMAND => {
                 let hmenu = msg.lParam as HMENU;
 
                 let Some(area_id) = self
                     .menus
+                    .borrow()
                     .iter()
-                    .position(|el| el.as_ref().map(|(h, _)| *h) == Some(hmenu))
+                    .position(|el| el.as_ref().map(|m| m.hmenu) == Some(hmenu))
                 else {
                     return true;
                 };
 
-                let event = MouseEvent {
-                    buttons: MouseButtons::empty(),
-                };
+                // NB: Since menus use MNS_NOTIFYBYPOS, the parameter is the
+                // position of the item, which is translated into its stable
+                // identifier.
+                let item_id = winuser::GetMenuItemID(hmenu, msg.wParam as i32);
+
+                if item_id == u32::MAX {
+                    return true;
+                }
+
+                let event = mouse_event(MouseButtons::empty(), None);
 
                 _ = self.events_tx.send(WindowEvent::MenuItemClicked(
                     AreaId::new(area_id as u32),
-                    msg.wParam as u32,
+                    item_id,
                     event,
                 ));
 
@@ -130,3 +261,100 @@ impl<'a> MenuManager<'a> {
         false
     }
 }
+
+/// Construct a mouse event, capturing which modifier keys are held down.
+///
+/// If an anchor point isn't provided by the shell, the current position of the
+/// cursor is used.
+pub(super) unsafe fn mouse_event(buttons: MouseButtons, anchor: Option<(i32, i32)>) -> MouseEvent {
+    let position = match anchor {
+        Some(anchor) => anchor,
+        None => {
+            let mut p = MaybeUninit::<POINT>::zeroed();
+
+            // NB: If the cursor position can't be determined the position is
+            // left zeroed.
+            winuser::GetCursorPos(p.as_mut_ptr());
+            let p = p.assume_init();
+            (p.x, p.y)
+        }
+    };
+
+    // NB: The asynchronous state is used since the window usually doesn't have
+    // keyboard focus when notification icons are interacted with.
+    let modifiers = [
+        (VK_SHIFT, Modifier::Shift),
+        (VK_CONTROL, Modifier::Control),
+        (VK_MENU, Modifier::Alt),
+    ]
+    .into_iter()
+    .filter(|&(key, _)| GetAsyncKeyState(i32::from(key)) < 0)
+    .map(|(_, modifier)| modifier);
+
+    MouseEvent {
+        buttons,
+        position,
+        modifiers: Modifiers::from_iter(modifiers),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::event::{MouseButton, MouseButtons};
+
+    use super::{show_menu, AreaMenu, ShowMenu};
+
+    fn area_menu(open_menu: &[MouseButton], take_foreground: bool) -> AreaMenu {
+        AreaMenu {
+            hmenu: 0,
+            open_menu: MouseButtons::from_iter(open_menu.iter().copied()),
+            take_foreground,
+            track_flags: 0,
+            double_click_default: false,
+        }
+    }
+
+    fn shown(take_foreground: bool) -> Option<ShowMenu> {
+        Some(ShowMenu {
+            hmenu: 0,
+            take_foreground,
+            track_flags: 0,
+        })
+    }
+
+    #[test]
+    fn icon_clicked_without_menu() {
+        assert_eq!(show_menu(None, Some(MouseButton::Left)), None);
+        assert_eq!(show_menu(None, Some(MouseButton::Right)), None);
+        assert_eq!(show_menu(None, None), None);
+    }
+
+    #[test]
+    fn icon_clicked_with_other_button() {
+        let menu = area_menu(&[MouseButton::Right], true);
+        assert_eq!(show_menu(Some(&menu), Some(MouseButton::Left)), None);
+        assert_eq!(show_menu(Some(&menu), Some(MouseButton::Middle)), None);
+    }
+
+    #[test]
+    fn icon_clicked_with_menu() {
+        let menu = area_menu(&[MouseButton::Left, MouseButton::Right], true);
+        assert_eq!(show_menu(Some(&menu), Some(MouseButton::Left)), shown(true));
+        assert_eq!(
+            show_menu(Some(&menu), Some(MouseButton::Right)),
+            shown(true)
+        );
+
+        let menu = area_menu(&[MouseButton::Right], false);
+        assert_eq!(
+            show_menu(Some(&menu), Some(MouseButton::Right)),
+            shown(false)
+        );
+    }
+
+    #[test]
+    fn open_menu_requested() {
+        let menu = area_menu(&[], true);
+        assert_eq!(show_menu(Some(&menu), None), shown(true));
+    }
+}
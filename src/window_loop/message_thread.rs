@@ -0,0 +1,270 @@
+use std::io;
+use std::mem::MaybeUninit;
+use std::ptr;
+use std::sync::mpsc;
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+use windows_sys::Win32::Foundation::{
+    GetLastError, ERROR_CLASS_ALREADY_EXISTS, FALSE, HWND, LPARAM, LRESULT, WPARAM,
+};
+use windows_sys::Win32::System::Threading::GetCurrentThreadId;
+use windows_sys::Win32::UI::WindowsAndMessaging as winuser;
+
+use crate::convert::ToWide;
+use crate::error::ErrorRepr::*;
+use crate::error::{Error, WindowError};
+use crate::Result;
+
+use super::{defer_message, messages, post_deferred, Runtime, Setup};
+
+/// The class of the message-only window used to wake up the thread.
+const WAKE_CLASS: &str = "se.tedro.winctx.MessageThread";
+
+/// Windows which are waiting to be created by the message thread.
+type Pending = Arc<Mutex<Vec<Setup>>>;
+
+/// A thread running the message loop of one or more windows.
+///
+/// Every window built with [`CreateWindow::build`] gets a thread of its own.
+/// To save on threads, several windows can instead share one by building them
+/// with [`CreateWindow::build_on`], such as when an application needs both a
+/// window for its notification area and a separate window for receiving
+/// data from other processes.
+///
+/// The thread keeps running for as long as a handle to it or any window built
+/// on it exists.
+///
+/// Note that while a window on the thread shows a popup menu, the thread is
+/// busy running the menu. Until the menu is closed, all other windows on the
+/// thread are blocked, and windows are neither created nor closed.
+///
+/// [`CreateWindow::build`]: crate::CreateWindow::build
+/// [`CreateWindow::build_on`]: crate::CreateWindow::build_on
+///
+/// # Examples
+///
+/// ```no_run
+/// use winctx::{CreateWindow, MessageThread};
+///
+/// # async fn test() -> winctx::Result<()> {
+/// let thread = MessageThread::new()?;
+///
+/// let mut tray = CreateWindow::new("se.tedro.Example");
+/// tray.new_area();
+///
+/// let (tray_sender, tray_loop) = tray.build_on(&thread).await?;
+///
+/// let ipc = CreateWindow::new("se.tedro.Example.Ipc");
+/// let (ipc_sender, ipc_loop) = ipc.build_on(&thread).await?;
+/// # Ok(()) }
+/// ```
+#[derive(Clone)]
+pub struct MessageThread {
+    inner: Arc<Inner>,
+}
+
+struct Inner {
+    thread_id: u32,
+    /// Window used to wake up the thread.
+    wake: HWND,
+    pending: Pending,
+    thread: Option<thread::JoinHandle<()>>,
+}
+
+impl MessageThread {
+    /// Start a new message thread.
+    pub fn new() -> Result<Self> {
+        let pending = Pending::default();
+        let (init_tx, init_rx) = mpsc::channel();
+
+        let thread = thread::Builder::new()
+            .name(String::from("winctx-messages"))
+            .spawn({
+                let pending = pending.clone();
+
+                move || unsafe {
+                    let mut msg = MaybeUninit::zeroed();
+
+                    // NB: Peeking a message makes sure the thread has a message
+                    // queue before anyone tries to post to it.
+                    winuser::PeekMessageW(msg.as_mut_ptr(), 0, 0, 0, winuser::PM_NOREMOVE);
+
+                    let wake = match create_wake_window() {
+                        Ok(wake) => wake,
+                        Err(error) => {
+                            _ = init_tx.send(Err(error));
+                            return;
+                        }
+                    };
+
+                    if init_tx.send(Ok((GetCurrentThreadId(), wake))).is_err() {
+                        winuser::DestroyWindow(wake);
+                        return;
+                    }
+
+                    run(&pending, wake);
+                    winuser::DestroyWindow(wake);
+                }
+            })
+            .map_err(StartMessageThread)?;
+
+        let (thread_id, wake) = match init_rx.recv() {
+            Ok(Ok(init)) => init,
+            Ok(Err(error)) => {
+                _ = thread.join();
+                return Err(Error::new(StartMessageThread(error)));
+            }
+            Err(..) => {
+                _ = thread.join();
+                return Err(Error::new(ThreadError(WindowError::ThreadExited)));
+            }
+        };
+
+        Ok(Self {
+            inner: Arc::new(Inner {
+                thread_id,
+                wake,
+                pending,
+                thread: Some(thread),
+            }),
+        })
+    }
+
+    /// Queue a window to be created by the thread.
+    pub(super) fn add(&self, setup: Setup) -> Result<(), WindowError> {
+        self.inner
+            .pending
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .push(setup);
+
+        // NB: This is posted to a window rather than to the thread, since
+        // thread messages are discarded by modal loops such as the one running
+        // while a popup menu is shown.
+        //
+        // SAFETY: Posting to a window is safe for any window handle.
+        let result = unsafe { winuser::PostMessageW(self.inner.wake, messages::WAKE_ID, 0, 0) };
+
+        if result == FALSE {
+            return Err(WindowError::Init(io::Error::last_os_error()));
+        }
+
+        Ok(())
+    }
+}
+
+impl Drop for Inner {
+    fn drop(&mut self) {
+        unsafe {
+            winuser::PostThreadMessageW(self.thread_id, winuser::WM_QUIT, 0, 0);
+        }
+
+        if let Some(thread) = self.thread.take() {
+            _ = thread.join();
+        }
+    }
+}
+
+/// Run the message loop of the thread until it's asked to quit.
+unsafe fn run(pending: &Mutex<Vec<Setup>>, wake: HWND) {
+    let mut windows = Vec::<Runtime>::new();
+    let mut msg = MaybeUninit::zeroed();
+
+    loop {
+        post_deferred();
+
+        // NB: Pending windows are picked up whenever the thread processes a
+        // message rather than only when woken up.
+        let setups = std::mem::take(&mut *pending.lock().unwrap_or_else(|e| e.into_inner()));
+
+        for setup in setups {
+            windows.extend(Runtime::create(setup));
+        }
+
+        if winuser::GetMessageW(msg.as_mut_ptr(), 0, 0, 0) == FALSE {
+            break;
+        }
+
+        let msg = &*msg.as_ptr();
+
+        if msg.hwnd == 0 || msg.hwnd == wake {
+            continue;
+        }
+
+        let Some(index) = windows.iter().position(|w| w.hwnd() == msg.hwnd) else {
+            winuser::TranslateMessage(msg);
+            winuser::DispatchMessageW(msg);
+            continue;
+        };
+
+        if !windows[index].dispatch(msg) {
+            windows.swap_remove(index).close();
+        }
+    }
+
+    for window in windows {
+        window.close();
+    }
+}
+
+/// Create the message-only window used to wake up the thread.
+unsafe fn create_wake_window() -> io::Result<HWND> {
+    let class_name = WAKE_CLASS.to_wide_null();
+
+    let class = winuser::WNDCLASSW {
+        style: 0,
+        lpfnWndProc: Some(wake_window_proc),
+        cbClsExtra: 0,
+        cbWndExtra: 0,
+        hInstance: 0,
+        hIcon: 0,
+        hCursor: 0,
+        hbrBackground: 0,
+        lpszMenuName: ptr::null(),
+        lpszClassName: class_name.as_ptr(),
+    };
+
+    if winuser::RegisterClassW(&class) == 0 && GetLastError() != ERROR_CLASS_ALREADY_EXISTS {
+        return Err(io::Error::last_os_error());
+    }
+
+    let hwnd = winuser::CreateWindowExW(
+        0,
+        class_name.as_ptr(),
+        ptr::null(),
+        0,
+        0,
+        0,
+        0,
+        0,
+        winuser::HWND_MESSAGE,
+        0,
+        0,
+        ptr::null(),
+    );
+
+    if hwnd == 0 {
+        return Err(io::Error::last_os_error());
+    }
+
+    Ok(hwnd)
+}
+
+unsafe extern "system" fn wake_window_proc(
+    hwnd: HWND,
+    msg: u32,
+    w_param: WPARAM,
+    l_param: LPARAM,
+) -> LRESULT {
+    // NB: Wake ups are handled by the message loop, so they only get here if
+    // they're dispatched by a modal loop, such as while a popup menu is shown by
+    // another window on the thread. They're posted again once the modal loop has
+    // exited.
+    if msg == messages::WAKE_ID {
+        defer_message(hwnd, msg, w_param, l_param);
+        return 0;
+    }
+
+    winuser::DefWindowProcW(hwnd, msg, w_param, l_param)
+}
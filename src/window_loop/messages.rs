@@ -1,6 +1,86 @@
+use windows_sys::Win32::Foundation::{LPARAM, WPARAM};
+use windows_sys::Win32::UI::Shell::{NINF_KEY, NIN_SELECT};
 use windows_sys::Win32::UI::WindowsAndMessaging::WM_USER;
 
+// Icon selected using the keyboard, which is missing from windows-sys.
+pub(super) const NIN_KEYSELECT: u32 = NIN_SELECT | NINF_KEY;
+
 // Icon message.
 pub(super) const ICON_ID: u32 = WM_USER + 1;
-// Transfer bytes payload.
-pub(super) const BYTES_ID: u32 = WM_USER + 2;
+// Schedule a shutdown, the delay in milliseconds is passed in wParam.
+pub(super) const SCHEDULE_SHUTDOWN_ID: u32 = WM_USER + 3;
+// Cancel a scheduled shutdown.
+pub(super) const CANCEL_SHUTDOWN_ID: u32 = WM_USER + 4;
+// Add the popup menu of an area, the area id is passed in wParam and a boxed
+// menu in lParam.
+pub(super) const ADD_AREA_ID: u32 = WM_USER + 5;
+// Remove the popup menu of an area, the area id is passed in wParam.
+pub(super) const REMOVE_AREA_ID: u32 = WM_USER + 6;
+// Open the popup menu of an area, the area id is passed in wParam and the
+// screen coordinates packed into lParam.
+pub(super) const OPEN_MENU_ID: u32 = WM_USER + 7;
+// Register a global hotkey for a menu item, the area id is passed in wParam
+// and a boxed hotkey binding in lParam.
+pub(super) const ADD_HOTKEY_ID: u32 = WM_USER + 8;
+// Unregister the global hotkey of a menu item, the area id is passed in wParam
+// and the menu item id in lParam.
+pub(super) const REMOVE_HOTKEY_ID: u32 = WM_USER + 9;
+// Schedule a wakeup for scheduled notifications, the delay in milliseconds is
+// passed in wParam.
+pub(super) const SCHEDULE_NOTIFICATION_ID: u32 = WM_USER + 10;
+// Copy data to another window, a boxed copy data request is passed in lParam.
+pub(super) const COPY_DATA_ID: u32 = WM_USER + 11;
+// Block the session from ending, the reason is passed in lParam as a boxed
+// null-terminated wide string.
+pub(super) const BLOCK_SHUTDOWN_ID: u32 = WM_USER + 12;
+// Stop blocking the session from ending.
+pub(super) const UNBLOCK_SHUTDOWN_ID: u32 = WM_USER + 13;
+// Start or replace a user timer, the timer id is passed in wParam and the
+// period in milliseconds in lParam.
+pub(super) const SET_TIMER_ID: u32 = WM_USER + 14;
+// Stop a user timer, the timer id is passed in wParam.
+pub(super) const KILL_TIMER_ID: u32 = WM_USER + 15;
+// Set the progress shown on the taskbar button, the value out of
+// `PROGRESS_TOTAL` is passed in wParam and the progress flags in lParam.
+pub(super) const TASKBAR_PROGRESS_ID: u32 = WM_USER + 16;
+// Show or hide a visible window, wParam is non-zero if it should be shown.
+pub(super) const SHOW_WINDOW_ID: u32 = WM_USER + 17;
+// Set the badge shown over the taskbar button, lParam is a boxed
+// `Option<Badge>`.
+pub(super) const TASKBAR_BADGE_ID: u32 = WM_USER + 18;
+// Close the window, which is only posted by the event loop once it has
+// stopped using the window.
+pub(super) const CLOSE_WINDOW_ID: u32 = WM_USER + 19;
+// Wake up whoever is waiting to retry adding a notification area after the
+// delay in milliseconds passed in wParam.
+pub(super) const SCHEDULE_RETRY_ID: u32 = WM_USER + 20;
+// Wake up a message thread to create windows which are waiting to be created.
+pub(super) const WAKE_ID: u32 = WM_USER + 21;
+
+/// A decoded [`ICON_ID`] callback message.
+///
+/// Since `NOTIFYICON_VERSION_4` is used, the notification event is stored in
+/// the low word of lParam and the identifier of the icon in the high word.
+/// wParam holds the anchor point of the event in screen coordinates, which for
+/// keyboard events is the location of the icon rather than of the cursor.
+pub(super) struct IconMessage {
+    pub(super) event: u32,
+    pub(super) id: u32,
+    pub(super) anchor: (i32, i32),
+}
+
+impl IconMessage {
+    pub(super) fn decode(w_param: WPARAM, l_param: LPARAM) -> Self {
+        let l_param = l_param as u32;
+        let w_param = w_param as u32;
+
+        Self {
+            event: l_param & 0xffff,
+            id: l_param >> 16,
+            anchor: (
+                i32::from(w_param as u16 as i16),
+                i32::from((w_param >> 16) as u16 as i16),
+            ),
+        }
+    }
+}
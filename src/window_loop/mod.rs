@@ -1,18 +1,45 @@
 mod messages;
 
-pub(super) use self::window_loop::{WindowEvent, WindowLoop};
+use self::window_loop::{defer_message, post_deferred, Runtime, Setup};
+pub(super) use self::window_loop::{CopiedData, VisibleSettings, WindowEvent, WindowLoop};
 mod window_loop;
 
+pub use self::message_thread::MessageThread;
+mod message_thread;
+
 pub(super) use self::icon_handle::IconHandle;
 mod icon_handle;
 
+use self::bitmap_handle::BitmapHandle;
+mod bitmap_handle;
+
+mod badge_icon;
+
 use self::clipboard_manager::ClipboardManager;
+pub(crate) use self::clipboard_manager::ClipboardSettings;
 mod clipboard_manager;
 
-use self::menu_manager::MenuManager;
+use self::menu_manager::{AreaMenu, MenuManager};
 mod menu_manager;
 
-use self::window_handle::WindowHandle;
+use self::hover_manager::HoverManager;
+mod hover_manager;
+
+use self::idle_manager::IdleManager;
+mod idle_manager;
+
+use self::taskbar::{Taskbar, PROGRESS_TOTAL};
+mod taskbar;
+
+pub(super) use self::hotkey::Hotkey;
+use self::hotkey::HotkeyBinding;
+mod hotkey;
+
+use self::owner_draw::{OwnerDrawItem, Theme, OWNER_DRAW_MENU};
+mod owner_draw;
+
+pub(super) use self::window_handle::WindowHandle;
+use self::window_handle::{area_rect, AddedAreas, CopyDataRequest, NOTIFY_ICON_VERSION};
 mod window_handle;
 
 use self::window_class_handle::WindowClassHandle;
@@ -0,0 +1,420 @@
+use std::cell::Cell;
+use std::mem::{size_of, MaybeUninit};
+use std::ptr;
+
+use windows_sys::Win32::Foundation::{ERROR_SUCCESS, FALSE, HWND, RECT, SIZE};
+use windows_sys::Win32::Graphics::Gdi as gdi;
+use windows_sys::Win32::System::Registry as winreg;
+use windows_sys::Win32::UI::Controls::{
+    DRAWITEMSTRUCT, MEASUREITEMSTRUCT, ODS_CHECKED, ODS_DEFAULT, ODS_GRAYED, ODS_NOACCEL,
+    ODS_SELECTED,
+};
+use windows_sys::Win32::UI::WindowsAndMessaging as winuser;
+use windows_sys::Win32::UI::WindowsAndMessaging::{HICON, HMENU};
+
+use crate::convert::ToWide;
+
+/// Marker stored in the menu data of popup menus which are owner drawn.
+pub(super) const OWNER_DRAW_MENU: usize = 0x6f776e72;
+
+/// Key and value used to test if applications should use the light theme.
+const PERSONALIZE_KEY: &str = r"Software\Microsoft\Windows\CurrentVersion\Themes\Personalize";
+const LIGHT_THEME_VALUE: &str = "AppsUseLightTheme";
+
+/// Colors used in dark mode, matching the system context menus.
+const DARK_BACKGROUND: u32 = rgb(0x2b, 0x2b, 0x2b);
+const DARK_SELECTED: u32 = rgb(0x41, 0x41, 0x41);
+const DARK_TEXT: u32 = rgb(0xff, 0xff, 0xff);
+const DARK_GRAYED: u32 = rgb(0x6e, 0x6e, 0x6e);
+const DARK_SEPARATOR: u32 = rgb(0x50, 0x50, 0x50);
+
+/// Padding around the contents of menu items.
+const PADDING: i32 = 4;
+/// Padding to the right of the text of menu items.
+const TEXT_PADDING: i32 = 16;
+/// Height of separators.
+const SEPARATOR_HEIGHT: i32 = 9;
+
+/// The check mark drawn for checked items without a custom check mark icon.
+const CHECK_MARK: char = '\u{2713}';
+
+/// The data associated with an owner-drawn menu item.
+///
+/// A pointer to this is stored in the item data of the menu item, so it must
+/// have a stable address for as long as the menu item exists.
+pub(super) struct OwnerDrawItem {
+    /// Null-terminated text of the item.
+    pub(super) text: Vec<u16>,
+    /// Shortcut hint displayed in a separate column, which is empty if the
+    /// item doesn't have one.
+    pub(super) shortcut: Vec<u16>,
+    /// Icon displayed next to the text.
+    pub(super) icon: HICON,
+    /// Icon displayed when the item is checked.
+    pub(super) checked_icon: HICON,
+    /// Icon displayed when the item isn't checked.
+    pub(super) unchecked_icon: HICON,
+    /// Whether the item is a separator.
+    pub(super) separator: bool,
+    /// Whether the item is laid out from right to left, in which case the
+    /// column holding check marks and icons is to the right of the text.
+    pub(super) rtl: bool,
+}
+
+impl OwnerDrawItem {
+    /// Text without the null terminator.
+    fn text(&self) -> &[u16] {
+        self.text.strip_suffix(&[0]).unwrap_or(&self.text)
+    }
+}
+
+/// Colors used to draw menu items.
+struct Colors {
+    background: u32,
+    selected: u32,
+    text: u32,
+    selected_text: u32,
+    grayed: u32,
+    separator: u32,
+}
+
+/// Theme state used to render owner-drawn popup menus.
+///
+/// Whether dark mode is used is refreshed every time a menu is opened, so that
+/// menus follow changes to the system theme.
+pub(super) struct Theme {
+    dark: Cell<bool>,
+    dark_brush: Cell<gdi::HBRUSH>,
+}
+
+impl Theme {
+    pub(super) fn new() -> Self {
+        Self {
+            dark: Cell::new(false),
+            dark_brush: Cell::new(0),
+        }
+    }
+
+    /// Prepare a menu which is about to be shown.
+    pub(super) unsafe fn init_menu(&self, hmenu: HMENU) {
+        let mut info: winuser::MENUINFO = MaybeUninit::zeroed().assume_init();
+        info.cbSize = size_of::<winuser::MENUINFO>() as u32;
+        info.fMask = winuser::MIM_MENUDATA;
+
+        if winuser::GetMenuInfo(hmenu, &mut info) == FALSE || info.dwMenuData != OWNER_DRAW_MENU {
+            return;
+        }
+
+        self.dark.set(is_dark_mode());
+
+        let brush = if self.dark.get() {
+            if self.dark_brush.get() == 0 {
+                self.dark_brush.set(gdi::CreateSolidBrush(DARK_BACKGROUND));
+            }
+
+            self.dark_brush.get()
+        } else {
+            gdi::GetSysColorBrush(gdi::COLOR_MENU)
+        };
+
+        info.fMask = winuser::MIM_BACKGROUND;
+        info.hbrBack = brush;
+        winuser::SetMenuInfo(hmenu, &info);
+    }
+
+    /// Measure an owner-drawn menu item.
+    pub(super) unsafe fn measure(&self, hwnd: HWND, mis: &mut MEASUREITEMSTRUCT) {
+        let item = &*(mis.itemData as *const OwnerDrawItem);
+
+        if item.separator {
+            mis.itemWidth = 0;
+            mis.itemHeight = SEPARATOR_HEIGHT as u32;
+            return;
+        }
+
+        let hdc = gdi::GetDC(hwnd);
+        let font = menu_font(false);
+        let old = gdi::SelectObject(hdc, font);
+
+        let text = item.text();
+        let mut size = SIZE { cx: 0, cy: 0 };
+        gdi::GetTextExtentPoint32W(hdc, text.as_ptr(), text.len() as i32, &mut size);
+
+        if !item.shortcut.is_empty() {
+            let mut shortcut = SIZE { cx: 0, cy: 0 };
+
+            gdi::GetTextExtentPoint32W(
+                hdc,
+                item.shortcut.as_ptr(),
+                item.shortcut.len() as i32,
+                &mut shortcut,
+            );
+
+            size.cx += TEXT_PADDING + shortcut.cx;
+        }
+
+        gdi::SelectObject(hdc, old);
+        gdi::DeleteObject(font);
+        gdi::ReleaseDC(hwnd, hdc);
+
+        let column = column_width();
+        let icon = winuser::GetSystemMetrics(winuser::SM_CYSMICON);
+
+        mis.itemWidth = (column + size.cx + TEXT_PADDING) as u32;
+        mis.itemHeight = (size.cy.max(icon) + PADDING * 2) as u32;
+    }
+
+    /// Draw an owner-drawn menu item.
+    pub(super) unsafe fn draw(&self, dis: &DRAWITEMSTRUCT) {
+        let item = &*(dis.itemData as *const OwnerDrawItem);
+        let colors = self.colors();
+        let hdc = dis.hDC;
+        let rect = dis.rcItem;
+
+        let selected = dis.itemState & ODS_SELECTED != 0 && !item.separator;
+
+        fill(
+            hdc,
+            &rect,
+            if selected {
+                colors.selected
+            } else {
+                colors.background
+            },
+        );
+
+        if item.separator {
+            let mid = rect.top + (rect.bottom - rect.top) / 2;
+
+            let line = RECT {
+                left: rect.left + PADDING,
+                top: mid,
+                right: rect.right - PADDING,
+                bottom: mid + 1,
+            };
+
+            fill(hdc, &line, colors.separator);
+            return;
+        }
+
+        let text_color = if dis.itemState & ODS_GRAYED != 0 {
+            colors.grayed
+        } else if selected {
+            colors.selected_text
+        } else {
+            colors.text
+        };
+
+        let column = if item.rtl {
+            RECT {
+                left: rect.right - column_width(),
+                top: rect.top,
+                right: rect.right,
+                bottom: rect.bottom,
+            }
+        } else {
+            RECT {
+                left: rect.left,
+                top: rect.top,
+                right: rect.left + column_width(),
+                bottom: rect.bottom,
+            }
+        };
+
+        let checked = dis.itemState & ODS_CHECKED != 0;
+
+        let icon = match (checked, item.checked_icon, item.unchecked_icon) {
+            (true, 0, _) => 0,
+            (true, icon, _) => icon,
+            (false, _, 0) => item.icon,
+            (false, _, icon) => icon,
+        };
+
+        let old_mode = gdi::SetBkMode(hdc, gdi::TRANSPARENT as i32);
+        let old_color = gdi::SetTextColor(hdc, text_color);
+
+        if icon != 0 {
+            let size = winuser::GetSystemMetrics(winuser::SM_CXSMICON);
+            let x = column.left + (column.right - column.left - size) / 2;
+            let y = column.top + (column.bottom - column.top - size) / 2;
+            winuser::DrawIconEx(hdc, x, y, icon, size, size, 0, 0, winuser::DI_NORMAL);
+        } else if checked {
+            let font = menu_font(false);
+            let old = gdi::SelectObject(hdc, font);
+            let mut check = [0; 2];
+            let check = CHECK_MARK.encode_utf16(&mut check);
+            let mut column = column;
+
+            gdi::DrawTextW(
+                hdc,
+                check.as_ptr(),
+                check.len() as i32,
+                &mut column,
+                gdi::DT_CENTER | gdi::DT_VCENTER | gdi::DT_SINGLELINE,
+            );
+
+            gdi::SelectObject(hdc, old);
+            gdi::DeleteObject(font);
+        }
+
+        let font = menu_font(dis.itemState & ODS_DEFAULT != 0);
+        let old = gdi::SelectObject(hdc, font);
+
+        let (mut text_rect, mut format) = if item.rtl {
+            let text_rect = RECT {
+                left: rect.left + PADDING,
+                top: rect.top,
+                right: column.left,
+                bottom: rect.bottom,
+            };
+
+            (text_rect, gdi::DT_RIGHT | gdi::DT_RTLREADING)
+        } else {
+            let text_rect = RECT {
+                left: column.right,
+                top: rect.top,
+                right: rect.right - PADDING,
+                bottom: rect.bottom,
+            };
+
+            (text_rect, gdi::DT_LEFT)
+        };
+
+        format |= gdi::DT_VCENTER | gdi::DT_SINGLELINE;
+
+        if dis.itemState & ODS_NOACCEL != 0 {
+            format |= gdi::DT_HIDEPREFIX;
+        }
+
+        let text = item.text();
+        gdi::DrawTextW(
+            hdc,
+            text.as_ptr(),
+            text.len() as i32,
+            &mut text_rect,
+            format,
+        );
+
+        if !item.shortcut.is_empty() {
+            // The shortcut is aligned to the edge opposite of the text.
+            let align = if item.rtl {
+                gdi::DT_LEFT
+            } else {
+                gdi::DT_RIGHT
+            };
+            let format = format & !(gdi::DT_LEFT | gdi::DT_RIGHT) | align;
+
+            gdi::DrawTextW(
+                hdc,
+                item.shortcut.as_ptr(),
+                item.shortcut.len() as i32,
+                &mut text_rect,
+                format,
+            );
+        }
+
+        gdi::SelectObject(hdc, old);
+        gdi::DeleteObject(font);
+        gdi::SetTextColor(hdc, old_color);
+        gdi::SetBkMode(hdc, old_mode);
+    }
+
+    unsafe fn colors(&self) -> Colors {
+        if self.dark.get() {
+            Colors {
+                background: DARK_BACKGROUND,
+                selected: DARK_SELECTED,
+                text: DARK_TEXT,
+                selected_text: DARK_TEXT,
+                grayed: DARK_GRAYED,
+                separator: DARK_SEPARATOR,
+            }
+        } else {
+            Colors {
+                background: gdi::GetSysColor(gdi::COLOR_MENU),
+                selected: gdi::GetSysColor(gdi::COLOR_HIGHLIGHT),
+                text: gdi::GetSysColor(gdi::COLOR_MENUTEXT),
+                selected_text: gdi::GetSysColor(gdi::COLOR_HIGHLIGHTTEXT),
+                grayed: gdi::GetSysColor(gdi::COLOR_GRAYTEXT),
+                separator: gdi::GetSysColor(gdi::COLOR_3DSHADOW),
+            }
+        }
+    }
+}
+
+impl Drop for Theme {
+    fn drop(&mut self) {
+        let brush = self.dark_brush.get();
+
+        if brush != 0 {
+            // SAFETY: The brush is owned by the theme.
+            unsafe {
+                gdi::DeleteObject(brush);
+            }
+        }
+    }
+}
+
+const fn rgb(r: u8, g: u8, b: u8) -> u32 {
+    r as u32 | (g as u32) << 8 | (b as u32) << 16
+}
+
+/// Width of the column to the left of the text, which holds check marks and
+/// icons.
+unsafe fn column_width() -> i32 {
+    let check = winuser::GetSystemMetrics(winuser::SM_CXMENUCHECK);
+    let icon = winuser::GetSystemMetrics(winuser::SM_CXSMICON);
+    check.max(icon) + PADDING * 2
+}
+
+unsafe fn fill(hdc: gdi::HDC, rect: &RECT, color: u32) {
+    let brush = gdi::CreateSolidBrush(color);
+    gdi::FillRect(hdc, rect, brush);
+    gdi::DeleteObject(brush);
+}
+
+/// Construct the font used in menus, which has to be deleted by the caller.
+unsafe fn menu_font(bold: bool) -> gdi::HFONT {
+    let mut metrics: winuser::NONCLIENTMETRICSW = MaybeUninit::zeroed().assume_init();
+    metrics.cbSize = size_of::<winuser::NONCLIENTMETRICSW>() as u32;
+
+    let result = winuser::SystemParametersInfoW(
+        winuser::SPI_GETNONCLIENTMETRICS,
+        metrics.cbSize,
+        ptr::addr_of_mut!(metrics).cast(),
+        0,
+    );
+
+    if result == FALSE {
+        return gdi::GetStockObject(gdi::DEFAULT_GUI_FONT);
+    }
+
+    if bold {
+        metrics.lfMenuFont.lfWeight = gdi::FW_BOLD as i32;
+    }
+
+    gdi::CreateFontIndirectW(&metrics.lfMenuFont)
+}
+
+/// Test if applications should use the dark theme.
+fn is_dark_mode() -> bool {
+    let key = PERSONALIZE_KEY.to_wide_null();
+    let value = LIGHT_THEME_VALUE.to_wide_null();
+
+    let mut data = 0u32;
+    let mut len = size_of::<u32>() as u32;
+
+    let result = unsafe {
+        winreg::RegGetValueW(
+            winreg::HKEY_CURRENT_USER,
+            key.as_ptr(),
+            value.as_ptr(),
+            winreg::RRF_RT_REG_DWORD,
+            ptr::null_mut(),
+            ptr::addr_of_mut!(data).cast(),
+            &mut len,
+        )
+    };
+
+    result == ERROR_SUCCESS && data == 0
+}
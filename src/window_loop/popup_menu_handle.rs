@@ -1,3 +1,4 @@
+use std::collections::BTreeMap;
 use std::io;
 use std::mem::{size_of, MaybeUninit};
 use std::str;
@@ -7,17 +8,47 @@ use windows_sys::Win32::UI::WindowsAndMessaging as winuser;
 
 use crate::convert::ToWide;
 use crate::event::MouseButtons;
-use crate::ModifyMenuItem;
+use crate::menu_item::{MenuItem, MenuItemKind};
+use crate::{IconId, ModifyMenuItem};
+
+use super::{BitmapHandle, Hotkey, IconHandle, OwnerDrawItem, OWNER_DRAW_MENU};
 
 #[repr(C)]
 pub(crate) struct PopupMenuHandle {
     pub(crate) hmenu: winuser::HMENU,
     pub(crate) open_menu: MouseButtons,
+    /// Flags used when showing the menu.
+    pub(crate) track_flags: u32,
+    /// Menu items which are members of each group.
+    pub(crate) groups: Vec<Vec<u32>>,
+    /// Bitmaps used by menu items, which have to outlive the menu.
+    bitmaps: BTreeMap<u32, Vec<BitmapHandle>>,
+    /// Whether menu items are owner drawn.
+    owner_draw: bool,
+    /// Whether menu items are laid out from right to left.
+    rtl: bool,
+    /// Data of owner-drawn menu items, which is referenced by the menu.
+    owner_draw_items: BTreeMap<u32, Box<OwnerDrawItem>>,
+    /// Global hotkeys which click menu items.
+    hotkeys: BTreeMap<u32, Hotkey>,
 }
 
 impl PopupMenuHandle {
     /// Construct a new menu handle.
-    pub(crate) fn new(open_menu: MouseButtons) -> io::Result<Self> {
+    ///
+    /// If `owner_draw` is set, menu items are rendered by the window so that
+    /// they follow the system theme. If `rtl` is set, the menu is laid out
+    /// from right to left.
+    pub(crate) fn new(
+        open_menu: MouseButtons,
+        mut track_flags: u32,
+        owner_draw: bool,
+        rtl: bool,
+    ) -> io::Result<Self> {
+        if rtl {
+            track_flags |= winuser::TPM_LAYOUTRTL;
+        }
+
         unsafe {
             // Setup menu
             let hmenu = winuser::CreatePopupMenu();
@@ -26,9 +57,19 @@ impl PopupMenuHandle {
                 return Err(io::Error::last_os_error());
             }
 
-            let menu = Self { hmenu, open_menu };
+            let menu = Self {
+                hmenu,
+                open_menu,
+                track_flags,
+                groups: Vec::new(),
+                bitmaps: BTreeMap::new(),
+                owner_draw,
+                rtl,
+                owner_draw_items: BTreeMap::new(),
+                hotkeys: BTreeMap::new(),
+            };
 
-            let m = winuser::MENUINFO {
+            let mut m = winuser::MENUINFO {
                 cbSize: size_of::<winuser::MENUINFO>() as u32,
                 fMask: winuser::MIM_APPLYTOSUBMENUS | winuser::MIM_STYLE,
                 dwStyle: winuser::MNS_NOTIFYBYPOS,
@@ -38,6 +79,11 @@ impl PopupMenuHandle {
                 dwMenuData: 0,
             };
 
+            if owner_draw {
+                m.fMask |= winuser::MIM_MENUDATA;
+                m.dwMenuData = OWNER_DRAW_MENU;
+            }
+
             if winuser::SetMenuInfo(hmenu, &m) == FALSE {
                 return Err(io::Error::last_os_error());
             }
@@ -46,37 +92,134 @@ impl PopupMenuHandle {
         }
     }
 
+    /// Insert a menu item at the given position.
+    ///
+    /// If the position is past the end of the menu the item is appended.
+    pub(crate) fn insert_item(
+        &mut self,
+        position: u32,
+        menu_item: &MenuItem,
+        default: bool,
+        icons: &[IconHandle],
+    ) -> io::Result<()> {
+        let menu_item_id = menu_item.item_id.id();
+
+        match &menu_item.kind {
+            MenuItemKind::Separator => {
+                self.add_menu_separator(position, menu_item_id, default, &menu_item.initial)?;
+            }
+            MenuItemKind::String { text } => {
+                // NB: The hotkey is parsed first so that an invalid shortcut
+                // doesn't leave a partially constructed menu item behind.
+                let hotkey = match &menu_item.shortcut {
+                    Some(shortcut) if menu_item.global_hotkey => Some(Hotkey::parse(shortcut)?),
+                    _ => None,
+                };
+
+                self.add_menu_entry(position, menu_item, text, default, icons)?;
+
+                if let Some(hotkey) = hotkey {
+                    self.hotkeys.insert(menu_item_id, hotkey);
+                }
+            }
+        }
+
+        for group in &menu_item.groups {
+            self.add_to_group(group.id(), menu_item_id);
+        }
+
+        Ok(())
+    }
+
     /// Add a menu entry.
-    pub(crate) fn add_menu_entry(
-        &self,
-        menu_item_id: u32,
+    fn add_menu_entry(
+        &mut self,
+        position: u32,
+        menu_item: &MenuItem,
         string: &str,
         default: bool,
-        modify: &ModifyMenuItem,
+        icons: &[IconHandle],
     ) -> io::Result<()> {
+        let menu_item_id = menu_item.item_id.id();
+        let icon = |id: Option<IconId>| id.and_then(|icon| icons.get(icon.as_usize()));
+
         let mut item = new_menuitem();
         item.fMask = winuser::MIIM_FTYPE | winuser::MIIM_ID;
         item.fType = winuser::MFT_STRING;
         item.wID = menu_item_id;
 
-        let string = string.to_wide_null();
+        if self.rtl {
+            item.fType |= winuser::MFT_RIGHTORDER;
+        }
 
-        modify_string(&mut item, Some(&string[..]));
+        // NB: Menus display text following a tab character right-aligned in
+        // a separate column, which is where accelerators are conventionally
+        // listed.
+        let label = match &menu_item.shortcut {
+            Some(shortcut) => format!("{string}\t{shortcut}").to_wide_null(),
+            None => string.to_wide_null(),
+        };
+
+        modify_string(&mut item, Some(&label[..]));
         modify_default(&mut item, default);
-        apply(&mut item, modify);
+        apply(&mut item, &menu_item.initial);
+
+        if self.owner_draw {
+            let hicon = |id: Option<IconId>| icon(id).map_or(0, |icon| icon.hicon);
+
+            let data = Box::new(OwnerDrawItem {
+                text: string.to_wide_null(),
+                shortcut: menu_item
+                    .shortcut
+                    .as_deref()
+                    .map(|s| s.encode_utf16().collect())
+                    .unwrap_or_default(),
+                icon: hicon(menu_item.icon),
+                checked_icon: hicon(menu_item.checked_icon),
+                unchecked_icon: hicon(menu_item.unchecked_icon),
+                separator: false,
+                rtl: self.rtl,
+            });
+
+            return self.insert_owner_draw(position, menu_item_id, item, data);
+        }
 
-        let result = unsafe { winuser::InsertMenuItemW(self.hmenu, menu_item_id, TRUE, &item) };
+        let mut bitmaps = Vec::new();
 
-        if result == FALSE {
-            return Err(io::Error::last_os_error());
+        if let Some(icon) = icon(menu_item.icon) {
+            let bitmap = BitmapHandle::from_icon(icon)?;
+            item.fMask |= winuser::MIIM_BITMAP;
+            item.hbmpItem = bitmap.hbitmap;
+            bitmaps.push(bitmap);
+        }
+
+        if let Some(icon) = icon(menu_item.checked_icon) {
+            let bitmap = BitmapHandle::check_mark(icon)?;
+            item.fMask |= winuser::MIIM_CHECKMARKS;
+            item.hbmpChecked = bitmap.hbitmap;
+            bitmaps.push(bitmap);
+        }
+
+        if let Some(icon) = icon(menu_item.unchecked_icon) {
+            let bitmap = BitmapHandle::check_mark(icon)?;
+            item.fMask |= winuser::MIIM_CHECKMARKS;
+            item.hbmpUnchecked = bitmap.hbitmap;
+            bitmaps.push(bitmap);
+        }
+
+        self.insert(position, &item)?;
+
+        if !bitmaps.is_empty() {
+            self.bitmaps.insert(menu_item_id, bitmaps);
         }
 
         Ok(())
     }
 
-    /// Add a menu separator at the given index.
-    pub(crate) fn add_menu_separator(
-        &self,
+    /// Add a menu separator.
+    fn add_menu_separator(
+        &mut self,
+        position: u32,
         menu_item_id: u32,
         default: bool,
         modify: &ModifyMenuItem,
@@ -89,7 +232,48 @@ impl PopupMenuHandle {
         apply(&mut item, modify);
         modify_default(&mut item, default);
 
-        let result = unsafe { winuser::InsertMenuItemW(self.hmenu, menu_item_id, 1, &item) };
+        if self.owner_draw {
+            let data = Box::new(OwnerDrawItem {
+                text: Vec::new(),
+                shortcut: Vec::new(),
+                icon: 0,
+                checked_icon: 0,
+                unchecked_icon: 0,
+                separator: true,
+                rtl: self.rtl,
+            });
+
+            return self.insert_owner_draw(position, menu_item_id, item, data);
+        }
+
+        self.insert(position, &item)
+    }
+
+    /// Insert an owner-drawn menu item which references the given data.
+    fn insert_owner_draw(
+        &mut self,
+        position: u32,
+        menu_item_id: u32,
+        mut item: winuser::MENUITEMINFOW,
+        data: Box<OwnerDrawItem>,
+    ) -> io::Result<()> {
+        item.fMask |= winuser::MIIM_DATA;
+        item.fType |= winuser::MFT_OWNERDRAW;
+        item.dwItemData = &*data as *const OwnerDrawItem as usize;
+        self.insert(position, &item)?;
+        self.owner_draw_items.insert(menu_item_id, data);
+        Ok(())
+    }
+
+    fn insert(&self, position: u32, item: &winuser::MENUITEMINFOW) -> io::Result<()> {
+        let count = unsafe { winuser::GetMenuItemCount(self.hmenu) };
+
+        let Ok(count) = u32::try_from(count) else {
+            return Err(io::Error::last_os_error());
+        };
+
+        let result =
+            unsafe { winuser::InsertMenuItemW(self.hmenu, position.min(count), TRUE, item) };
 
         if result == FALSE {
             return Err(io::Error::last_os_error());
@@ -98,16 +282,65 @@ impl PopupMenuHandle {
         Ok(())
     }
 
-    /// Set the checked state of the specified menu item.
+    /// Remove the specified menu item.
+    pub(crate) fn remove_menu_item(&mut self, menu_item_id: u32) -> io::Result<()> {
+        let result =
+            unsafe { winuser::DeleteMenu(self.hmenu, menu_item_id, winuser::MF_BYCOMMAND) };
+
+        if result == FALSE {
+            return Err(io::Error::last_os_error());
+        }
+
+        for group in &mut self.groups {
+            group.retain(|&id| id != menu_item_id);
+        }
+
+        self.bitmaps.remove(&menu_item_id);
+        self.owner_draw_items.remove(&menu_item_id);
+        self.hotkeys.remove(&menu_item_id);
+        Ok(())
+    }
+
+    /// Get the global hotkey which clicks the given menu item.
+    pub(crate) fn hotkey(&self, menu_item_id: u32) -> Option<Hotkey> {
+        self.hotkeys.get(&menu_item_id).copied()
+    }
+
+    /// Iterate over all global hotkeys which click menu items.
+    pub(crate) fn hotkeys(&self) -> impl Iterator<Item = (u32, Hotkey)> + '_ {
+        self.hotkeys.iter().map(|(&id, &hotkey)| (id, hotkey))
+    }
+
+    /// Modify the state of the specified menu item.
+    /// Test if the menu contains the given menu item.
+    pub(crate) fn contains(&self, menu_item_id: u32) -> bool {
+        let state =
+            unsafe { winuser::GetMenuState(self.hmenu, menu_item_id, winuser::MF_BYCOMMAND) };
+        state != u32::MAX
+    }
+
     pub(crate) fn modify_menu_item(
         &self,
-        item_idx: u32,
+        menu_item_id: u32,
         modify: &ModifyMenuItem,
     ) -> io::Result<()> {
         let mut item = new_menuitem();
+
+        // Setting the state replaces all of it, so start out from the current
+        // state of the item.
+        item.fMask = winuser::MIIM_STATE;
+
+        let result =
+            unsafe { winuser::GetMenuItemInfoW(self.hmenu, menu_item_id, FALSE, &mut item) };
+
+        if result == FALSE {
+            return Err(io::Error::last_os_error());
+        }
+
+        item.fMask = 0;
         apply(&mut item, modify);
 
-        let result = unsafe { winuser::SetMenuItemInfoW(self.hmenu, item_idx, 1, &item) };
+        let result = unsafe { winuser::SetMenuItemInfoW(self.hmenu, menu_item_id, FALSE, &item) };
 
         if result == FALSE {
             return Err(io::Error::last_os_error());
@@ -115,6 +348,22 @@ impl PopupMenuHandle {
 
         Ok(())
     }
+
+    /// Add a menu item to the given group.
+    fn add_to_group(&mut self, group: u32, menu_item_id: u32) {
+        let group = group as usize;
+
+        if self.groups.len() <= group {
+            self.groups.resize_with(group + 1, Vec::new);
+        }
+
+        self.groups[group].push(menu_item_id);
+    }
+
+    /// Get the menu items which are members of the given group.
+    pub(crate) fn group(&self, group: u32) -> &[u32] {
+        self.groups.get(group as usize).map_or(&[], Vec::as_slice)
+    }
 }
 
 fn modify_string(item: &mut winuser::MENUITEMINFOW, string: Option<&[u16]>) {
@@ -133,31 +382,24 @@ fn modify_default(item: &mut winuser::MENUITEMINFOW, default: bool) {
 }
 
 fn apply(item: &mut winuser::MENUITEMINFOW, modify: &ModifyMenuItem) {
-    modify_checked(item, modify.checked);
-    modify_highlight(item, modify.highlight);
+    modify_state(item, winuser::MFS_CHECKED, modify.checked);
+    modify_state(item, winuser::MFS_HILITE, modify.highlight);
+    modify_state(
+        item,
+        winuser::MFS_DISABLED,
+        modify.enabled.map(|enabled| !enabled),
+    );
 }
 
-fn modify_checked(item: &mut winuser::MENUITEMINFOW, checked: Option<bool>) {
-    if let Some(checked) = checked {
+fn modify_state(item: &mut winuser::MENUITEMINFOW, flag: u32, value: Option<bool>) {
+    if let Some(value) = value {
         item.fMask |= winuser::MIIM_STATE;
 
-        item.fState |= if checked {
-            winuser::MFS_CHECKED
+        if value {
+            item.fState |= flag;
         } else {
-            winuser::MFS_UNCHECKED
-        };
-    }
-}
-
-fn modify_highlight(item: &mut winuser::MENUITEMINFOW, highlight: Option<bool>) {
-    if let Some(highlight) = highlight {
-        item.fMask |= winuser::MIIM_STATE;
-
-        item.fState |= if highlight {
-            winuser::MFS_HILITE
-        } else {
-            winuser::MFS_UNHILITE
-        };
+            item.fState &= !flag;
+        }
     }
 }
 
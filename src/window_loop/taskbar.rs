@@ -0,0 +1,106 @@
+use std::ffi::c_void;
+use std::io;
+
+use windows_sys::core::{GUID, HRESULT, PCWSTR};
+use windows_sys::Win32::Foundation::HWND;
+use windows_sys::Win32::UI::Shell::{TaskbarList, TBPFLAG, TBPF_ERROR, TBPF_NORMAL, TBPF_PAUSED};
+use windows_sys::Win32::UI::WindowsAndMessaging::HICON;
+
+use crate::com::{check, Com, ComPtr, IUnknownVtbl};
+use crate::convert::ToWide;
+
+use super::IconHandle;
+
+const IID_ITASKBAR_LIST_3: GUID = GUID::from_u128(0xea1afb91_9e28_4b86_90e9_9e9f8a5eefaf);
+
+/// The total used when setting the progress value.
+pub(super) const PROGRESS_TOTAL: u64 = 10000;
+
+#[repr(C)]
+struct ITaskbarList3Vtbl {
+    base: IUnknownVtbl,
+    hr_init: unsafe extern "system" fn(*mut c_void) -> HRESULT,
+    add_tab: usize,
+    delete_tab: usize,
+    activate_tab: usize,
+    set_active_alt: usize,
+    mark_fullscreen_window: usize,
+    set_progress_value: unsafe extern "system" fn(*mut c_void, HWND, u64, u64) -> HRESULT,
+    set_progress_state: unsafe extern "system" fn(*mut c_void, HWND, TBPFLAG) -> HRESULT,
+    register_tab: usize,
+    unregister_tab: usize,
+    set_tab_order: usize,
+    set_tab_active: usize,
+    thumb_bar_add_buttons: usize,
+    thumb_bar_update_buttons: usize,
+    thumb_bar_set_image_list: usize,
+    set_overlay_icon: unsafe extern "system" fn(*mut c_void, HWND, HICON, PCWSTR) -> HRESULT,
+}
+
+/// Access to the taskbar button of windows.
+///
+/// This must only be used on the thread it was created on.
+pub(super) struct Taskbar {
+    list: ComPtr<ITaskbarList3Vtbl>,
+    // NB: Must be dropped last.
+    _com: Com,
+}
+
+impl Taskbar {
+    /// Connect to the taskbar.
+    pub(super) fn new() -> io::Result<Self> {
+        let com = Com::new()?;
+
+        unsafe {
+            let list = com.create::<ITaskbarList3Vtbl>(&TaskbarList, &IID_ITASKBAR_LIST_3)?;
+            check((list.hr_init)(list.as_raw()))?;
+            Ok(Self { list, _com: com })
+        }
+    }
+
+    /// Set the progress shown on the taskbar button of the given window, where
+    /// the value is out of [`PROGRESS_TOTAL`].
+    pub(super) fn set_progress(&self, hwnd: HWND, value: u64, flags: TBPFLAG) -> io::Result<()> {
+        unsafe {
+            check((self.list.set_progress_state)(
+                self.list.as_raw(),
+                hwnd,
+                flags,
+            ))?;
+
+            // NB: Setting a value switches indeterminate or no progress into
+            // normal progress, so it's only set when it's displayed.
+            if matches!(flags, TBPF_NORMAL | TBPF_ERROR | TBPF_PAUSED) {
+                check((self.list.set_progress_value)(
+                    self.list.as_raw(),
+                    hwnd,
+                    value.min(PROGRESS_TOTAL),
+                    PROGRESS_TOTAL,
+                ))?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Set or clear the overlay icon shown over the taskbar button of the
+    /// given window, with a description used for accessibility.
+    pub(super) fn set_overlay_icon(
+        &self,
+        hwnd: HWND,
+        icon: Option<&IconHandle>,
+        description: &str,
+    ) -> io::Result<()> {
+        let description = description.to_wide_null();
+
+        // NB: The taskbar keeps its own copy of the icon.
+        unsafe {
+            check((self.list.set_overlay_icon)(
+                self.list.as_raw(),
+                hwnd,
+                icon.map_or(0, |icon| icon.hicon),
+                description.as_ptr(),
+            ))
+        }
+    }
+}
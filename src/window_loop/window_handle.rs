@@ -1,34 +1,143 @@
+use std::collections::{BTreeMap, BTreeSet};
 use std::io;
 use std::mem::{size_of, MaybeUninit};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::Duration;
 
-use windows_sys::Win32::Foundation::{FALSE, HWND};
+use windows_sys::Win32::Foundation::{FALSE, HWND, LPARAM, POINT, RECT, S_OK, WPARAM};
+use windows_sys::Win32::Media::Audio as audio;
 use windows_sys::Win32::UI::Shell::{self as shellapi, SHGetStockIconInfo};
+use windows_sys::Win32::UI::WindowsAndMessaging as winuser;
 
+use crate::area::Rect;
 use crate::convert::copy_wstring_lossy;
-use crate::notification::NotificationIcon;
-use crate::{AreaId, Notification};
+use crate::convert::ToWide;
+use crate::notification::{NotificationIcon, NotificationSound};
+use crate::{AreaId, Attention, Badge, IconId, ModifyArea, Notification, TaskbarState};
 
-use super::{messages, IconHandle};
+use super::{badge_icon, messages, Hotkey, HotkeyBinding, IconHandle, PROGRESS_TOTAL};
+
+/// A request to copy data to another window, as it's sent to the window
+/// thread.
+pub(super) struct CopyDataRequest {
+    pub(super) to: HWND,
+    pub(super) ty: usize,
+    pub(super) bytes: Vec<u8>,
+}
+
+/// The set of notification areas which are currently added to the
+/// notification tray.
+///
+/// This is shared with the window thread, which deletes any remaining areas
+/// before the window is destroyed. Each area is therefore deleted exactly once,
+/// regardless of whether the window is shut down or dropped.
+#[derive(Default, Clone)]
+pub(super) struct AddedAreas {
+    inner: Arc<Mutex<BTreeSet<u32>>>,
+}
+
+impl AddedAreas {
+    fn lock(&self) -> MutexGuard<'_, BTreeSet<u32>> {
+        self.inner.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Delete all areas which are currently added.
+    pub(super) fn delete_all(&self, hwnd: HWND) {
+        self.delete_all_with(|id| {
+            _ = delete_area(hwnd, id);
+        });
+    }
+
+    /// Add an area using `add` unless it's already added, returning `true` if
+    /// it was added.
+    fn add_with<F>(&self, id: u32, add: F) -> io::Result<bool>
+    where
+        F: FnOnce() -> io::Result<()>,
+    {
+        let mut added = self.lock();
+
+        if added.contains(&id) {
+            return Ok(false);
+        }
+
+        add()?;
+        added.insert(id);
+        Ok(true)
+    }
+
+    /// Delete an area using `delete` if it's added.
+    fn delete_with<F>(&self, id: u32, delete: F) -> io::Result<()>
+    where
+        F: FnOnce(u32) -> io::Result<()>,
+    {
+        if !self.lock().remove(&id) {
+            return Ok(());
+        }
+
+        delete(id)
+    }
+
+    /// Delete all areas which are currently added using `delete`.
+    fn delete_all_with<F>(&self, delete: F)
+    where
+        F: FnMut(u32),
+    {
+        let added = std::mem::take(&mut *self.lock());
+        added.into_iter().for_each(delete);
+    }
+}
+
+/// The version of the notification icon behavior requested from the shell.
+///
+/// Version 4 changes the layout of callback messages, see
+/// [`IconMessage`][super::messages::IconMessage], and enables hover
+/// notifications through `NIN_POPUPOPEN` and `NIN_POPUPCLOSE`.
+pub(super) const NOTIFY_ICON_VERSION: u32 = shellapi::NOTIFYICON_VERSION_4;
 
 pub(crate) struct WindowHandle {
     pub(super) hwnd: HWND,
+    pub(super) added: AddedAreas,
+    /// Areas for which the standard tooltip is suppressed.
+    pub(super) custom_tooltips: BTreeSet<u32>,
+    /// The current icon of each area.
+    pub(super) icons: BTreeMap<u32, IconId>,
+    /// The badge of each area, along with the icon it was drawn onto.
+    pub(super) badges: BTreeMap<u32, (Badge, Option<IconHandle>)>,
 }
 
 impl WindowHandle {
+    /// Get the raw handle of the window.
+    pub(crate) fn hwnd(&self) -> HWND {
+        self.hwnd
+    }
+
     fn new_nid(&self, area_id: AreaId) -> shellapi::NOTIFYICONDATAW {
-        let mut nid: shellapi::NOTIFYICONDATAW = unsafe { MaybeUninit::zeroed().assume_init() };
-        nid.cbSize = size_of::<shellapi::NOTIFYICONDATAW>() as u32;
-        nid.hWnd = self.hwnd;
-        nid.uID = area_id.id();
-        nid
+        new_nid(self.hwnd, area_id.id())
     }
 
     pub(crate) fn add_notification(&mut self, area_id: AreaId) -> io::Result<()> {
+        let added = self.added.add_with(area_id.id(), || {
+            let mut nid = self.new_nid(area_id);
+            nid.uFlags = shellapi::NIF_MESSAGE;
+            nid.uCallbackMessage = messages::ICON_ID;
+
+            let result = unsafe { shellapi::Shell_NotifyIconW(shellapi::NIM_ADD, &nid) };
+
+            if result == FALSE {
+                return Err(io::Error::last_os_error());
+            }
+
+            Ok(())
+        })?;
+
+        if !added {
+            return Ok(());
+        }
+
         let mut nid = self.new_nid(area_id);
-        nid.uFlags = shellapi::NIF_MESSAGE;
-        nid.uCallbackMessage = messages::ICON_ID;
+        nid.Anonymous.uVersion = NOTIFY_ICON_VERSION;
 
-        let result = unsafe { shellapi::Shell_NotifyIconW(shellapi::NIM_ADD, &nid) };
+        let result = unsafe { shellapi::Shell_NotifyIconW(shellapi::NIM_SETVERSION, &nid) };
 
         if result == FALSE {
             return Err(io::Error::last_os_error());
@@ -37,37 +146,99 @@ impl WindowHandle {
         Ok(())
     }
 
+    /// Delete the given notification area if it's added.
     pub(crate) fn delete_notification(&mut self, area_id: AreaId) -> io::Result<()> {
-        let result = unsafe {
-            let mut nid = self.new_nid(area_id);
-            nid.uFlags = shellapi::NIF_ICON;
-            shellapi::Shell_NotifyIconW(shellapi::NIM_DELETE, &nid)
-        };
+        self.custom_tooltips.remove(&area_id.id());
+        self.icons.remove(&area_id.id());
+        self.badges.remove(&area_id.id());
 
-        if result == FALSE {
-            return Err(io::Error::last_os_error());
-        }
+        let hwnd = self.hwnd;
+        self.added
+            .delete_with(area_id.id(), |id| delete_area(hwnd, id))
+    }
 
-        Ok(())
+    /// Delete all notification areas which are currently added.
+    pub(crate) fn delete_all_notifications(&mut self) {
+        self.added.delete_all(self.hwnd);
     }
 
-    /// Clear out tooltip.
+    /// Modify the icon, tooltip, or visibility of a notification area.
     pub(crate) fn modify_notification(
-        &self,
+        &mut self,
         area_id: AreaId,
-        icon: Option<&IconHandle>,
-        tooltip: Option<&str>,
+        modify: &ModifyArea,
+        icons: &[IconHandle],
     ) -> io::Result<()> {
         let mut nid = self.new_nid(area_id);
 
-        if let Some(icon) = icon {
+        match modify.icon {
+            Some(Some(icon)) => {
+                self.icons.insert(area_id.id(), icon);
+            }
+            Some(None) => {
+                self.icons.remove(&area_id.id());
+            }
+            None => {}
+        }
+
+        let icon = self
+            .icons
+            .get(&area_id.id())
+            .and_then(|icon| icons.get(icon.as_usize()));
+
+        match modify.badge {
+            Some(Some(badge)) => {
+                self.badges.insert(area_id.id(), (badge, None));
+            }
+            Some(None) => {
+                self.badges.remove(&area_id.id());
+            }
+            None => {}
+        }
+
+        if let Some((badge, badged)) = self.badges.get_mut(&area_id.id()) {
+            if modify.icon.is_some() || badged.is_none() {
+                let badged = badged.insert(badge_icon::render(icon, *badge)?);
+                nid.uFlags |= shellapi::NIF_ICON;
+                nid.hIcon = badged.hicon;
+            }
+        } else if modify.icon.is_some() || modify.badge.is_some() {
+            // NB: If the area has no icon, clearing the badge or the icon
+            // clears the icon which is displayed.
             nid.uFlags |= shellapi::NIF_ICON;
-            nid.hIcon = icon.hicon;
+            nid.hIcon = icon.map(|icon| icon.hicon).unwrap_or_default();
+        }
+
+        if let Some(tooltip) = &modify.tooltip {
+            nid.uFlags |= shellapi::NIF_TIP;
+            // NB: The tip is zeroed by default, so clearing it sends an empty
+            // tip.
+            copy_wstring_lossy(&mut nid.szTip, tooltip.as_deref().unwrap_or_default());
         }
 
-        if let Some(tooltip) = tooltip {
-            nid.uFlags |= shellapi::NIF_TIP | shellapi::NIF_SHOWTIP;
-            copy_wstring_lossy(&mut nid.szTip, tooltip);
+        if let Some(show_tooltip) = modify.show_tooltip {
+            if show_tooltip {
+                self.custom_tooltips.remove(&area_id.id());
+            } else {
+                self.custom_tooltips.insert(area_id.id());
+            }
+        }
+
+        // NB: With version 4 of the notification icon behavior, the standard
+        // tooltip is only shown if NIF_SHOWTIP is passed along with each
+        // modification. Without it the tooltip text is still used for
+        // accessibility.
+        if !self.custom_tooltips.contains(&area_id.id()) {
+            nid.uFlags |= shellapi::NIF_SHOWTIP;
+        }
+
+        if let Some(hidden) = modify.hidden {
+            nid.uFlags |= shellapi::NIF_STATE;
+            nid.dwStateMask = shellapi::NIS_HIDDEN;
+
+            if hidden {
+                nid.dwState = shellapi::NIS_HIDDEN;
+            }
         }
 
         let result = unsafe { shellapi::Shell_NotifyIconW(shellapi::NIM_MODIFY, &nid) };
@@ -80,16 +251,21 @@ impl WindowHandle {
     }
 
     /// Send a notification.
-    pub(crate) fn send_notification(&self, area_id: AreaId, n: Notification) -> io::Result<()> {
+    pub(crate) fn send_notification(
+        &self,
+        area_id: AreaId,
+        n: &Notification,
+        icons: &[IconHandle],
+    ) -> io::Result<()> {
         let mut nid = self.new_nid(area_id);
         nid.uFlags = shellapi::NIF_INFO;
 
-        if let Some(title) = n.title {
+        if let Some(title) = &n.title {
             copy_wstring_lossy(&mut nid.szInfoTitle, title.as_str());
         }
 
-        if let Some(message) = n.message {
-            copy_wstring_lossy(&mut nid.szInfo, message.as_str());
+        if let Some(body) = n.body() {
+            copy_wstring_lossy(&mut nid.szInfo, body.as_str());
         }
 
         if let Some(timeout) = n.timeout {
@@ -98,7 +274,17 @@ impl WindowHandle {
 
         nid.dwInfoFlags = n.options;
 
-        if let Some(icon) = n.icon {
+        // NB: A custom sound is played by us, so the one played by the shell
+        // is suppressed. Explicitly disabling the sound takes precedence.
+        let sound = match &n.sound {
+            Some(sound) if nid.dwInfoFlags & shellapi::NIIF_NOSOUND == 0 => {
+                nid.dwInfoFlags |= shellapi::NIIF_NOSOUND;
+                Some(sound)
+            }
+            _ => None,
+        };
+
+        if let Some(icon) = &n.icon {
             match icon {
                 NotificationIcon::Info => {
                     nid.dwInfoFlags |= shellapi::NIIF_INFO;
@@ -126,6 +312,12 @@ impl WindowHandle {
                         nid.dwInfoFlags |= shellapi::NIIF_USER;
                     }
                 },
+                NotificationIcon::Custom(icon) => {
+                    if let Some(icon) = icons.get(icon.as_usize()) {
+                        nid.hBalloonIcon = icon.hicon;
+                        nid.dwInfoFlags |= shellapi::NIIF_USER;
+                    }
+                }
             };
         }
 
@@ -135,9 +327,454 @@ impl WindowHandle {
             return Err(io::Error::last_os_error());
         }
 
+        if let Some(sound) = sound {
+            play_sound(sound);
+        }
+
+        Ok(())
+    }
+
+    /// Remove the notification which is currently shown by the given area.
+    ///
+    /// This is done by modifying the notification to have an empty message.
+    pub(crate) fn clear_notification(&self, area_id: AreaId) -> io::Result<()> {
+        let mut nid = self.new_nid(area_id);
+        nid.uFlags = shellapi::NIF_INFO;
+
+        let result = unsafe { shellapi::Shell_NotifyIconW(shellapi::NIM_MODIFY, &nid) };
+
+        if result == FALSE {
+            return Err(io::Error::last_os_error());
+        }
+
         Ok(())
     }
+
+    /// Get the bounding rectangle of the icon of the given area.
+    pub(crate) fn area_rect(&self, area_id: AreaId) -> io::Result<Rect> {
+        let rect = area_rect(self.hwnd, area_id.id())?;
+
+        Ok(Rect {
+            left: rect.left,
+            top: rect.top,
+            right: rect.right,
+            bottom: rect.bottom,
+        })
+    }
+
+    /// Open the popup menu of the given area.
+    ///
+    /// If no position is specified, the menu is opened next to the icon of the
+    /// area, or at the cursor if the location of the icon can't be
+    /// determined.
+    pub(crate) fn open_menu(
+        &self,
+        area_id: AreaId,
+        position: Option<(i32, i32)>,
+    ) -> io::Result<()> {
+        let (x, y) = match position {
+            Some(position) => position,
+            None => match area_rect(self.hwnd, area_id.id()) {
+                Ok(rect) => (
+                    rect.left + (rect.right - rect.left) / 2,
+                    rect.top + (rect.bottom - rect.top) / 2,
+                ),
+                Err(..) => unsafe {
+                    let mut p = MaybeUninit::<POINT>::zeroed();
+
+                    if winuser::GetCursorPos(p.as_mut_ptr()) == FALSE {
+                        return Err(io::Error::last_os_error());
+                    }
+
+                    let p = p.assume_init();
+                    (p.x, p.y)
+                },
+            },
+        };
+
+        let position = (x as u16 as u32) | ((y as u16 as u32) << 16);
+        self.post(
+            messages::OPEN_MENU_ID,
+            area_id.id() as WPARAM,
+            position as LPARAM,
+        )
+    }
+
+    /// Register a global hotkey which clicks the given menu item.
+    ///
+    /// Failing to register the hotkey is reported as an error event by the
+    /// window thread.
+    pub(crate) fn add_hotkey(
+        &self,
+        area_id: AreaId,
+        item_id: u32,
+        hotkey: Hotkey,
+    ) -> io::Result<()> {
+        let binding = Box::into_raw(Box::new(HotkeyBinding { item_id, hotkey }));
+
+        if let Err(error) = self.post(
+            messages::ADD_HOTKEY_ID,
+            area_id.id() as WPARAM,
+            binding as LPARAM,
+        ) {
+            // SAFETY: The message was never posted, so we still own the
+            // binding.
+            drop(unsafe { Box::from_raw(binding) });
+            return Err(error);
+        }
+
+        Ok(())
+    }
+
+    /// Copy data to another window from the window thread.
+    pub(crate) fn copy_data(&self, to: HWND, ty: usize, bytes: Vec<u8>) -> io::Result<()> {
+        let request = Box::into_raw(Box::new(CopyDataRequest { to, ty, bytes }));
+
+        if let Err(error) = self.post(messages::COPY_DATA_ID, 0, request as LPARAM) {
+            // SAFETY: The message was never posted, so we still own the
+            // request.
+            drop(unsafe { Box::from_raw(request) });
+            return Err(error);
+        }
+
+        Ok(())
+    }
+
+    /// Unregister the global hotkey of the given menu item.
+    pub(crate) fn remove_hotkey(&self, area_id: AreaId, item_id: u32) -> io::Result<()> {
+        self.post(
+            messages::REMOVE_HOTKEY_ID,
+            area_id.id() as WPARAM,
+            item_id as LPARAM,
+        )
+    }
+
+    /// Schedule the window to shut down after the given duration.
+    ///
+    /// The delay is clamped to the maximum timeout supported by timers, and
+    /// the actual delay used is returned.
+    pub(crate) fn schedule_shutdown(&self, duration: Duration) -> io::Result<Duration> {
+        let millis = duration
+            .as_millis()
+            .min(winuser::USER_TIMER_MAXIMUM as u128) as u64;
+        self.post(messages::SCHEDULE_SHUTDOWN_ID, millis as usize, 0)?;
+        Ok(Duration::from_millis(millis))
+    }
+
+    /// Wake up whoever is waiting to retry adding a notification area after
+    /// the given duration, replacing any previously scheduled wakeup.
+    pub(crate) fn schedule_retry(&self, duration: Duration) -> io::Result<()> {
+        let millis = duration.as_millis().clamp(
+            winuser::USER_TIMER_MINIMUM as u128,
+            winuser::USER_TIMER_MAXIMUM as u128,
+        );
+        self.post(messages::SCHEDULE_RETRY_ID, millis as usize, 0)
+    }
+
+    /// Add the given notification area again after the taskbar has been
+    /// created, such as when Explorer has restarted.
+    ///
+    /// Any notification area which is already added is deleted first, since
+    /// the taskbar might have been created after it was added.
+    pub(crate) fn readd_notification(&mut self, area_id: AreaId) -> io::Result<()> {
+        let hwnd = self.hwnd;
+        _ = self
+            .added
+            .delete_with(area_id.id(), |id| delete_area(hwnd, id));
+
+        // NB: Badges are drawn again, so that the icon is set when the area
+        // is modified.
+        if let Some((_, badged)) = self.badges.get_mut(&area_id.id()) {
+            *badged = None;
+        }
+
+        self.add_notification(area_id)
+    }
+
+    /// Wake up the event loop after the given duration to show scheduled
+    /// notifications.
+    ///
+    /// This replaces any previously scheduled wakeup. The delay is clamped to
+    /// the maximum timeout supported by timers, in which case the wakeup
+    /// happens early and has to be scheduled again.
+    pub(crate) fn schedule_notifications(&self, duration: Duration) -> io::Result<()> {
+        let millis = duration.as_millis().clamp(
+            winuser::USER_TIMER_MINIMUM as u128,
+            winuser::USER_TIMER_MAXIMUM as u128,
+        );
+        self.post(messages::SCHEDULE_NOTIFICATION_ID, millis as usize, 0)
+    }
+
+    /// Block the session from ending with the given reason, which is shown
+    /// to the user.
+    pub(crate) fn block_shutdown(&self, reason: &str) -> io::Result<()> {
+        let reason = Box::into_raw(Box::new(reason.to_wide_null()));
+
+        if let Err(error) = self.post(messages::BLOCK_SHUTDOWN_ID, 0, reason as LPARAM) {
+            // SAFETY: The message was never posted, so we still own the
+            // reason.
+            drop(unsafe { Box::from_raw(reason) });
+            return Err(error);
+        }
+
+        Ok(())
+    }
+
+    /// Stop blocking the session from ending.
+    pub(crate) fn unblock_shutdown(&self) -> io::Result<()> {
+        self.post(messages::UNBLOCK_SHUTDOWN_ID, 0, 0)
+    }
+
+    /// Start or replace the user timer with the given identifier.
+    ///
+    /// The period is clamped to what is supported by timers.
+    pub(crate) fn set_timer(&self, id: u16, period: Duration) -> io::Result<()> {
+        let millis = period.as_millis().clamp(
+            winuser::USER_TIMER_MINIMUM as u128,
+            winuser::USER_TIMER_MAXIMUM as u128,
+        );
+        self.post(messages::SET_TIMER_ID, id as WPARAM, millis as LPARAM)
+    }
+
+    /// Stop the user timer with the given identifier.
+    pub(crate) fn kill_timer(&self, id: u16) -> io::Result<()> {
+        self.post(messages::KILL_TIMER_ID, id as WPARAM, 0)
+    }
+
+    /// Set the progress shown on the taskbar button of the window.
+    pub(crate) fn set_taskbar_progress(&self, value: f32, state: TaskbarState) -> io::Result<()> {
+        let value = (value.clamp(0.0, 1.0) * PROGRESS_TOTAL as f32).round() as u64;
+        self.post(
+            messages::TASKBAR_PROGRESS_ID,
+            value as WPARAM,
+            state.flags() as LPARAM,
+        )
+    }
+
+    /// Set or clear the badge shown over the taskbar button of the window.
+    pub(crate) fn set_taskbar_badge(&self, badge: Option<Badge>) -> io::Result<()> {
+        let badge = Box::into_raw(Box::new(badge));
+
+        if let Err(error) = self.post(messages::TASKBAR_BADGE_ID, 0, badge as LPARAM) {
+            // SAFETY: The message was never posted, so we still own the badge.
+            drop(unsafe { Box::from_raw(badge) });
+            return Err(error);
+        }
+
+        Ok(())
+    }
+
+    /// Show or hide a visible window.
+    pub(crate) fn show(&self, show: bool) -> io::Result<()> {
+        self.post(messages::SHOW_WINDOW_ID, show as WPARAM, 0)
+    }
+
+    /// Flash the window to request the attention of the user.
+    pub(crate) fn request_attention(&self, attention: Attention) {
+        let info = winuser::FLASHWINFO {
+            cbSize: size_of::<winuser::FLASHWINFO>() as u32,
+            hwnd: self.hwnd,
+            dwFlags: attention.flags(),
+            uCount: attention.count(),
+            dwTimeout: 0,
+        };
+
+        // NB: The return value is the previous flash state of the window, so
+        // there's no error to report.
+        unsafe {
+            winuser::FlashWindowEx(&info);
+        }
+    }
+
+    /// Cancel any scheduled shutdown.
+    pub(crate) fn cancel_scheduled_shutdown(&self) -> io::Result<()> {
+        self.post(messages::CANCEL_SHUTDOWN_ID, 0, 0)
+    }
+
+    fn post(&self, msg: u32, w_param: WPARAM, l_param: LPARAM) -> io::Result<()> {
+        let result = unsafe { winuser::PostMessageW(self.hwnd, msg, w_param, l_param) };
+
+        if result == FALSE {
+            return Err(io::Error::last_os_error());
+        }
+
+        Ok(())
+    }
+}
+
+fn new_nid(hwnd: HWND, id: u32) -> shellapi::NOTIFYICONDATAW {
+    let mut nid: shellapi::NOTIFYICONDATAW = unsafe { MaybeUninit::zeroed().assume_init() };
+    nid.cbSize = size_of::<shellapi::NOTIFYICONDATAW>() as u32;
+    nid.hWnd = hwnd;
+    nid.uID = id;
+    nid
+}
+
+/// Play a notification sound asynchronously.
+///
+/// Sounds are played on a best-effort basis, so failing to play one is
+/// ignored.
+fn play_sound(sound: &NotificationSound) {
+    let (name, flags) = match sound {
+        NotificationSound::File(path) => (path.as_os_str().to_wide_null(), audio::SND_FILENAME),
+        sound => match sound.alias() {
+            Some(alias) => (alias.to_wide_null(), audio::SND_ALIAS),
+            None => return,
+        },
+    };
+
+    // SAFETY: The name is null-terminated and copied by the call.
+    unsafe {
+        audio::PlaySoundW(
+            name.as_ptr(),
+            0,
+            flags | audio::SND_ASYNC | audio::SND_NODEFAULT | audio::SND_SYSTEM,
+        );
+    }
+}
+
+/// Get the bounding rectangle of the icon with the given id.
+pub(super) fn area_rect(hwnd: HWND, id: u32) -> io::Result<RECT> {
+    unsafe {
+        let mut identifier: shellapi::NOTIFYICONIDENTIFIER = MaybeUninit::zeroed().assume_init();
+        identifier.cbSize = size_of::<shellapi::NOTIFYICONIDENTIFIER>() as u32;
+        identifier.hWnd = hwnd;
+        identifier.uID = id;
+
+        let mut rect = MaybeUninit::zeroed();
+
+        let result = shellapi::Shell_NotifyIconGetRect(&identifier, rect.as_mut_ptr());
+
+        if result != S_OK {
+            return Err(io::Error::from_raw_os_error(result));
+        }
+
+        Ok(rect.assume_init())
+    }
+}
+
+fn delete_area(hwnd: HWND, id: u32) -> io::Result<()> {
+    let result = unsafe {
+        let mut nid = new_nid(hwnd, id);
+        nid.uFlags = shellapi::NIF_ICON;
+        shellapi::Shell_NotifyIconW(shellapi::NIM_DELETE, &nid)
+    };
+
+    if result == FALSE {
+        return Err(io::Error::last_os_error());
+    }
+
+    Ok(())
 }
 
 unsafe impl Send for WindowHandle {}
 unsafe impl Sync for WindowHandle {}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use std::collections::BTreeSet;
+    use std::io;
+
+    use super::AddedAreas;
+
+    /// A fake notification tray which keeps track of the handles it holds.
+    #[derive(Default)]
+    struct Tray {
+        handles: RefCell<BTreeSet<u32>>,
+        calls: RefCell<usize>,
+    }
+
+    impl Tray {
+        fn add(&self, id: u32) -> io::Result<()> {
+            *self.calls.borrow_mut() += 1;
+            assert!(self.handles.borrow_mut().insert(id), "{id} added twice");
+            Ok(())
+        }
+
+        fn delete(&self, id: u32) -> io::Result<()> {
+            *self.calls.borrow_mut() += 1;
+            assert!(self.handles.borrow_mut().remove(&id), "{id} deleted twice");
+            Ok(())
+        }
+
+        fn count(&self) -> usize {
+            self.handles.borrow().len()
+        }
+
+        fn calls(&self) -> usize {
+            *self.calls.borrow()
+        }
+    }
+
+    #[test]
+    fn add_and_delete() {
+        let tray = Tray::default();
+        let added = AddedAreas::default();
+
+        assert!(added.add_with(0, || tray.add(0)).unwrap());
+        assert!(added.add_with(1, || tray.add(1)).unwrap());
+        assert!(!added.add_with(0, || tray.add(0)).unwrap());
+        assert_eq!(tray.count(), 2);
+        assert_eq!(tray.calls(), 2);
+
+        added.delete_with(0, |id| tray.delete(id)).unwrap();
+        added.delete_with(0, |id| tray.delete(id)).unwrap();
+        assert_eq!(tray.count(), 1);
+        assert_eq!(tray.calls(), 3);
+
+        // Areas can be added again after being deleted.
+        assert!(added.add_with(0, || tray.add(0)).unwrap());
+        assert_eq!(tray.count(), 2);
+
+        added.delete_all_with(|id| tray.delete(id).unwrap());
+        assert_eq!(tray.count(), 0);
+        assert_eq!(tray.calls(), 6);
+    }
+
+    #[test]
+    fn failed_add() {
+        let tray = Tray::default();
+        let added = AddedAreas::default();
+
+        let error = io::Error::new(io::ErrorKind::Other, "tray is missing");
+        assert!(added.add_with(0, || Err(error)).is_err());
+
+        // Areas which failed to be added are neither deleted nor considered
+        // added.
+        added.delete_with(0, |id| tray.delete(id)).unwrap();
+        added.delete_all_with(|id| tray.delete(id).unwrap());
+        assert_eq!(tray.calls(), 0);
+
+        assert!(added.add_with(0, || tray.add(0)).unwrap());
+        assert_eq!(tray.count(), 1);
+    }
+
+    #[test]
+    fn shutdown() {
+        let tray = Tray::default();
+        let added = AddedAreas::default();
+
+        // The window thread holds a clone which deletes remaining areas when
+        // the window is closed.
+        let window = added.clone();
+
+        for id in 0..4 {
+            added.add_with(id, || tray.add(id)).unwrap();
+        }
+
+        added.delete_with(2, |id| tray.delete(id)).unwrap();
+        assert_eq!(tray.count(), 3);
+
+        window.delete_all_with(|id| tray.delete(id).unwrap());
+        assert_eq!(tray.count(), 0);
+        assert_eq!(tray.calls(), 8);
+
+        // Deleting after shutdown, such as when the handle is dropped, does
+        // nothing.
+        added.delete_with(0, |id| tray.delete(id)).unwrap();
+        added.delete_all_with(|id| tray.delete(id).unwrap());
+        window.delete_all_with(|id| tray.delete(id).unwrap());
+        assert_eq!(tray.calls(), 8);
+    }
+}
@@ -1,52 +1,459 @@
 #![allow(clippy::field_reassign_with_default)]
 
-use std::ffi::OsStr;
+use std::cell::{Cell, RefCell};
+use std::collections::{BTreeMap, BTreeSet};
+use std::ffi::{OsStr, OsString};
 use std::io;
 use std::mem::size_of;
-use std::mem::ManuallyDrop;
-use std::mem::MaybeUninit;
+use std::path::PathBuf;
 use std::ptr;
+use std::rc::Rc;
 use std::slice;
-use std::thread;
+use std::sync::mpsc as std_mpsc;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 
 use tokio::sync::mpsc;
 use tokio::sync::oneshot;
-use windows_sys::Win32::Foundation::{FALSE, HWND, LPARAM, LRESULT, WPARAM};
+use tokio::sync::Notify;
+use windows_sys::Win32::Foundation::{FALSE, HWND, LPARAM, LRESULT, POINT, TRUE, WPARAM};
+use windows_sys::Win32::Graphics::Gdi::{COLOR_WINDOW, HBRUSH};
 use windows_sys::Win32::System::DataExchange::AddClipboardFormatListener;
 use windows_sys::Win32::System::DataExchange::COPYDATASTRUCT;
+use windows_sys::Win32::System::Shutdown::{ShutdownBlockReasonCreate, ShutdownBlockReasonDestroy};
+use windows_sys::Win32::UI::Controls::{DRAWITEMSTRUCT, MEASUREITEMSTRUCT, ODT_MENU};
+use windows_sys::Win32::UI::Input::KeyboardAndMouse::{RegisterHotKey, UnregisterHotKey};
 use windows_sys::Win32::UI::Shell as shellapi;
 use windows_sys::Win32::UI::WindowsAndMessaging as winuser;
+use windows_sys::Win32::UI::WindowsAndMessaging::MSG;
 
-use crate::convert::ToWide;
-use crate::error::ErrorKind::*;
+use crate::convert::{FromWide, ToWide};
+use crate::error::ErrorRepr::*;
 use crate::error::{Error, WindowError};
-use crate::event::{ClipboardEvent, MouseEvent};
+use crate::event::{ClipboardEvent, EndSessionReason, MouseButtons, MouseEvent, ShutdownReason};
+use crate::window::chunk::{ChunkHeader, Received, Transfers};
+use crate::window::{
+    Peer, PeerInfo, RawMessage, ARGS_TY, CHUNK_TY, INFO_TY, REQUEST_TY, RESERVED_TY,
+};
 use crate::window_loop::messages;
-use crate::AreaId;
 use crate::Result;
+use crate::{AreaId, Badge, Event};
 
-use super::{AreaHandle, ClipboardManager, MenuManager, WindowClassHandle, WindowHandle};
+use super::badge_icon;
+use super::menu_manager::mouse_event;
+use super::{
+    AddedAreas, AreaHandle, AreaMenu, ClipboardManager, ClipboardSettings, CopyDataRequest,
+    HotkeyBinding, HoverManager, IdleManager, MenuManager, MessageThread, Taskbar, Theme,
+    WindowClassHandle, WindowHandle, NOTIFY_ICON_VERSION,
+};
+
+/// A timer used to implement scheduled shutdowns.
+const SHUTDOWN_TIMER: usize = 1002;
+/// A timer used to show scheduled notifications.
+const NOTIFICATION_TIMER: usize = 1004;
+/// A timer used to retry adding notification areas.
+const RETRY_TIMER: usize = 1006;
+/// The base of timers set by the user, whose identifiers are stored in the low
+/// 16 bits.
+const USER_TIMER_BASE: usize = 0x10000;
+
+/// Timeout in milliseconds used when replying to a handshake.
+const INFO_REPLY_TIMEOUT: u32 = 1000;
+/// Timeout in milliseconds used when copying data to another window.
+const COPY_DATA_TIMEOUT: u32 = 1000;
+
+/// The delay before adding a notification area is first retried, which is
+/// doubled for every subsequent attempt.
+const ADD_NOTIFICATION_BACKOFF: Duration = Duration::from_millis(250);
+/// The number of times adding a notification area is retried, which with the
+/// backoff above waits for up to about 16 seconds.
+const ADD_NOTIFICATION_RETRIES: u32 = 6;
+
+/// Data copied to the window by another process.
+#[derive(Debug)]
+pub(crate) struct CopiedData {
+    pub(crate) ty: usize,
+    pub(crate) data: Vec<u8>,
+    /// The process which sent the data, if it identified itself.
+    pub(crate) peer: Option<Peer>,
+    /// The window to reply to if the data was sent as a request.
+    pub(crate) reply: Option<HWND>,
+}
+
+/// Settings for a visible top-level window.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct VisibleSettings {
+    /// The initial width of the window.
+    pub(crate) width: u32,
+    /// The initial height of the window.
+    pub(crate) height: u32,
+    /// Hide the window instead of minimizing or closing it.
+    pub(crate) hide_to_tray: bool,
+}
 
 #[derive(Debug)]
 pub(crate) enum WindowEvent {
     /// A meny item was clicked.
     MenuItemClicked(AreaId, u32, MouseEvent),
-    /// Shutdown was requested.
-    Shutdown,
+    /// A popup menu was opened.
+    MenuOpened(AreaId),
+    /// A popup menu was closed.
+    MenuClosed(AreaId),
+    /// The window has shut down.
+    Shutdown(ShutdownReason),
     /// Clipboard event.
-    Clipboard(ClipboardEvent),
+    Clipboard(u32, ClipboardEvent),
     /// The notification icon has been clicked.
     IconClicked(AreaId, MouseEvent),
+    /// The notification icon has been double clicked.
+    IconDoubleClicked(AreaId, MouseEvent),
+    /// The cursor entered or left the notification icon.
+    IconHovered(AreaId, bool),
     /// Balloon was clicked.
     NotificationClicked(AreaId, MouseEvent),
     /// Balloon timed out.
     NotificationDismissed(AreaId),
+    /// Scheduled notifications might be due.
+    NotificationTimer,
     /// Data copied to this process.
-    CopyData(usize, Vec<u8>),
+    CopyData(CopiedData),
+    /// Progress of a chunked copy data transfer, with the type, the number
+    /// of bytes received and the total number of bytes.
+    CopyDataProgress(usize, usize, usize),
+    /// A registered window message was received.
+    AppMessage(u32, usize, isize),
+    /// The session is about to end for the given reason, and whether it is
+    /// being forced.
+    EndSession(EndSessionReason, bool),
+    /// Ending the session was cancelled.
+    EndSessionCancelled,
+    /// The user timer with the given identifier has elapsed.
+    Timer(u16),
+    /// The close button of the visible window was pressed.
+    WindowClosed,
+    /// The visible window was resized to the given width and height.
+    Resized(u32, u32),
+    /// The visible window was hidden instead of being minimized or closed.
+    WindowHidden,
+    /// The taskbar has been created, such as when Explorer has restarted.
+    TaskbarCreated,
+    /// Files were dropped onto the visible window at the given position in
+    /// client coordinates.
+    FilesDropped(Vec<PathBuf>, (i32, i32)),
+    /// The user has been idle for the given duration.
+    Idle(Duration),
+    /// The user is active again after being idle.
+    Active,
+    /// An event produced by the message filter.
+    Filtered(Event),
     /// Non-fatal error.
     Error(Error),
 }
 
+/// State which is accessible from the window procedure.
+///
+/// This is associated with the window through `GWLP_USERDATA` and is only
+/// ever accessed from the window thread.
+struct ProcState {
+    /// Encoded peer information sent in response to handshakes.
+    info: Vec<u8>,
+    /// Sender used for events which are raised directly from the window
+    /// procedure.
+    events_tx: mpsc::UnboundedSender<WindowEvent>,
+    /// Popup menus indexed by the area they belong to.
+    ///
+    /// Areas can be added and removed while a popup menu is being shown, so
+    /// borrows of this must never be held while the modal menu loop runs.
+    menus: Rc<RefCell<Vec<Option<AreaMenu>>>>,
+    /// Theme used to render owner-drawn popup menus.
+    theme: Theme,
+    /// Menu items clicked by registered global hotkeys, indexed by the
+    /// identifier the hotkey was registered with.
+    hotkeys: RefCell<Vec<Option<(AreaId, u32)>>>,
+    /// Chunked transfers which are being reassembled.
+    transfers: RefCell<Transfers>,
+    /// Registered window messages which are forwarded to the event loop.
+    messages: Vec<u32>,
+    /// Whether the session is blocked from ending.
+    shutdown_blocked: Cell<bool>,
+    /// Filter called with messages which aren't otherwise handled.
+    message_filter: Option<fn(&RawMessage) -> Option<Event>>,
+    /// Access to the taskbar, which is connected to on first use.
+    taskbar: RefCell<Option<Taskbar>>,
+    /// Settings if the window is a visible top-level window.
+    visible: Option<VisibleSettings>,
+    /// The registered message broadcast when the taskbar has been created.
+    taskbar_created: u32,
+    /// Notified when adding notification areas should be retried, either
+    /// since the taskbar has been created or since the retry timer elapsed.
+    retry: Arc<Notify>,
+}
+
+impl ProcState {
+    /// Add a chunk to the transfer it belongs to, sending the reassembled
+    /// data as an event once the transfer is complete.
+    ///
+    /// Returns `false` if the chunk was rejected.
+    fn receive_chunk(&self, header: ChunkHeader, chunk: &[u8], peer: Option<Peer>) -> bool {
+        let received = self
+            .transfers
+            .borrow_mut()
+            .receive(header, chunk, Instant::now());
+
+        match received {
+            Received::Rejected => return false,
+            Received::Progress {
+                ty,
+                received,
+                total,
+            } => {
+                _ = self.events_tx.send(WindowEvent::CopyDataProgress(
+                    ty,
+                    received as usize,
+                    total as usize,
+                ));
+            }
+            Received::Complete { ty, data } => {
+                _ = self
+                    .events_tx
+                    .send(WindowEvent::CopyDataProgress(ty, data.len(), data.len()));
+
+                _ = self.events_tx.send(WindowEvent::CopyData(CopiedData {
+                    ty,
+                    data,
+                    peer,
+                    reply: None,
+                }));
+            }
+        }
+
+        true
+    }
+
+    /// Access the taskbar, connecting to it if necessary.
+    fn with_taskbar<T>(&self, f: impl FnOnce(&Taskbar) -> io::Result<T>) -> io::Result<T> {
+        let mut taskbar = self.taskbar.borrow_mut();
+
+        let taskbar = match &mut *taskbar {
+            Some(taskbar) => taskbar,
+            taskbar => taskbar.insert(Taskbar::new()?),
+        };
+
+        f(taskbar)
+    }
+
+    /// Set the progress shown on the taskbar button of the window.
+    fn set_taskbar_progress(&self, hwnd: HWND, value: u64, flags: i32) -> io::Result<()> {
+        self.with_taskbar(|taskbar| taskbar.set_progress(hwnd, value, flags))
+    }
+
+    /// Set or clear the badge shown over the taskbar button of the window.
+    fn set_taskbar_badge(&self, hwnd: HWND, badge: Option<Badge>) -> io::Result<()> {
+        let icon = badge
+            .map(|badge| badge_icon::render(None, badge))
+            .transpose()?;
+
+        let description = badge.and_then(Badge::text).unwrap_or_default();
+        self.with_taskbar(|taskbar| taskbar.set_overlay_icon(hwnd, icon.as_ref(), &description))
+    }
+
+    /// Send an event for the area the given popup menu belongs to.
+    fn send_menu_event(&self, hmenu: winuser::HMENU, event: fn(AreaId) -> WindowEvent) {
+        let area_id = self
+            .menus
+            .borrow()
+            .iter()
+            .position(|m| m.as_ref().map(|m| m.hmenu) == Some(hmenu));
+
+        if let Some(area_id) = area_id {
+            _ = self.events_tx.send(event(AreaId::new(area_id as u32)));
+        }
+    }
+
+    /// Register the popup menu of an area which has been added.
+    fn add_menu(&self, area_id: AreaId, menu: AreaMenu) {
+        let mut menus = self.menus.borrow_mut();
+        let index = area_id.id() as usize;
+
+        if menus.len() <= index {
+            menus.resize_with(index + 1, || None);
+        }
+
+        menus[index] = Some(menu);
+    }
+
+    /// Unregister the popup menu of an area which has been removed.
+    fn remove_menu(&self, hwnd: HWND, area_id: AreaId) {
+        if let Some(menu) = self.menus.borrow_mut().get_mut(area_id.id() as usize) {
+            *menu = None;
+        }
+
+        self.remove_hotkeys(hwnd, |a, _| a == area_id);
+    }
+
+    /// Register a global hotkey which clicks the given menu item.
+    ///
+    /// Hotkeys are registered system-wide, so this fails if another
+    /// application has already registered the same combination of keys.
+    unsafe fn add_hotkey(&self, hwnd: HWND, area_id: AreaId, binding: &HotkeyBinding) {
+        let mut hotkeys = self.hotkeys.borrow_mut();
+
+        let id = match hotkeys.iter().position(Option::is_none) {
+            Some(id) => id,
+            None => {
+                hotkeys.push(None);
+                hotkeys.len() - 1
+            }
+        };
+
+        let hotkey = &binding.hotkey;
+
+        if RegisterHotKey(hwnd, id as i32, hotkey.modifiers, hotkey.vk) == FALSE {
+            let error = Error::new(RegisterHotkey(io::Error::last_os_error()));
+            _ = self.events_tx.send(WindowEvent::Error(error));
+            return;
+        }
+
+        hotkeys[id] = Some((area_id, binding.item_id));
+    }
+
+    /// Unregister global hotkeys matching the given predicate.
+    fn remove_hotkeys(&self, hwnd: HWND, mut f: impl FnMut(AreaId, u32) -> bool) {
+        for (id, hotkey) in self.hotkeys.borrow_mut().iter_mut().enumerate() {
+            if matches!(*hotkey, Some((area_id, item_id)) if f(area_id, item_id)) {
+                // SAFETY: The hotkey was registered by this window.
+                unsafe {
+                    UnregisterHotKey(hwnd, id as i32);
+                }
+
+                *hotkey = None;
+            }
+        }
+    }
+}
+
+impl ProcState {
+    unsafe fn install(&self, hwnd: HWND) {
+        winuser::SetWindowLongPtrW(hwnd, winuser::GWLP_USERDATA, self as *const _ as isize);
+    }
+
+    unsafe fn uninstall(hwnd: HWND) {
+        winuser::SetWindowLongPtrW(hwnd, winuser::GWLP_USERDATA, 0);
+    }
+
+    unsafe fn get<'a>(hwnd: HWND) -> Option<&'a ProcState> {
+        (winuser::GetWindowLongPtrW(hwnd, winuser::GWLP_USERDATA) as *const ProcState).as_ref()
+    }
+}
+
+impl Drop for ProcState {
+    fn drop(&mut self) {
+        // NB: Wake up anyone waiting to retry adding a notification area, so
+        // that they don't wait for a window which is gone.
+        self.retry.notify_one();
+    }
+}
+
+/// Copy data to another window, passing our own window as the sender.
+unsafe fn copy_data(hwnd: HWND, request: &CopyDataRequest) -> io::Result<()> {
+    let data = COPYDATASTRUCT {
+        dwData: request.ty,
+        cbData: request.bytes.len() as u32,
+        lpData: request.bytes.as_ptr() as *mut _,
+    };
+
+    let result = winuser::SendMessageTimeoutW(
+        request.to,
+        winuser::WM_COPYDATA,
+        hwnd as WPARAM,
+        &data as *const _ as LPARAM,
+        winuser::SMTO_NORMAL | winuser::SMTO_ABORTIFHUNG,
+        COPY_DATA_TIMEOUT,
+        ptr::null_mut(),
+    );
+
+    if result == 0 {
+        return Err(io::Error::last_os_error());
+    }
+
+    Ok(())
+}
+
+/// Split a request into its type and data.
+fn split_request(bytes: &[u8]) -> Option<(usize, &[u8])> {
+    if bytes.len() < size_of::<usize>() {
+        return None;
+    }
+
+    let (ty, bytes) = bytes.split_at(size_of::<usize>());
+    let ty = usize::from_ne_bytes(ty.try_into().ok()?);
+    Some((ty, bytes))
+}
+
+/// Reply to a handshake by copying peer information to the given window.
+unsafe fn reply_info(hwnd: HWND, to: HWND, info: &[u8]) {
+    if to == 0 {
+        return;
+    }
+
+    let data = COPYDATASTRUCT {
+        dwData: INFO_TY,
+        cbData: info.len() as u32,
+        lpData: info.as_ptr() as *mut _,
+    };
+
+    winuser::SendMessageTimeoutW(
+        to,
+        winuser::WM_COPYDATA,
+        hwnd as WPARAM,
+        &data as *const _ as LPARAM,
+        winuser::SMTO_NORMAL,
+        INFO_REPLY_TIMEOUT,
+        ptr::null_mut(),
+    );
+}
+
+/// Get the paths of the files dropped onto the window and the position they
+/// were dropped at in client coordinates.
+unsafe fn dropped_files(hdrop: shellapi::HDROP) -> (Vec<PathBuf>, (i32, i32)) {
+    let count = shellapi::DragQueryFileW(hdrop, u32::MAX, ptr::null_mut(), 0);
+    let mut paths = Vec::with_capacity(count as usize);
+
+    for index in 0..count {
+        let len = shellapi::DragQueryFileW(hdrop, index, ptr::null_mut(), 0);
+        let mut buf = vec![0u16; len as usize + 1];
+        let len = shellapi::DragQueryFileW(hdrop, index, buf.as_mut_ptr(), buf.len() as u32);
+        paths.push(PathBuf::from(OsString::from_wide(&buf[..len as usize])));
+    }
+
+    let mut point = POINT { x: 0, y: 0 };
+    shellapi::DragQueryPoint(hdrop, &mut point);
+    (paths, (point.x, point.y))
+}
+
+thread_local! {
+    /// Messages which were dispatched by a modal loop rather than by the
+    /// message loop, and which are posted again once the modal loop has exited.
+    static DEFERRED: RefCell<Vec<(HWND, u32, WPARAM, LPARAM)>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Defer a message until the modal loop which is currently running has exited.
+pub(super) fn defer_message(hwnd: HWND, msg: u32, w_param: WPARAM, l_param: LPARAM) {
+    DEFERRED.with(|deferred| deferred.borrow_mut().push((hwnd, msg, w_param, l_param)));
+}
+
+/// Post messages which were deferred while a modal loop was running.
+///
+/// This is called by the message loop, which never runs while a modal loop is
+/// running on the same thread.
+pub(super) unsafe fn post_deferred() {
+    let deferred = DEFERRED.with(|deferred| std::mem::take(&mut *deferred.borrow_mut()));
+
+    for (hwnd, msg, w_param, l_param) in deferred {
+        winuser::PostMessageW(hwnd, msg, w_param, l_param);
+    }
+}
+
 unsafe extern "system" fn window_proc(
     hwnd: HWND,
     msg: u32,
@@ -57,20 +464,353 @@ unsafe extern "system" fn window_proc(
     match msg {
         messages::ICON_ID => {
             if matches!(
-                l_param as u32,
+                messages::IconMessage::decode(w_param, l_param).event,
                 shellapi::NIN_BALLOONUSERCLICK
                     | shellapi::NIN_BALLOONTIMEOUT
                     | winuser::WM_LBUTTONUP
-                    | winuser::WM_RBUTTONUP
+                    | winuser::WM_MBUTTONUP
+                    | winuser::WM_LBUTTONDBLCLK
+                    | winuser::WM_CONTEXTMENU
+                    | winuser::WM_MOUSEMOVE
+                    | shellapi::NIN_POPUPOPEN
+                    | shellapi::NIN_POPUPCLOSE
+                    | messages::NIN_KEYSELECT
             ) {
                 winuser::PostMessageW(hwnd, msg, w_param, l_param);
                 return 0;
             }
         }
+        // NB: These are sent while the modal menu loop is running, so they
+        // are forwarded directly rather than being posted to the message
+        // loop which isn't running until the menu is closed.
+        winuser::WM_INITMENUPOPUP => {
+            if let Some(state) = ProcState::get(hwnd) {
+                state.theme.init_menu(w_param as winuser::HMENU);
+                state.send_menu_event(w_param as winuser::HMENU, WindowEvent::MenuOpened);
+            }
+
+            return 0;
+        }
+        winuser::WM_MEASUREITEM => {
+            let mis = &mut *(l_param as *mut MEASUREITEMSTRUCT);
+
+            if let (ODT_MENU, Some(state)) = (mis.CtlType, ProcState::get(hwnd)) {
+                if mis.itemData != 0 {
+                    state.theme.measure(hwnd, mis);
+                    return TRUE as LRESULT;
+                }
+            }
+        }
+        winuser::WM_DRAWITEM => {
+            let dis = &*(l_param as *const DRAWITEMSTRUCT);
+
+            if let (ODT_MENU, Some(state)) = (dis.CtlType, ProcState::get(hwnd)) {
+                if dis.itemData != 0 {
+                    state.theme.draw(dis);
+                    return TRUE as LRESULT;
+                }
+            }
+        }
+        winuser::WM_UNINITMENUPOPUP => {
+            if let Some(state) = ProcState::get(hwnd) {
+                state.send_menu_event(w_param as winuser::HMENU, WindowEvent::MenuClosed);
+            }
+
+            return 0;
+        }
         winuser::WM_MENUCOMMAND => {
             winuser::PostMessageW(hwnd, msg, w_param, l_param);
             return 0;
         }
+        // NB: These are handled directly since they might be dispatched from
+        // the modal menu loop, in which case they'd never reach the message
+        // loop.
+        messages::ADD_AREA_ID => {
+            let menu = Box::from_raw(l_param as *mut AreaMenu);
+
+            if let Some(state) = ProcState::get(hwnd) {
+                state.add_menu(AreaId::new(w_param as u32), *menu);
+            }
+
+            return 0;
+        }
+        messages::REMOVE_AREA_ID => {
+            if let Some(state) = ProcState::get(hwnd) {
+                state.remove_menu(hwnd, AreaId::new(w_param as u32));
+            }
+
+            return 0;
+        }
+        messages::ADD_HOTKEY_ID => {
+            let binding = Box::from_raw(l_param as *mut HotkeyBinding);
+
+            if let Some(state) = ProcState::get(hwnd) {
+                state.add_hotkey(hwnd, AreaId::new(w_param as u32), &binding);
+            }
+
+            return 0;
+        }
+        messages::COPY_DATA_ID => {
+            let request = Box::from_raw(l_param as *mut CopyDataRequest);
+
+            if let Err(error) = copy_data(hwnd, &request) {
+                if let Some(state) = ProcState::get(hwnd) {
+                    _ = state
+                        .events_tx
+                        .send(WindowEvent::Error(Error::new(CopyData(error))));
+                }
+            }
+
+            return 0;
+        }
+        messages::BLOCK_SHUTDOWN_ID => {
+            let reason = Box::from_raw(l_param as *mut Vec<u16>);
+
+            if let Some(state) = ProcState::get(hwnd) {
+                if ShutdownBlockReasonCreate(hwnd, reason.as_ptr()) == FALSE {
+                    let error = Error::new(BlockShutdown(io::Error::last_os_error()));
+                    _ = state.events_tx.send(WindowEvent::Error(error));
+                } else {
+                    state.shutdown_blocked.set(true);
+                }
+            }
+
+            return 0;
+        }
+        messages::UNBLOCK_SHUTDOWN_ID => {
+            if let Some(state) = ProcState::get(hwnd) {
+                if state.shutdown_blocked.take() && ShutdownBlockReasonDestroy(hwnd) == FALSE {
+                    let error = Error::new(BlockShutdown(io::Error::last_os_error()));
+                    _ = state.events_tx.send(WindowEvent::Error(error));
+                }
+            }
+
+            return 0;
+        }
+        // NB: The session might end as soon as these have been handled, so
+        // they are forwarded directly.
+        winuser::WM_QUERYENDSESSION => {
+            let Some(state) = ProcState::get(hwnd) else {
+                return TRUE as LRESULT;
+            };
+
+            let flags = l_param as u32;
+
+            let reason = if flags & winuser::ENDSESSION_LOGOFF != 0 {
+                EndSessionReason::Logoff
+            } else if flags & winuser::ENDSESSION_CLOSEAPP != 0 {
+                EndSessionReason::CloseApp
+            } else {
+                EndSessionReason::Shutdown
+            };
+
+            let critical = flags & winuser::ENDSESSION_CRITICAL != 0;
+
+            _ = state
+                .events_tx
+                .send(WindowEvent::EndSession(reason, critical));
+
+            return (!state.shutdown_blocked.get()) as LRESULT;
+        }
+        winuser::WM_ENDSESSION => {
+            if let Some(state) = ProcState::get(hwnd) {
+                let event = if w_param == 0 {
+                    WindowEvent::EndSessionCancelled
+                } else {
+                    WindowEvent::Shutdown(ShutdownReason::SessionEnding)
+                };
+
+                _ = state.events_tx.send(event);
+            }
+
+            return 0;
+        }
+        messages::TASKBAR_PROGRESS_ID => {
+            if let Some(state) = ProcState::get(hwnd) {
+                if let Err(error) = state.set_taskbar_progress(hwnd, w_param as u64, l_param as i32)
+                {
+                    let error = Error::new(TaskbarProgress(error));
+                    _ = state.events_tx.send(WindowEvent::Error(error));
+                }
+            }
+
+            return 0;
+        }
+        messages::TASKBAR_BADGE_ID => {
+            let badge = Box::from_raw(l_param as *mut Option<Badge>);
+
+            if let Some(state) = ProcState::get(hwnd) {
+                if let Err(error) = state.set_taskbar_badge(hwnd, *badge) {
+                    let error = Error::new(TaskbarBadge(error));
+                    _ = state.events_tx.send(WindowEvent::Error(error));
+                }
+            }
+
+            return 0;
+        }
+        winuser::WM_DROPFILES => {
+            let hdrop = w_param as shellapi::HDROP;
+
+            if let Some(state) = ProcState::get(hwnd) {
+                let (paths, position) = dropped_files(hdrop);
+                _ = state
+                    .events_tx
+                    .send(WindowEvent::FilesDropped(paths, position));
+            }
+
+            shellapi::DragFinish(hdrop);
+            return 0;
+        }
+        messages::SHOW_WINDOW_ID => {
+            if let Some(state) = ProcState::get(hwnd) {
+                if state.visible.is_some() {
+                    if w_param == 0 {
+                        winuser::ShowWindow(hwnd, winuser::SW_HIDE);
+                    } else {
+                        let cmd = if winuser::IsIconic(hwnd) != FALSE {
+                            winuser::SW_RESTORE
+                        } else {
+                            winuser::SW_SHOW
+                        };
+
+                        winuser::ShowWindow(hwnd, cmd);
+                        winuser::SetForegroundWindow(hwnd);
+                    }
+                }
+            }
+
+            return 0;
+        }
+        // NB: Closing the window would destroy it, so it's reported to the
+        // event loop instead. The window is only ever destroyed once the event
+        // loop has stopped using it.
+        winuser::WM_CLOSE => {
+            if let Some(state) = ProcState::get(hwnd) {
+                let event = match state.visible {
+                    Some(visible) if visible.hide_to_tray => {
+                        winuser::ShowWindow(hwnd, winuser::SW_HIDE);
+                        WindowEvent::WindowHidden
+                    }
+                    Some(..) => WindowEvent::WindowClosed,
+                    None => WindowEvent::Shutdown(ShutdownReason::Destroyed),
+                };
+
+                _ = state.events_tx.send(event);
+                return 0;
+            }
+        }
+        winuser::WM_SIZE => {
+            if let Some(state) = ProcState::get(hwnd) {
+                if let Some(visible) = state.visible {
+                    if w_param != winuser::SIZE_MINIMIZED as usize {
+                        let width = (l_param & 0xffff) as u32;
+                        let height = ((l_param >> 16) & 0xffff) as u32;
+                        _ = state.events_tx.send(WindowEvent::Resized(width, height));
+                    } else if visible.hide_to_tray {
+                        // NB: The window stays minimized while hidden, so it's
+                        // restored when it's shown again.
+                        winuser::ShowWindow(hwnd, winuser::SW_HIDE);
+                        _ = state.events_tx.send(WindowEvent::WindowHidden);
+                    }
+                }
+            }
+        }
+        messages::REMOVE_HOTKEY_ID => {
+            if let Some(state) = ProcState::get(hwnd) {
+                let area_id = AreaId::new(w_param as u32);
+                let item_id = l_param as u32;
+                state.remove_hotkeys(hwnd, |a, i| a == area_id && i == item_id);
+            }
+
+            return 0;
+        }
+        // NB: Hotkeys are handled directly, since they should work while a
+        // popup menu is being shown.
+        winuser::WM_HOTKEY => {
+            if let Some(state) = ProcState::get(hwnd) {
+                let hotkey = state.hotkeys.borrow().get(w_param).copied().flatten();
+
+                if let Some((area_id, item_id)) = hotkey {
+                    let event = mouse_event(MouseButtons::empty(), None);
+                    _ = state
+                        .events_tx
+                        .send(WindowEvent::MenuItemClicked(area_id, item_id, event));
+                }
+            }
+
+            return 0;
+        }
+        // NB: Timers are handled directly, since both the messages controlling
+        // them and the timers themselves might be dispatched by a modal loop,
+        // such as while a popup menu is shown.
+        messages::SCHEDULE_SHUTDOWN_ID => {
+            winuser::SetTimer(hwnd, SHUTDOWN_TIMER, w_param as u32, None);
+            return 0;
+        }
+        messages::CANCEL_SHUTDOWN_ID => {
+            winuser::KillTimer(hwnd, SHUTDOWN_TIMER);
+            return 0;
+        }
+        winuser::WM_TIMER if w_param == SHUTDOWN_TIMER => {
+            winuser::KillTimer(hwnd, SHUTDOWN_TIMER);
+
+            if let Some(state) = ProcState::get(hwnd) {
+                _ = state
+                    .events_tx
+                    .send(WindowEvent::Shutdown(ShutdownReason::Requested));
+            }
+
+            return 0;
+        }
+        messages::SET_TIMER_ID => {
+            let id = USER_TIMER_BASE | w_param;
+
+            if winuser::SetTimer(hwnd, id, l_param as u32, None) == 0 {
+                if let Some(state) = ProcState::get(hwnd) {
+                    let error = Error::new(SetTimer(io::Error::last_os_error()));
+                    _ = state.events_tx.send(WindowEvent::Error(error));
+                }
+            }
+
+            return 0;
+        }
+        messages::KILL_TIMER_ID => {
+            winuser::KillTimer(hwnd, USER_TIMER_BASE | w_param);
+            return 0;
+        }
+        winuser::WM_TIMER if w_param & !0xffff == USER_TIMER_BASE => {
+            if let Some(state) = ProcState::get(hwnd) {
+                _ = state.events_tx.send(WindowEvent::Timer(w_param as u16));
+            }
+
+            return 0;
+        }
+        messages::SCHEDULE_NOTIFICATION_ID => {
+            winuser::SetTimer(hwnd, NOTIFICATION_TIMER, w_param as u32, None);
+            return 0;
+        }
+        winuser::WM_TIMER if w_param == NOTIFICATION_TIMER => {
+            winuser::KillTimer(hwnd, NOTIFICATION_TIMER);
+
+            if let Some(state) = ProcState::get(hwnd) {
+                _ = state.events_tx.send(WindowEvent::NotificationTimer);
+            }
+
+            return 0;
+        }
+        messages::SCHEDULE_RETRY_ID => {
+            winuser::SetTimer(hwnd, RETRY_TIMER, w_param as u32, None);
+            return 0;
+        }
+        winuser::WM_TIMER if w_param == RETRY_TIMER => {
+            winuser::KillTimer(hwnd, RETRY_TIMER);
+
+            if let Some(state) = ProcState::get(hwnd) {
+                state.retry.notify_one();
+            }
+
+            return 0;
+        }
         winuser::WM_CLIPBOARDUPDATE => {
             winuser::PostMessageW(hwnd, msg, w_param, l_param);
             return 0;
@@ -79,28 +819,362 @@ unsafe extern "system" fn window_proc(
             winuser::PostMessageW(hwnd, msg, w_param, l_param);
             return 0;
         }
+        // NB: This is handled by the message loop, so it only gets here if it's
+        // dispatched by a modal loop, such as while a popup menu is shown.
+        // Dropping it would leave the event loop waiting for the window to
+        // close forever, so it's posted again once the modal loop has exited.
+        // Posting it immediately would only spin the modal loop.
+        messages::CLOSE_WINDOW_ID => {
+            defer_message(hwnd, msg, w_param, l_param);
+            return 0;
+        }
         winuser::WM_COPYDATA => {
             let data = &*(l_param as *const COPYDATASTRUCT);
 
-            let len = data.cbData as usize;
-            let mut vec = Vec::with_capacity(len + size_of::<usize>());
-            vec.extend_from_slice(slice::from_raw_parts(data.lpData.cast::<u8>(), len));
-            vec.extend_from_slice(&data.dwData.to_ne_bytes());
-            let mut vec = ManuallyDrop::new(vec);
-            let bytes = vec.as_mut_ptr();
-            winuser::PostMessageW(hwnd, messages::BYTES_ID, len, bytes as isize);
+            let bytes = if data.cbData == 0 {
+                &[][..]
+            } else {
+                slice::from_raw_parts(data.lpData.cast::<u8>(), data.cbData as usize)
+            };
+
+            // NB: By convention the sender passes its window in wParam, which
+            // is only guaranteed to be alive while we're processing the
+            // message.
+            let sender = w_param as HWND;
+
+            let (ty, bytes, reply) = match data.dwData {
+                // Chunks are reassembled before being forwarded.
+                CHUNK_TY => {
+                    let (Some((header, chunk)), Some(state)) =
+                        (ChunkHeader::decode(bytes), ProcState::get(hwnd))
+                    else {
+                        return 0;
+                    };
+
+                    // NB: The peer is only resolved for the last chunk.
+                    let peer = if sender != 0 && state.transfers.borrow().is_last(&header, chunk) {
+                        Peer::from_hwnd(sender)
+                    } else {
+                        None
+                    };
+
+                    return state.receive_chunk(header, chunk, peer) as LRESULT;
+                }
+                // Requests are prefixed with the type of the request, and
+                // carry the window to reply to.
+                REQUEST_TY => {
+                    let Some((ty, bytes)) = split_request(bytes) else {
+                        return 1;
+                    };
+
+                    (ty, bytes, Some(w_param as HWND))
+                }
+                // Forwarded arguments are decoded by the event loop.
+                ARGS_TY => (ARGS_TY, bytes, None),
+                // Typed messages are decoded by the event loop.
+                #[cfg(feature = "serde")]
+                crate::window::MESSAGE_TY => (crate::window::MESSAGE_TY, bytes, None),
+                // Reserved types are handled by us and never forwarded.
+                ty if ty >= RESERVED_TY => {
+                    if let (INFO_TY, Some(state)) = (ty, ProcState::get(hwnd)) {
+                        reply_info(hwnd, w_param as HWND, &state.info);
+                    }
+
+                    return 1;
+                }
+                ty => (ty, bytes, None),
+            };
+
+            let Some(state) = ProcState::get(hwnd) else {
+                return 0;
+            };
+
+            let peer = if sender != 0 {
+                Peer::from_hwnd(sender)
+            } else {
+                None
+            };
+
+            // NB: The data is only valid while the message is being processed,
+            // so it's copied once and handed directly to the event loop.
+            _ = state.events_tx.send(WindowEvent::CopyData(CopiedData {
+                ty,
+                data: bytes.to_vec(),
+                peer,
+                reply,
+            }));
+
             return 0;
         }
+        // NB: Registered window messages are allocated in this range.
+        0xC000..=0xFFFF => {
+            if let Some(state) = ProcState::get(hwnd) {
+                if msg == state.taskbar_created {
+                    state.retry.notify_one();
+                    _ = state.events_tx.send(WindowEvent::TaskbarCreated);
+                    return 0;
+                }
+
+                if state.messages.contains(&msg) {
+                    _ = state
+                        .events_tx
+                        .send(WindowEvent::AppMessage(msg, w_param, l_param));
+                    return 0;
+                }
+            }
+        }
         _ => {}
     }
 
+    if let Some(state) = ProcState::get(hwnd) {
+        if let Some(filter) = state.message_filter {
+            let message = RawMessage {
+                hwnd,
+                message: msg,
+                wparam: w_param,
+                lparam: l_param,
+            };
+
+            if let Some(event) = filter(&message) {
+                _ = state.events_tx.send(WindowEvent::Filtered(event));
+                return 0;
+            }
+        }
+    }
+
     winuser::DefWindowProcW(hwnd, msg, w_param, l_param)
 }
 
+/// Everything needed to create a window on the thread which runs it.
+pub(super) struct Setup {
+    class_name: Vec<u16>,
+    window_name: Option<Vec<u16>>,
+    rtl: bool,
+    visible: Option<VisibleSettings>,
+    idle_threshold: Option<Duration>,
+    clipboard: Option<ClipboardSettings>,
+    info: Vec<u8>,
+    menus: Vec<Option<AreaMenu>>,
+    messages: Vec<u32>,
+    message_filter: Option<fn(&RawMessage) -> Option<Event>>,
+    retry: Arc<Notify>,
+    events_tx: mpsc::UnboundedSender<WindowEvent>,
+    #[allow(clippy::type_complexity)]
+    return_tx: oneshot::Sender<
+        Result<(WindowClassHandle, WindowHandle, std_mpsc::Receiver<()>), WindowError>,
+    >,
+}
+
+/// A window being run by a message thread.
+pub(super) struct Runtime {
+    hwnd: HWND,
+    added: AddedAreas,
+    // NB: Boxed since its address is installed in the window.
+    state: Box<ProcState>,
+    events_tx: mpsc::UnboundedSender<WindowEvent>,
+    clipboard_manager: Option<ClipboardManager>,
+    menu_manager: MenuManager,
+    hover_manager: HoverManager,
+    idle_manager: Option<IdleManager>,
+    closed_tx: std_mpsc::Sender<()>,
+}
+
+impl Runtime {
+    /// Create the window described by the given setup on the current thread,
+    /// replying with its handles.
+    ///
+    /// Returns `None` if the window couldn't be created, in which case the
+    /// error has been sent as the reply.
+    pub(super) unsafe fn create(setup: Setup) -> Option<Self> {
+        let Setup {
+            class_name,
+            window_name,
+            rtl,
+            visible,
+            idle_threshold,
+            clipboard,
+            info,
+            menus,
+            messages,
+            message_filter,
+            retry,
+            events_tx,
+            return_tx,
+        } = setup;
+
+        let (window_class, window) = match init_window(class_name, window_name, rtl, visible) {
+            Ok(window) => window,
+            Err(error) => {
+                _ = return_tx.send(Err(WindowError::Init(error)));
+                return None;
+            }
+        };
+
+        let clipboard_manager = if let Some(clipboard) = clipboard {
+            if AddClipboardFormatListener(window.hwnd) == FALSE {
+                let error = io::Error::last_os_error();
+                winuser::DestroyWindow(window.hwnd);
+                drop(window_class);
+                _ = return_tx.send(Err(WindowError::AddClipboardFormatListener(error)));
+                return None;
+            }
+
+            Some(ClipboardManager::new(events_tx.clone(), clipboard))
+        } else {
+            None
+        };
+
+        // NB: If the message can't be registered, adding notification areas
+        // is only retried with a backoff, and they're not added again if
+        // Explorer restarts.
+        let taskbar_created = crate::window::register_message("TaskbarCreated").unwrap_or(0);
+
+        // NB: The broadcast is filtered out by default if the process is
+        // running elevated.
+        if taskbar_created != 0 {
+            winuser::ChangeWindowMessageFilterEx(
+                window.hwnd,
+                taskbar_created,
+                winuser::MSGFLT_ALLOW,
+                ptr::null_mut(),
+            );
+        }
+
+        let state = Box::new(ProcState {
+            info,
+            events_tx: events_tx.clone(),
+            menus: Rc::new(RefCell::new(menus)),
+            theme: Theme::new(),
+            hotkeys: RefCell::new(Vec::new()),
+            transfers: RefCell::new(Transfers::default()),
+            messages,
+            shutdown_blocked: Cell::new(false),
+            message_filter,
+            taskbar: RefCell::new(None),
+            visible,
+            taskbar_created,
+            retry,
+        });
+
+        state.install(window.hwnd);
+
+        // NB: Shown once the state is installed, so that the initial size is
+        // reported.
+        if visible.is_some() {
+            shellapi::DragAcceptFiles(window.hwnd, TRUE);
+            winuser::ShowWindow(window.hwnd, winuser::SW_SHOW);
+        }
+
+        let menu_manager = MenuManager::new(events_tx.clone(), state.menus.clone());
+        let hover_manager = HoverManager::new(events_tx.clone());
+
+        let idle_manager = idle_threshold
+            .map(|threshold| IdleManager::new(window.hwnd, events_tx.clone(), threshold));
+
+        let (closed_tx, closed_rx) = std_mpsc::channel();
+
+        let runtime = Self {
+            hwnd: window.hwnd,
+            added: window.added.clone(),
+            state,
+            events_tx,
+            clipboard_manager,
+            menu_manager,
+            hover_manager,
+            idle_manager,
+            closed_tx,
+        };
+
+        if return_tx
+            .send(Ok((window_class, window, closed_rx)))
+            .is_err()
+        {
+            runtime.close();
+            return None;
+        }
+
+        Some(runtime)
+    }
+
+    /// The handle of the window.
+    pub(super) fn hwnd(&self) -> HWND {
+        self.hwnd
+    }
+
+    /// Process a message sent to the window.
+    ///
+    /// Returns `false` if the window should be closed.
+    pub(super) unsafe fn dispatch(&mut self, msg: &MSG) -> bool {
+        if let Some(clipboard_manager) = &mut self.clipboard_manager {
+            if clipboard_manager.dispatch(msg) {
+                return true;
+            }
+        }
+
+        if self.menu_manager.dispatch(msg) {
+            return true;
+        }
+
+        if self.hover_manager.dispatch(msg) {
+            return true;
+        }
+
+        if let Some(idle_manager) = &mut self.idle_manager {
+            if idle_manager.dispatch(msg) {
+                return true;
+            }
+        }
+
+        match msg.message {
+            messages::CLOSE_WINDOW_ID => {
+                return false;
+            }
+            winuser::WM_QUIT | winuser::WM_DESTROY => {
+                // NB: This was posted from the outside, so the event loop is
+                // told to shut down and closes the window once it has stopped
+                // using it.
+                _ = self
+                    .events_tx
+                    .send(WindowEvent::Shutdown(ShutdownReason::Destroyed));
+                return true;
+            }
+            _ => {}
+        }
+
+        winuser::TranslateMessage(msg);
+        winuser::DispatchMessageW(msg);
+        true
+    }
+
+    /// Close the window.
+    pub(super) unsafe fn close(self) {
+        let hwnd = self.hwnd;
+
+        // NB: Areas have to be deleted while the window is still alive,
+        // otherwise they linger in the notification tray.
+        self.added.delete_all(hwnd);
+        self.state.remove_hotkeys(hwnd, |_, _| true);
+        ProcState::uninstall(hwnd);
+        winuser::DestroyWindow(hwnd);
+        _ = self.closed_tx.send(());
+    }
+}
+
 unsafe fn init_window(
     class_name: Vec<u16>,
     window_name: Option<Vec<u16>>,
+    rtl: bool,
+    visible: Option<VisibleSettings>,
 ) -> io::Result<(WindowClassHandle, WindowHandle)> {
+    let (cursor, background) = if visible.is_some() {
+        (
+            winuser::LoadCursorW(0, winuser::IDC_ARROW),
+            (COLOR_WINDOW + 1) as HBRUSH,
+        )
+    } else {
+        (0, 0)
+    };
+
     let wnd = winuser::WNDCLASSW {
         style: 0,
         lpfnWndProc: Some(window_proc),
@@ -108,8 +1182,8 @@ unsafe fn init_window(
         cbWndExtra: 0,
         hInstance: 0,
         hIcon: 0,
-        hCursor: 0,
-        hbrBackground: 0,
+        hCursor: cursor,
+        hbrBackground: background,
         lpszMenuName: ptr::null(),
         lpszClassName: class_name.as_ptr(),
     };
@@ -120,15 +1194,31 @@ unsafe fn init_window(
 
     let class = WindowClassHandle { class_name };
 
+    let ex_style = if rtl { winuser::WS_EX_LAYOUTRTL } else { 0 };
+
+    let (style, x, y, width, height) = match visible {
+        Some(visible) => (
+            winuser::WS_OVERLAPPEDWINDOW,
+            winuser::CW_USEDEFAULT,
+            winuser::CW_USEDEFAULT,
+            visible.width.min(i32::MAX as u32) as i32,
+            visible.height.min(i32::MAX as u32) as i32,
+        ),
+        None => (winuser::WS_DISABLED, 0, 0, 0, 0),
+    };
+
     let hwnd = winuser::CreateWindowExW(
-        0,
+        ex_style,
         class.class_name.as_ptr(),
-        window_name.map(|n| n.as_ptr()).unwrap_or_else(ptr::null),
-        winuser::WS_DISABLED,
-        0,
-        0,
-        0,
-        0,
+        window_name
+            .as_ref()
+            .map(|n| n.as_ptr())
+            .unwrap_or_else(ptr::null),
+        style,
+        x,
+        y,
+        width,
+        height,
         0,
         0,
         0,
@@ -139,7 +1229,13 @@ unsafe fn init_window(
         return Err(io::Error::last_os_error());
     }
 
-    let window = WindowHandle { hwnd };
+    let window = WindowHandle {
+        hwnd,
+        added: AddedAreas::default(),
+        custom_tooltips: BTreeSet::new(),
+        icons: BTreeMap::new(),
+        badges: BTreeMap::new(),
+    };
     Ok((class, window))
 }
 
@@ -148,20 +1244,37 @@ unsafe fn init_window(
 /// Note: repr(C) is important here to ensure drop order.
 #[repr(C)]
 pub(crate) struct WindowLoop {
-    pub(crate) areas: Vec<AreaHandle>,
+    /// Areas indexed by their identifier, where removed areas are `None`.
+    pub(crate) areas: Vec<Option<AreaHandle>>,
     pub(crate) window: WindowHandle,
+    /// Whether the window uses a right-to-left layout.
+    pub(crate) rtl: bool,
     window_class: WindowClassHandle,
     events_rx: mpsc::UnboundedReceiver<WindowEvent>,
-    thread: Option<thread::JoinHandle<Result<(), WindowError>>>,
+    /// Receives a message once the window has been closed.
+    closed: Option<std_mpsc::Receiver<()>>,
+    /// Notified when adding notification areas should be retried.
+    retry: Arc<Notify>,
+    /// The thread running the window, which is kept alive for as long as the
+    /// window exists.
+    thread: MessageThread,
 }
 
 impl WindowLoop {
     /// Construct a new window.
+    #[allow(clippy::too_many_arguments)]
     pub(crate) async fn new(
         class_name: &OsStr,
         window_name: Option<&OsStr>,
-        clipboard_events: bool,
+        clipboard: Option<ClipboardSettings>,
+        rtl: bool,
+        visible: Option<VisibleSettings>,
+        idle_threshold: Option<Duration>,
+        app_version: Option<&str>,
         areas: Vec<AreaHandle>,
+        messages: Vec<u32>,
+        message_filter: Option<fn(&RawMessage) -> Option<Event>>,
+        thread: &MessageThread,
     ) -> Result<WindowLoop, WindowError> {
         let class_name = class_name.to_wide_null();
         let window_name = window_name.map(|n| n.to_wide_null());
@@ -170,136 +1283,195 @@ impl WindowLoop {
             return Err(WindowError::ClassNameTooLong(class_name.len()));
         }
 
+        let info = PeerInfo {
+            crate_version: env!("CARGO_PKG_VERSION").to_owned(),
+            app_version: app_version.map(str::to_owned),
+            notify_icon_version: NOTIFY_ICON_VERSION,
+            clipboard_events: clipboard.is_some(),
+        };
+
         let (return_tx, return_rx) = oneshot::channel();
         let (events_tx, events_rx) = mpsc::unbounded_channel();
 
-        let mut hmenus = Vec::with_capacity(areas.len());
-
-        for menu in &areas {
-            hmenus.push(
-                menu.popup_menu
-                    .as_ref()
-                    .map(|p| (p.hmenu, p.open_menu.copy_data())),
-            );
-        }
+        let menus = areas.iter().map(AreaHandle::menu).collect();
+        let areas = areas.into_iter().map(Some).collect();
+        let retry = Arc::new(Notify::new());
 
-        let thread = thread::spawn(move || unsafe {
-            // NB: Don't move this, it's important that the window is
-            // initialized in the background thread.
-            let (window_class, window) =
-                init_window(class_name, window_name).map_err(WindowError::Init)?;
+        thread.add(Setup {
+            class_name,
+            window_name,
+            rtl,
+            visible,
+            idle_threshold,
+            clipboard,
+            info: info.encode(),
+            menus,
+            messages,
+            message_filter,
+            retry: retry.clone(),
+            events_tx,
+            return_tx,
+        })?;
 
-            let mut clipboard_manager = if clipboard_events {
-                if AddClipboardFormatListener(window.hwnd) == FALSE {
-                    return Err(WindowError::AddClipboardFormatListener(
-                        io::Error::last_os_error(),
-                    ));
-                }
+        let Ok(result) = return_rx.await else {
+            return Err(WindowError::ThreadExited);
+        };
 
-                Some(ClipboardManager::new(&events_tx))
-            } else {
-                None
-            };
+        let (window_class, window, closed) = result?;
 
-            let mut menu_manager =
-                (!hmenus.is_empty()).then(|| MenuManager::new(&events_tx, &hmenus));
+        Ok(WindowLoop {
+            areas,
+            window,
+            rtl,
+            window_class,
+            events_rx,
+            closed: Some(closed),
+            retry,
+            thread: thread.clone(),
+        })
+    }
 
-            let hwnd = window.hwnd;
+    /// Add the notification area for the given area, retrying while the shell
+    /// isn't ready.
+    ///
+    /// At logon the taskbar might not have been created yet, in which case
+    /// adding a notification area fails. Failed attempts are retried with an
+    /// exponential backoff, or as soon as the taskbar has been created, before
+    /// the error is returned.
+    ///
+    /// The backoff is timed by the window thread, so this doesn't depend on
+    /// the timers of any particular async runtime.
+    pub(crate) async fn add_notification_with_retry(&mut self, area_id: AreaId) -> io::Result<()> {
+        let mut backoff = ADD_NOTIFICATION_BACKOFF;
 
-            if return_tx.send((window_class, window)).is_err() {
+        for _ in 0..ADD_NOTIFICATION_RETRIES {
+            if self.window.add_notification(area_id).is_ok() {
                 return Ok(());
             }
 
-            let mut msg = MaybeUninit::zeroed();
+            self.window.schedule_retry(backoff)?;
+            self.retry.notified().await;
+            backoff *= 2;
+        }
 
-            while winuser::GetMessageW(msg.as_mut_ptr(), hwnd, 0, 0) != FALSE {
-                let msg = &*msg.as_ptr();
+        self.window.add_notification(area_id)
+    }
 
-                if let Some(clipboard_manager) = &mut clipboard_manager {
-                    if clipboard_manager.dispatch(msg) {
-                        continue;
-                    }
-                }
+    /// Tick the window through a single event cycle.
+    pub(crate) async fn tick(&mut self) -> WindowEvent {
+        // NB: The window thread always reports why it shut down, so the
+        // channel closing without a reason means it exited abnormally.
+        self.events_rx
+            .recv()
+            .await
+            .unwrap_or(WindowEvent::Shutdown(ShutdownReason::ThreadError))
+    }
 
-                if let Some(menu_manager) = &mut menu_manager {
-                    if menu_manager.dispatch(msg) {
-                        continue;
-                    }
-                }
+    /// Get the area with the given identifier.
+    pub(crate) fn area(&self, area_id: AreaId) -> Option<&AreaHandle> {
+        self.areas.get(area_id.id() as usize)?.as_ref()
+    }
 
-                match msg.message {
-                    winuser::WM_QUIT | winuser::WM_DESTROY => {
-                        break;
-                    }
-                    messages::BYTES_ID => {
-                        let len = msg.wParam;
-
-                        let bytes = Vec::from_raw_parts(
-                            msg.lParam as *mut u8,
-                            len,
-                            len + size_of::<usize>(),
-                        );
-
-                        let ty = bytes
-                            .as_ptr()
-                            .add(bytes.len())
-                            .cast::<usize>()
-                            .read_unaligned();
-
-                        _ = events_tx.send(WindowEvent::CopyData(ty, bytes));
-                        continue;
-                    }
-                    _ => {}
-                }
+    /// Get the area with the given identifier mutably.
+    pub(crate) fn area_mut(&mut self, area_id: AreaId) -> Option<&mut AreaHandle> {
+        self.areas.get_mut(area_id.id() as usize)?.as_mut()
+    }
 
-                winuser::TranslateMessage(msg);
-                winuser::DispatchMessageW(msg);
+    /// Add an area to the window.
+    ///
+    /// This only registers the popup menu of the area with the window thread,
+    /// the notification area itself has to be added separately.
+    pub(crate) fn add_area(&mut self, area: AreaHandle) -> io::Result<()> {
+        if let Some(menu) = area.menu() {
+            let menu = Box::into_raw(Box::new(menu));
+
+            let result = unsafe {
+                winuser::PostMessageW(
+                    self.window.hwnd,
+                    messages::ADD_AREA_ID,
+                    area.area_id.id() as WPARAM,
+                    menu as LPARAM,
+                )
+            };
+
+            if result == FALSE {
+                let error = io::Error::last_os_error();
+                // SAFETY: The message was never posted, so we still own the
+                // menu.
+                drop(unsafe { Box::from_raw(menu) });
+                return Err(error);
             }
+        }
 
-            Ok(())
-        });
+        let index = area.area_id.id() as usize;
 
-        let Some((window_class, window)) = return_rx.await.ok() else {
-            thread.join().map_err(|_| WindowError::ThreadPanicked)??;
-            return Err(WindowError::ThreadExited);
-        };
+        if self.areas.len() <= index {
+            self.areas.resize_with(index + 1, || None);
+        }
 
-        Ok(WindowLoop {
-            areas,
-            window,
-            window_class,
-            events_rx,
-            thread: Some(thread),
-        })
+        self.areas[index] = Some(area);
+        Ok(())
     }
 
-    /// Tick the window through a single event cycle.
-    pub(crate) async fn tick(&mut self) -> WindowEvent {
-        self.events_rx.recv().await.unwrap_or(WindowEvent::Shutdown)
+    /// Remove an area from the window, deleting its notification area.
+    ///
+    /// Returns `false` if the area doesn't exist.
+    pub(crate) fn remove_area(&mut self, area_id: AreaId) -> io::Result<bool> {
+        let Some(area) = self
+            .areas
+            .get_mut(area_id.id() as usize)
+            .and_then(Option::take)
+        else {
+            return Ok(false);
+        };
+
+        if area.popup_menu.is_some() {
+            let result = unsafe {
+                winuser::PostMessageW(
+                    self.window.hwnd,
+                    messages::REMOVE_AREA_ID,
+                    area_id.id() as WPARAM,
+                    0,
+                )
+            };
+
+            if result == FALSE {
+                return Err(io::Error::last_os_error());
+            }
+        }
+
+        self.window.delete_notification(area_id)?;
+        Ok(true)
     }
 
     /// Test if the window has been closed.
     pub(crate) fn is_closed(&self) -> bool {
-        self.thread.is_none()
+        self.closed.is_none()
     }
 
-    /// Join the current window.
+    /// Close the current window, waiting for the thread running it to let go
+    /// of it.
+    ///
+    /// If a popup menu is shown by a window on the same thread, this waits
+    /// until it has been closed.
     pub(crate) fn join(&mut self) -> Result<()> {
-        if self.thread.is_none() {
+        if self.closed.is_none() {
             return Ok(());
         }
 
-        let result = unsafe { winuser::PostMessageW(self.window.hwnd, winuser::WM_DESTROY, 0, 0) };
+        let result =
+            unsafe { winuser::PostMessageW(self.window.hwnd, messages::CLOSE_WINDOW_ID, 0, 0) };
 
         if result == FALSE {
             return Err(Error::new(PostMessageDestroy));
         }
 
-        if let Some(thread) = self.thread.take() {
-            thread
-                .join()
-                .map_err(|_| ThreadError(WindowError::ThreadPanicked))?
-                .map_err(ThreadError)?;
+        if let Some(closed) = self.closed.take() {
+            // NB: The sender is dropped without a message if the thread
+            // panicked.
+            closed
+                .recv()
+                .map_err(|_| ThreadError(WindowError::ThreadPanicked))?;
         }
 
         Ok(())
@@ -308,8 +1480,8 @@ impl WindowLoop {
 
 impl Drop for WindowLoop {
     fn drop(&mut self) {
-        for menu in &self.areas {
-            _ = self.window.delete_notification(menu.area_id);
-        }
+        // NB: This is only relevant if the window thread exited abnormally,
+        // since it otherwise deletes all added areas before exiting.
+        self.window.delete_all_notifications();
     }
 }